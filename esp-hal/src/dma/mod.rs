@@ -318,6 +318,8 @@ pub enum DmaError {
     Exhausted,
     /// The given buffer is too small
     BufferTooSmall,
+    /// The buffer is in a memory region the DMA can't access, e.g. flash
+    UnsupportedMemoryRegion,
 }
 
 /// DMA Priorities
@@ -429,6 +431,10 @@ pub trait AesPeripheral: PeripheralMarker {}
 #[doc(hidden)]
 pub trait LcdCamPeripheral: PeripheralMarker {}
 
+/// Marks channels as usable for UHCI
+#[doc(hidden)]
+pub trait UhciPeripheral: PeripheralMarker {}
+
 /// DMA Rx
 #[doc(hidden)]
 pub trait Rx: RxPrivate {}
@@ -456,6 +462,14 @@ pub trait RxPrivate: crate::private::Sealed {
         len: usize,
     ) -> Result<(), DmaError>;
 
+    /// Prepares a transfer into several buffers linked into one descriptor
+    /// list
+    unsafe fn prepare_chain_without_start(
+        &mut self,
+        peri: DmaPeripheral,
+        buffers: &mut dyn Iterator<Item = (*mut u8, usize)>,
+    ) -> Result<(), DmaError>;
+
     fn start_transfer(&mut self) -> Result<(), DmaError>;
 
     fn listen_ch_in_done(&self);
@@ -482,6 +496,10 @@ pub trait RxPrivate: crate::private::Sealed {
 
     fn drain_buffer(&mut self, dst: &mut [u8]) -> Result<usize, DmaError>;
 
+    /// Number of bytes written by the DMA until the first descriptor still
+    /// owned by the DMA or the descriptor which ended the transfer
+    fn received_len(&self) -> usize;
+
     /// Descriptor error detected
     fn has_error(&self) -> bool;
 
@@ -589,6 +607,55 @@ where
         Ok(())
     }
 
+    unsafe fn prepare_chain_without_start(
+        &mut self,
+        descriptors: &mut [DmaDescriptor],
+        peri: DmaPeripheral,
+        buffers: &mut dyn Iterator<Item = (*mut u8, usize)>,
+    ) -> Result<(), DmaError> {
+        descriptors.fill(DmaDescriptor::EMPTY);
+
+        compiler_fence(core::sync::atomic::Ordering::SeqCst);
+
+        let mut descr = 0;
+        for (data, len) in buffers {
+            let mut processed = 0;
+            while processed < len {
+                if descr >= descriptors.len() {
+                    return Err(DmaError::OutOfDescriptors);
+                }
+
+                if descr > 0 {
+                    descriptors[descr - 1].next = addr_of_mut!(descriptors[descr]);
+                }
+
+                let chunk_size = usize::min(CHUNK_SIZE, len - processed);
+
+                let dw0 = &mut descriptors[descr];
+                dw0.set_suc_eof(false);
+                dw0.set_owner(Owner::Dma);
+                dw0.set_size(chunk_size);
+                dw0.set_length(0); // hardware will fill in the received number of bytes
+                dw0.buffer = unsafe { data.add(processed) };
+                dw0.next = core::ptr::null_mut();
+
+                processed += chunk_size;
+                descr += 1;
+            }
+        }
+
+        if descr == 0 {
+            return Err(DmaError::BufferTooSmall);
+        }
+
+        R::clear_in_interrupts();
+        R::reset_in();
+        R::set_in_descriptors(descriptors.as_ptr() as u32);
+        R::set_in_peripheral(peri as u8);
+
+        Ok(())
+    }
+
     fn start_transfer(&mut self) -> Result<(), DmaError> {
         R::start_in();
 
@@ -699,6 +766,20 @@ where
             .prepare_transfer_without_start(self.descriptors, circular, peri, data, len)
     }
 
+    unsafe fn prepare_chain_without_start(
+        &mut self,
+        peri: DmaPeripheral,
+        buffers: &mut dyn Iterator<Item = (*mut u8, usize)>,
+    ) -> Result<(), DmaError> {
+        self.available = 0;
+        self.read_descr_ptr = self.descriptors.as_mut_ptr();
+        self.last_seen_handled_descriptor_ptr = core::ptr::null_mut();
+        self.read_buffer_start = core::ptr::null_mut();
+
+        self.rx_impl
+            .prepare_chain_without_start(self.descriptors, peri, buffers)
+    }
+
     fn start_transfer(&mut self) -> Result<(), DmaError> {
         self.rx_impl.start_transfer()
     }
@@ -818,6 +899,24 @@ where
         Ok(len)
     }
 
+    fn received_len(&self) -> usize {
+        let mut len = 0;
+        for descriptor in self.descriptors.iter() {
+            let descriptor = unsafe { (descriptor as *const DmaDescriptor).read_volatile() };
+            if descriptor.owner() == Owner::Dma {
+                break;
+            }
+
+            len += descriptor.len();
+
+            if descriptor.flags.suc_eof() || descriptor.next.is_null() {
+                break;
+            }
+        }
+
+        len
+    }
+
     fn is_listening_eof(&self) -> bool {
         R::is_listening_in_eof()
     }
@@ -903,6 +1002,14 @@ pub trait TxPrivate: crate::private::Sealed {
         len: usize,
     ) -> Result<(), DmaError>;
 
+    /// Prepares a transfer of several buffers linked into one descriptor
+    /// list
+    fn prepare_chain_without_start(
+        &mut self,
+        peri: DmaPeripheral,
+        buffers: &mut dyn Iterator<Item = (*const u8, usize)>,
+    ) -> Result<(), DmaError>;
+
     fn start_transfer(&mut self) -> Result<(), DmaError>;
 
     fn clear_ch_out_done(&self);
@@ -1021,6 +1128,58 @@ where
         Ok(())
     }
 
+    fn prepare_chain_without_start(
+        &mut self,
+        descriptors: &mut [DmaDescriptor],
+        peri: DmaPeripheral,
+        buffers: &mut dyn Iterator<Item = (*const u8, usize)>,
+    ) -> Result<(), DmaError> {
+        descriptors.fill(DmaDescriptor::EMPTY);
+
+        compiler_fence(core::sync::atomic::Ordering::SeqCst);
+
+        let mut descr = 0;
+        for (data, len) in buffers {
+            let mut processed = 0;
+            while processed < len {
+                if descr >= descriptors.len() {
+                    return Err(DmaError::OutOfDescriptors);
+                }
+
+                if descr > 0 {
+                    descriptors[descr - 1].next = addr_of_mut!(descriptors[descr]);
+                }
+
+                let chunk_size = usize::min(CHUNK_SIZE, len - processed);
+
+                let dw0 = &mut descriptors[descr];
+                dw0.set_suc_eof(false);
+                dw0.set_owner(Owner::Dma);
+                dw0.set_size(chunk_size);
+                dw0.set_length(chunk_size); // the hardware will transmit this many bytes
+                dw0.buffer = unsafe { data.cast_mut().add(processed) };
+                dw0.next = core::ptr::null_mut();
+
+                processed += chunk_size;
+                descr += 1;
+            }
+        }
+
+        if descr == 0 {
+            return Err(DmaError::BufferTooSmall);
+        }
+
+        // Only the end of the whole chain triggers the EOF interrupt
+        descriptors[descr - 1].set_suc_eof(true);
+
+        R::clear_out_interrupts();
+        R::reset_out();
+        R::set_out_descriptors(addr_of_mut!(descriptors[0]) as u32);
+        R::set_out_peripheral(peri as u8);
+
+        Ok(())
+    }
+
     fn start_transfer(&mut self) -> Result<(), DmaError> {
         R::start_out();
 
@@ -1165,6 +1324,22 @@ where
             .prepare_transfer_without_start(self.descriptors, circular, peri, data, len)
     }
 
+    fn prepare_chain_without_start(
+        &mut self,
+        peri: DmaPeripheral,
+        buffers: &mut dyn Iterator<Item = (*const u8, usize)>,
+    ) -> Result<(), DmaError> {
+        self.write_offset = 0;
+        self.available = 0;
+        self.write_descr_ptr = self.descriptors.as_mut_ptr();
+        self.last_seen_handled_descriptor_ptr = self.descriptors.as_mut_ptr();
+        self.buffer_start = core::ptr::null();
+        self.buffer_len = 0;
+
+        self.tx_impl
+            .prepare_chain_without_start(self.descriptors, peri, buffers)
+    }
+
     fn start_transfer(&mut self) -> Result<(), DmaError> {
         self.tx_impl.start_transfer()
     }
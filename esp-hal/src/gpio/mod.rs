@@ -529,6 +529,51 @@ impl BankGpioRegisterAccess for Bank1GpioRegisterAccess {
     }
 }
 
+/// Drives the output of the pin with the given number from the GPIO output
+/// register instead of a peripheral signal.
+///
+/// Used by drivers which need to bit-bang a pin they otherwise route to their
+/// peripheral, see [connect_output_signal] to undo this.
+pub(crate) fn connect_gpio_output(pin: u8) {
+    unsafe { &*GPIO::PTR }
+        .func_out_sel_cfg(pin as usize)
+        .modify(|_, w| unsafe { w.out_sel().bits(OutputSignal::GPIO as OutputSignalType) });
+}
+
+/// Routes a peripheral output signal to the pin with the given number again
+pub(crate) fn connect_output_signal(pin: u8, signal: OutputSignal) {
+    unsafe { &*GPIO::PTR }
+        .func_out_sel_cfg(pin as usize)
+        .modify(|_, w| unsafe { w.out_sel().bits(signal as OutputSignalType) });
+}
+
+/// Sets the GPIO output level of the pin with the given number
+pub(crate) fn set_output_level(pin: u8, high: bool) {
+    match (pin / 32, high) {
+        (0, true) => Bank0GpioRegisterAccess::write_output_set(1 << (pin % 32)),
+        (0, false) => Bank0GpioRegisterAccess::write_output_clear(1 << (pin % 32)),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        (_, true) => Bank1GpioRegisterAccess::write_output_set(1 << (pin % 32)),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        (_, false) => Bank1GpioRegisterAccess::write_output_clear(1 << (pin % 32)),
+        #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2))]
+        _ => unreachable!(),
+    }
+}
+
+/// Reads the input level of the pin with the given number
+pub(crate) fn read_input_level(pin: u8) -> bool {
+    let input = match pin / 32 {
+        0 => Bank0GpioRegisterAccess::read_input(),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        _ => Bank1GpioRegisterAccess::read_input(),
+        #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2))]
+        _ => unreachable!(),
+    };
+
+    input & (1 << (pin % 32)) != 0
+}
+
 /// Connect an always-low signal to the peripheral input signal
 pub fn connect_low_to_peripheral(signal: InputSignal) {
     unsafe { &*GPIO::PTR }
@@ -11,6 +11,29 @@
 //! multiple I2C peripheral instances on `ESP32`, `ESP32H2`, `ESP32S2`, and
 //! `ESP32S3` chips
 //!
+//! Arbitrary sequences of write and read operations, separated by repeated
+//! START conditions, can be executed as one transaction with
+//! [I2C::transaction], which also accepts 10-bit addresses. Transactions which
+//! don't fit into the command RAM or the FIFOs are split transparently.
+//!
+//! The controllers can also be configured as an I2C slave (target) with their
+//! own address, see the [slave] module.
+//!
+//! ## Bus recovery
+//! A slave which is reset in the middle of a transfer may keep SDA low
+//! indefinitely. [I2C::clear_bus] frees such a bus by clocking out up to 9 SCL
+//! pulses, generating a STOP condition and resetting the controller's state
+//! machine and FIFOs. When a transaction fails with [Error::TimeOut] or
+//! [Error::ArbitrationLost] while SDA is held low, this is done automatically
+//! before the error is returned, see [I2C::set_auto_clear_bus].
+//!
+//! Losing arbitration is not an error on a bus with multiple masters: the
+//! other master's transaction continues undisturbed. By default
+//! [Error::ArbitrationLost] is returned to the caller, with
+//! [I2C::set_arbitration_retries] the driver instead waits for the bus to
+//! become idle and restarts the whole transaction. Data read by the failed
+//! attempt is overwritten.
+//!
 //! ## Example
 //! Following code shows how to read data from a BMP180 sensor using I2C.
 //!
@@ -35,7 +58,7 @@
 
 use core::marker::PhantomData;
 
-use fugit::HertzU32;
+use fugit::{HertzU32, MicrosDurationU64};
 
 use crate::{
     clock::Clocks,
@@ -46,6 +69,32 @@ use crate::{
     system::PeripheralClockControl,
 };
 
+pub mod slave;
+
+/// Size of the hardware TX and RX FIFOs
+#[cfg(esp32c2)]
+const I2C_FIFO_SIZE: usize = 16;
+#[cfg(not(esp32c2))]
+const I2C_FIFO_SIZE: usize = 32;
+
+/// Maximum number of bytes a single READ or WRITE command can transfer
+const MAX_CMD_LENGTH: usize = 255;
+
+/// Number of SCL pulses to clear the bus: up to 8 data bits sent by the slave
+/// plus the ACK bit
+const BUS_CLEAR_PULSES: usize = 9;
+
+/// Half of an SCL period while clearing the bus (100 kHz)
+const BUS_CLEAR_HALF_PERIOD_US: u32 = 5;
+
+/// How long SDA has to stay low while SCL is high for the bus to be considered
+/// stuck
+const BUS_STUCK_TIME_US: u64 = 1000;
+
+/// Maximum exponent of the timeout, the width of the `TIME_OUT_VALUE` field
+#[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+const MAX_TIMEOUT_EXPONENT: u32 = 0x1f;
+
 cfg_if::cfg_if! {
     if #[cfg(esp32s2)] {
         const I2C_LL_INTR_MASK: u32 = 0x1ffff;
@@ -64,17 +113,27 @@ pub enum Error {
     ArbitrationLost,
     ExecIncomplete,
     CommandNrExceeded,
+    /// SDA is still held low after trying to clear the bus
+    BusStuck,
+    /// A read operation with an empty buffer was requested, the master has
+    /// to read at least one byte after addressing a slave for a read
+    InvalidZeroLength,
 }
 
-#[cfg(any(feature = "embedded-hal", feature = "async"))]
-#[derive(PartialEq)]
-// This enum is used to keep track of the last operation that was performed
-// in an embedded-hal(-async) I2C::transaction. It used to determine whether
-// a START condition should be issued at the start of the current operation.
-enum LastOpWas {
-    Write,
-    Read,
-    None,
+/// I2C device address, either 7-bit or 10-bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Address {
+    /// 7-bit address, the upper bit is ignored
+    SevenBit(u8),
+    /// 10-bit address, the upper six bits are ignored
+    TenBit(u16),
+}
+
+impl From<u8> for Address {
+    fn from(address: u8) -> Self {
+        Address::SevenBit(address)
+    }
 }
 
 #[cfg(feature = "embedded-hal")]
@@ -86,6 +145,7 @@ impl embedded_hal::i2c::Error for Error {
             Self::ExceedingFifo => ErrorKind::Overrun,
             Self::ArbitrationLost => ErrorKind::ArbitrationLoss,
             Self::AckCheckFailed => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+            Self::BusStuck => ErrorKind::Bus,
             _ => ErrorKind::Other,
         }
     }
@@ -274,10 +334,370 @@ impl From<u32> for Opcode {
     }
 }
 
+/// I2C operation, executed as part of a transaction
+pub enum Operation<'a> {
+    /// Write the bytes to the slave
+    Write(&'a [u8]),
+    /// Read from the slave until the buffer is full
+    Read(&'a mut [u8]),
+}
+
+/// Common access to the operations of a transaction, regardless of whether
+/// they come from this driver or from `embedded-hal`
+trait I2cOperation {
+    fn is_read(&self) -> bool;
+
+    fn len(&self) -> usize;
+
+    /// The bytes to write, empty for reads
+    fn write_buffer(&self) -> &[u8];
+
+    /// The buffer to read into, empty for writes
+    fn read_buffer(&mut self) -> &mut [u8];
+}
+
+impl I2cOperation for Operation<'_> {
+    fn is_read(&self) -> bool {
+        matches!(self, Operation::Read(_))
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Operation::Write(bytes) => bytes.len(),
+            Operation::Read(buffer) => buffer.len(),
+        }
+    }
+
+    fn write_buffer(&self) -> &[u8] {
+        match self {
+            Operation::Write(bytes) => bytes,
+            Operation::Read(_) => &[],
+        }
+    }
+
+    fn read_buffer(&mut self) -> &mut [u8] {
+        match self {
+            Operation::Write(_) => &mut [],
+            Operation::Read(buffer) => buffer,
+        }
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl I2cOperation for embedded_hal::i2c::Operation<'_> {
+    fn is_read(&self) -> bool {
+        matches!(self, embedded_hal::i2c::Operation::Read(_))
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            embedded_hal::i2c::Operation::Write(bytes) => bytes.len(),
+            embedded_hal::i2c::Operation::Read(buffer) => buffer.len(),
+        }
+    }
+
+    fn write_buffer(&self) -> &[u8] {
+        match self {
+            embedded_hal::i2c::Operation::Write(bytes) => bytes,
+            embedded_hal::i2c::Operation::Read(_) => &[],
+        }
+    }
+
+    fn read_buffer(&mut self) -> &mut [u8] {
+        match self {
+            embedded_hal::i2c::Operation::Write(_) => &mut [],
+            embedded_hal::i2c::Operation::Read(buffer) => buffer,
+        }
+    }
+}
+
+/// Where the [CommandList] currently is within an operation
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    /// Issue a (repeated) START
+    Start,
+    /// Send the address, including the R/W bit for 7-bit addresses
+    Address,
+    /// 10-bit reads: repeated START after the full address was sent
+    ReadRestart,
+    /// 10-bit reads: resend the address header with the R/W bit set
+    ReadHeader,
+    /// Transfer the data of the operation
+    Data,
+    /// Issue a STOP, the transaction is complete
+    Stop,
+}
+
+/// Result of programming one segment of a transaction
+struct Segment {
+    /// Number of bytes the segment will leave in the RX FIFO
+    rx_len: usize,
+    /// The segment ends with STOP rather than END
+    last: bool,
+}
+
+/// Packs the operations of a transaction into the command RAM.
+///
+/// The transaction is split into segments which fit into the command RAM (8 or
+/// 16 entries depending on the chip) and whose data fits into the FIFOs. All
+/// segments but the last end with an END command, which pauses the bus with
+/// SCL held low until the next segment is started.
+struct CommandList<'a, O: I2cOperation> {
+    address: Address,
+    operations: &'a mut [O],
+    /// Next operation to program and the offset into its data
+    op: usize,
+    pos: usize,
+    stage: Stage,
+    /// Next operation to receive data for and the offset into its buffer
+    rx_op: usize,
+    rx_pos: usize,
+}
+
+impl<'a, O: I2cOperation> CommandList<'a, O> {
+    fn new(address: Address, operations: &'a mut [O]) -> Result<Self, Error> {
+        if operations.iter().any(|op| op.is_read() && op.len() == 0) {
+            return Err(Error::InvalidZeroLength);
+        }
+
+        Ok(Self {
+            address,
+            operations,
+            op: 0,
+            pos: 0,
+            stage: Stage::Start,
+            rx_op: 0,
+            rx_pos: 0,
+        })
+    }
+
+    /// Bytes sent to address the slave in the [Stage::Address] stage
+    fn address_bytes(&self, read: bool) -> ([u8; 2], usize) {
+        match self.address {
+            Address::SevenBit(address) => {
+                let rw = if read {
+                    OperationType::Read
+                } else {
+                    OperationType::Write
+                };
+                ([address << 1 | rw as u8, 0], 1)
+            }
+            // Always sent with the W bit, reads resend the header with R set
+            // after a repeated START
+            Address::TenBit(address) => ([ten_bit_header(address), address as u8], 2),
+        }
+    }
+
+    /// Whether the read in the current operation is the last one before a
+    /// repeated START or STOP, i.e. whether its last byte has to be NACKed
+    fn ends_read_run(&self) -> bool {
+        match self.operations.get(self.op + 1) {
+            Some(next) => !next.is_read(),
+            None => true,
+        }
+    }
+
+    /// Moves on to the next operation once the current one is complete
+    fn next_operation(&mut self) {
+        let was_read = self.operations[self.op].is_read();
+
+        self.op += 1;
+        self.pos = 0;
+        self.stage = match self.operations.get(self.op) {
+            None => Stage::Stop,
+            // Consecutive operations of the same type are merged
+            Some(op) if op.is_read() == was_read => Stage::Data,
+            Some(_) => Stage::Start,
+        };
+    }
+
+    /// Programs the next segment into the command RAM and queues the bytes
+    /// to write into the TX FIFO
+    fn program_segment(&mut self, register_block: &RegisterBlock) -> Result<Segment, Error> {
+        let cmd_iterator = &mut register_block.comd_iter();
+        // One entry is reserved for the terminating END or STOP
+        let mut slots = register_block.comd_iter().count() - 1;
+        let mut tx_room = I2C_FIFO_SIZE;
+        let mut rx_room = I2C_FIFO_SIZE;
+        let mut rx_len = 0;
+
+        loop {
+            if self.stage == Stage::Stop {
+                add_cmd(cmd_iterator, Command::Stop)?;
+                return Ok(Segment { rx_len, last: true });
+            }
+
+            if slots == 0 {
+                break;
+            }
+
+            match self.stage {
+                Stage::Start | Stage::ReadRestart => {
+                    add_cmd(cmd_iterator, Command::Start)?;
+                    self.stage = if self.stage == Stage::Start {
+                        Stage::Address
+                    } else {
+                        Stage::ReadHeader
+                    };
+                }
+                Stage::Address => {
+                    let read = self.operations[self.op].is_read();
+                    let (bytes, length) = self.address_bytes(read);
+                    if length > tx_room {
+                        break;
+                    }
+
+                    add_cmd(
+                        cmd_iterator,
+                        Command::Write {
+                            ack_exp: Ack::Ack,
+                            ack_check_en: true,
+                            length: length as u8,
+                        },
+                    )?;
+                    for byte in &bytes[..length] {
+                        write_fifo(register_block, *byte);
+                    }
+                    tx_room -= length;
+
+                    self.stage = match self.address {
+                        Address::TenBit(_) if read => Stage::ReadRestart,
+                        _ => Stage::Data,
+                    };
+                }
+                Stage::ReadHeader => {
+                    let Address::TenBit(address) = self.address else {
+                        unreachable!()
+                    };
+                    if tx_room == 0 {
+                        break;
+                    }
+
+                    add_cmd(
+                        cmd_iterator,
+                        Command::Write {
+                            ack_exp: Ack::Ack,
+                            ack_check_en: true,
+                            length: 1,
+                        },
+                    )?;
+                    write_fifo(
+                        register_block,
+                        ten_bit_header(address) | OperationType::Read as u8,
+                    );
+                    tx_room -= 1;
+
+                    self.stage = Stage::Data;
+                }
+                Stage::Data => {
+                    let remaining = self.operations[self.op].len() - self.pos;
+                    if remaining == 0 {
+                        self.next_operation();
+                        continue;
+                    }
+
+                    if self.operations[self.op].is_read() {
+                        let mut length = remaining.min(rx_room).min(MAX_CMD_LENGTH);
+                        // The last byte before a repeated START or STOP is NACKed,
+                        // in a separate command
+                        let ack_value = if self.ends_read_run() && length == remaining {
+                            if remaining == 1 {
+                                Ack::Nack
+                            } else {
+                                length -= 1;
+                                Ack::Ack
+                            }
+                        } else {
+                            Ack::Ack
+                        };
+                        if length == 0 {
+                            break;
+                        }
+
+                        add_cmd(
+                            cmd_iterator,
+                            Command::Read {
+                                ack_value,
+                                length: length as u8,
+                            },
+                        )?;
+                        rx_room -= length;
+                        rx_len += length;
+                        self.pos += length;
+                    } else {
+                        let length = remaining.min(tx_room).min(MAX_CMD_LENGTH);
+                        if length == 0 {
+                            break;
+                        }
+
+                        add_cmd(
+                            cmd_iterator,
+                            Command::Write {
+                                ack_exp: Ack::Ack,
+                                ack_check_en: true,
+                                length: length as u8,
+                            },
+                        )?;
+                        let bytes = &self.operations[self.op].write_buffer()[self.pos..][..length];
+                        for byte in bytes {
+                            write_fifo(register_block, *byte);
+                        }
+                        tx_room -= length;
+                        self.pos += length;
+                    }
+                }
+                Stage::Stop => unreachable!(),
+            }
+
+            slots -= 1;
+        }
+
+        add_cmd(cmd_iterator, Command::End)?;
+        Ok(Segment {
+            rx_len,
+            last: false,
+        })
+    }
+
+    /// Distributes the bytes received in the last segment over the read
+    /// buffers
+    fn read_rx_fifo(&mut self, register_block: &RegisterBlock, mut rx_len: usize) {
+        while rx_len > 0 {
+            let op = &mut self.operations[self.rx_op];
+            if !op.is_read() || self.rx_pos >= op.len() {
+                self.rx_op += 1;
+                self.rx_pos = 0;
+                continue;
+            }
+
+            op.read_buffer()[self.rx_pos] = read_fifo(register_block);
+            self.rx_pos += 1;
+            rx_len -= 1;
+        }
+    }
+}
+
+/// First byte of a 10-bit address: `11110` followed by the two MSBs of the
+/// address and the R/W bit (cleared)
+fn ten_bit_header(address: u16) -> u8 {
+    0xf0 | ((address >> 7) as u8 & 0x06)
+}
+
+/// State of SCL while waiting for another master to release the bus
+struct BusWatch {
+    scl: bool,
+    deadline: fugit::Instant<u64, 1, 1_000_000>,
+}
+
 /// I2C peripheral container (I2C)
 pub struct I2C<'d, T, DM: crate::Mode> {
     peripheral: PeripheralRef<'d, T>,
     phantom: PhantomData<DM>,
+    sda_pin: u8,
+    scl_pin: u8,
+    auto_clear_bus: bool,
+    arbitration_retries: u8,
+    scl_timeout: MicrosDurationU64,
 }
 
 impl<T> I2C<'_, T, crate::Blocking>
@@ -286,12 +706,12 @@ where
 {
     /// Reads enough bytes from slave with `address` to fill `buffer`
     pub fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Error> {
-        self.peripheral.master_read(address, buffer)
+        self.transaction(address.into(), &mut [Operation::Read(buffer)])
     }
 
     /// Writes bytes to slave with address `address`
     pub fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
-        self.peripheral.master_write(addr, bytes)
+        self.transaction(addr.into(), &mut [Operation::Write(bytes)])
     }
 
     /// Writes bytes to slave with address `address` and then reads enough bytes
@@ -302,7 +722,27 @@ where
         bytes: &[u8],
         buffer: &mut [u8],
     ) -> Result<(), Error> {
-        self.peripheral.master_write_read(address, bytes, buffer)
+        self.transaction(
+            address.into(),
+            &mut [Operation::Write(bytes), Operation::Read(buffer)],
+        )
+    }
+
+    /// Executes a sequence of operations on the slave with `address` as a
+    /// single transaction.
+    ///
+    /// A START is issued before the first operation, a repeated START
+    /// between operations of different type and a STOP after the last
+    /// operation. Consecutive operations of the same type are merged.
+    ///
+    /// Read operations with an empty buffer are rejected with
+    /// [Error::InvalidZeroLength].
+    pub fn transaction(
+        &mut self,
+        address: Address,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error> {
+        self.execute(address, operations)
     }
 }
 
@@ -314,7 +754,7 @@ where
     type Error = Error;
 
     fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        self.peripheral.master_read(address, buffer)
+        self.transaction(address.into(), &mut [Operation::Read(buffer)])
     }
 }
 
@@ -326,7 +766,7 @@ where
     type Error = Error;
 
     fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-        self.peripheral.master_write(addr, bytes)
+        self.transaction(addr.into(), &mut [Operation::Write(bytes)])
     }
 }
 
@@ -343,7 +783,10 @@ where
         bytes: &[u8],
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
-        self.peripheral.master_write_read(address, bytes, buffer)
+        self.transaction(
+            address.into(),
+            &mut [Operation::Write(bytes), Operation::Read(buffer)],
+        )
     }
 }
 
@@ -362,45 +805,21 @@ where
         address: u8,
         operations: &mut [embedded_hal::i2c::Operation<'_>],
     ) -> Result<(), Self::Error> {
-        use embedded_hal::i2c::Operation;
-        let mut last_op = LastOpWas::None;
-        let mut op_iter = operations.iter_mut().peekable();
-        while let Some(op) = op_iter.next() {
-            // Clear all I2C interrupts
-            self.peripheral.clear_all_interrupts();
+        self.execute(Address::SevenBit(address), operations)
+    }
+}
 
-            // TODO somehow know that we can combine a write and a read into one transaction
-            let cmd_iterator = &mut self.peripheral.register_block().comd_iter();
-            match op {
-                Operation::Write(bytes) => {
-                    // execute a write operation:
-                    // - issue START/RSTART if op is different from previous
-                    // - issue STOP if op is the last one
-                    self.peripheral.write_operation(
-                        address,
-                        bytes,
-                        last_op != LastOpWas::Write,
-                        op_iter.peek().is_none(),
-                        cmd_iterator,
-                    )?;
-                    last_op = LastOpWas::Write;
-                }
-                Operation::Read(buffer) => {
-                    // execute a read operation:
-                    // - issue START/RSTART if op is different from previous
-                    // - issue STOP if op is the last one
-                    self.peripheral.read_operation(
-                        address,
-                        buffer,
-                        last_op != LastOpWas::Read,
-                        op_iter.peek().is_none(),
-                        cmd_iterator,
-                    )?;
-                    last_op = LastOpWas::Read;
-                }
-            }
-        }
-        Ok(())
+#[cfg(feature = "embedded-hal")]
+impl<T, DM: crate::Mode> embedded_hal::i2c::I2c<embedded_hal::i2c::TenBitAddress> for I2C<'_, T, DM>
+where
+    T: Instance,
+{
+    fn transaction(
+        &mut self,
+        address: u16,
+        operations: &mut [embedded_hal::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.execute(Address::TenBit(address), operations)
     }
 }
 
@@ -419,47 +838,21 @@ where
     ) -> Self {
         crate::into_ref!(i2c, sda, scl);
 
-        PeripheralClockControl::enable(match i2c.i2c_number() {
-            0 => crate::system::Peripheral::I2cExt0,
-            #[cfg(i2c1)]
-            1 => crate::system::Peripheral::I2cExt1,
-            _ => unreachable!(), // will never happen
-        });
+        enable_peripheral(&*i2c);
 
         let mut i2c = I2C {
             peripheral: i2c,
             phantom: PhantomData,
+            sda_pin: sda.number(crate::private::Internal),
+            scl_pin: scl.number(crate::private::Internal),
+            auto_clear_bus: true,
+            arbitration_retries: 0,
+            scl_timeout: MicrosDurationU64::micros(0),
         };
 
-        // avoid SCL/SDA going low during configuration
-        scl.set_output_high(true, crate::private::Internal);
-        sda.set_output_high(true, crate::private::Internal);
-
-        scl.set_to_open_drain_output(crate::private::Internal);
-        scl.enable_input(true, crate::private::Internal);
-        scl.internal_pull_up(true, crate::private::Internal);
-        scl.connect_peripheral_to_output(
-            i2c.peripheral.scl_output_signal(),
-            crate::private::Internal,
-        );
-        scl.connect_input_to_peripheral(
-            i2c.peripheral.scl_input_signal(),
-            crate::private::Internal,
-        );
-
-        sda.set_to_open_drain_output(crate::private::Internal);
-        sda.enable_input(true, crate::private::Internal);
-        sda.internal_pull_up(true, crate::private::Internal);
-        sda.connect_peripheral_to_output(
-            i2c.peripheral.sda_output_signal(),
-            crate::private::Internal,
-        );
-        sda.connect_input_to_peripheral(
-            i2c.peripheral.sda_input_signal(),
-            crate::private::Internal,
-        );
+        connect_pins(&*i2c.peripheral, &mut *sda, &mut *scl);
 
-        i2c.peripheral.setup(frequency, clocks, timeout);
+        i2c.scl_timeout = i2c.peripheral.setup(frequency, clocks, timeout);
 
         if let Some(interrupt) = isr {
             unsafe {
@@ -470,6 +863,225 @@ where
 
         i2c
     }
+
+    /// Enables or disables clearing a stuck bus automatically when a
+    /// transaction fails, see [I2C::clear_bus]. Enabled by default.
+    pub fn set_auto_clear_bus(&mut self, enable: bool) {
+        self.auto_clear_bus = enable;
+    }
+
+    /// Sets how often a transaction is restarted after another master won
+    /// arbitration. Defaults to 0, i.e. [Error::ArbitrationLost] is returned
+    /// right away.
+    ///
+    /// Before retrying, the driver waits for the other master to finish. If
+    /// SCL stops changing for longer than the configured timeout meanwhile,
+    /// [Error::TimeOut] is returned.
+    pub fn set_arbitration_retries(&mut self, retries: u8) {
+        self.arbitration_retries = retries;
+    }
+
+    /// Frees a bus on which a slave holds SDA low.
+    ///
+    /// Clocks out up to 9 SCL pulses until the slave releases SDA, generates a
+    /// STOP condition and resets the controller's state machine and FIFOs.
+    /// Returns [Error::BusStuck] if SDA is still held low afterwards.
+    pub fn clear_bus(&mut self) -> Result<(), Error> {
+        use crate::gpio::{
+            connect_gpio_output,
+            connect_output_signal,
+            read_input_level,
+            set_output_level,
+        };
+
+        let (sda, scl) = (self.sda_pin, self.scl_pin);
+
+        // Take over both lines, they stay open-drain
+        set_output_level(sda, true);
+        set_output_level(scl, true);
+        connect_gpio_output(sda);
+        connect_gpio_output(scl);
+        crate::rom::ets_delay_us(BUS_CLEAR_HALF_PERIOD_US);
+
+        // The slave releases SDA at the latest after the remaining bits of the
+        // byte it is sending and the (missing) ACK
+        for _ in 0..BUS_CLEAR_PULSES {
+            if read_input_level(sda) {
+                break;
+            }
+
+            set_output_level(scl, false);
+            crate::rom::ets_delay_us(BUS_CLEAR_HALF_PERIOD_US);
+            set_output_level(scl, true);
+            crate::rom::ets_delay_us(BUS_CLEAR_HALF_PERIOD_US);
+        }
+
+        // STOP: SDA goes low -> high while SCL is high
+        set_output_level(scl, false);
+        crate::rom::ets_delay_us(BUS_CLEAR_HALF_PERIOD_US);
+        set_output_level(sda, false);
+        crate::rom::ets_delay_us(BUS_CLEAR_HALF_PERIOD_US);
+        set_output_level(scl, true);
+        crate::rom::ets_delay_us(BUS_CLEAR_HALF_PERIOD_US);
+        set_output_level(sda, true);
+        crate::rom::ets_delay_us(BUS_CLEAR_HALF_PERIOD_US);
+
+        let released = read_input_level(sda);
+
+        connect_output_signal(sda, self.peripheral.sda_output_signal());
+        connect_output_signal(scl, self.peripheral.scl_output_signal());
+
+        self.peripheral.reset_fsm();
+        self.peripheral.reset();
+
+        if released {
+            Ok(())
+        } else {
+            Err(Error::BusStuck)
+        }
+    }
+
+    fn sda_held_low(&self) -> bool {
+        use crate::gpio::read_input_level;
+
+        !read_input_level(self.sda_pin) && read_input_level(self.scl_pin)
+    }
+
+    /// SDA is held low while SCL idles high for longer than any legitimate
+    /// transfer would keep it there. Returns as soon as SDA is released.
+    fn sda_stuck_low(&self) -> bool {
+        let deadline = crate::time::current_time() + MicrosDurationU64::micros(BUS_STUCK_TIME_US);
+        while self.sda_held_low() {
+            if crate::time::current_time() > deadline {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Applies the recovery policy after a failed transaction attempt.
+    /// Returns `true` if the transaction should be restarted.
+    fn recover(&mut self, error: Error, retries: &mut u8) -> bool {
+        if !Self::is_recoverable(error) {
+            return false;
+        }
+
+        let stuck = self.auto_clear_bus && self.sda_stuck_low();
+        self.reset_after_error(error, stuck, retries)
+    }
+
+    fn is_recoverable(error: Error) -> bool {
+        matches!(error, Error::TimeOut | Error::ArbitrationLost)
+    }
+
+    /// Clears a stuck bus or resets the controller after a failed attempt.
+    /// Returns `true` if the transaction should be restarted.
+    fn reset_after_error(&mut self, error: Error, stuck: bool, retries: &mut u8) -> bool {
+        if stuck {
+            if self.clear_bus().is_err() {
+                return false;
+            }
+        } else {
+            self.peripheral.reset_fsm();
+            self.peripheral.reset();
+        }
+
+        if error == Error::ArbitrationLost && *retries > 0 {
+            *retries -= 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn bus_busy(&self) -> bool {
+        self.peripheral
+            .register_block()
+            .sr()
+            .read()
+            .bus_busy()
+            .bit_is_set()
+    }
+
+    fn watch_bus(&self) -> BusWatch {
+        BusWatch {
+            scl: crate::gpio::read_input_level(self.scl_pin),
+            deadline: crate::time::current_time() + self.scl_timeout,
+        }
+    }
+
+    /// Checks whether the transaction of another master is still running.
+    ///
+    /// Fails with [Error::TimeOut] if SCL did not change for longer than the
+    /// configured timeout while the bus is busy.
+    fn poll_bus_busy(&self, watch: &mut BusWatch) -> Result<bool, Error> {
+        if !self.bus_busy() {
+            return Ok(false);
+        }
+
+        let scl = crate::gpio::read_input_level(self.scl_pin);
+        let now = crate::time::current_time();
+        if scl != watch.scl {
+            watch.scl = scl;
+            watch.deadline = now + self.scl_timeout;
+        } else if now > watch.deadline {
+            return Err(Error::TimeOut);
+        }
+
+        Ok(true)
+    }
+
+    /// Runs a transaction, applying the recovery policy on failure
+    fn execute<O: I2cOperation>(
+        &mut self,
+        address: Address,
+        operations: &mut [O],
+    ) -> Result<(), Error> {
+        let mut retries = self.arbitration_retries;
+
+        loop {
+            match self.execute_once(address, operations) {
+                Err(error) if self.recover(error, &mut retries) => {
+                    // Let the master which won arbitration finish first
+                    let mut watch = self.watch_bus();
+                    while self.poll_bus_busy(&mut watch)? {}
+                }
+                result => break result,
+            }
+        }
+    }
+
+    /// Runs a transaction segment by segment, waiting for each segment to
+    /// complete by busy-polling
+    fn execute_once<O: I2cOperation>(
+        &mut self,
+        address: Address,
+        operations: &mut [O],
+    ) -> Result<(), Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+
+        let register_block = self.peripheral.register_block();
+        let mut commands = CommandList::new(address, operations)?;
+
+        self.peripheral.reset_fifo();
+        loop {
+            self.peripheral.clear_all_interrupts();
+            self.peripheral.reset_command_list();
+
+            let segment = commands.program_segment(register_block)?;
+            self.peripheral.update_config();
+            self.peripheral.start_transmission();
+            self.peripheral.wait_for_completion(!segment.last)?;
+
+            commands.read_rx_fifo(register_block, segment.rx_len);
+            if segment.last {
+                break Ok(());
+            }
+        }
+    }
 }
 
 impl<'d, T> I2C<'d, T, crate::Blocking>
@@ -493,6 +1105,10 @@ where
     /// Create a new I2C instance with a custom timeout value.
     /// This will enable the peripheral but the peripheral won't get
     /// automatically disabled when this gets dropped.
+    ///
+    /// On ESP32-C2, ESP32-C3, ESP32-C6, ESP32-H2 and ESP32-S3 the timeout is
+    /// the power of two of divided source clock cycles, values above 31 are
+    /// clamped.
     pub fn new_with_timeout<SDA: OutputPin + InputPin, SCL: OutputPin + InputPin>(
         i2c: impl Peripheral<P = T> + 'd,
         sda: impl Peripheral<P = SDA> + 'd,
@@ -527,6 +1143,8 @@ where
     /// Create a new I2C instance with a custom timeout value.
     /// This will enable the peripheral but the peripheral won't get
     /// automatically disabled when this gets dropped.
+    ///
+    /// See [I2C::new_with_timeout] for the meaning of the timeout.
     pub fn new_with_timeout_async<SDA: OutputPin + InputPin, SCL: OutputPin + InputPin>(
         i2c: impl Peripheral<P = T> + 'd,
         sda: impl Peripheral<P = SDA> + 'd,
@@ -557,7 +1175,7 @@ mod asynch {
     };
 
     use cfg_if::cfg_if;
-    use embassy_futures::select::select;
+    use embassy_futures::{select::select, yield_now};
     use embassy_sync::waitqueue::AtomicWaker;
     use embedded_hal::i2c::Operation;
     use procmacros::handler;
@@ -578,8 +1196,6 @@ mod asynch {
     pub(crate) enum Event {
         EndDetect,
         TxComplete,
-        #[cfg(not(any(esp32, esp32s2)))]
-        TxFifoWatermark,
     }
 
     pub(crate) struct I2cFuture<'a, T>
@@ -601,8 +1217,6 @@ mod asynch {
                 .modify(|_, w| match event {
                     Event::EndDetect => w.end_detect().set_bit(),
                     Event::TxComplete => w.trans_complete().set_bit(),
-                    #[cfg(not(any(esp32, esp32s2)))]
-                    Event::TxFifoWatermark => w.txfifo_wm().set_bit(),
                 });
 
             Self { event, instance }
@@ -614,8 +1228,6 @@ mod asynch {
             match self.event {
                 Event::EndDetect => r.end_detect().bit_is_clear(),
                 Event::TxComplete => r.trans_complete().bit_is_clear(),
-                #[cfg(not(any(esp32, esp32s2)))]
-                Event::TxFifoWatermark => r.txfifo_wm().bit_is_clear(),
             }
         }
     }
@@ -637,76 +1249,10 @@ mod asynch {
         }
     }
 
-    impl<T> I2C<'_, T, crate::Async>
-    where
-        T: Instance,
-    {
-        #[cfg(any(esp32, esp32s2))]
-        async fn read_all_from_fifo(&self, buffer: &mut [u8]) -> Result<(), Error> {
-            if buffer.len() > 32 {
-                panic!("On ESP32 and ESP32-S2 the max I2C read is limited to 32 bytes");
-            }
-
-            self.wait_for_completion(false).await?;
-
-            for byte in buffer.iter_mut() {
-                *byte = read_fifo(self.peripheral.register_block());
-            }
-
-            Ok(())
-        }
-
-        #[cfg(not(any(esp32, esp32s2)))]
-        async fn read_all_from_fifo(&self, buffer: &mut [u8]) -> Result<(), Error> {
-            self.peripheral.read_all_from_fifo(buffer)
-        }
-
-        #[cfg(any(esp32, esp32s2))]
-        async fn write_remaining_tx_fifo(
-            &self,
-            start_index: usize,
-            bytes: &[u8],
-        ) -> Result<(), Error> {
-            if start_index >= bytes.len() {
-                return Ok(());
-            }
-
-            for b in bytes {
-                write_fifo(self.peripheral.register_block(), *b);
-                self.peripheral.check_errors()?;
-            }
-
-            Ok(())
-        }
-
-        #[cfg(not(any(esp32, esp32s2)))]
-        async fn write_remaining_tx_fifo(
-            &self,
-            start_index: usize,
-            bytes: &[u8],
-        ) -> Result<(), Error> {
-            let mut index = start_index;
-            loop {
-                self.peripheral.check_errors()?;
-
-                I2cFuture::new(Event::TxFifoWatermark, self.inner()).await;
-
-                self.peripheral
-                    .register_block()
-                    .int_clr()
-                    .write(|w| w.txfifo_wm().clear_bit_by_one());
-
-                I2cFuture::new(Event::TxFifoWatermark, self.inner()).await;
-
-                if index >= bytes.len() {
-                    break Ok(());
-                }
-
-                write_fifo(self.peripheral.register_block(), bytes[index]);
-                index += 1;
-            }
-        }
-
+    impl<T> I2C<'_, T, crate::Async>
+    where
+        T: Instance,
+    {
         async fn wait_for_completion(&self, end_only: bool) -> Result<(), Error> {
             self.peripheral.check_errors()?;
 
@@ -719,148 +1265,102 @@ mod asynch {
                 )
                 .await;
             }
+            self.peripheral.check_errors()?;
             self.peripheral.check_all_commands_done()?;
 
             Ok(())
         }
 
-        async fn write_operation<'a, I>(
-            &self,
-            address: u8,
-            bytes: &[u8],
-            start: bool,
-            stop: bool,
-            cmd_iterator: &mut I,
-        ) -> Result<(), Error>
-        where
-            I: Iterator<Item = &'a COMD>,
-        {
-            // Reset FIFO and command list
-            self.peripheral.reset_fifo();
-            self.peripheral.reset_command_list();
-            if start {
-                add_cmd(cmd_iterator, Command::Start)?;
-            }
-            self.peripheral.setup_write(address, bytes, cmd_iterator)?;
-            add_cmd(
-                cmd_iterator,
-                if stop { Command::Stop } else { Command::End },
-            )?;
-            let index = self.peripheral.fill_tx_fifo(bytes);
-            self.peripheral.start_transmission();
+        /// Runs a transaction, applying the recovery policy on failure
+        async fn execute_async<O: I2cOperation>(
+            &mut self,
+            address: Address,
+            operations: &mut [O],
+        ) -> Result<(), Error> {
+            let mut retries = self.arbitration_retries;
 
-            // Fill the FIFO with the remaining bytes:
-            self.write_remaining_tx_fifo(index, bytes).await?;
-            self.wait_for_completion(!stop).await?;
-            Ok(())
-        }
+            loop {
+                let result = self.execute_once_async(address, operations).await;
+                if let Err(error) = result {
+                    if self.recover_async(error, &mut retries).await {
+                        // Let the master which won arbitration finish first
+                        let mut watch = self.watch_bus();
+                        while self.poll_bus_busy(&mut watch)? {
+                            yield_now().await;
+                        }
+                        continue;
+                    }
+                }
 
-        async fn read_operation<'a, I>(
-            &self,
-            address: u8,
-            buffer: &mut [u8],
-            start: bool,
-            stop: bool,
-            cmd_iterator: &mut I,
-        ) -> Result<(), Error>
-        where
-            I: Iterator<Item = &'a COMD>,
-        {
-            // Reset FIFO and command list
-            self.peripheral.reset_fifo();
-            self.peripheral.reset_command_list();
-            if start {
-                add_cmd(cmd_iterator, Command::Start)?;
+                break result;
             }
-            self.peripheral.setup_read(address, buffer, cmd_iterator)?;
-            add_cmd(
-                cmd_iterator,
-                if stop { Command::Stop } else { Command::End },
-            )?;
-            self.peripheral.start_transmission();
-            self.read_all_from_fifo(buffer).await?;
-            self.wait_for_completion(!stop).await?;
-            Ok(())
         }
 
-        /// Send data bytes from the `bytes` array to a target slave with the
-        /// address `addr`
-        async fn master_write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
-            // Clear all I2C interrupts
-            self.peripheral.clear_all_interrupts();
-            self.write_operation(
-                addr,
-                bytes,
-                true,
-                true,
-                &mut self.peripheral.register_block().comd_iter(),
-            )
-            .await?;
-            Ok(())
+        /// Applies the recovery policy without blocking the executor while
+        /// checking whether a slave holds SDA low
+        async fn recover_async(&mut self, error: Error, retries: &mut u8) -> bool {
+            if !Self::is_recoverable(error) {
+                return false;
+            }
+
+            let stuck = self.auto_clear_bus && self.sda_stuck_low_async().await;
+            self.reset_after_error(error, stuck, retries)
         }
 
-        /// Read bytes from a target slave with the address `addr`
-        /// The number of read bytes is deterimed by the size of the `buffer`
-        /// argument
-        async fn master_read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
-            // Clear all I2C interrupts
-            self.peripheral.clear_all_interrupts();
-            self.read_operation(
-                addr,
-                buffer,
-                true,
-                true,
-                &mut self.peripheral.register_block().comd_iter(),
-            )
-            .await?;
-            Ok(())
+        async fn sda_stuck_low_async(&self) -> bool {
+            let deadline =
+                crate::time::current_time() + MicrosDurationU64::micros(BUS_STUCK_TIME_US);
+            while self.sda_held_low() {
+                if crate::time::current_time() > deadline {
+                    return true;
+                }
+                yield_now().await;
+            }
+
+            false
         }
 
-        /// Write bytes from the `bytes` array first and then read n bytes into
-        /// the `buffer` array with n being the size of the array.
-        async fn master_write_read(
+        /// Runs a transaction segment by segment, waiting for each segment to
+        /// complete on the I2C interrupt
+        async fn execute_once_async<O: I2cOperation>(
             &mut self,
-            addr: u8,
-            bytes: &[u8],
-            buffer: &mut [u8],
+            address: Address,
+            operations: &mut [O],
         ) -> Result<(), Error> {
-            // it would be possible to combine the write and read
-            // in one transaction but filling the tx fifo with
-            // the current code is somewhat slow even in release mode
-            // which can cause issues
+            if operations.is_empty() {
+                return Ok(());
+            }
 
-            // Clear all I2C interrupts
-            self.peripheral.clear_all_interrupts();
-            self.write_operation(
-                addr,
-                bytes,
-                true,
-                false,
-                &mut self.peripheral.register_block().comd_iter(),
-            )
-            .await?;
-            self.peripheral.clear_all_interrupts();
-            self.read_operation(
-                addr,
-                buffer,
-                true,
-                true,
-                &mut self.peripheral.register_block().comd_iter(),
-            )
-            .await?;
-            Ok(())
+            let register_block = self.peripheral.register_block();
+            let mut commands = CommandList::new(address, operations)?;
+
+            self.peripheral.reset_fifo();
+            loop {
+                self.peripheral.clear_all_interrupts();
+                self.peripheral.reset_command_list();
+
+                let segment = commands.program_segment(register_block)?;
+                self.peripheral.update_config();
+                self.peripheral.start_transmission();
+                self.wait_for_completion(!segment.last).await?;
+
+                commands.read_rx_fifo(register_block, segment.rx_len);
+                if segment.last {
+                    break Ok(());
+                }
+            }
         }
 
         /// Writes bytes to slave with address `address`
         pub async fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
-            self.master_write(addr, bytes).await?;
-            Ok(())
+            self.execute_async(addr.into(), &mut [super::Operation::Write(bytes)])
+                .await
         }
 
         /// Reads enough bytes from slave with `address` to fill `buffer`
         pub async fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
-            self.master_read(addr, buffer).await?;
-            Ok(())
+            self.execute_async(addr.into(), &mut [super::Operation::Read(buffer)])
+                .await
         }
 
         /// Writes bytes to slave with address `address` and then reads enough
@@ -871,8 +1371,24 @@ mod asynch {
             bytes: &[u8],
             buffer: &mut [u8],
         ) -> Result<(), Error> {
-            self.master_write_read(addr, bytes, buffer).await?;
-            Ok(())
+            self.execute_async(
+                addr.into(),
+                &mut [
+                    super::Operation::Write(bytes),
+                    super::Operation::Read(buffer),
+                ],
+            )
+            .await
+        }
+
+        /// Executes a sequence of operations on the slave with `address` as a
+        /// single transaction, see [I2C::transaction]
+        pub async fn transaction(
+            &mut self,
+            address: Address,
+            operations: &mut [super::Operation<'_>],
+        ) -> Result<(), Error> {
+            self.execute_async(address, operations).await
         }
     }
 
@@ -886,54 +1402,32 @@ mod asynch {
             address: u8,
             operations: &mut [Operation<'_>],
         ) -> Result<(), Self::Error> {
-            let mut last_op = LastOpWas::None;
-            let mut op_iter = operations.iter_mut().peekable();
-            while let Some(op) = op_iter.next() {
-                // Clear all I2C interrupts
-                self.peripheral.clear_all_interrupts();
-
-                let cmd_iterator = &mut self.peripheral.register_block().comd_iter();
-                match op {
-                    Operation::Write(bytes) => {
-                        self.write_operation(
-                            address,
-                            bytes,
-                            last_op != LastOpWas::Write,
-                            op_iter.peek().is_none(),
-                            cmd_iterator,
-                        )
-                        .await?;
-                        last_op = LastOpWas::Write;
-                    }
-                    Operation::Read(buffer) => {
-                        // execute a read operation:
-                        // - issue START/RSTART if op is different from previous
-                        // - issue STOP if op is the last one
-                        self.read_operation(
-                            address,
-                            buffer,
-                            last_op != LastOpWas::Read,
-                            op_iter.peek().is_none(),
-                            cmd_iterator,
-                        )
-                        .await?;
-                        last_op = LastOpWas::Read;
-                    }
-                }
-            }
-            Ok(())
+            self.execute_async(Address::SevenBit(address), operations)
+                .await
         }
     }
 
+    #[cfg(feature = "embedded-hal")]
+    impl<'d, T> embedded_hal_async::i2c::I2c<embedded_hal::i2c::TenBitAddress>
+        for I2C<'d, T, crate::Async>
+    where
+        T: Instance,
+    {
+        async fn transaction(
+            &mut self,
+            address: u16,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.execute_async(Address::TenBit(address), operations)
+                .await
+        }
+    }
     #[handler]
     pub(super) fn i2c0_handler() {
         let regs = unsafe { &*crate::peripherals::I2C0::PTR };
         regs.int_ena()
             .modify(|_, w| w.end_detect().clear_bit().trans_complete().clear_bit());
 
-        #[cfg(not(any(esp32, esp32s2)))]
-        regs.int_ena().modify(|_, w| w.txfifo_wm().clear_bit());
-
         WAKERS[0].wake();
     }
 
@@ -944,9 +1438,6 @@ mod asynch {
         regs.int_ena()
             .modify(|_, w| w.end_detect().clear_bit().trans_complete().clear_bit());
 
-        #[cfg(not(any(esp32, esp32s2)))]
-        regs.int_ena().modify(|_, w| w.txfifo_wm().clear_bit());
-
         WAKERS[1].wake();
     }
 }
@@ -966,7 +1457,14 @@ pub trait Instance: crate::private::Sealed {
 
     fn i2c_number(&self) -> usize;
 
-    fn setup(&mut self, frequency: HertzU32, clocks: &Clocks, timeout: Option<u32>) {
+    /// Configures the controller as master, returns how long SCL may stay
+    /// unchanged before a transaction times out
+    fn setup(
+        &mut self,
+        frequency: HertzU32,
+        clocks: &Clocks,
+        timeout: Option<u32>,
+    ) -> MicrosDurationU64 {
         self.register_block().ctr().modify(|_, w| unsafe {
             // Clear register
             w.bits(0)
@@ -999,16 +1497,18 @@ pub trait Instance: crate::private::Sealed {
 
         // Configure frequency
         #[cfg(esp32)]
-        self.set_frequency(clocks.i2c_clock.convert(), frequency, timeout);
+        let scl_timeout = self.set_frequency(clocks.i2c_clock.convert(), frequency, timeout);
         #[cfg(esp32s2)]
-        self.set_frequency(clocks.apb_clock.convert(), frequency, timeout);
+        let scl_timeout = self.set_frequency(clocks.apb_clock.convert(), frequency, timeout);
         #[cfg(not(any(esp32, esp32s2)))]
-        self.set_frequency(clocks.xtal_clock.convert(), frequency, timeout);
+        let scl_timeout = self.set_frequency(clocks.xtal_clock.convert(), frequency, timeout);
 
         self.update_config();
 
         // Reset entire peripheral (also resets fifo)
         self.reset();
+
+        scl_timeout
     }
 
     /// Resets the I2C controller (FIFO + FSM + command list)
@@ -1033,6 +1533,66 @@ pub trait Instance: crate::private::Sealed {
         self.reset_command_list();
     }
 
+    /// Resets the controller's state machine, e.g. after the bus was cleared
+    /// or arbitration was lost
+    #[cfg(not(esp32))]
+    fn reset_fsm(&self) {
+        self.register_block()
+            .ctr()
+            .modify(|_, w| w.fsm_rst().set_bit());
+        self.update_config();
+    }
+
+    /// Resets the controller's state machine, e.g. after the bus was cleared
+    /// or arbitration was lost
+    ///
+    /// The ESP32 has no dedicated FSM reset, so the whole peripheral is reset
+    /// and its configuration restored afterwards.
+    #[cfg(esp32)]
+    fn reset_fsm(&self) {
+        let regs = self.register_block();
+
+        let ctr = regs.ctr().read().bits();
+        let fifo_conf = regs.fifo_conf().read().bits();
+        let scl_low_period = regs.scl_low_period().read().bits();
+        let scl_high_period = regs.scl_high_period().read().bits();
+        let sda_hold = regs.sda_hold().read().bits();
+        let sda_sample = regs.sda_sample().read().bits();
+        let scl_rstart_setup = regs.scl_rstart_setup().read().bits();
+        let scl_stop_setup = regs.scl_stop_setup().read().bits();
+        let scl_start_hold = regs.scl_start_hold().read().bits();
+        let scl_stop_hold = regs.scl_stop_hold().read().bits();
+        let to = regs.to().read().bits();
+        let sda_filter_cfg = regs.sda_filter_cfg().read().bits();
+        let scl_filter_cfg = regs.scl_filter_cfg().read().bits();
+        let slave_addr = regs.slave_addr().read().bits();
+        let int_ena = regs.int_ena().read().bits();
+
+        PeripheralClockControl::reset(match self.i2c_number() {
+            0 => crate::system::Peripheral::I2cExt0,
+            1 => crate::system::Peripheral::I2cExt1,
+            _ => unreachable!(), // will never happen
+        });
+
+        unsafe {
+            regs.ctr().write(|w| w.bits(ctr));
+            regs.fifo_conf().write(|w| w.bits(fifo_conf));
+            regs.scl_low_period().write(|w| w.bits(scl_low_period));
+            regs.scl_high_period().write(|w| w.bits(scl_high_period));
+            regs.sda_hold().write(|w| w.bits(sda_hold));
+            regs.sda_sample().write(|w| w.bits(sda_sample));
+            regs.scl_rstart_setup().write(|w| w.bits(scl_rstart_setup));
+            regs.scl_stop_setup().write(|w| w.bits(scl_stop_setup));
+            regs.scl_start_hold().write(|w| w.bits(scl_start_hold));
+            regs.scl_stop_hold().write(|w| w.bits(scl_stop_hold));
+            regs.to().write(|w| w.bits(to));
+            regs.sda_filter_cfg().write(|w| w.bits(sda_filter_cfg));
+            regs.scl_filter_cfg().write(|w| w.bits(scl_filter_cfg));
+            regs.slave_addr().write(|w| w.bits(slave_addr));
+            regs.int_ena().write(|w| w.bits(int_ena));
+        }
+    }
+
     /// Resets the I2C peripheral's command registers
     fn reset_command_list(&self) {
         // Confirm that all commands that were configured were actually executed
@@ -1074,8 +1634,13 @@ pub trait Instance: crate::private::Sealed {
     #[cfg(esp32)]
     /// Sets the frequency of the I2C interface by calculating and applying the
     /// associated timings - corresponds to i2c_ll_cal_bus_clk and
-    /// i2c_ll_set_bus_timing in ESP-IDF
-    fn set_frequency(&mut self, source_clk: HertzU32, bus_freq: HertzU32, timeout: Option<u32>) {
+    /// i2c_ll_set_bus_timing in ESP-IDF. Returns the configured timeout.
+    fn set_frequency(
+        &mut self,
+        source_clk: HertzU32,
+        bus_freq: HertzU32,
+        timeout: Option<u32>,
+    ) -> MicrosDurationU64 {
         let source_clk = source_clk.raw();
         let bus_freq = bus_freq.raw();
 
@@ -1147,13 +1712,21 @@ pub trait Instance: crate::private::Sealed {
             time_out_value,
             true,
         );
+
+        // The timeout is given in source clock cycles
+        MicrosDurationU64::micros(tout as u64 * 1_000_000 / source_clk as u64)
     }
 
     #[cfg(esp32s2)]
     /// Sets the frequency of the I2C interface by calculating and applying the
     /// associated timings - corresponds to i2c_ll_cal_bus_clk and
-    /// i2c_ll_set_bus_timing in ESP-IDF
-    fn set_frequency(&mut self, source_clk: HertzU32, bus_freq: HertzU32, timeout: Option<u32>) {
+    /// i2c_ll_set_bus_timing in ESP-IDF. Returns the configured timeout.
+    fn set_frequency(
+        &mut self,
+        source_clk: HertzU32,
+        bus_freq: HertzU32,
+        timeout: Option<u32>,
+    ) -> MicrosDurationU64 {
         let source_clk = source_clk.raw();
         let bus_freq = bus_freq.raw();
 
@@ -1205,13 +1778,21 @@ pub trait Instance: crate::private::Sealed {
             time_out_value,
             time_out_en,
         );
+
+        // The timeout is given in source clock cycles
+        MicrosDurationU64::micros(tout as u64 * 1_000_000 / source_clk as u64)
     }
 
     #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
     /// Sets the frequency of the I2C interface by calculating and applying the
     /// associated timings - corresponds to i2c_ll_cal_bus_clk and
-    /// i2c_ll_set_bus_timing in ESP-IDF
-    fn set_frequency(&mut self, source_clk: HertzU32, bus_freq: HertzU32, timeout: Option<u32>) {
+    /// i2c_ll_set_bus_timing in ESP-IDF. Returns the configured timeout.
+    fn set_frequency(
+        &mut self,
+        source_clk: HertzU32,
+        bus_freq: HertzU32,
+        timeout: Option<u32>,
+    ) -> MicrosDurationU64 {
         let source_clk = source_clk.raw();
         let bus_freq = bus_freq.raw();
 
@@ -1236,7 +1817,7 @@ pub trait Instance: crate::private::Sealed {
         let hold = half_cycle;
 
         let tout = if let Some(timeout) = timeout {
-            timeout
+            timeout.min(MAX_TIMEOUT_EXPONENT)
         } else {
             // default we set the timeout value to about 10 bus cycles
             // log(20*half_cycle)/log(2) = log(half_cycle)/log(2) +  log(20)/log(2)
@@ -1279,6 +1860,9 @@ pub trait Instance: crate::private::Sealed {
             time_out_value,
             time_out_en,
         );
+
+        // The timeout is given as power of two of divided source clock cycles
+        MicrosDurationU64::micros((1u64 << tout) * 1_000_000 / sclk_freq as u64)
     }
 
     #[allow(clippy::too_many_arguments, unused)]
@@ -1388,134 +1972,6 @@ pub trait Instance: crate::private::Sealed {
         }
     }
 
-    fn setup_write<'a, I>(&self, addr: u8, bytes: &[u8], cmd_iterator: &mut I) -> Result<(), Error>
-    where
-        I: Iterator<Item = &'a COMD>,
-    {
-        if bytes.len() > 254 {
-            // we could support more by adding multiple write operations
-            return Err(Error::ExceedingFifo);
-        }
-
-        // WRITE command
-        add_cmd(
-            cmd_iterator,
-            Command::Write {
-                ack_exp: Ack::Ack,
-                ack_check_en: true,
-                length: 1 + bytes.len() as u8,
-            },
-        )?;
-
-        self.update_config();
-
-        // Load address and R/W bit into FIFO
-        write_fifo(
-            self.register_block(),
-            addr << 1 | OperationType::Write as u8,
-        );
-
-        Ok(())
-    }
-
-    fn setup_read<'a, I>(
-        &self,
-        addr: u8,
-        buffer: &mut [u8],
-        cmd_iterator: &mut I,
-    ) -> Result<(), Error>
-    where
-        I: Iterator<Item = &'a COMD>,
-    {
-        if buffer.len() > 254 {
-            // we could support more by adding multiple read operations
-            return Err(Error::ExceedingFifo);
-        }
-
-        // WRITE command
-        add_cmd(
-            cmd_iterator,
-            Command::Write {
-                ack_exp: Ack::Ack,
-                ack_check_en: true,
-                length: 1,
-            },
-        )?;
-
-        if buffer.len() > 1 {
-            // READ command (N - 1)
-            add_cmd(
-                cmd_iterator,
-                Command::Read {
-                    ack_value: Ack::Ack,
-                    length: buffer.len() as u8 - 1,
-                },
-            )?;
-        }
-
-        // READ w/o ACK
-        add_cmd(
-            cmd_iterator,
-            Command::Read {
-                ack_value: Ack::Nack,
-                length: 1,
-            },
-        )?;
-
-        self.update_config();
-
-        // Load address and R/W bit into FIFO
-        write_fifo(self.register_block(), addr << 1 | OperationType::Read as u8);
-
-        Ok(())
-    }
-
-    #[cfg(not(any(esp32, esp32s2)))]
-    fn read_all_from_fifo(&self, buffer: &mut [u8]) -> Result<(), Error> {
-        // Read bytes from FIFO
-        // FIXME: Handle case where less data has been provided by the slave than
-        // requested? Or is this prevented from a protocol perspective?
-        for byte in buffer.iter_mut() {
-            loop {
-                self.check_errors()?;
-
-                let reg = self.register_block().fifo_st().read();
-                if reg.rxfifo_raddr().bits() != reg.rxfifo_waddr().bits() {
-                    break;
-                }
-            }
-
-            *byte = read_fifo(self.register_block());
-        }
-
-        Ok(())
-    }
-
-    #[cfg(any(esp32, esp32s2))]
-    fn read_all_from_fifo(&self, buffer: &mut [u8]) -> Result<(), Error> {
-        // on ESP32/ESP32-S2 we currently don't support I2C transactions larger than the
-        // FIFO apparently it would be possible by using non-fifo mode
-        // see https://github.com/espressif/arduino-esp32/blob/7e9afe8c5ed7b5bf29624a5cd6e07d431c027b97/cores/esp32/esp32-hal-i2c.c#L615
-
-        if buffer.len() > 32 {
-            panic!("On ESP32 and ESP32-S2 the max I2C read is limited to 32 bytes");
-        }
-
-        // wait for completion - then we can just read the data from FIFO
-        // once we change to non-fifo mode to support larger transfers that
-        // won't work anymore
-        self.wait_for_completion(false)?;
-
-        // Read bytes from FIFO
-        // FIXME: Handle case where less data has been provided by the slave than
-        // requested? Or is this prevented from a protocol perspective?
-        for byte in buffer.iter_mut() {
-            *byte = read_fifo(self.register_block());
-        }
-
-        Ok(())
-    }
-
     fn clear_all_interrupts(&self) {
         self.register_block()
             .int_clr()
@@ -1610,107 +2066,6 @@ pub trait Instance: crate::private::Sealed {
             .modify(|_, w| w.trans_start().set_bit());
     }
 
-    #[cfg(not(any(esp32, esp32s2)))]
-    fn fill_tx_fifo(&self, bytes: &[u8]) -> usize {
-        let mut index = 0;
-        while index < bytes.len()
-            && !self
-                .register_block()
-                .int_raw()
-                .read()
-                .txfifo_ovf()
-                .bit_is_set()
-        {
-            write_fifo(self.register_block(), bytes[index]);
-            index += 1;
-        }
-        if self
-            .register_block()
-            .int_raw()
-            .read()
-            .txfifo_ovf()
-            .bit_is_set()
-        {
-            index -= 1;
-            self.register_block()
-                .int_clr()
-                .write(|w| w.txfifo_ovf().clear_bit_by_one());
-        }
-        index
-    }
-
-    #[cfg(not(any(esp32, esp32s2)))]
-    fn write_remaining_tx_fifo(&self, start_index: usize, bytes: &[u8]) -> Result<(), Error> {
-        let mut index = start_index;
-        loop {
-            self.check_errors()?;
-
-            while !self
-                .register_block()
-                .int_raw()
-                .read()
-                .txfifo_wm()
-                .bit_is_set()
-            {}
-
-            self.register_block()
-                .int_clr()
-                .write(|w| w.txfifo_wm().clear_bit_by_one());
-
-            while !self
-                .register_block()
-                .int_raw()
-                .read()
-                .txfifo_wm()
-                .bit_is_set()
-            {}
-
-            if index >= bytes.len() {
-                break Ok(());
-            }
-
-            write_fifo(self.register_block(), bytes[index]);
-            index += 1;
-        }
-    }
-
-    #[cfg(any(esp32, esp32s2))]
-    fn fill_tx_fifo(&self, bytes: &[u8]) -> usize {
-        // on ESP32/ESP32-S2 we currently don't support I2C transactions larger than the
-        // FIFO apparently it would be possible by using non-fifo mode
-        // see  https://github.com/espressif/arduino-esp32/blob/7e9afe8c5ed7b5bf29624a5cd6e07d431c027b97/cores/esp32/esp32-hal-i2c.c#L615
-
-        if bytes.len() > 31 {
-            panic!("On ESP32 and ESP32-S2 the max I2C transfer is limited to 31 bytes");
-        }
-
-        for b in bytes {
-            write_fifo(self.register_block(), *b);
-        }
-
-        bytes.len()
-    }
-
-    #[cfg(any(esp32, esp32s2))]
-    fn write_remaining_tx_fifo(&self, start_index: usize, bytes: &[u8]) -> Result<(), Error> {
-        // on ESP32/ESP32-S2 we currently don't support I2C transactions larger than the
-        // FIFO apparently it would be possible by using non-fifo mode
-        // see  https://github.com/espressif/arduino-esp32/blob/7e9afe8c5ed7b5bf29624a5cd6e07d431c027b97/cores/esp32/esp32-hal-i2c.c#L615
-
-        if start_index >= bytes.len() {
-            return Ok(());
-        }
-
-        // this is only possible when writing the I2C address in release mode
-        // from [perform_write_read]
-        for b in bytes {
-            write_fifo(self.register_block(), *b);
-            self.check_errors()?;
-        }
-
-        Ok(())
-    }
-
     /// Resets the transmit and receive FIFO buffers
     #[cfg(not(esp32))]
     fn reset_fifo(&self) {
@@ -1769,131 +2124,40 @@ pub trait Instance: crate::private::Sealed {
             .int_clr()
             .write(|w| w.rxfifo_full().clear_bit_by_one());
     }
+}
 
-    fn write_operation<'a, I>(
-        &self,
-        address: u8,
-        bytes: &[u8],
-        start: bool,
-        stop: bool,
-        cmd_iterator: &mut I,
-    ) -> Result<(), Error>
-    where
-        I: Iterator<Item = &'a COMD>,
-    {
-        // Reset FIFO and command list
-        self.reset_fifo();
-        self.reset_command_list();
-
-        if start {
-            add_cmd(cmd_iterator, Command::Start)?;
-        }
-        self.setup_write(address, bytes, cmd_iterator)?;
-        add_cmd(
-            cmd_iterator,
-            if stop { Command::Stop } else { Command::End },
-        )?;
-        let index = self.fill_tx_fifo(bytes);
-        self.start_transmission();
-
-        // Fill the FIFO with the remaining bytes:
-        self.write_remaining_tx_fifo(index, bytes)?;
-        self.wait_for_completion(!stop)?;
-        Ok(())
-    }
-
-    fn read_operation<'a, I>(
-        &self,
-        address: u8,
-        buffer: &mut [u8],
-        start: bool,
-        stop: bool,
-        cmd_iterator: &mut I,
-    ) -> Result<(), Error>
-    where
-        I: Iterator<Item = &'a COMD>,
-    {
-        // Reset FIFO and command list
-        self.reset_fifo();
-        self.reset_command_list();
-
-        if start {
-            add_cmd(cmd_iterator, Command::Start)?;
-        }
-        self.setup_read(address, buffer, cmd_iterator)?;
-        add_cmd(
-            cmd_iterator,
-            if stop { Command::Stop } else { Command::End },
-        )?;
-        self.start_transmission();
-        self.read_all_from_fifo(buffer)?;
-        self.wait_for_completion(!stop)?;
-        Ok(())
-    }
-
-    /// Send data bytes from the `bytes` array to a target slave with the
-    /// address `addr`
-    fn master_write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
-        // Clear all I2C interrupts
-        self.clear_all_interrupts();
-        self.write_operation(
-            addr,
-            bytes,
-            true,
-            true,
-            &mut self.register_block().comd_iter(),
-        )?;
-        Ok(())
-    }
-
-    /// Read bytes from a target slave with the address `addr`
-    /// The number of read bytes is deterimed by the size of the `buffer`
-    /// argument
-    fn master_read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
-        // Clear all I2C interrupts
-        self.clear_all_interrupts();
-        self.read_operation(
-            addr,
-            buffer,
-            true,
-            true,
-            &mut self.register_block().comd_iter(),
-        )?;
-        Ok(())
-    }
-
-    /// Write bytes from the `bytes` array first and then read n bytes into
-    /// the `buffer` array with n being the size of the array.
-    fn master_write_read(
-        &mut self,
-        addr: u8,
-        bytes: &[u8],
-        buffer: &mut [u8],
-    ) -> Result<(), Error> {
-        // it would be possible to combine the write and read
-        // in one transaction but filling the tx fifo with
-        // the current code is somewhat slow even in release mode
-        // which can cause issues
+/// Routes SDA and SCL to the I2C controller as open-drain lines with the
+/// internal pull-ups enabled. Shared by the master and slave drivers.
+fn connect_pins<T, SDA, SCL>(i2c: &T, sda: &mut SDA, scl: &mut SCL)
+where
+    T: Instance,
+    SDA: OutputPin + InputPin,
+    SCL: OutputPin + InputPin,
+{
+    // avoid SCL/SDA going low during configuration
+    scl.set_output_high(true, crate::private::Internal);
+    sda.set_output_high(true, crate::private::Internal);
+
+    scl.set_to_open_drain_output(crate::private::Internal);
+    scl.enable_input(true, crate::private::Internal);
+    scl.internal_pull_up(true, crate::private::Internal);
+    scl.connect_peripheral_to_output(i2c.scl_output_signal(), crate::private::Internal);
+    scl.connect_input_to_peripheral(i2c.scl_input_signal(), crate::private::Internal);
+
+    sda.set_to_open_drain_output(crate::private::Internal);
+    sda.enable_input(true, crate::private::Internal);
+    sda.internal_pull_up(true, crate::private::Internal);
+    sda.connect_peripheral_to_output(i2c.sda_output_signal(), crate::private::Internal);
+    sda.connect_input_to_peripheral(i2c.sda_input_signal(), crate::private::Internal);
+}
 
-        // Clear all I2C interrupts
-        self.clear_all_interrupts();
-        self.write_operation(
-            addr,
-            bytes,
-            true,
-            false,
-            &mut self.register_block().comd_iter(),
-        )?;
-        self.clear_all_interrupts();
-        self.read_operation(
-            addr,
-            buffer,
-            true,
-            true,
-            &mut self.register_block().comd_iter(),
-        )?;
-        Ok(())
-    }
+fn enable_peripheral<T: Instance>(i2c: &T) {
+    PeripheralClockControl::enable(match i2c.i2c_number() {
+        0 => crate::system::Peripheral::I2cExt0,
+        #[cfg(i2c1)]
+        1 => crate::system::Peripheral::I2cExt1,
+        _ => unreachable!(), // will never happen
+    });
 }
 
 fn add_cmd<'a, I>(cmd_iterator: &mut I, command: Command) -> Result<(), Error>
@@ -0,0 +1,822 @@
+//! # I2C Slave Driver
+//!
+//! ## Overview
+//! Besides acting as a bus master, the I2C controllers can be configured as a
+//! slave (target) device responding to their own 7-bit or 10-bit address.
+//! Data written by the master is collected in the RX FIFO, data requested by
+//! the master is served from the TX FIFO.
+//!
+//! On chips which support it (all but `ESP32` and `ESP32-S2`) the slave can
+//! stretch SCL while its TX FIFO is empty or its RX FIFO is full, which gives
+//! the application time to react to a read request. On `ESP32` and `ESP32-S2`
+//! the data for a read has to be queued before the master starts reading.
+//!
+//! ## Example
+//! Following code shows how to serve a simple register file.
+//!
+//! ```no_run
+//! let mut i2c = I2cSlave::new(
+//!     peripherals.I2C0,
+//!     io.pins.gpio1,
+//!     io.pins.gpio2,
+//!     Config::new(0x55),
+//!     None,
+//! );
+//!
+//! let registers = [0u8; 16];
+//! loop {
+//!     let mut reg = [0u8; 1];
+//!     // The master writes the register index ...
+//!     if i2c.read(&mut reg).unwrap() == 1 && i2c.is_read_request() {
+//!         // ... and then reads the register contents after a repeated START,
+//!         // unknown registers read as an empty response
+//!         let contents = registers.get(reg[0] as usize..).unwrap_or(&[]);
+//!         i2c.write(contents).unwrap();
+//!     }
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+use enumset::{EnumSet, EnumSetType};
+use fugit::MicrosDurationU64;
+
+use super::{
+    connect_pins,
+    enable_peripheral,
+    read_fifo,
+    write_fifo,
+    Address,
+    Error,
+    Instance,
+    I2C_FIFO_SIZE,
+};
+use crate::{
+    gpio::{InputPin, OutputPin},
+    interrupt::InterruptHandler,
+    peripheral::{Peripheral, PeripheralRef},
+};
+
+/// Number of peripheral clock cycles SDA is held / sampled after SCL edges
+const SDA_TIMING: u16 = 10;
+
+/// Number of clock cycles the hardware waits before stretching SCL
+#[cfg(not(any(esp32, esp32s2)))]
+const STRETCH_PROTECT_NUM: u16 = 500;
+
+/// I2C slave events
+#[derive(EnumSetType)]
+pub enum Event {
+    /// The master addressed this slave, i.e. a START condition followed by
+    /// this slave's address was received
+    AddressMatch,
+    /// A STOP condition was detected on the bus
+    Stop,
+    /// The RX FIFO contains data
+    RxFifoWatermark,
+    /// The TX FIFO has room for more data
+    TxFifoWatermark,
+    /// The master wants to read from this slave and SCL is being stretched
+    /// until data is available
+    #[cfg(not(any(esp32, esp32s2)))]
+    ReadRequest,
+}
+
+/// I2C slave configuration
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// The address this slave responds to
+    pub address: Address,
+    /// Hold SCL low while the TX FIFO is empty or the RX FIFO is full
+    #[cfg(not(any(esp32, esp32s2)))]
+    pub clock_stretch: bool,
+    /// Maximum time the blocking [`I2cSlave::read`] and [`I2cSlave::write`]
+    /// wait for the master to finish a transfer, `None` waits indefinitely
+    pub timeout: Option<MicrosDurationU64>,
+}
+
+impl Config {
+    /// Creates a configuration for the given own address with clock
+    /// stretching enabled where available
+    pub fn new(address: impl Into<Address>) -> Self {
+        Self {
+            address: address.into(),
+            #[cfg(not(any(esp32, esp32s2)))]
+            clock_stretch: true,
+            timeout: None,
+        }
+    }
+
+    /// Sets the address this slave responds to
+    pub fn address(mut self, address: impl Into<Address>) -> Self {
+        self.address = address.into();
+        self
+    }
+
+    /// Enables or disables clock stretching
+    #[cfg(not(any(esp32, esp32s2)))]
+    pub fn clock_stretch(mut self, enable: bool) -> Self {
+        self.clock_stretch = enable;
+        self
+    }
+
+    /// Sets the maximum time the blocking transfer functions wait for the
+    /// master
+    pub fn timeout(mut self, timeout: Option<MicrosDurationU64>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// I2C peripheral operating as slave
+pub struct I2cSlave<'d, T, DM: crate::Mode> {
+    peripheral: PeripheralRef<'d, T>,
+    timeout: Option<MicrosDurationU64>,
+    phantom: PhantomData<DM>,
+}
+
+impl<'d, T, DM: crate::Mode> I2cSlave<'d, T, DM>
+where
+    T: Instance,
+{
+    fn new_internal<SDA: OutputPin + InputPin, SCL: OutputPin + InputPin>(
+        i2c: impl Peripheral<P = T> + 'd,
+        sda: impl Peripheral<P = SDA> + 'd,
+        scl: impl Peripheral<P = SCL> + 'd,
+        config: Config,
+        isr: Option<InterruptHandler>,
+    ) -> Self {
+        crate::into_ref!(i2c, sda, scl);
+
+        enable_peripheral(&*i2c);
+
+        let mut i2c = I2cSlave {
+            peripheral: i2c,
+            timeout: config.timeout,
+            phantom: PhantomData,
+        };
+
+        connect_pins(&*i2c.peripheral, &mut *sda, &mut *scl);
+
+        i2c.setup(&config);
+
+        if let Some(interrupt) = isr {
+            unsafe {
+                crate::interrupt::bind_interrupt(T::interrupt(), interrupt.handler());
+                crate::interrupt::enable(T::interrupt(), interrupt.priority()).unwrap();
+            }
+        }
+
+        i2c
+    }
+
+    fn setup(&mut self, config: &Config) {
+        let register_block = self.peripheral.register_block();
+
+        register_block.ctr().modify(|_, w| unsafe {
+            // Clear register
+            w.bits(0)
+                // Set I2C controller to slave mode
+                .ms_mode()
+                .clear_bit()
+                // Use open drain output for SDA and SCL
+                .sda_force_out()
+                .set_bit()
+                .scl_force_out()
+                .set_bit()
+                // Use Most Significant Bit first for sending and receiving data
+                .tx_lsb_first()
+                .clear_bit()
+                .rx_lsb_first()
+                .clear_bit()
+                // Ensure that clock is enabled
+                .clk_en()
+                .set_bit()
+        });
+
+        #[cfg(esp32s2)]
+        register_block
+            .ctr()
+            .modify(|_, w| w.ref_always_on().set_bit());
+
+        // Start sending as soon as the master addresses us for a read
+        #[cfg(not(any(esp32, esp32s2)))]
+        register_block
+            .ctr()
+            .modify(|_, w| w.slv_tx_auto_start_en().set_bit());
+
+        // The slave is clocked by the master, the peripheral clock is only
+        // used for filtering and sampling
+        #[cfg(not(any(esp32, esp32s2)))]
+        register_block
+            .clk_conf()
+            .modify(|_, w| unsafe { w.sclk_sel().clear_bit().sclk_div_num().bits(0) });
+
+        register_block
+            .sda_hold()
+            .write(|w| unsafe { w.time().bits(SDA_TIMING) });
+        register_block
+            .sda_sample()
+            .write(|w| unsafe { w.time().bits(SDA_TIMING) });
+
+        #[cfg(not(any(esp32, esp32s2)))]
+        register_block.scl_stretch_conf().modify(|_, w| unsafe {
+            w.stretch_protect_num()
+                .bits(STRETCH_PROTECT_NUM)
+                .slave_scl_stretch_en()
+                .bit(config.clock_stretch)
+        });
+
+        self.set_address(config.address);
+
+        self.peripheral.set_filter(Some(7), Some(7));
+
+        self.peripheral.update_config();
+
+        // Reset entire peripheral (also resets fifo)
+        self.peripheral.reset();
+    }
+
+    /// Changes the address this slave responds to
+    pub fn set_address(&mut self, address: Address) {
+        let register_block = self.peripheral.register_block();
+
+        match address {
+            Address::SevenBit(address) => {
+                register_block.slave_addr().write(|w| unsafe {
+                    w.slave_addr()
+                        .bits(address as u16 & 0x7f)
+                        .addr_10bit_en()
+                        .clear_bit()
+                });
+            }
+            Address::TenBit(address) => {
+                // On newer chips the address bytes are stored in the order they
+                // appear on the bus: the `11110` prefix plus the two MSBs first,
+                // followed by the remaining eight bits.
+                #[cfg(not(any(esp32, esp32s2)))]
+                let address = ((address & 0xff) << 7) | ((address >> 8) & 0x3) | 0x78;
+                #[cfg(any(esp32, esp32s2))]
+                let address = address & 0x3ff;
+
+                register_block
+                    .slave_addr()
+                    .write(|w| unsafe { w.slave_addr().bits(address).addr_10bit_en().set_bit() });
+            }
+        }
+
+        self.peripheral.update_config();
+    }
+
+    /// Listen for the given events
+    pub fn listen(&mut self, events: EnumSet<Event>) {
+        enable_events(self.peripheral.register_block(), events, true);
+    }
+
+    /// Unlisten the given events
+    pub fn unlisten(&mut self, events: EnumSet<Event>) {
+        enable_events(self.peripheral.register_block(), events, false);
+    }
+
+    /// Gets asserted events
+    pub fn interrupts(&mut self) -> EnumSet<Event> {
+        raw_events(self.peripheral.register_block())
+    }
+
+    /// Resets asserted events
+    pub fn clear_interrupts(&mut self, events: EnumSet<Event>) {
+        let register_block = self.peripheral.register_block();
+
+        for event in events {
+            match event {
+                Event::AddressMatch => register_block
+                    .int_clr()
+                    .write(|w| w.trans_start().clear_bit_by_one()),
+                Event::Stop => register_block
+                    .int_clr()
+                    .write(|w| w.trans_complete().clear_bit_by_one()),
+                #[cfg(esp32)]
+                Event::RxFifoWatermark => register_block
+                    .int_clr()
+                    .write(|w| w.rxfifo_full().clear_bit_by_one()),
+                #[cfg(not(esp32))]
+                Event::RxFifoWatermark => register_block
+                    .int_clr()
+                    .write(|w| w.rxfifo_wm().clear_bit_by_one()),
+                #[cfg(esp32)]
+                Event::TxFifoWatermark => register_block
+                    .int_clr()
+                    .write(|w| w.txfifo_empty().clear_bit_by_one()),
+                #[cfg(not(esp32))]
+                Event::TxFifoWatermark => register_block
+                    .int_clr()
+                    .write(|w| w.txfifo_wm().clear_bit_by_one()),
+                #[cfg(not(any(esp32, esp32s2)))]
+                Event::ReadRequest => register_block
+                    .int_clr()
+                    .write(|w| w.slave_stretch().clear_bit_by_one()),
+            };
+        }
+    }
+
+    /// Returns `true` if the master addressed this slave for a read
+    pub fn is_read_request(&self) -> bool {
+        let status = self.peripheral.register_block().sr().read();
+        status.slave_addressed().bit_is_set() && status.slave_rw().bit_is_set()
+    }
+
+    /// Number of bytes waiting in the RX FIFO
+    pub fn rx_fifo_count(&self) -> usize {
+        self.peripheral
+            .register_block()
+            .sr()
+            .read()
+            .rxfifo_cnt()
+            .bits() as usize
+    }
+
+    /// Number of bytes queued in the TX FIFO which have not been read by the
+    /// master yet
+    pub fn tx_fifo_count(&self) -> usize {
+        self.peripheral
+            .register_block()
+            .sr()
+            .read()
+            .txfifo_cnt()
+            .bits() as usize
+    }
+
+    /// Releases SCL if the hardware is currently stretching the clock
+    #[cfg(not(any(esp32, esp32s2)))]
+    pub fn release_scl(&mut self) {
+        let register_block = self.peripheral.register_block();
+
+        register_block
+            .scl_stretch_conf()
+            .modify(|_, w| w.slave_scl_stretch_clr().set_bit());
+        register_block
+            .int_clr()
+            .write(|w| w.slave_stretch().clear_bit_by_one());
+    }
+
+    /// Queues as many bytes as fit into the TX FIFO, returns the number of
+    /// bytes queued
+    fn fill_tx_fifo(&self, bytes: &[u8]) -> usize {
+        let free = I2C_FIFO_SIZE.saturating_sub(self.tx_fifo_count());
+        let count = usize::min(free, bytes.len());
+
+        for byte in &bytes[..count] {
+            write_fifo(self.peripheral.register_block(), *byte);
+        }
+
+        count
+    }
+
+    /// Moves as many bytes as available from the RX FIFO into `buffer`,
+    /// returns the number of bytes read
+    fn drain_rx_fifo(&self, buffer: &mut [u8]) -> usize {
+        let count = usize::min(self.rx_fifo_count(), buffer.len());
+
+        for byte in &mut buffer[..count] {
+            *byte = read_fifo(self.peripheral.register_block());
+        }
+
+        count
+    }
+
+    fn reset_tx_fifo(&self) {
+        let register_block = self.peripheral.register_block();
+
+        register_block
+            .fifo_conf()
+            .modify(|_, w| w.tx_fifo_rst().set_bit());
+        register_block
+            .fifo_conf()
+            .modify(|_, w| w.tx_fifo_rst().clear_bit());
+    }
+
+    fn check_errors(&self) -> Result<(), Error> {
+        if self
+            .peripheral
+            .register_block()
+            .int_raw()
+            .read()
+            .rxfifo_ovf()
+            .bit_is_set()
+        {
+            self.peripheral.reset();
+            return Err(Error::ExceedingFifo);
+        }
+
+        Ok(())
+    }
+
+    /// The master either ended the transfer or switched to reading from us
+    fn write_phase_done(&self) -> bool {
+        let interrupts = self.peripheral.register_block().int_raw().read();
+
+        #[cfg(not(any(esp32, esp32s2)))]
+        if interrupts.slave_stretch().bit_is_set() && self.is_read_request() {
+            return true;
+        }
+
+        interrupts.trans_complete().bit_is_set()
+    }
+
+    fn read_phase_done(&self) -> bool {
+        self.peripheral
+            .register_block()
+            .int_raw()
+            .read()
+            .trans_complete()
+            .bit_is_set()
+    }
+
+    fn clear_stop(&self) {
+        self.peripheral
+            .register_block()
+            .int_clr()
+            .write(|w| w.trans_complete().clear_bit_by_one());
+    }
+
+    /// Prepares a read phase, returns the number of queued bytes
+    fn start_write(&mut self, bytes: &[u8]) -> usize {
+        self.reset_tx_fifo();
+        let queued = self.fill_tx_fifo(bytes);
+
+        #[cfg(not(any(esp32, esp32s2)))]
+        self.release_scl();
+
+        queued
+    }
+
+    /// Finishes a read phase, returns the number of bytes the master actually
+    /// clocked out
+    fn finish_write(&mut self, queued: usize) -> usize {
+        let sent = queued.saturating_sub(self.tx_fifo_count());
+
+        self.clear_stop();
+        // Discard whatever the master did not read
+        self.reset_tx_fifo();
+
+        sent
+    }
+
+    /// Returns the point in time after which a blocking transfer gives up
+    fn deadline(&self) -> Option<fugit::Instant<u64, 1, 1_000_000>> {
+        self.timeout
+            .map(|timeout| crate::time::current_time() + timeout)
+    }
+
+    fn check_deadline(
+        &self,
+        deadline: Option<fugit::Instant<u64, 1, 1_000_000>>,
+    ) -> Result<(), Error> {
+        match deadline {
+            Some(deadline) if crate::time::current_time() > deadline => Err(Error::TimeOut),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'d, T> I2cSlave<'d, T, crate::Blocking>
+where
+    T: Instance,
+{
+    /// Create a new I2C slave instance
+    /// This will enable the peripheral but the peripheral won't get
+    /// automatically disabled when this gets dropped.
+    pub fn new<SDA: OutputPin + InputPin, SCL: OutputPin + InputPin>(
+        i2c: impl Peripheral<P = T> + 'd,
+        sda: impl Peripheral<P = SDA> + 'd,
+        scl: impl Peripheral<P = SCL> + 'd,
+        config: Config,
+        isr: Option<InterruptHandler>,
+    ) -> Self {
+        Self::new_internal(i2c, sda, scl, config, isr)
+    }
+
+    /// Receives data written by the master.
+    ///
+    /// Blocks until the master ends the transfer with a STOP condition, until
+    /// the master addresses this slave for a read (when clock stretching is
+    /// enabled) or until `buffer` is full. Returns the number of bytes
+    /// received.
+    ///
+    /// Fails with [`Error::TimeOut`] if the configured
+    /// [`timeout`](Config::timeout) expires first.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+        let deadline = self.deadline();
+        let mut count = 0;
+
+        loop {
+            count += self.drain_rx_fifo(&mut buffer[count..]);
+            self.check_errors()?;
+
+            if count == buffer.len() {
+                break;
+            }
+
+            if self.write_phase_done() && self.rx_fifo_count() == 0 {
+                if self.read_phase_done() {
+                    self.clear_stop();
+                }
+                break;
+            }
+
+            self.check_deadline(deadline)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Provides data to the master.
+    ///
+    /// Queues `bytes` into the TX FIFO and blocks until the master ends the
+    /// transfer with a STOP condition. Returns the number of bytes the master
+    /// actually read, bytes which were not read are discarded.
+    ///
+    /// Fails with [`Error::TimeOut`] if the configured
+    /// [`timeout`](Config::timeout) expires first, the queued bytes are
+    /// discarded in that case.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let deadline = self.deadline();
+        let mut queued = self.start_write(bytes);
+
+        while !self.read_phase_done() {
+            if queued < bytes.len() {
+                queued += self.fill_tx_fifo(&bytes[queued..]);
+            }
+
+            if let Err(error) = self.check_deadline(deadline) {
+                self.reset_tx_fifo();
+                return Err(error);
+            }
+        }
+
+        Ok(self.finish_write(queued))
+    }
+}
+
+fn enable_events(register_block: &super::RegisterBlock, events: EnumSet<Event>, enable: bool) {
+    register_block.int_ena().modify(|_, w| {
+        for event in events {
+            match event {
+                Event::AddressMatch => w.trans_start().bit(enable),
+                Event::Stop => w.trans_complete().bit(enable),
+                #[cfg(esp32)]
+                Event::RxFifoWatermark => w.rxfifo_full().bit(enable),
+                #[cfg(not(esp32))]
+                Event::RxFifoWatermark => w.rxfifo_wm().bit(enable),
+                #[cfg(esp32)]
+                Event::TxFifoWatermark => w.txfifo_empty().bit(enable),
+                #[cfg(not(esp32))]
+                Event::TxFifoWatermark => w.txfifo_wm().bit(enable),
+                #[cfg(not(any(esp32, esp32s2)))]
+                Event::ReadRequest => w.slave_stretch().bit(enable),
+            };
+        }
+        w
+    });
+}
+
+fn raw_events(register_block: &super::RegisterBlock) -> EnumSet<Event> {
+    let mut res = EnumSet::new();
+    let ints = register_block.int_raw().read();
+
+    if ints.trans_start().bit_is_set() {
+        res.insert(Event::AddressMatch);
+    }
+    if ints.trans_complete().bit_is_set() {
+        res.insert(Event::Stop);
+    }
+    #[cfg(esp32)]
+    if ints.rxfifo_full().bit_is_set() {
+        res.insert(Event::RxFifoWatermark);
+    }
+    #[cfg(not(esp32))]
+    if ints.rxfifo_wm().bit_is_set() {
+        res.insert(Event::RxFifoWatermark);
+    }
+    #[cfg(esp32)]
+    if ints.txfifo_empty().bit_is_set() {
+        res.insert(Event::TxFifoWatermark);
+    }
+    #[cfg(not(esp32))]
+    if ints.txfifo_wm().bit_is_set() {
+        res.insert(Event::TxFifoWatermark);
+    }
+    #[cfg(not(any(esp32, esp32s2)))]
+    if ints.slave_stretch().bit_is_set() {
+        res.insert(Event::ReadRequest);
+    }
+
+    res
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use core::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use cfg_if::cfg_if;
+    use embassy_sync::waitqueue::AtomicWaker;
+    use procmacros::handler;
+
+    use super::*;
+
+    cfg_if! {
+        if #[cfg(all(i2c0, i2c1))] {
+            const NUM_I2C: usize = 2;
+        } else if #[cfg(i2c0)] {
+            const NUM_I2C: usize = 1;
+        }
+    }
+
+    const INIT: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; NUM_I2C] = [INIT; NUM_I2C];
+
+    /// Resolves as soon as any of the given events is raised. The interrupt
+    /// handler disables all events, which is how completion is detected.
+    pub(crate) struct I2cSlaveFuture<'a, T>
+    where
+        T: Instance,
+    {
+        events: EnumSet<Event>,
+        instance: &'a T,
+    }
+
+    impl<'a, T> I2cSlaveFuture<'a, T>
+    where
+        T: Instance,
+    {
+        pub fn new(events: EnumSet<Event>, instance: &'a T) -> Self {
+            enable_events(instance.register_block(), events, true);
+
+            Self { events, instance }
+        }
+
+        fn any_event_disabled(&self) -> bool {
+            let r = self.instance.register_block().int_ena().read();
+
+            self.events.iter().any(|event| match event {
+                Event::AddressMatch => r.trans_start().bit_is_clear(),
+                Event::Stop => r.trans_complete().bit_is_clear(),
+                #[cfg(esp32)]
+                Event::RxFifoWatermark => r.rxfifo_full().bit_is_clear(),
+                #[cfg(not(esp32))]
+                Event::RxFifoWatermark => r.rxfifo_wm().bit_is_clear(),
+                #[cfg(esp32)]
+                Event::TxFifoWatermark => r.txfifo_empty().bit_is_clear(),
+                #[cfg(not(esp32))]
+                Event::TxFifoWatermark => r.txfifo_wm().bit_is_clear(),
+                #[cfg(not(any(esp32, esp32s2)))]
+                Event::ReadRequest => r.slave_stretch().bit_is_clear(),
+            })
+        }
+    }
+
+    impl<'a, T> core::future::Future for I2cSlaveFuture<'a, T>
+    where
+        T: Instance,
+    {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+            WAKERS[self.instance.i2c_number()].register(ctx.waker());
+
+            if self.any_event_disabled() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    impl<'a, T> Drop for I2cSlaveFuture<'a, T>
+    where
+        T: Instance,
+    {
+        fn drop(&mut self) {
+            enable_events(self.instance.register_block(), self.events, false);
+        }
+    }
+
+    impl<'d, T> I2cSlave<'d, T, crate::Async>
+    where
+        T: Instance,
+    {
+        /// Create a new I2C slave instance
+        /// This will enable the peripheral but the peripheral won't get
+        /// automatically disabled when this gets dropped.
+        pub fn new_async<SDA: OutputPin + InputPin, SCL: OutputPin + InputPin>(
+            i2c: impl Peripheral<P = T> + 'd,
+            sda: impl Peripheral<P = SDA> + 'd,
+            scl: impl Peripheral<P = SCL> + 'd,
+            config: Config,
+        ) -> Self {
+            let handler = Some(match T::I2C_NUMBER {
+                0 => i2c0_slave_handler,
+                #[cfg(i2c1)]
+                1 => i2c1_slave_handler,
+                _ => panic!("Unexpected I2C peripheral"),
+            });
+            Self::new_internal(i2c, sda, scl, config, handler)
+        }
+
+        /// Waits until any of the given events is raised and returns the
+        /// raised events. The events are not cleared.
+        pub async fn wait_for_event(&mut self, events: EnumSet<Event>) -> EnumSet<Event> {
+            loop {
+                let raised = raw_events(self.peripheral.register_block()) & events;
+                if !raised.is_empty() {
+                    break raised;
+                }
+
+                I2cSlaveFuture::new(events, &*self.peripheral).await;
+            }
+        }
+
+        /// Receives data written by the master.
+        ///
+        /// Completes when the master ends the transfer with a STOP condition,
+        /// when the master addresses this slave for a read (when clock
+        /// stretching is enabled) or when `buffer` is full. Returns the number
+        /// of bytes received.
+        pub async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+            let mut count = 0;
+
+            loop {
+                self.clear_interrupts(Event::RxFifoWatermark.into());
+
+                count += self.drain_rx_fifo(&mut buffer[count..]);
+                self.check_errors()?;
+
+                if count == buffer.len() {
+                    break;
+                }
+
+                if self.write_phase_done() && self.rx_fifo_count() == 0 {
+                    if self.read_phase_done() {
+                        self.clear_stop();
+                    }
+                    break;
+                }
+
+                #[allow(unused_mut)]
+                let mut events = Event::RxFifoWatermark | Event::Stop;
+                #[cfg(not(any(esp32, esp32s2)))]
+                events.insert(Event::ReadRequest);
+
+                I2cSlaveFuture::new(events, &*self.peripheral).await;
+            }
+
+            Ok(count)
+        }
+
+        /// Provides data to the master.
+        ///
+        /// Queues `bytes` into the TX FIFO and completes when the master ends
+        /// the transfer with a STOP condition. Returns the number of bytes the
+        /// master actually read, bytes which were not read are discarded.
+        pub async fn write(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+            let mut queued = self.start_write(bytes);
+
+            while !self.read_phase_done() {
+                let events = if queued < bytes.len() {
+                    self.clear_interrupts(Event::TxFifoWatermark.into());
+                    queued += self.fill_tx_fifo(&bytes[queued..]);
+                    Event::TxFifoWatermark | Event::Stop
+                } else {
+                    Event::Stop.into()
+                };
+
+                if !self.read_phase_done() {
+                    I2cSlaveFuture::new(events, &*self.peripheral).await;
+                }
+            }
+
+            Ok(self.finish_write(queued))
+        }
+    }
+
+    #[handler]
+    pub(super) fn i2c0_slave_handler() {
+        let regs = unsafe { &*crate::peripherals::I2C0::PTR };
+        regs.int_ena().write(|w| unsafe { w.bits(0) });
+
+        WAKERS[0].wake();
+    }
+
+    #[cfg(i2c1)]
+    #[handler]
+    pub(super) fn i2c1_slave_handler() {
+        let regs = unsafe { &*crate::peripherals::I2C1::PTR };
+        regs.int_ena().write(|w| unsafe { w.bits(0) });
+
+        WAKERS[1].wake();
+    }
+}
@@ -9,7 +9,8 @@
 //! interface. Also this module supports different data formats, including
 //! varying data and channel widths, different standards, such as the Philips
 //! standard and configurable pin mappings for I2S clock (BCLK), word select
-//! (WS), and data input/output (DOUT/DIN).
+//! (WS), and data input/output (DOUT/DIN). Instead of generating BCLK and WS
+//! the peripheral can also operate as slave of an external master.
 //!
 //! The driver uses DMA (Direct Memory Access) for efficient data transfer and
 //! supports various configurations, such as different data formats, standards
@@ -36,6 +37,7 @@
 //!     ),
 //!     &clocks,
 //! )
+//! .unwrap()
 //! .with_mclk(io.pins.gpio4);
 //! ```
 //!
@@ -64,6 +66,62 @@
 //!     }
 //! }
 //! ```
+//!
+//! ### TDM and PDM
+//! A microphone array with 8 slots per frame, of which slots 0 to 5 are
+//! used. The samples of the active slots are interleaved in the DMA buffers.
+//! ```no_run
+//! let i2s = I2s::new(
+//!     peripherals.I2S0,
+//!     Standard::Tdm {
+//!         slots: 8,
+//!         slot_mask: 0b0011_1111,
+//!     },
+//!     DataFormat::Data32Channel32,
+//!     16000.Hz(),
+//!     dma_channel.configure(
+//!         false,
+//!         &mut tx_descriptors,
+//!         &mut rx_descriptors,
+//!         DmaPriority::Priority0,
+//!     ),
+//!     &clocks,
+//! )
+//! .unwrap();
+//! ```
+//!
+//! With [Standard::Pdm] the DMA buffers hold 16 bit PCM samples, the PDM
+//! clock is output on the WS pin.
+//!
+//! ### Slave mode
+//! Receiving the left channel from a codec which supplies BCLK and WS, using
+//! the PCM short frame format.
+//! ```no_run
+//! let i2s = I2s::new(
+//!     peripherals.I2S0,
+//!     Standard::PcmShort,
+//!     DataFormat::Data16Channel16,
+//!     16000.Hz(),
+//!     dma_channel.configure(
+//!         false,
+//!         &mut tx_descriptors,
+//!         &mut rx_descriptors,
+//!         DmaPriority::Priority0,
+//!     ),
+//!     &clocks,
+//! )
+//! .unwrap()
+//! .with_slave_mode()
+//! .with_channels(Channels::MonoLeft)
+//! .unwrap();
+//!
+//! let i2s_rx = i2s
+//!     .i2s_rx
+//!     .with_bclk_input(io.pins.gpio1)
+//!     .with_ws_input(io.pins.gpio2)
+//!     .with_din(io.pins.gpio5)
+//!     .build();
+//! ```
 
 use core::marker::PhantomData;
 
@@ -97,6 +155,9 @@ use crate::{
     Mode,
 };
 
+#[cfg(any(esp32, esp32s2))]
+pub mod parallel;
+
 #[derive(EnumSetType)]
 pub enum I2sInterrupt {
     TxHung,
@@ -113,6 +174,15 @@ const I2S_LL_MCLK_DIVIDER_BIT_WIDTH: usize = 6;
 #[cfg(any(esp32c3, esp32c6, esp32h2))]
 const I2S_LL_MCLK_DIVIDER_BIT_WIDTH: usize = 9;
 
+// Limited by the half sample bits field (half of the frame minus one), which is
+// 6 bits wide on ESP32-C3 / ESP32-S3 and 8 bits wide on ESP32-C6 / ESP32-H2.
+// The WS width field holds the same value and is wider on all of them.
+#[cfg(any(esp32c3, esp32s3))]
+const I2S_LL_SLOT_FRAME_BIT_MAX: u16 = 128;
+
+#[cfg(any(esp32c6, esp32h2))]
+const I2S_LL_SLOT_FRAME_BIT_MAX: u16 = 512;
+
 const I2S_LL_MCLK_DIVIDER_MAX: usize = (1 << I2S_LL_MCLK_DIVIDER_BIT_WIDTH) - 1;
 
 trait AcceptedWord {}
@@ -143,8 +213,144 @@ impl From<DmaError> for Error {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Standard {
     Philips,
-    // Tdm,
-    // Pdm,
+    /// MSB-justified (left-justified) format: like [Standard::Philips] but the
+    /// MSB is transferred in the first BCLK cycle after the WS edge.
+    Msb,
+    /// PCM short frame sync: WS is a pulse of one BCLK cycle at the start of
+    /// each frame.
+    PcmShort,
+    /// PCM long frame sync: WS is active for the first slot of each frame.
+    #[cfg(not(any(esp32, esp32s2)))]
+    PcmLong,
+    /// Time-division multiplexing of up to 16 slots per frame.
+    ///
+    /// WS is high for the first half of the frame. Only the slots selected by
+    /// `slot_mask` are transferred, the data of the active slots is
+    /// interleaved in the DMA buffers.
+    #[cfg_attr(
+        any(esp32c3, esp32s3),
+        doc = "A frame can hold at most 128 bits, e.g. 8 slots of 16 bits or 4 slots of 32 bits."
+    )]
+    #[cfg_attr(
+        any(esp32c6, esp32h2),
+        doc = "A frame can hold at most 512 bits, i.e. all 16 slots can be 32 bits wide."
+    )]
+    #[cfg(not(any(esp32, esp32s2)))]
+    Tdm {
+        /// The total number of slots per frame (1 to 16).
+        slots: u8,
+        /// Bit `n` set means slot `n` is active.
+        slot_mask: u16,
+    },
+    /// Pulse-density modulation using 16 bit PCM samples in the DMA buffers.
+    ///
+    /// The PDM clock is output on the WS pin. Transmitted PCM samples are
+    /// converted to PDM by the hardware.
+    #[cfg_attr(
+        any(esp32, esp32s3),
+        doc = "Received PDM data is converted to PCM by the hardware. Only supported by `I2S0`, other instances fail with [Error::IllegalArgument]."
+    )]
+    #[cfg_attr(
+        not(any(esp32, esp32s3)),
+        doc = "This chip only supports PDM for transmitting, the receiver is configured for `Philips` with 16 bit stereo samples at the same sample rate."
+    )]
+    #[cfg(not(esp32s2))]
+    Pdm,
+    /// Pulse-density modulation output suited for DAC-less audio, the data
+    /// line can be low-pass filtered to directly drive an amplifier.
+    ///
+    /// The receiver is configured like for [Standard::Pdm].
+    #[cfg(not(any(esp32, esp32s2)))]
+    PdmDac,
+}
+
+impl Standard {
+    /// Left-justified is another name for the MSB-justified format.
+    pub const LEFT_JUSTIFIED: Standard = Standard::Msb;
+
+    fn is_two_slot(&self) -> bool {
+        match self {
+            Standard::Philips | Standard::Msb | Standard::PcmShort => true,
+            #[cfg(not(any(esp32, esp32s2)))]
+            Standard::PcmLong => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    fn is_pdm(&self) -> bool {
+        cfg_if::cfg_if! {
+            if #[cfg(esp32s2)] {
+                false
+            } else if #[cfg(esp32)] {
+                matches!(self, Standard::Pdm)
+            } else {
+                matches!(self, Standard::Pdm | Standard::PdmDac)
+            }
+        }
+    }
+
+    /// The number of slots per frame and the number of bits per slot used
+    /// for the bit clock.
+    fn frame_layout(&self, data_format: &DataFormat) -> (u8, u8) {
+        match self {
+            #[cfg(not(any(esp32, esp32s2)))]
+            Standard::Tdm { slots, .. } => (*slots, data_format.channel_bits()),
+            // The PDM bit clock runs at 128 times the sample rate.
+            _ if self.is_pdm() => (2, 64),
+            _ => (2, data_format.channel_bits()),
+        }
+    }
+
+    /// Check that the standard can be used with the given data format.
+    fn validate(&self, data_format: &DataFormat) -> Result<(), Error> {
+        #[cfg(not(any(esp32, esp32s2)))]
+        if let Standard::Tdm { slots, slot_mask } = *self {
+            if !(1..=16).contains(&slots) || slot_mask == 0 || (slot_mask as u32) >> slots != 0 {
+                return Err(Error::IllegalArgument);
+            }
+
+            if slots as u16 * data_format.channel_bits() as u16 > I2S_LL_SLOT_FRAME_BIT_MAX {
+                return Err(Error::IllegalArgument);
+            }
+        }
+
+        if self.is_pdm() && *data_format != DataFormat::Data16Channel16 {
+            return Err(Error::IllegalArgument);
+        }
+
+        Ok(())
+    }
+}
+
+/// Channels transferred with the two slot standards.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Channels {
+    /// Both channels, the samples are interleaved in the DMA buffers.
+    #[default]
+    Stereo,
+    /// Only the left channel, the DMA buffers hold one sample per frame.
+    MonoLeft,
+    /// Only the right channel, the DMA buffers hold one sample per frame.
+    MonoRight,
+}
+
+/// Polarity of the WS signal.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WsPolarity {
+    /// The polarity defined by the standard, e.g. WS low for the left channel
+    /// with [Standard::Philips] and a high pulse with [Standard::PcmShort].
+    #[default]
+    Normal,
+    /// The inverse of the polarity defined by the standard.
+    #[cfg_attr(
+        any(esp32, esp32s2),
+        doc = "",
+        doc = "This chip can't invert WS, instead the right channel is transferred first."
+    )]
+    Inverted,
 }
 
 /// Supported data formats
@@ -284,6 +490,8 @@ where
 {
     pub i2s_tx: TxCreator<'d, I, CH, DmaMode>,
     pub i2s_rx: RxCreator<'d, I, CH, DmaMode>,
+    standard: Standard,
+    data_format: DataFormat,
     phantom: PhantomData<DmaMode>,
 }
 
@@ -300,24 +508,43 @@ where
         sample_rate: impl Into<fugit::HertzU32>,
         mut channel: Channel<'d, CH, DmaMode>,
         clocks: &Clocks,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         // on ESP32-C3 / ESP32-S3 and later RX and TX are independent and
         // could be configured totally independently but for now handle all
         // the targets the same and force same configuration for both, TX and RX
 
+        standard.validate(&data_format)?;
+
+        // I2S1 has no PDM support
+        #[cfg(any(esp32, esp32s3))]
+        if standard.is_pdm() && I::get_dma_peripheral() != crate::dma::DmaPeripheral::I2s0 {
+            return Err(Error::IllegalArgument);
+        }
+
+        let sample_rate: fugit::HertzU32 = sample_rate.into();
+
         channel.tx.init_channel();
         PeripheralClockControl::enable(I::get_peripheral());
-        I::set_clock(calculate_clock(
-            sample_rate,
-            2,
-            data_format.channel_bits(),
-            clocks,
-        ));
+        let (slots, slot_bits) = standard.frame_layout(&data_format);
+        I::set_clock(calculate_clock(sample_rate, slots, slot_bits, clocks));
+
+        // The receiver has no PDM support and is configured for 16 bit stereo samples,
+        // which use the same master clock but a slower bit clock.
+        #[cfg(any(esp32c3, esp32c6, esp32h2))]
+        if standard.is_pdm() {
+            I::set_rx_bclk_divider(calculate_clock(
+                sample_rate,
+                2,
+                data_format.channel_bits(),
+                clocks,
+            ));
+        }
+
         I::configure(&standard, &data_format);
         I::set_master();
         I::update();
 
-        Self {
+        Ok(Self {
             i2s_tx: TxCreator {
                 register_access: PhantomData,
                 tx_channel: channel.tx,
@@ -328,8 +555,10 @@ where
                 rx_channel: channel.rx,
                 phantom: PhantomData,
             },
+            standard,
+            data_format,
             phantom: PhantomData,
-        }
+        })
     }
 }
 
@@ -376,6 +605,9 @@ where
 {
     /// Construct a new I2S peripheral driver instance for the first I2S
     /// peripheral
+    ///
+    /// Fails with [Error::IllegalArgument] if the standard can't be used
+    /// with the data format, e.g. for TDM frames with too many slots.
     pub fn new(
         i2s: impl Peripheral<P = I> + 'd,
         standard: Standard,
@@ -383,7 +615,7 @@ where
         sample_rate: impl Into<fugit::HertzU32>,
         channel: Channel<'d, CH, DmaMode>,
         clocks: &Clocks,
-    ) -> Self
+    ) -> Result<Self, Error>
     where
         I: I2s0Instance,
         CH::P: I2sPeripheral + I2s0Peripheral,
@@ -394,6 +626,8 @@ where
 
     /// Construct a new I2S peripheral driver instance for the second I2S
     /// peripheral
+    ///
+    /// See [I2s::new] for the possible errors.
     #[cfg(any(esp32s3, esp32))]
     pub fn new_i2s1(
         i2s: impl Peripheral<P = I> + 'd,
@@ -402,7 +636,7 @@ where
         sample_rate: impl Into<fugit::HertzU32>,
         channel: Channel<'d, CH, DmaMode>,
         clocks: &Clocks,
-    ) -> Self
+    ) -> Result<Self, Error>
     where
         I: I2s1Instance,
         CH::P: I2sPeripheral + I2s1Peripheral,
@@ -416,6 +650,40 @@ where
         pin.connect_peripheral_to_output(I::mclk_signal(), crate::private::Internal);
         self
     }
+
+    /// Operate as slave, BCLK and WS are supplied by an external master.
+    ///
+    /// Assign the pins with `with_bclk_input` and `with_ws_input` of the TX
+    /// and RX creators, which connect them to the inputs of the peripheral.
+    /// The sample rate the driver was created with has to match
+    /// the master's, the internal clock derived from it must be faster than
+    /// the received BCLK.
+    pub fn with_slave_mode(self) -> Self {
+        I::set_slave();
+        I::update();
+        self
+    }
+
+    /// Select the channels to transfer.
+    ///
+    /// Only supported by the two slot standards, fails with
+    /// [Error::IllegalArgument] for TDM or PDM.
+    pub fn with_channels(self, channels: Channels) -> Result<Self, Error> {
+        if !self.standard.is_two_slot() {
+            return Err(Error::IllegalArgument);
+        }
+
+        I::set_channels(channels, &self.data_format);
+        I::update();
+        Ok(self)
+    }
+
+    /// Set the polarity of the WS signal.
+    pub fn with_ws_polarity(self, polarity: WsPolarity) -> Self {
+        I::set_ws_polarity(&self.standard, polarity);
+        I::update();
+        self
+    }
 }
 
 /// I2S TX channel
@@ -768,12 +1036,14 @@ mod private {
     use fugit::HertzU32;
 
     use super::{
+        Channels,
         DataFormat,
         I2sInterrupt,
         I2sRx,
         I2sTx,
         RegisterAccess,
         Standard,
+        WsPolarity,
         I2S_LL_MCLK_DIVIDER_MAX,
     };
     #[cfg(not(any(esp32, esp32s3)))]
@@ -825,6 +1095,18 @@ mod private {
             self
         }
 
+        /// Receive BCLK from an external master, see
+        /// [super::I2s::with_slave_mode]
+        pub fn with_bclk_input<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
+        where
+            P: InputPin,
+        {
+            into_ref!(pin);
+            pin.set_to_input(private::Internal);
+            pin.connect_input_to_peripheral(T::bclk_in_signal(), private::Internal);
+            self
+        }
+
         pub fn with_ws<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
         where
             P: OutputPin,
@@ -835,6 +1117,18 @@ mod private {
             self
         }
 
+        /// Receive WS from an external master, see
+        /// [super::I2s::with_slave_mode]
+        pub fn with_ws_input<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
+        where
+            P: InputPin,
+        {
+            into_ref!(pin);
+            pin.set_to_input(private::Internal);
+            pin.connect_input_to_peripheral(T::ws_in_signal(), private::Internal);
+            self
+        }
+
         pub fn with_dout<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
         where
             P: OutputPin,
@@ -877,6 +1171,18 @@ mod private {
             self
         }
 
+        /// Receive BCLK from an external master, see
+        /// [super::I2s::with_slave_mode]
+        pub fn with_bclk_input<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
+        where
+            P: InputPin,
+        {
+            into_ref!(pin);
+            pin.set_to_input(crate::private::Internal);
+            pin.connect_input_to_peripheral(T::bclk_rx_in_signal(), crate::private::Internal);
+            self
+        }
+
         pub fn with_ws<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
         where
             P: OutputPin,
@@ -887,6 +1193,18 @@ mod private {
             self
         }
 
+        /// Receive WS from an external master, see
+        /// [super::I2s::with_slave_mode]
+        pub fn with_ws_input<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
+        where
+            P: InputPin,
+        {
+            into_ref!(pin);
+            pin.set_to_input(crate::private::Internal);
+            pin.connect_input_to_peripheral(T::ws_rx_in_signal(), crate::private::Internal);
+            self
+        }
+
         pub fn with_din<P>(self, pin: impl crate::peripheral::Peripheral<P = P> + 'd) -> Self
         where
             P: InputPin,
@@ -916,6 +1234,14 @@ mod private {
         fn ws_rx_signal() -> OutputSignal;
 
         fn din_signal() -> InputSignal;
+
+        fn bclk_in_signal() -> InputSignal;
+
+        fn ws_in_signal() -> InputSignal;
+
+        fn bclk_rx_in_signal() -> InputSignal;
+
+        fn ws_rx_in_signal() -> InputSignal;
     }
 
     pub trait RegBlock {
@@ -1019,9 +1345,18 @@ mod private {
             });
         }
 
-        fn configure(_standard: &Standard, data_format: &DataFormat) {
+        fn configure(standard: &Standard, data_format: &DataFormat) {
             let i2s = Self::register_block();
 
+            #[cfg(esp32)]
+            Self::configure_pdm(standard.is_pdm());
+
+            let (msb_shift, short_sync) = match standard {
+                Standard::Msb => (false, false),
+                Standard::PcmShort => (false, true),
+                _ => (true, false),
+            };
+
             let fifo_mod = match data_format {
                 DataFormat::Data32Channel32 => 2,
                 DataFormat::Data16Channel16 => 0,
@@ -1038,13 +1373,13 @@ mod private {
                     .rx_slave_mod()
                     .clear_bit()
                     .tx_msb_shift()
-                    .set_bit() // ?
+                    .bit(msb_shift)
                     .rx_msb_shift()
-                    .set_bit() // ?
+                    .bit(msb_shift)
                     .tx_short_sync()
-                    .bit(false) //??
+                    .bit(short_sync)
                     .rx_short_sync()
-                    .bit(false) //??
+                    .bit(short_sync)
                     .tx_msb_right()
                     .clear_bit()
                     .rx_msb_right()
@@ -1075,7 +1410,7 @@ mod private {
             });
 
             i2s.conf_chan()
-                .modify(|_, w| unsafe { w.tx_chan_mod().bits(0).rx_chan_mod().bits(0) }); // for now only stereo
+                .modify(|_, w| unsafe { w.tx_chan_mod().bits(0).rx_chan_mod().bits(0) });
 
             i2s.conf1()
                 .modify(|_, w| w.tx_pcm_bypass().set_bit().rx_pcm_bypass().set_bit());
@@ -1087,12 +1422,93 @@ mod private {
                 .modify(|_, w| w.camera_en().clear_bit().lcd_en().clear_bit());
         }
 
+        #[cfg(esp32)]
+        fn configure_pdm(enable: bool) {
+            // I2S1 has no PDM support, its register block doesn't have the PDM registers.
+            // Enabling PDM on it is rejected by `I2s::new_i2s1`.
+            if Self::get_dma_peripheral() != DmaPeripheral::I2s0 {
+                return;
+            }
+
+            let i2s = unsafe { &*I2S0::PTR };
+
+            // Up-sample by 2 (960 / 480), the PDM clock is 128 times the sample rate.
+            i2s.pdm_freq_conf()
+                .modify(|_, w| unsafe { w.tx_pdm_fp().bits(960).tx_pdm_fs().bits(480) });
+
+            i2s.pdm_conf().modify(|_, w| unsafe {
+                w.tx_pdm_sinc_osr2()
+                    .bits(2)
+                    .tx_pdm_hp_bypass()
+                    .clear_bit()
+                    .tx_pdm_prescale()
+                    .bits(0)
+                    .tx_pdm_hp_in_shift()
+                    .bits(1)
+                    .tx_pdm_lp_in_shift()
+                    .bits(1)
+                    .tx_pdm_sinc_in_shift()
+                    .bits(1)
+                    .tx_pdm_sigmadelta_in_shift()
+                    .bits(1)
+                    .rx_pdm_sinc_dsr_16_en()
+                    .set_bit()
+                    .tx_pdm_en()
+                    .bit(enable)
+                    .pcm2pdm_conv_en()
+                    .bit(enable)
+                    .rx_pdm_en()
+                    .bit(enable)
+                    .pdm2pcm_conv_en()
+                    .bit(enable)
+            });
+        }
+
+        fn set_channels(channels: Channels, data_format: &DataFormat) {
+            let i2s = Self::register_block();
+
+            let (chan_mod, mono) = match channels {
+                Channels::Stereo => (0, false),
+                Channels::MonoLeft => (2, true),
+                Channels::MonoRight => (1, true),
+            };
+            // the single channel FIFO modes follow the dual channel ones
+            let fifo_mod = match data_format {
+                DataFormat::Data32Channel32 => 2,
+                DataFormat::Data16Channel16 => 0,
+            } + mono as u8;
+
+            i2s.fifo_conf().modify(|_, w| unsafe {
+                w.tx_fifo_mod().bits(fifo_mod).rx_fifo_mod().bits(fifo_mod)
+            });
+            i2s.conf_chan().modify(|_, w| unsafe {
+                w.tx_chan_mod().bits(chan_mod).rx_chan_mod().bits(chan_mod)
+            });
+        }
+
+        fn set_ws_polarity(_standard: &Standard, polarity: WsPolarity) {
+            let i2s = Self::register_block();
+            let inverted = polarity == WsPolarity::Inverted;
+            i2s.conf().modify(|_, w| {
+                w.tx_right_first()
+                    .bit(inverted)
+                    .rx_right_first()
+                    .bit(inverted)
+            });
+        }
+
         fn set_master() {
             let i2s = Self::register_block();
             i2s.conf()
                 .modify(|_, w| w.rx_slave_mod().clear_bit().tx_slave_mod().clear_bit());
         }
 
+        fn set_slave() {
+            let i2s = Self::register_block();
+            i2s.conf()
+                .modify(|_, w| w.rx_slave_mod().set_bit().tx_slave_mod().set_bit());
+        }
+
         fn update() {
             // nothing to do
         }
@@ -1480,24 +1896,63 @@ mod private {
             });
         }
 
-        fn configure(_standard: &Standard, data_format: &DataFormat) {
+        /// Set the bit clock divider of the receiver, keeping its master clock.
+        #[cfg(any(esp32c3, esp32c6, esp32h2))]
+        fn set_rx_bclk_divider(clock_settings: I2sClockDividers) {
+            let i2s = Self::register_block();
+
+            #[cfg(not(esp32h2))]
+            i2s.rx_conf1().modify(|_, w| unsafe {
+                w.rx_bck_div_num()
+                    .bits((clock_settings.bclk_divider - 1) as u8)
+            });
+            #[cfg(esp32h2)]
+            i2s.rx_conf().modify(|_, w| unsafe {
+                w.rx_bck_div_num()
+                    .bits((clock_settings.bclk_divider - 1) as u8)
+            });
+        }
+
+        fn configure(standard: &Standard, data_format: &DataFormat) {
             let i2s = Self::register_block();
 
+            let (slots, slot_mask) = match standard {
+                Standard::Tdm { slots, slot_mask } => (*slots, *slot_mask),
+                _ => (2, 0b11),
+            };
+            let tx_pdm = standard.is_pdm();
+            #[cfg(esp32s3)]
+            let rx_pdm = standard.is_pdm();
+            #[cfg(not(esp32s3))]
+            let rx_pdm = false;
+
+            // At most I2S_LL_SLOT_FRAME_BIT_MAX, checked by `I2s::new`
+            let half_frame_bits = slots as u16 * data_format.channel_bits() as u16 / 2;
+            let (ws_width, msb_shift) = match standard {
+                Standard::Msb => (half_frame_bits, false),
+                Standard::PcmShort => (1, true),
+                Standard::PcmLong => (data_format.channel_bits() as u16, true),
+                // WS is high for half of the frame
+                _ => (half_frame_bits, true),
+            };
+            let ws_idle_pol = Self::default_ws_idle_pol(standard);
+
             #[allow(clippy::useless_conversion)]
             i2s.tx_conf1().modify(|_, w| unsafe {
                 w.tx_tdm_ws_width()
-                    .bits((data_format.channel_bits() - 1).into())
+                    .bits((ws_width - 1).try_into().unwrap())
                     .tx_bits_mod()
                     .bits(data_format.data_bits() - 1)
                     .tx_tdm_chan_bits()
                     .bits(data_format.channel_bits() - 1)
                     .tx_half_sample_bits()
-                    .bits(data_format.channel_bits() - 1)
+                    .bits((half_frame_bits - 1).try_into().unwrap())
             });
             #[cfg(not(esp32h2))]
-            i2s.tx_conf1().modify(|_, w| w.tx_msb_shift().set_bit());
+            i2s.tx_conf1()
+                .modify(|_, w| w.tx_msb_shift().bit(msb_shift));
             #[cfg(esp32h2)]
-            i2s.tx_conf().modify(|_, w| w.tx_msb_shift().set_bit());
+            i2s.tx_conf().modify(|_, w| w.tx_msb_shift().bit(msb_shift));
             i2s.tx_conf().modify(|_, w| unsafe {
                 w.tx_mono()
                     .clear_bit()
@@ -1505,12 +1960,14 @@ mod private {
                     .set_bit()
                     .tx_stop_en()
                     .set_bit()
+                    .tx_ws_idle_pol()
+                    .bit(ws_idle_pol)
                     .tx_chan_equal()
                     .clear_bit()
                     .tx_tdm_en()
-                    .set_bit()
+                    .bit(!tx_pdm)
                     .tx_pdm_en()
-                    .clear_bit()
+                    .bit(tx_pdm)
                     .tx_pcm_bypass()
                     .set_bit()
                     .tx_big_endian()
@@ -1523,56 +1980,57 @@ mod private {
 
             i2s.tx_tdm_ctrl().modify(|_, w| unsafe {
                 w.tx_tdm_tot_chan_num()
-                    .bits(1)
+                    .bits(slots - 1)
                     .tx_tdm_chan0_en()
-                    .set_bit()
+                    .bit(slot_mask & (1 << 0) != 0)
                     .tx_tdm_chan1_en()
-                    .set_bit()
+                    .bit(slot_mask & (1 << 1) != 0)
                     .tx_tdm_chan2_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 2) != 0)
                     .tx_tdm_chan3_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 3) != 0)
                     .tx_tdm_chan4_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 4) != 0)
                     .tx_tdm_chan5_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 5) != 0)
                     .tx_tdm_chan6_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 6) != 0)
                     .tx_tdm_chan7_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 7) != 0)
                     .tx_tdm_chan8_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 8) != 0)
                     .tx_tdm_chan9_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 9) != 0)
                     .tx_tdm_chan10_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 10) != 0)
                     .tx_tdm_chan11_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 11) != 0)
                     .tx_tdm_chan12_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 12) != 0)
                     .tx_tdm_chan13_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 13) != 0)
                     .tx_tdm_chan14_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 14) != 0)
                     .tx_tdm_chan15_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 15) != 0)
             });
 
             #[allow(clippy::useless_conversion)]
             i2s.rx_conf1().modify(|_, w| unsafe {
                 w.rx_tdm_ws_width()
-                    .bits((data_format.channel_bits() - 1).into())
+                    .bits((ws_width - 1).try_into().unwrap())
                     .rx_bits_mod()
                     .bits(data_format.data_bits() - 1)
                     .rx_tdm_chan_bits()
                     .bits(data_format.channel_bits() - 1)
                     .rx_half_sample_bits()
-                    .bits(data_format.channel_bits() - 1)
+                    .bits((half_frame_bits - 1).try_into().unwrap())
             });
             #[cfg(not(esp32h2))]
-            i2s.rx_conf1().modify(|_, w| w.rx_msb_shift().set_bit());
+            i2s.rx_conf1()
+                .modify(|_, w| w.rx_msb_shift().bit(msb_shift));
             #[cfg(esp32h2)]
-            i2s.rx_conf().modify(|_, w| w.rx_msb_shift().set_bit());
+            i2s.rx_conf().modify(|_, w| w.rx_msb_shift().bit(msb_shift));
 
             i2s.rx_conf().modify(|_, w| unsafe {
                 w.rx_mono()
@@ -1581,10 +2039,12 @@ mod private {
                     .set_bit()
                     .rx_stop_mode()
                     .bits(2)
+                    .rx_ws_idle_pol()
+                    .bit(ws_idle_pol)
                     .rx_tdm_en()
-                    .set_bit()
+                    .bit(!rx_pdm)
                     .rx_pdm_en()
-                    .clear_bit()
+                    .bit(rx_pdm)
                     .rx_pcm_bypass()
                     .set_bit()
                     .rx_big_endian()
@@ -1595,40 +2055,142 @@ mod private {
 
             i2s.rx_tdm_ctrl().modify(|_, w| unsafe {
                 w.rx_tdm_tot_chan_num()
-                    .bits(1)
+                    .bits(slots - 1)
                     .rx_tdm_pdm_chan0_en()
-                    .set_bit()
+                    .bit(slot_mask & (1 << 0) != 0)
                     .rx_tdm_pdm_chan1_en()
-                    .set_bit()
+                    .bit(slot_mask & (1 << 1) != 0)
                     .rx_tdm_pdm_chan2_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 2) != 0)
                     .rx_tdm_pdm_chan3_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 3) != 0)
                     .rx_tdm_pdm_chan4_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 4) != 0)
                     .rx_tdm_pdm_chan5_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 5) != 0)
                     .rx_tdm_pdm_chan6_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 6) != 0)
                     .rx_tdm_pdm_chan7_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 7) != 0)
                     .rx_tdm_chan8_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 8) != 0)
                     .rx_tdm_chan9_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 9) != 0)
                     .rx_tdm_chan10_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 10) != 0)
                     .rx_tdm_chan11_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 11) != 0)
                     .rx_tdm_chan12_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 12) != 0)
                     .rx_tdm_chan13_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 13) != 0)
                     .rx_tdm_chan14_en()
-                    .clear_bit()
+                    .bit(slot_mask & (1 << 14) != 0)
                     .rx_tdm_chan15_en()
+                    .bit(slot_mask & (1 << 15) != 0)
+            });
+
+            Self::configure_pdm(standard, tx_pdm, rx_pdm);
+        }
+
+        fn configure_pdm(standard: &Standard, tx_pdm: bool, rx_pdm: bool) {
+            if !tx_pdm && !rx_pdm {
+                return;
+            }
+
+            // On ESP32-S3 I2S1 has no PDM support, its register block doesn't have the PDM
+            // registers. Enabling PDM on it is rejected by `I2s::new_i2s1`.
+            let i2s = unsafe { &*crate::peripherals::I2S0::PTR };
+
+            let dac_mode = matches!(standard, Standard::PdmDac);
+
+            // Up-sample by 2 (960 / 480), the PDM clock is 128 times the sample rate.
+            i2s.tx_pcm2pdm_conf1().modify(|_, w| unsafe {
+                w.tx_pdm_fp()
+                    .bits(960)
+                    .tx_pdm_fs()
+                    .bits(480)
+                    .tx_iir_hp_mult12_5()
+                    .bits(7)
+                    .tx_iir_hp_mult12_0()
+                    .bits(6)
+            });
+
+            i2s.tx_pcm2pdm_conf().modify(|_, w| unsafe {
+                w.tx_pdm_hp_bypass()
                     .clear_bit()
+                    .tx_pdm_sinc_osr2()
+                    .bits(2)
+                    .tx_pdm_prescale()
+                    .bits(0)
+                    .tx_pdm_hp_in_shift()
+                    .bits(1)
+                    .tx_pdm_lp_in_shift()
+                    .bits(1)
+                    .tx_pdm_sinc_in_shift()
+                    .bits(1)
+                    .tx_pdm_sigmadelta_in_shift()
+                    .bits(1)
+                    .tx_pdm_sigmadelta_dither2()
+                    .clear_bit()
+                    .tx_pdm_sigmadelta_dither()
+                    .set_bit()
+                    .tx_pdm_dac_2out_en()
+                    .clear_bit()
+                    .tx_pdm_dac_mode_en()
+                    .bit(dac_mode)
+                    .pcm2pdm_conv_en()
+                    .bit(tx_pdm)
             });
+
+            // Down-sample by 16 and convert to PCM in hardware.
+            #[cfg(esp32s3)]
+            i2s.rx_conf().modify(|_, w| {
+                w.rx_pdm_sinc_dsr_16_en()
+                    .set_bit()
+                    .rx_pdm2pcm_en()
+                    .bit(rx_pdm)
+            });
+        }
+
+        /// The PCM formats use a WS pulse which is high, the others start the
+        /// frame with WS low.
+        fn default_ws_idle_pol(standard: &Standard) -> bool {
+            matches!(standard, Standard::PcmShort | Standard::PcmLong)
+        }
+
+        fn set_channels(channels: Channels, _data_format: &DataFormat) {
+            let i2s = Self::register_block();
+
+            let (mono, left, right) = match channels {
+                Channels::Stereo => (false, true, true),
+                Channels::MonoLeft => (true, true, false),
+                Channels::MonoRight => (true, false, true),
+            };
+
+            // in mono mode the sample is sent in both slots
+            i2s.tx_conf()
+                .modify(|_, w| w.tx_mono().bit(mono).tx_chan_equal().bit(mono));
+            i2s.tx_tdm_ctrl()
+                .modify(|_, w| w.tx_tdm_chan0_en().bit(left).tx_tdm_chan1_en().bit(right));
+
+            i2s.rx_conf().modify(|_, w| w.rx_mono().bit(mono));
+            i2s.rx_tdm_ctrl().modify(|_, w| {
+                w.rx_tdm_pdm_chan0_en()
+                    .bit(left)
+                    .rx_tdm_pdm_chan1_en()
+                    .bit(right)
+            });
+        }
+
+        fn set_ws_polarity(standard: &Standard, polarity: WsPolarity) {
+            let i2s = Self::register_block();
+            let ws_idle_pol =
+                Self::default_ws_idle_pol(standard) ^ (polarity == WsPolarity::Inverted);
+            i2s.tx_conf()
+                .modify(|_, w| w.tx_ws_idle_pol().bit(ws_idle_pol));
+            i2s.rx_conf()
+                .modify(|_, w| w.rx_ws_idle_pol().bit(ws_idle_pol));
         }
 
         fn set_master() {
@@ -1637,6 +2199,12 @@ mod private {
             i2s.rx_conf().modify(|_, w| w.rx_slave_mod().clear_bit());
         }
 
+        fn set_slave() {
+            let i2s = Self::register_block();
+            i2s.tx_conf().modify(|_, w| w.tx_slave_mod().set_bit());
+            i2s.rx_conf().modify(|_, w| w.rx_slave_mod().set_bit());
+        }
+
         fn update() {
             let i2s = Self::register_block();
             i2s.tx_conf().modify(|_, w| w.tx_update().clear_bit());
@@ -1743,6 +2311,22 @@ mod private {
         fn din_signal() -> InputSignal {
             InputSignal::I2SI_SD
         }
+
+        fn bclk_in_signal() -> InputSignal {
+            InputSignal::I2SO_BCK
+        }
+
+        fn ws_in_signal() -> InputSignal {
+            InputSignal::I2SO_WS
+        }
+
+        fn bclk_rx_in_signal() -> InputSignal {
+            InputSignal::I2SI_BCK
+        }
+
+        fn ws_rx_in_signal() -> InputSignal {
+            InputSignal::I2SI_WS
+        }
     }
 
     #[cfg(esp32s3)]
@@ -1782,6 +2366,22 @@ mod private {
         fn din_signal() -> InputSignal {
             InputSignal::I2S0I_SD
         }
+
+        fn bclk_in_signal() -> InputSignal {
+            InputSignal::I2S0O_BCK
+        }
+
+        fn ws_in_signal() -> InputSignal {
+            InputSignal::I2S0O_WS
+        }
+
+        fn bclk_rx_in_signal() -> InputSignal {
+            InputSignal::I2S0I_BCK
+        }
+
+        fn ws_rx_in_signal() -> InputSignal {
+            InputSignal::I2S0I_WS
+        }
     }
 
     #[cfg(esp32s3)]
@@ -1821,6 +2421,22 @@ mod private {
         fn din_signal() -> InputSignal {
             InputSignal::I2S1I_SD
         }
+
+        fn bclk_in_signal() -> InputSignal {
+            InputSignal::I2S1O_BCK
+        }
+
+        fn ws_in_signal() -> InputSignal {
+            InputSignal::I2S1O_WS
+        }
+
+        fn bclk_rx_in_signal() -> InputSignal {
+            InputSignal::I2S1I_BCK
+        }
+
+        fn ws_rx_in_signal() -> InputSignal {
+            InputSignal::I2S1I_WS
+        }
     }
 
     #[cfg(esp32)]
@@ -1860,6 +2476,22 @@ mod private {
         fn din_signal() -> InputSignal {
             InputSignal::I2S0I_DATA_15
         }
+
+        fn bclk_in_signal() -> InputSignal {
+            InputSignal::I2S0O_BCK
+        }
+
+        fn ws_in_signal() -> InputSignal {
+            InputSignal::I2S0O_WS
+        }
+
+        fn bclk_rx_in_signal() -> InputSignal {
+            InputSignal::I2S0I_BCK
+        }
+
+        fn ws_rx_in_signal() -> InputSignal {
+            InputSignal::I2S0I_WS
+        }
     }
 
     #[cfg(esp32)]
@@ -1899,6 +2531,22 @@ mod private {
         fn din_signal() -> InputSignal {
             InputSignal::I2S1I_DATA_15
         }
+
+        fn bclk_in_signal() -> InputSignal {
+            InputSignal::I2S1O_BCK
+        }
+
+        fn ws_in_signal() -> InputSignal {
+            InputSignal::I2S1O_WS
+        }
+
+        fn bclk_rx_in_signal() -> InputSignal {
+            InputSignal::I2S1I_BCK
+        }
+
+        fn ws_rx_in_signal() -> InputSignal {
+            InputSignal::I2S1I_WS
+        }
     }
 
     #[cfg(esp32s2)]
@@ -1938,6 +2586,22 @@ mod private {
         fn din_signal() -> InputSignal {
             InputSignal::I2S0I_DATA_IN15
         }
+
+        fn bclk_in_signal() -> InputSignal {
+            InputSignal::I2S0O_BCK
+        }
+
+        fn ws_in_signal() -> InputSignal {
+            InputSignal::I2S0O_WS
+        }
+
+        fn bclk_rx_in_signal() -> InputSignal {
+            InputSignal::I2S0I_BCK
+        }
+
+        fn ws_rx_in_signal() -> InputSignal {
+            InputSignal::I2S0I_WS
+        }
     }
 
     impl RegBlock for I2S0 {
@@ -2004,14 +2668,24 @@ mod private {
         // If data_bits is a power of two, use 256 as the mclk_multiple
         // If data_bits is 24, use 192 (24 * 8) as the mclk_multiple
         let mclk_multiple = if data_bits == 24 { 192 } else { 256 };
-        let sclk = crate::soc::constants::I2S_SCLK; // for now it's fixed 160MHz and 96MHz (just H2)
 
         let rate_hz: HertzU32 = sample_rate.into();
         let rate = rate_hz.raw();
 
         let bclk = rate * channels as u32 * data_bits as u32;
-        let mclk = rate * mclk_multiple;
+        // Frames with many slots (TDM) or high bit clocks (PDM) need a higher
+        // master clock to keep the bit clock divider at 2 or more
+        let mclk = rate * u32::max(mclk_multiple, 2 * channels as u32 * data_bits as u32);
         let bclk_divider = mclk / bclk;
+
+        calculate_mclk_dividers(mclk, bclk_divider)
+    }
+
+    /// Calculates the integral and fractional dividers generating `mclk`
+    /// from the I2S source clock.
+    pub fn calculate_mclk_dividers(mclk: u32, bclk_divider: u32) -> I2sClockDividers {
+        let sclk = crate::soc::constants::I2S_SCLK; // for now it's fixed 160MHz and 96MHz (just H2)
+
         let mut mclk_divider = sclk / mclk;
 
         let mut ma: u32;
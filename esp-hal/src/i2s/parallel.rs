@@ -0,0 +1,1420 @@
+//! # Parallel LCD and Camera Mode
+//!
+//! ## Overview
+//! ESP32 and ESP32-S2 have no LCD_CAM peripheral, instead their I2S
+//! peripherals can be switched to a parallel mode driving 8/16 bit I8080
+//! displays or receiving 8/16 bit DVP camera data. [I8080] and [Camera]
+//! mirror the drivers of the same name in the `lcd_cam` module of the
+//! ESP32-S3. An I2S peripheral is used either for a display or for a camera.
+//!
+//! ## Data layout
+//! Both drivers take the same buffers as their `lcd_cam` counterparts, the
+//! data is sent and received in memory order.
+//!
+//! The DMA moves 4 bytes at a time. Commands, dummy cycles and the data of
+//! [I8080::send] are instead written to the I2S FIFO by the CPU, one bus word
+//! per FIFO entry, and can have any length. [I8080::send_dma] sends the last
+//! bytes which don't fill 4 bytes the same way once the DMA transfer is done.
+//!
+//! The I2S peripheral receives every sample of an 8 bit camera bus into 16
+//! bits. [Camera::read_dma] packs the samples when the transfer is done, so a
+//! buffer of `n` bytes receives `n / 2` samples. Circular transfers return
+//! every sample in the low byte of 16 bits.
+//!
+//! The DC and CS lines are driven by software, the write clock is generated
+//! on the WS output of the I2S peripheral.
+#![cfg_attr(
+    esp32,
+    doc = "\nOn ESP32 `I2S0` only outputs every other byte with an 8 bit bus, use `I2S1` for 8 bit displays."
+)]
+//! ## Examples
+//! Following code shows how to send commands to a MIPI-DSI display.
+//!
+//! ```no_run
+//! let tx_pins = TxEightBits::new(
+//!     io.pins.gpio16,
+//!     io.pins.gpio4,
+//!     io.pins.gpio17,
+//!     io.pins.gpio18,
+//!     io.pins.gpio5,
+//!     io.pins.gpio19,
+//!     io.pins.gpio12,
+//!     io.pins.gpio14,
+//! );
+//!
+//! let mut i8080 = I8080::new_i2s1(
+//!     peripherals.I2S1,
+//!     channel.tx,
+//!     tx_pins,
+//!     10.MHz(),
+//!     Config::default(),
+//!     &clocks,
+//! )
+//! .with_ctrl_pins(io.pins.gpio21, io.pins.gpio22);
+//!
+//! i8080.send(0x2A, 0, &[0x00, 0x00, 0x00, 0xEF]).unwrap(); // Columns 0 to 239
+//! i8080.send(0x3A, 0, &[0x55]).unwrap(); // 16 bit pixels
+//! ```
+//!
+//! The camera needs its XCLK from another peripheral, e.g. `LEDC`.
+//!
+//! ```no_run
+//! let data_pins = RxEightBits::new(
+//!     io.pins.gpio5,
+//!     io.pins.gpio18,
+//!     io.pins.gpio19,
+//!     io.pins.gpio21,
+//!     io.pins.gpio36,
+//!     io.pins.gpio39,
+//!     io.pins.gpio34,
+//!     io.pins.gpio35,
+//! );
+//!
+//! let mut camera = Camera::new(peripherals.I2S0, channel.rx, data_pins, &clocks).with_ctrl_pins(
+//!     io.pins.gpio25,
+//!     io.pins.gpio23,
+//!     io.pins.gpio22,
+//! );
+//! ```
+
+use core::{marker::PhantomData, mem::size_of};
+
+use embedded_dma::{ReadBuffer, WriteBuffer};
+use fugit::HertzU32;
+
+use self::private::{RxPins, TxPins};
+#[cfg(esp32)]
+use super::private::I2s1Instance;
+use super::{
+    private::{calculate_mclk_dividers, I2s0Instance},
+    RegisterAccess,
+};
+#[cfg(esp32)]
+use crate::dma::I2s1Peripheral;
+use crate::{
+    clock::Clocks,
+    dma::{
+        dma_private::{DmaSupport, DmaSupportRx, DmaSupportTx},
+        ChannelRx,
+        ChannelTx,
+        ChannelTypes,
+        DmaError,
+        DmaTransferRx,
+        DmaTransferRxCircular,
+        DmaTransferTx,
+        I2s0Peripheral,
+        I2sPeripheral,
+        RegisterAccess as DmaRegisterAccess,
+        Rx,
+        RxChannel,
+        RxPrivate,
+        Tx,
+        TxChannel,
+        TxPrivate,
+    },
+    gpio::{connect_high_to_peripheral, AnyOutput, CreateErasedPin, InputPin, Level, OutputPin},
+    peripheral::{Peripheral, PeripheralRef},
+    system::PeripheralClockControl,
+};
+
+/// Number of 32 bit entries of the I2S TX FIFO.
+const TX_FIFO_ENTRIES: usize = 64;
+
+/// An I2S peripheral supporting the parallel mode.
+pub trait Instance: RegisterAccess + private::ParallelSignals {}
+
+impl Instance for crate::peripherals::I2S0 {}
+
+#[cfg(esp32)]
+impl Instance for crate::peripherals::I2S1 {}
+
+/// Selects whether the CPU or the DMA fills the TX FIFO.
+///
+/// The CPU writes one 32 bit sample per bus word, the bus word is taken from
+/// bits 16 and up. The DMA fills the FIFO with samples of the bus width.
+fn select_tx_fifo_access<I: Instance>(cpu: bool, bus_width: u8) {
+    let i2s = I::register_block();
+    i2s.fifo_conf().modify(|_, w| w.dscr_en().bit(!cpu));
+    i2s.sample_rate_conf()
+        .modify(|_, w| unsafe { w.tx_bits_mod().bits(if cpu { 32 } else { bus_width }) });
+}
+
+/// Resets the TX unit, the I2S peripheral needs a few clock cycles before
+/// leaving the reset or the next transfer won't start.
+fn reset_tx<I: Instance>() {
+    let i2s = I::register_block();
+    i2s.conf()
+        .modify(|_, w| w.tx_reset().set_bit().tx_fifo_reset().set_bit());
+    crate::rom::ets_delay_us(1);
+    i2s.conf()
+        .modify(|_, w| w.tx_reset().clear_bit().tx_fifo_reset().clear_bit());
+
+    i2s.lc_conf().modify(|_, w| w.out_rst().set_bit());
+    i2s.lc_conf().modify(|_, w| w.out_rst().clear_bit());
+
+    i2s.int_clr().write(|w| {
+        w.out_done()
+            .clear_bit_by_one()
+            .out_total_eof()
+            .clear_bit_by_one()
+    });
+}
+
+pub struct I8080<'d, I, TX, P> {
+    register_access: PhantomData<I>,
+    tx_channel: TX,
+    _pins: P,
+    dc: Option<AnyOutput<'d>>,
+    cs: Option<AnyOutput<'d>>,
+    config: Config,
+    // The words of a DMA transfer which are left for the CPU to send
+    tail: Option<(*const u8, usize)>,
+}
+
+impl<'d, I, T, R, P> I8080<'d, I, ChannelTx<'d, T, R>, P>
+where
+    I: Instance,
+    T: TxChannel<R>,
+    R: ChannelTypes + DmaRegisterAccess,
+    P: TxPins,
+{
+    /// Create the driver on the first I2S peripheral.
+    pub fn new(
+        _i2s: impl Peripheral<P = I> + 'd,
+        channel: ChannelTx<'d, T, R>,
+        pins: P,
+        frequency: HertzU32,
+        config: Config,
+        _clocks: &Clocks,
+    ) -> Self
+    where
+        I: I2s0Instance,
+        R::P: I2sPeripheral + I2s0Peripheral,
+    {
+        Self::new_internal(channel, pins, frequency, config)
+    }
+
+    /// Create the driver on the second I2S peripheral.
+    #[cfg(esp32)]
+    pub fn new_i2s1(
+        _i2s: impl Peripheral<P = I> + 'd,
+        channel: ChannelTx<'d, T, R>,
+        pins: P,
+        frequency: HertzU32,
+        config: Config,
+        _clocks: &Clocks,
+    ) -> Self
+    where
+        I: I2s1Instance,
+        R::P: I2sPeripheral + I2s1Peripheral,
+    {
+        Self::new_internal(channel, pins, frequency, config)
+    }
+
+    fn new_internal(
+        mut channel: ChannelTx<'d, T, R>,
+        mut pins: P,
+        frequency: HertzU32,
+        config: Config,
+    ) -> Self {
+        let bus_width = (size_of::<P::Word>() * 8) as u8;
+
+        PeripheralClockControl::enable(I::get_peripheral());
+
+        // The write clock is half the module clock, with an 8 bit bus WS toggles
+        // twice per 16 bit sample.
+        let bclk_divider = if bus_width == 8 { 2 } else { 1 };
+        I::set_clock(calculate_mclk_dividers(frequency.raw() * 2, bclk_divider));
+
+        reset_tx::<I>();
+        I::reset_rx();
+
+        let i2s = I::register_block();
+        i2s.conf2()
+            .write(|w| w.lcd_tx_wrx2_en().bit(bus_width == 8).lcd_en().set_bit());
+        i2s.sample_rate_conf().modify(|_, w| unsafe {
+            w.tx_bits_mod()
+                .bits(bus_width)
+                .rx_bits_mod()
+                .bits(bus_width)
+        });
+        i2s.fifo_conf().write(|w| unsafe {
+            w.tx_fifo_mod_force_en()
+                .set_bit()
+                .rx_fifo_mod_force_en()
+                .set_bit()
+                .tx_fifo_mod()
+                .bits(1)
+                .rx_fifo_mod()
+                .bits(1)
+                .tx_data_num()
+                .bits(32)
+                .rx_data_num()
+                .bits(32)
+                .dscr_en()
+                .set_bit()
+        });
+        i2s.conf1().write(|w| {
+            w.tx_stop_en()
+                .set_bit()
+                .tx_pcm_bypass()
+                .set_bit()
+                .rx_pcm_bypass()
+                .set_bit()
+        });
+        i2s.conf_chan()
+            .write(|w| unsafe { w.tx_chan_mod().bits(1).rx_chan_mod().bits(1) });
+        i2s.conf().modify(|_, w| {
+            w.tx_slave_mod()
+                .clear_bit()
+                .tx_mono()
+                .set_bit()
+                .rx_mono()
+                .set_bit()
+                // Send the lower half of each FIFO entry first, i.e. in memory
+                // order
+                .tx_right_first()
+                .clear_bit()
+                .rx_right_first()
+                .clear_bit()
+        });
+        i2s.timing().reset();
+        i2s.pd_conf()
+            .modify(|_, w| w.fifo_force_pu().set_bit().fifo_force_pd().clear_bit());
+
+        channel.init_channel();
+        pins.configure::<I>();
+
+        Self {
+            register_access: PhantomData,
+            tx_channel: channel,
+            _pins: pins,
+            dc: None,
+            cs: None,
+            config,
+            tail: None,
+        }
+    }
+}
+
+impl<'d, I: Instance, TX: Tx, P: TxPins> DmaSupport for I8080<'d, I, TX, P> {
+    fn peripheral_wait_dma(&mut self, _is_tx: bool, _is_rx: bool) {
+        I::wait_for_tx_done();
+        if let Some((ptr, len)) = self.tail.take() {
+            let words = unsafe { core::slice::from_raw_parts(ptr as *const P::Word, len) };
+            self.write_fifo(words.iter().copied());
+        }
+        self.tear_down_send();
+    }
+
+    fn peripheral_dma_stop(&mut self) {
+        I::tx_stop();
+    }
+}
+
+impl<'d, I: Instance, TX: Tx, P: TxPins> DmaSupportTx for I8080<'d, I, TX, P> {
+    type TX = TX;
+
+    fn tx(&mut self) -> &mut Self::TX {
+        &mut self.tx_channel
+    }
+}
+
+impl<'d, I: Instance, TX: Tx, P: TxPins> I8080<'d, I, TX, P> {
+    const BUS_WIDTH: u8 = (size_of::<P::Word>() * 8) as u8;
+
+    /// Use a chip select line, which is driven low during transfers.
+    pub fn with_cs<CS: OutputPin + CreateErasedPin>(
+        mut self,
+        cs: impl Peripheral<P = CS> + 'd,
+    ) -> Self {
+        self.cs = Some(AnyOutput::new(cs, Level::High));
+        self
+    }
+
+    pub fn with_ctrl_pins<DC: OutputPin + CreateErasedPin, WRX: OutputPin>(
+        mut self,
+        dc: impl Peripheral<P = DC> + 'd,
+        wrx: impl Peripheral<P = WRX> + 'd,
+    ) -> Self {
+        crate::into_ref!(wrx);
+
+        self.dc = Some(AnyOutput::new(dc, self.config.cd_idle_edge.into()));
+
+        // The data changes with the rising edge of WS, inverting it makes the
+        // display latch stable data.
+        wrx.set_to_push_pull_output(crate::private::Internal);
+        wrx.connect_peripheral_to_output_with_options(
+            I::ws_signal(),
+            true,
+            false,
+            false,
+            false,
+            crate::private::Internal,
+        );
+
+        self
+    }
+
+    /// Send a command and data.
+    ///
+    /// The data is written to the FIFO by the CPU, this is meant for commands
+    /// with a few parameters.
+    pub fn send(
+        &mut self,
+        cmd: impl Into<Command<P::Word>>,
+        dummy: u8,
+        data: &[P::Word],
+    ) -> Result<(), DmaError> {
+        self.setup_send(cmd.into(), dummy);
+        self.write_fifo(data.iter().copied());
+        self.tear_down_send();
+
+        Ok(())
+    }
+
+    /// Send a command and start sending the data using DMA.
+    ///
+    /// The command phase is sent before this returns. If the length of the
+    /// data isn't a multiple of 4 bytes, the remaining words are sent when
+    /// the transfer is waited for.
+    pub fn send_dma<'t, TXBUF>(
+        &'t mut self,
+        cmd: impl Into<Command<P::Word>>,
+        dummy: u8,
+        data: &'t TXBUF,
+    ) -> Result<DmaTransferTx<Self>, DmaError>
+    where
+        TXBUF: ReadBuffer<Word = P::Word>,
+    {
+        let (ptr, len) = unsafe { data.read_buffer() };
+        let len = len * size_of::<P::Word>();
+        // The DMA moves whole FIFO entries
+        let dma_len = len & !3;
+
+        self.setup_send(cmd.into(), dummy);
+        if dma_len > 0 {
+            if let Err(err) = self.start_write_bytes_dma(ptr as _, dma_len) {
+                self.tear_down_send();
+                return Err(err);
+            }
+        }
+        if dma_len < len {
+            let tail = unsafe { (ptr as *const u8).add(dma_len) };
+            self.tail = Some((tail, (len - dma_len) / size_of::<P::Word>()));
+        }
+
+        Ok(DmaTransferTx::new(self))
+    }
+
+    fn set_dc(&mut self, level: bool) {
+        if let Some(dc) = self.dc.as_mut() {
+            dc.set_level(level.into());
+        }
+    }
+
+    /// Sends the command and dummy phases, leaving DC at the data level.
+    fn setup_send(&mut self, cmd: Command<P::Word>, dummy: u8) {
+        if let Some(cs) = self.cs.as_mut() {
+            cs.set_low();
+        }
+
+        let (len, words) = match cmd {
+            Command::None => (0, [P::Word::default(); 2]),
+            Command::One(value) => (1, [value, P::Word::default()]),
+            Command::Two(first, second) => (2, [first, second]),
+        };
+        if len > 0 {
+            self.set_dc(self.config.cd_cmd_edge);
+            self.write_fifo(words[..len].iter().copied());
+        }
+
+        if dummy > 0 {
+            self.set_dc(self.config.cd_dummy_edge);
+            self.write_fifo(core::iter::repeat(P::Word::default()).take(dummy as usize));
+        }
+
+        self.set_dc(self.config.cd_data_edge);
+    }
+
+    fn tear_down_send(&mut self) {
+        I::tx_stop();
+
+        let idle = self.config.cd_idle_edge;
+        self.set_dc(idle);
+        if let Some(cs) = self.cs.as_mut() {
+            cs.set_high();
+        }
+    }
+
+    /// Sends `words` by writing them to the FIFO, blocks until they are sent.
+    fn write_fifo(&mut self, words: impl IntoIterator<Item = P::Word>) {
+        let i2s = I::register_block();
+        let mut words = words.into_iter().peekable();
+
+        while words.peek().is_some() {
+            reset_tx::<I>();
+            select_tx_fifo_access::<I>(true, Self::BUS_WIDTH);
+
+            for word in words.by_ref().take(TX_FIFO_ENTRIES) {
+                let word: u32 = word.into();
+                i2s.fifo_wr().write(|w| unsafe { w.bits(word << 16) });
+            }
+
+            I::tx_start();
+            I::wait_for_tx_done();
+        }
+    }
+
+    fn start_write_bytes_dma(&mut self, ptr: *const u8, len: usize) -> Result<(), DmaError> {
+        reset_tx::<I>();
+        select_tx_fifo_access::<I>(false, Self::BUS_WIDTH);
+
+        self.tx_channel
+            .prepare_transfer_without_start(I::get_dma_peripheral(), false, ptr, len)?;
+        self.tx_channel.start_transfer()?;
+
+        // Before starting need to wait shortly for the FIFO to get data,
+        // otherwise garbage data is sent out
+        crate::rom::ets_delay_us(1);
+        I::tx_start();
+
+        Ok(())
+    }
+}
+
+impl<'d, I, TX, P> core::fmt::Debug for I8080<'d, I, TX, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("I8080").finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// The level of DC while idle.
+    pub cd_idle_edge: bool,
+    /// The level of DC during the command phase.
+    pub cd_cmd_edge: bool,
+    /// The level of DC during the dummy phase.
+    pub cd_dummy_edge: bool,
+    /// The level of DC during the data phase.
+    pub cd_data_edge: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cd_idle_edge: false,
+            cd_cmd_edge: false,
+            cd_dummy_edge: false,
+            cd_data_edge: true,
+        }
+    }
+}
+
+/// I8080 command.
+///
+/// Can be [Command::None] if command phase should be suppressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command<T> {
+    None,
+    One(T),
+    Two(T, T),
+}
+
+impl<T> From<T> for Command<T> {
+    fn from(value: T) -> Self {
+        Command::One(value)
+    }
+}
+
+pub struct Camera<'d, I, RX> {
+    register_access: PhantomData<I>,
+    rx_channel: RX,
+    bus_width: usize,
+    // The buffer of a transfer whose samples are packed once it's done
+    unpacked: Option<(*mut u8, usize)>,
+    phantom: PhantomData<&'d ()>,
+}
+
+impl<'d, I, T, R> Camera<'d, I, ChannelRx<'d, T, R>>
+where
+    I: Instance,
+    T: RxChannel<R>,
+    R: ChannelTypes + DmaRegisterAccess,
+{
+    /// Create the driver on the first I2S peripheral.
+    pub fn new<P: RxPins + 'd>(
+        _i2s: impl Peripheral<P = I> + 'd,
+        channel: ChannelRx<'d, T, R>,
+        pins: P,
+        _clocks: &Clocks,
+    ) -> Self
+    where
+        I: I2s0Instance,
+        R::P: I2sPeripheral + I2s0Peripheral,
+    {
+        Self::new_internal(channel, pins)
+    }
+
+    /// Create the driver on the second I2S peripheral.
+    #[cfg(esp32)]
+    pub fn new_i2s1<P: RxPins + 'd>(
+        _i2s: impl Peripheral<P = I> + 'd,
+        channel: ChannelRx<'d, T, R>,
+        pins: P,
+        _clocks: &Clocks,
+    ) -> Self
+    where
+        I: I2s1Instance,
+        R::P: I2sPeripheral + I2s1Peripheral,
+    {
+        Self::new_internal(channel, pins)
+    }
+
+    fn new_internal<P: RxPins>(mut channel: ChannelRx<'d, T, R>, mut pins: P) -> Self {
+        PeripheralClockControl::enable(I::get_peripheral());
+
+        // The data is sampled with PCLK, the module clock only has to be faster
+        I::set_clock(calculate_mclk_dividers(80_000_000, 1));
+        I::reset_rx();
+
+        let i2s = I::register_block();
+        i2s.conf().modify(|_, w| {
+            w.rx_slave_mod()
+                .set_bit()
+                // Receive into the lower half of each FIFO entry first, i.e. in
+                // memory order
+                .rx_right_first()
+                .clear_bit()
+                .rx_msb_right()
+                .clear_bit()
+                .rx_msb_shift()
+                .clear_bit()
+                .rx_mono()
+                .clear_bit()
+                .rx_short_sync()
+                .clear_bit()
+        });
+        i2s.conf2()
+            .write(|w| w.lcd_en().set_bit().camera_en().set_bit());
+        i2s.fifo_conf().modify(|_, w| unsafe {
+            w.dscr_en()
+                .set_bit()
+                .rx_fifo_mod()
+                .bits(1)
+                .rx_fifo_mod_force_en()
+                .set_bit()
+        });
+        i2s.conf_chan()
+            .modify(|_, w| unsafe { w.rx_chan_mod().bits(1) });
+        i2s.sample_rate_conf()
+            .modify(|_, w| unsafe { w.rx_bits_mod().bits(0) });
+        i2s.timing().write(|w| w.rx_dsync_sw().set_bit());
+        i2s.pd_conf()
+            .modify(|_, w| w.fifo_force_pu().set_bit().fifo_force_pd().clear_bit());
+
+        // There is no HSYNC input, lines are framed by HREF only
+        connect_high_to_peripheral(I::h_sync_signal());
+
+        channel.init_channel();
+        pins.configure::<I>();
+
+        Self {
+            register_access: PhantomData,
+            rx_channel: channel,
+            bus_width: size_of::<P::Word>(),
+            unpacked: None,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'d, I: Instance, RX: Rx> DmaSupport for Camera<'d, I, RX> {
+    fn peripheral_wait_dma(&mut self, _is_tx: bool, _is_rx: bool) {
+        I::wait_for_rx_done();
+
+        if let Some((ptr, len)) = self.unpacked.take() {
+            // Every sample of an 8 bit bus was received into the low byte of 16 bits
+            let data = unsafe { core::slice::from_raw_parts_mut(ptr, len) };
+            (0..len / 2).for_each(|i| data[i] = data[2 * i]);
+        }
+    }
+
+    fn peripheral_dma_stop(&mut self) {
+        I::reset_rx();
+    }
+}
+
+impl<'d, I: Instance, RX: Rx> DmaSupportRx for Camera<'d, I, RX> {
+    type RX = RX;
+
+    fn rx(&mut self) -> &mut Self::RX {
+        &mut self.rx_channel
+    }
+}
+
+impl<'d, I: Instance, RX: Rx> Camera<'d, I, RX> {
+    pub fn with_ctrl_pins<VSYNC: InputPin, HENABLE: InputPin, PCLK: InputPin>(
+        self,
+        vsync: impl Peripheral<P = VSYNC> + 'd,
+        h_enable: impl Peripheral<P = HENABLE> + 'd,
+        pclk: impl Peripheral<P = PCLK> + 'd,
+    ) -> Self {
+        crate::into_ref!(vsync);
+        crate::into_ref!(h_enable);
+        crate::into_ref!(pclk);
+
+        vsync.set_to_input(crate::private::Internal);
+        vsync.connect_input_to_peripheral(I::v_sync_signal(), crate::private::Internal);
+        h_enable.set_to_input(crate::private::Internal);
+        h_enable.connect_input_to_peripheral(I::h_enable_signal(), crate::private::Internal);
+        pclk.set_to_input(crate::private::Internal);
+        pclk.connect_input_to_peripheral(I::ws_rx_in_signal(), crate::private::Internal);
+
+        self
+    }
+
+    fn start_dma<RXBUF: WriteBuffer>(
+        &mut self,
+        circular: bool,
+        buf: &mut RXBUF,
+    ) -> Result<(*mut u8, usize), DmaError> {
+        let (ptr, len) = unsafe { buf.write_buffer() };
+        let len = len * size_of::<RXBUF::Word>();
+
+        // The DMA moves whole FIFO entries
+        if len % 4 != 0 {
+            return Err(DmaError::InvalidAlignment);
+        }
+
+        I::reset_rx();
+
+        unsafe {
+            self.rx_channel.prepare_transfer_without_start(
+                circular,
+                I::get_dma_peripheral(),
+                ptr as _,
+                len,
+            )?;
+        }
+        self.rx_channel.start_transfer()?;
+
+        I::rx_start(len);
+
+        Ok((ptr as _, len))
+    }
+
+    /// Receive into `buf`, the transfer ends when the buffer is full. The
+    /// length of `buf` must be a multiple of 4 bytes.
+    ///
+    /// With an 8 bit bus every sample is received into 16 bits, the samples
+    /// are packed into the first half of `buf` when the transfer is waited
+    /// for.
+    pub fn read_dma<'t, RXBUF: WriteBuffer>(
+        &'t mut self,
+        buf: &'t mut RXBUF,
+    ) -> Result<DmaTransferRx<Self>, DmaError> {
+        let (ptr, len) = self.start_dma(false, buf)?;
+        if self.bus_width == 1 {
+            self.unpacked = Some((ptr, len));
+        }
+
+        Ok(DmaTransferRx::new(self))
+    }
+
+    /// Continuously receive into `buf`.
+    ///
+    /// With an 8 bit bus every sample is in the low byte of 16 bits.
+    pub fn read_dma_circular<'t, RXBUF: WriteBuffer>(
+        &'t mut self,
+        buf: &'t mut RXBUF,
+    ) -> Result<DmaTransferRxCircular<Self>, DmaError> {
+        self.start_dma(true, buf)?;
+        Ok(DmaTransferRxCircular::new(self))
+    }
+}
+
+impl<'d, I, RX> core::fmt::Debug for Camera<'d, I, RX> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Camera").finish()
+    }
+}
+
+pub struct TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7> {
+    pin_0: PeripheralRef<'d, P0>,
+    pin_1: PeripheralRef<'d, P1>,
+    pin_2: PeripheralRef<'d, P2>,
+    pin_3: PeripheralRef<'d, P3>,
+    pin_4: PeripheralRef<'d, P4>,
+    pin_5: PeripheralRef<'d, P5>,
+    pin_6: PeripheralRef<'d, P6>,
+    pin_7: PeripheralRef<'d, P7>,
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pin_0: impl Peripheral<P = P0> + 'd,
+        pin_1: impl Peripheral<P = P1> + 'd,
+        pin_2: impl Peripheral<P = P2> + 'd,
+        pin_3: impl Peripheral<P = P3> + 'd,
+        pin_4: impl Peripheral<P = P4> + 'd,
+        pin_5: impl Peripheral<P = P5> + 'd,
+        pin_6: impl Peripheral<P = P6> + 'd,
+        pin_7: impl Peripheral<P = P7> + 'd,
+    ) -> Self {
+        crate::into_ref!(pin_0);
+        crate::into_ref!(pin_1);
+        crate::into_ref!(pin_2);
+        crate::into_ref!(pin_3);
+        crate::into_ref!(pin_4);
+        crate::into_ref!(pin_5);
+        crate::into_ref!(pin_6);
+        crate::into_ref!(pin_7);
+
+        Self {
+            pin_0,
+            pin_1,
+            pin_2,
+            pin_3,
+            pin_4,
+            pin_5,
+            pin_6,
+            pin_7,
+        }
+    }
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> TxPins for TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+{
+    type Word = u8;
+
+    fn configure<I: Instance>(&mut self) {
+        connect_output::<I>(&mut self.pin_0, 0, 8);
+        connect_output::<I>(&mut self.pin_1, 1, 8);
+        connect_output::<I>(&mut self.pin_2, 2, 8);
+        connect_output::<I>(&mut self.pin_3, 3, 8);
+        connect_output::<I>(&mut self.pin_4, 4, 8);
+        connect_output::<I>(&mut self.pin_5, 5, 8);
+        connect_output::<I>(&mut self.pin_6, 6, 8);
+        connect_output::<I>(&mut self.pin_7, 7, 8);
+    }
+}
+
+pub struct TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> {
+    pin_0: PeripheralRef<'d, P0>,
+    pin_1: PeripheralRef<'d, P1>,
+    pin_2: PeripheralRef<'d, P2>,
+    pin_3: PeripheralRef<'d, P3>,
+    pin_4: PeripheralRef<'d, P4>,
+    pin_5: PeripheralRef<'d, P5>,
+    pin_6: PeripheralRef<'d, P6>,
+    pin_7: PeripheralRef<'d, P7>,
+    pin_8: PeripheralRef<'d, P8>,
+    pin_9: PeripheralRef<'d, P9>,
+    pin_10: PeripheralRef<'d, P10>,
+    pin_11: PeripheralRef<'d, P11>,
+    pin_12: PeripheralRef<'d, P12>,
+    pin_13: PeripheralRef<'d, P13>,
+    pin_14: PeripheralRef<'d, P14>,
+    pin_15: PeripheralRef<'d, P15>,
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+    TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+    P8: OutputPin,
+    P9: OutputPin,
+    P10: OutputPin,
+    P11: OutputPin,
+    P12: OutputPin,
+    P13: OutputPin,
+    P14: OutputPin,
+    P15: OutputPin,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pin_0: impl Peripheral<P = P0> + 'd,
+        pin_1: impl Peripheral<P = P1> + 'd,
+        pin_2: impl Peripheral<P = P2> + 'd,
+        pin_3: impl Peripheral<P = P3> + 'd,
+        pin_4: impl Peripheral<P = P4> + 'd,
+        pin_5: impl Peripheral<P = P5> + 'd,
+        pin_6: impl Peripheral<P = P6> + 'd,
+        pin_7: impl Peripheral<P = P7> + 'd,
+        pin_8: impl Peripheral<P = P8> + 'd,
+        pin_9: impl Peripheral<P = P9> + 'd,
+        pin_10: impl Peripheral<P = P10> + 'd,
+        pin_11: impl Peripheral<P = P11> + 'd,
+        pin_12: impl Peripheral<P = P12> + 'd,
+        pin_13: impl Peripheral<P = P13> + 'd,
+        pin_14: impl Peripheral<P = P14> + 'd,
+        pin_15: impl Peripheral<P = P15> + 'd,
+    ) -> Self {
+        crate::into_ref!(pin_0);
+        crate::into_ref!(pin_1);
+        crate::into_ref!(pin_2);
+        crate::into_ref!(pin_3);
+        crate::into_ref!(pin_4);
+        crate::into_ref!(pin_5);
+        crate::into_ref!(pin_6);
+        crate::into_ref!(pin_7);
+        crate::into_ref!(pin_8);
+        crate::into_ref!(pin_9);
+        crate::into_ref!(pin_10);
+        crate::into_ref!(pin_11);
+        crate::into_ref!(pin_12);
+        crate::into_ref!(pin_13);
+        crate::into_ref!(pin_14);
+        crate::into_ref!(pin_15);
+
+        Self {
+            pin_0,
+            pin_1,
+            pin_2,
+            pin_3,
+            pin_4,
+            pin_5,
+            pin_6,
+            pin_7,
+            pin_8,
+            pin_9,
+            pin_10,
+            pin_11,
+            pin_12,
+            pin_13,
+            pin_14,
+            pin_15,
+        }
+    }
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> TxPins
+    for TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+    P8: OutputPin,
+    P9: OutputPin,
+    P10: OutputPin,
+    P11: OutputPin,
+    P12: OutputPin,
+    P13: OutputPin,
+    P14: OutputPin,
+    P15: OutputPin,
+{
+    type Word = u16;
+
+    fn configure<I: Instance>(&mut self) {
+        connect_output::<I>(&mut self.pin_0, 0, 16);
+        connect_output::<I>(&mut self.pin_1, 1, 16);
+        connect_output::<I>(&mut self.pin_2, 2, 16);
+        connect_output::<I>(&mut self.pin_3, 3, 16);
+        connect_output::<I>(&mut self.pin_4, 4, 16);
+        connect_output::<I>(&mut self.pin_5, 5, 16);
+        connect_output::<I>(&mut self.pin_6, 6, 16);
+        connect_output::<I>(&mut self.pin_7, 7, 16);
+        connect_output::<I>(&mut self.pin_8, 8, 16);
+        connect_output::<I>(&mut self.pin_9, 9, 16);
+        connect_output::<I>(&mut self.pin_10, 10, 16);
+        connect_output::<I>(&mut self.pin_11, 11, 16);
+        connect_output::<I>(&mut self.pin_12, 12, 16);
+        connect_output::<I>(&mut self.pin_13, 13, 16);
+        connect_output::<I>(&mut self.pin_14, 14, 16);
+        connect_output::<I>(&mut self.pin_15, 15, 16);
+    }
+}
+
+fn connect_output<I: Instance>(pin: &mut impl OutputPin, index: usize, bus_width: usize) {
+    pin.set_to_push_pull_output(crate::private::Internal);
+    pin.connect_peripheral_to_output(
+        I::data_out_signal(index, bus_width),
+        crate::private::Internal,
+    );
+}
+
+pub struct RxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7> {
+    pin_0: PeripheralRef<'d, P0>,
+    pin_1: PeripheralRef<'d, P1>,
+    pin_2: PeripheralRef<'d, P2>,
+    pin_3: PeripheralRef<'d, P3>,
+    pin_4: PeripheralRef<'d, P4>,
+    pin_5: PeripheralRef<'d, P5>,
+    pin_6: PeripheralRef<'d, P6>,
+    pin_7: PeripheralRef<'d, P7>,
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> RxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: InputPin,
+    P1: InputPin,
+    P2: InputPin,
+    P3: InputPin,
+    P4: InputPin,
+    P5: InputPin,
+    P6: InputPin,
+    P7: InputPin,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pin_0: impl Peripheral<P = P0> + 'd,
+        pin_1: impl Peripheral<P = P1> + 'd,
+        pin_2: impl Peripheral<P = P2> + 'd,
+        pin_3: impl Peripheral<P = P3> + 'd,
+        pin_4: impl Peripheral<P = P4> + 'd,
+        pin_5: impl Peripheral<P = P5> + 'd,
+        pin_6: impl Peripheral<P = P6> + 'd,
+        pin_7: impl Peripheral<P = P7> + 'd,
+    ) -> Self {
+        crate::into_ref!(pin_0);
+        crate::into_ref!(pin_1);
+        crate::into_ref!(pin_2);
+        crate::into_ref!(pin_3);
+        crate::into_ref!(pin_4);
+        crate::into_ref!(pin_5);
+        crate::into_ref!(pin_6);
+        crate::into_ref!(pin_7);
+
+        Self {
+            pin_0,
+            pin_1,
+            pin_2,
+            pin_3,
+            pin_4,
+            pin_5,
+            pin_6,
+            pin_7,
+        }
+    }
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> RxPins for RxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: InputPin,
+    P1: InputPin,
+    P2: InputPin,
+    P3: InputPin,
+    P4: InputPin,
+    P5: InputPin,
+    P6: InputPin,
+    P7: InputPin,
+{
+    type Word = u8;
+
+    fn configure<I: Instance>(&mut self) {
+        connect_input::<I>(&mut self.pin_0, 0);
+        connect_input::<I>(&mut self.pin_1, 1);
+        connect_input::<I>(&mut self.pin_2, 2);
+        connect_input::<I>(&mut self.pin_3, 3);
+        connect_input::<I>(&mut self.pin_4, 4);
+        connect_input::<I>(&mut self.pin_5, 5);
+        connect_input::<I>(&mut self.pin_6, 6);
+        connect_input::<I>(&mut self.pin_7, 7);
+    }
+}
+
+pub struct RxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> {
+    pin_0: PeripheralRef<'d, P0>,
+    pin_1: PeripheralRef<'d, P1>,
+    pin_2: PeripheralRef<'d, P2>,
+    pin_3: PeripheralRef<'d, P3>,
+    pin_4: PeripheralRef<'d, P4>,
+    pin_5: PeripheralRef<'d, P5>,
+    pin_6: PeripheralRef<'d, P6>,
+    pin_7: PeripheralRef<'d, P7>,
+    pin_8: PeripheralRef<'d, P8>,
+    pin_9: PeripheralRef<'d, P9>,
+    pin_10: PeripheralRef<'d, P10>,
+    pin_11: PeripheralRef<'d, P11>,
+    pin_12: PeripheralRef<'d, P12>,
+    pin_13: PeripheralRef<'d, P13>,
+    pin_14: PeripheralRef<'d, P14>,
+    pin_15: PeripheralRef<'d, P15>,
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+    RxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+where
+    P0: InputPin,
+    P1: InputPin,
+    P2: InputPin,
+    P3: InputPin,
+    P4: InputPin,
+    P5: InputPin,
+    P6: InputPin,
+    P7: InputPin,
+    P8: InputPin,
+    P9: InputPin,
+    P10: InputPin,
+    P11: InputPin,
+    P12: InputPin,
+    P13: InputPin,
+    P14: InputPin,
+    P15: InputPin,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pin_0: impl Peripheral<P = P0> + 'd,
+        pin_1: impl Peripheral<P = P1> + 'd,
+        pin_2: impl Peripheral<P = P2> + 'd,
+        pin_3: impl Peripheral<P = P3> + 'd,
+        pin_4: impl Peripheral<P = P4> + 'd,
+        pin_5: impl Peripheral<P = P5> + 'd,
+        pin_6: impl Peripheral<P = P6> + 'd,
+        pin_7: impl Peripheral<P = P7> + 'd,
+        pin_8: impl Peripheral<P = P8> + 'd,
+        pin_9: impl Peripheral<P = P9> + 'd,
+        pin_10: impl Peripheral<P = P10> + 'd,
+        pin_11: impl Peripheral<P = P11> + 'd,
+        pin_12: impl Peripheral<P = P12> + 'd,
+        pin_13: impl Peripheral<P = P13> + 'd,
+        pin_14: impl Peripheral<P = P14> + 'd,
+        pin_15: impl Peripheral<P = P15> + 'd,
+    ) -> Self {
+        crate::into_ref!(pin_0);
+        crate::into_ref!(pin_1);
+        crate::into_ref!(pin_2);
+        crate::into_ref!(pin_3);
+        crate::into_ref!(pin_4);
+        crate::into_ref!(pin_5);
+        crate::into_ref!(pin_6);
+        crate::into_ref!(pin_7);
+        crate::into_ref!(pin_8);
+        crate::into_ref!(pin_9);
+        crate::into_ref!(pin_10);
+        crate::into_ref!(pin_11);
+        crate::into_ref!(pin_12);
+        crate::into_ref!(pin_13);
+        crate::into_ref!(pin_14);
+        crate::into_ref!(pin_15);
+
+        Self {
+            pin_0,
+            pin_1,
+            pin_2,
+            pin_3,
+            pin_4,
+            pin_5,
+            pin_6,
+            pin_7,
+            pin_8,
+            pin_9,
+            pin_10,
+            pin_11,
+            pin_12,
+            pin_13,
+            pin_14,
+            pin_15,
+        }
+    }
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> RxPins
+    for RxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+where
+    P0: InputPin,
+    P1: InputPin,
+    P2: InputPin,
+    P3: InputPin,
+    P4: InputPin,
+    P5: InputPin,
+    P6: InputPin,
+    P7: InputPin,
+    P8: InputPin,
+    P9: InputPin,
+    P10: InputPin,
+    P11: InputPin,
+    P12: InputPin,
+    P13: InputPin,
+    P14: InputPin,
+    P15: InputPin,
+{
+    type Word = u16;
+
+    fn configure<I: Instance>(&mut self) {
+        connect_input::<I>(&mut self.pin_0, 0);
+        connect_input::<I>(&mut self.pin_1, 1);
+        connect_input::<I>(&mut self.pin_2, 2);
+        connect_input::<I>(&mut self.pin_3, 3);
+        connect_input::<I>(&mut self.pin_4, 4);
+        connect_input::<I>(&mut self.pin_5, 5);
+        connect_input::<I>(&mut self.pin_6, 6);
+        connect_input::<I>(&mut self.pin_7, 7);
+        connect_input::<I>(&mut self.pin_8, 8);
+        connect_input::<I>(&mut self.pin_9, 9);
+        connect_input::<I>(&mut self.pin_10, 10);
+        connect_input::<I>(&mut self.pin_11, 11);
+        connect_input::<I>(&mut self.pin_12, 12);
+        connect_input::<I>(&mut self.pin_13, 13);
+        connect_input::<I>(&mut self.pin_14, 14);
+        connect_input::<I>(&mut self.pin_15, 15);
+    }
+}
+
+fn connect_input<I: Instance>(pin: &mut impl InputPin, index: usize) {
+    pin.set_to_input(crate::private::Internal);
+    pin.connect_input_to_peripheral(I::data_in_signal(index), crate::private::Internal);
+}
+
+#[cfg(esp32)]
+mod signals {
+    use crate::gpio::{InputSignal, OutputSignal};
+
+    pub const I2S0_DATA_OUT: [OutputSignal; 24] = [
+        OutputSignal::I2S0O_DATA_0,
+        OutputSignal::I2S0O_DATA_1,
+        OutputSignal::I2S0O_DATA_2,
+        OutputSignal::I2S0O_DATA_3,
+        OutputSignal::I2S0O_DATA_4,
+        OutputSignal::I2S0O_DATA_5,
+        OutputSignal::I2S0O_DATA_6,
+        OutputSignal::I2S0O_DATA_7,
+        OutputSignal::I2S0O_DATA_8,
+        OutputSignal::I2S0O_DATA_9,
+        OutputSignal::I2S0O_DATA_10,
+        OutputSignal::I2S0O_DATA_11,
+        OutputSignal::I2S0O_DATA_12,
+        OutputSignal::I2S0O_DATA_13,
+        OutputSignal::I2S0O_DATA_14,
+        OutputSignal::I2S0O_DATA_15,
+        OutputSignal::I2S0O_DATA_16,
+        OutputSignal::I2S0O_DATA_17,
+        OutputSignal::I2S0O_DATA_18,
+        OutputSignal::I2S0O_DATA_19,
+        OutputSignal::I2S0O_DATA_20,
+        OutputSignal::I2S0O_DATA_21,
+        OutputSignal::I2S0O_DATA_22,
+        OutputSignal::I2S0O_DATA_23,
+    ];
+
+    pub const I2S0_DATA_IN: [InputSignal; 16] = [
+        InputSignal::I2S0I_DATA_0,
+        InputSignal::I2S0I_DATA_1,
+        InputSignal::I2S0I_DATA_2,
+        InputSignal::I2S0I_DATA_3,
+        InputSignal::I2S0I_DATA_4,
+        InputSignal::I2S0I_DATA_5,
+        InputSignal::I2S0I_DATA_6,
+        InputSignal::I2S0I_DATA_7,
+        InputSignal::I2S0I_DATA_8,
+        InputSignal::I2S0I_DATA_9,
+        InputSignal::I2S0I_DATA_10,
+        InputSignal::I2S0I_DATA_11,
+        InputSignal::I2S0I_DATA_12,
+        InputSignal::I2S0I_DATA_13,
+        InputSignal::I2S0I_DATA_14,
+        InputSignal::I2S0I_DATA_15,
+    ];
+
+    pub const I2S1_DATA_OUT: [OutputSignal; 24] = [
+        OutputSignal::I2S1O_DATA_0,
+        OutputSignal::I2S1O_DATA_1,
+        OutputSignal::I2S1O_DATA_2,
+        OutputSignal::I2S1O_DATA_3,
+        OutputSignal::I2S1O_DATA_4,
+        OutputSignal::I2S1O_DATA_5,
+        OutputSignal::I2S1O_DATA_6,
+        OutputSignal::I2S1O_DATA_7,
+        OutputSignal::I2S1O_DATA_8,
+        OutputSignal::I2S1O_DATA_9,
+        OutputSignal::I2S1O_DATA_10,
+        OutputSignal::I2S1O_DATA_11,
+        OutputSignal::I2S1O_DATA_12,
+        OutputSignal::I2S1O_DATA_13,
+        OutputSignal::I2S1O_DATA_14,
+        OutputSignal::I2S1O_DATA_15,
+        OutputSignal::I2S1O_DATA_16,
+        OutputSignal::I2S1O_DATA_17,
+        OutputSignal::I2S1O_DATA_18,
+        OutputSignal::I2S1O_DATA_19,
+        OutputSignal::I2S1O_DATA_20,
+        OutputSignal::I2S1O_DATA_21,
+        OutputSignal::I2S1O_DATA_22,
+        OutputSignal::I2S1O_DATA_23,
+    ];
+
+    pub const I2S1_DATA_IN: [InputSignal; 16] = [
+        InputSignal::I2S1I_DATA_0,
+        InputSignal::I2S1I_DATA_1,
+        InputSignal::I2S1I_DATA_2,
+        InputSignal::I2S1I_DATA_3,
+        InputSignal::I2S1I_DATA_4,
+        InputSignal::I2S1I_DATA_5,
+        InputSignal::I2S1I_DATA_6,
+        InputSignal::I2S1I_DATA_7,
+        InputSignal::I2S1I_DATA_8,
+        InputSignal::I2S1I_DATA_9,
+        InputSignal::I2S1I_DATA_10,
+        InputSignal::I2S1I_DATA_11,
+        InputSignal::I2S1I_DATA_12,
+        InputSignal::I2S1I_DATA_13,
+        InputSignal::I2S1I_DATA_14,
+        InputSignal::I2S1I_DATA_15,
+    ];
+}
+
+#[cfg(esp32s2)]
+mod signals {
+    use crate::gpio::{InputSignal, OutputSignal};
+
+    pub const I2S0_DATA_OUT: [OutputSignal; 24] = [
+        OutputSignal::I2S0O_DATA_OUT0,
+        OutputSignal::I2S0O_DATA_OUT1,
+        OutputSignal::I2S0O_DATA_OUT2,
+        OutputSignal::I2S0O_DATA_OUT3,
+        OutputSignal::I2S0O_DATA_OUT4,
+        OutputSignal::I2S0O_DATA_OUT5,
+        OutputSignal::I2S0O_DATA_OUT6,
+        OutputSignal::I2S0O_DATA_OUT7,
+        OutputSignal::I2S0O_DATA_OUT8,
+        OutputSignal::I2S0O_DATA_OUT9,
+        OutputSignal::I2S0O_DATA_OUT10,
+        OutputSignal::I2S0O_DATA_OUT11,
+        OutputSignal::I2S0O_DATA_OUT12,
+        OutputSignal::I2S0O_DATA_OUT13,
+        OutputSignal::I2S0O_DATA_OUT14,
+        OutputSignal::I2S0O_DATA_OUT15,
+        OutputSignal::I2S0O_DATA_OUT16,
+        OutputSignal::I2S0O_DATA_OUT17,
+        OutputSignal::I2S0O_DATA_OUT18,
+        OutputSignal::I2S0O_DATA_OUT19,
+        OutputSignal::I2S0O_DATA_OUT20,
+        OutputSignal::I2S0O_DATA_OUT21,
+        OutputSignal::I2S0O_DATA_OUT22,
+        OutputSignal::I2S0O_DATA_OUT23,
+    ];
+
+    pub const I2S0_DATA_IN: [InputSignal; 16] = [
+        InputSignal::I2S0I_DATA_IN0,
+        InputSignal::I2S0I_DATA_IN1,
+        InputSignal::I2S0I_DATA_IN2,
+        InputSignal::I2S0I_DATA_IN3,
+        InputSignal::I2S0I_DATA_IN4,
+        InputSignal::I2S0I_DATA_IN5,
+        InputSignal::I2S0I_DATA_IN6,
+        InputSignal::I2S0I_DATA_IN7,
+        InputSignal::I2S0I_DATA_IN8,
+        InputSignal::I2S0I_DATA_IN9,
+        InputSignal::I2S0I_DATA_IN10,
+        InputSignal::I2S0I_DATA_IN11,
+        InputSignal::I2S0I_DATA_IN12,
+        InputSignal::I2S0I_DATA_IN13,
+        InputSignal::I2S0I_DATA_IN14,
+        InputSignal::I2S0I_DATA_IN15,
+    ];
+}
+
+mod private {
+    use super::{signals, Instance};
+    use crate::gpio::{InputSignal, OutputSignal};
+
+    pub trait TxPins {
+        type Word: Copy + Default + Into<u32>;
+
+        fn configure<I: Instance>(&mut self);
+    }
+
+    pub trait RxPins {
+        type Word;
+
+        fn configure<I: Instance>(&mut self);
+    }
+
+    pub trait ParallelSignals {
+        /// The output signal of data line `index` for a bus of `bus_width`
+        /// bits.
+        fn data_out_signal(index: usize, bus_width: usize) -> OutputSignal;
+
+        fn data_in_signal(index: usize) -> InputSignal;
+
+        fn h_sync_signal() -> InputSignal;
+
+        fn v_sync_signal() -> InputSignal;
+
+        fn h_enable_signal() -> InputSignal;
+    }
+
+    impl ParallelSignals for crate::peripherals::I2S0 {
+        fn data_out_signal(index: usize, _bus_width: usize) -> OutputSignal {
+            // The data lines start at offset 8 for both bus widths
+            signals::I2S0_DATA_OUT[index + 8]
+        }
+
+        fn data_in_signal(index: usize) -> InputSignal {
+            signals::I2S0_DATA_IN[index]
+        }
+
+        fn h_sync_signal() -> InputSignal {
+            InputSignal::I2S0I_H_SYNC
+        }
+
+        fn v_sync_signal() -> InputSignal {
+            InputSignal::I2S0I_V_SYNC
+        }
+
+        fn h_enable_signal() -> InputSignal {
+            InputSignal::I2S0I_H_ENABLE
+        }
+    }
+
+    #[cfg(esp32)]
+    impl ParallelSignals for crate::peripherals::I2S1 {
+        fn data_out_signal(index: usize, bus_width: usize) -> OutputSignal {
+            // An 8 bit bus starts at offset 0, a 16 bit bus at offset 8
+            let offset = if bus_width == 16 { 8 } else { 0 };
+            signals::I2S1_DATA_OUT[index + offset]
+        }
+
+        fn data_in_signal(index: usize) -> InputSignal {
+            signals::I2S1_DATA_IN[index]
+        }
+
+        fn h_sync_signal() -> InputSignal {
+            InputSignal::I2S1I_H_SYNC
+        }
+
+        fn v_sync_signal() -> InputSignal {
+            InputSignal::I2S1I_V_SYNC
+        }
+
+        fn h_enable_signal() -> InputSignal {
+            InputSignal::I2S1I_H_ENABLE
+        }
+    }
+}
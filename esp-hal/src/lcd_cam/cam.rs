@@ -33,12 +33,29 @@
 //!     .with_master_clock(mclk_pin) // Remove this for slave mode.
 //!     .with_ctrl_pins(vsync_pin, href_pin, pclk_pin);
 //! ```
+//!
+//! Following code shows how to capture JPEG frames into two buffers while
+//! processing them.
+//!
+//! ```no_run
+//! let mut capture = camera
+//!     .capture_frames(first_buffer, second_buffer, FrameFormat::Jpeg)
+//!     .unwrap();
+//!
+//! loop {
+//!     let frame = capture.wait_for_frame().unwrap();
+//!     process(&frame.buffer[..frame.len]);
+//!     capture.release_frame(frame.buffer);
+//! }
+//! ```
 
 use core::mem::size_of;
 
 use embedded_dma::WriteBuffer;
 use fugit::HertzU32;
 
+#[cfg(feature = "async")]
+use crate::dma::asynch::DmaRxFuture;
 use crate::{
     clock::Clocks,
     dma::{
@@ -298,6 +315,13 @@ impl<'d, RX: Rx> Camera<'d, RX> {
             .modify(|_, w| w.cam_start().set_bit());
     }
 
+    // Stop the Camera unit, the DMA doesn't receive data anymore.
+    fn stop_unit(&self) {
+        self.lcd_cam
+            .cam_ctrl1()
+            .modify(|_, w| w.cam_start().clear_bit());
+    }
+
     fn start_dma<RXBUF: WriteBuffer>(
         &mut self,
         circular: bool,
@@ -341,6 +365,253 @@ impl<'d, RX: Rx> Camera<'d, RX> {
 
         Ok(DmaTransferRxCircular::new(self))
     }
+
+    /// Receive a frame into `buf`, returns the number of bytes received.
+    ///
+    /// The transfer ends with VSYNC. If it starts in the middle of a frame
+    /// only the rest of the frame is received.
+    ///
+    /// The DMA channel must be configured with `configure_for_async`.
+    #[cfg(feature = "async")]
+    pub async fn read_frame_async<RXBUF: WriteBuffer>(
+        &mut self,
+        buf: &mut RXBUF,
+    ) -> Result<usize, DmaError> {
+        self.reset_unit_and_fifo();
+        self.start_dma(false, buf)?;
+        self.start_unit();
+
+        let result = DmaRxFuture::new(&mut self.rx_channel).await;
+        self.stop_unit();
+        result?;
+
+        Ok(self.rx_channel.received_len())
+    }
+
+    /// Capture frames alternately into two buffers, so the application can
+    /// process one frame while the next one is captured.
+    ///
+    /// See [FrameCapture] for how frames are handed over.
+    pub fn capture_frames<'t, RXBUF: WriteBuffer>(
+        &'t mut self,
+        first: RXBUF,
+        second: RXBUF,
+        format: FrameFormat,
+    ) -> Result<FrameCapture<'t, 'd, RX, RXBUF>, DmaError> {
+        let mut capture = FrameCapture {
+            camera: self,
+            format,
+            capturing: Some(first),
+            other: Some(second),
+            other_len: None,
+            dropped: 0,
+        };
+        capture.start_capture()?;
+
+        Ok(capture)
+    }
+}
+
+/// The format of the captured frames, used to detect incomplete frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameFormat {
+    /// Every non-empty frame is complete.
+    #[default]
+    Raw,
+    /// JPEG images, frames without start or end of image marker are dropped.
+    Jpeg,
+}
+
+impl FrameFormat {
+    fn frame_len(&self, data: &[u8]) -> Option<usize> {
+        match self {
+            FrameFormat::Raw => (!data.is_empty()).then_some(data.len()),
+            FrameFormat::Jpeg => jpeg_len(data),
+        }
+    }
+}
+
+/// Returns the length of the JPEG image at the start of `data`, up to and
+/// including the end of image marker.
+///
+/// Returns `None` if `data` doesn't start with a start of image marker or has
+/// no end of image marker, e.g. because the capture started in the middle of
+/// a frame.
+pub fn jpeg_len(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return None;
+    }
+
+    // The data received after the end of image marker is padding
+    data.windows(2)
+        .rposition(|marker| marker == [0xFF, 0xD9])
+        .map(|position| position + 2)
+}
+
+/// A captured frame.
+#[derive(Debug)]
+pub struct Frame<BUF> {
+    /// The buffer holding the frame.
+    pub buffer: BUF,
+    /// The length of the frame in bytes.
+    pub len: usize,
+    /// The number of frames dropped since the previously taken frame.
+    pub dropped: u32,
+}
+
+/// Double-buffered frame capture.
+///
+/// One buffer receives the next frame while the other one holds the last
+/// complete frame or is processed by the application. Each frame ends with
+/// VSYNC, after which the capture continues in the other buffer.
+///
+/// A frame is dropped if it is incomplete, if it's replaced by a newer frame
+/// before it was taken or if the application still holds the other buffer.
+///
+/// [FrameCapture::poll] must be called soon after a frame ended, otherwise the
+/// capture of the next frame starts late and the frame is incomplete.
+#[must_use]
+pub struct FrameCapture<'t, 'd, RX: Rx, BUF: WriteBuffer> {
+    camera: &'t mut Camera<'d, RX>,
+    format: FrameFormat,
+    capturing: Option<BUF>,
+    other: Option<BUF>,
+    // The length of the frame in `other`, if it holds a frame
+    other_len: Option<usize>,
+    dropped: u32,
+}
+
+impl<'t, 'd, RX: Rx, BUF: WriteBuffer> FrameCapture<'t, 'd, RX, BUF> {
+    /// Hand over the frame if it ended and continue with the next one,
+    /// returns whether a frame can be taken.
+    pub fn poll(&mut self) -> Result<bool, DmaError> {
+        let rx = &mut self.camera.rx_channel;
+        if rx.has_error() {
+            return Err(DmaError::DescriptorError);
+        }
+
+        // The frame didn't fit into the buffer
+        let overflow = rx.has_dscr_empty_error();
+        if !overflow && !rx.is_done() {
+            return Ok(self.other_len.is_some());
+        }
+
+        let received = rx.received_len();
+        self.camera.stop_unit();
+
+        let mut buffer = self.capturing.take().unwrap();
+        let frame_len = if overflow {
+            None
+        } else {
+            let (ptr, _) = unsafe { buffer.write_buffer() };
+            let data = unsafe { core::slice::from_raw_parts(ptr as *const u8, received) };
+            self.format.frame_len(data)
+        };
+
+        match (frame_len, self.other.take()) {
+            (Some(len), Some(other)) => {
+                if self.other_len.is_some() {
+                    // The previous frame was never taken
+                    self.dropped += 1;
+                }
+
+                self.capturing = Some(other);
+                self.other = Some(buffer);
+                self.other_len = Some(len);
+            }
+            (_, other) => {
+                self.dropped += 1;
+
+                self.capturing = Some(buffer);
+                self.other = other;
+            }
+        }
+
+        self.start_capture()?;
+
+        Ok(self.other_len.is_some())
+    }
+
+    /// Take the last complete frame.
+    ///
+    /// The buffer has to be given back with [FrameCapture::release_frame],
+    /// until then new frames are dropped.
+    pub fn take_frame(&mut self) -> Option<Frame<BUF>> {
+        let len = self.other_len.take()?;
+        let buffer = self.other.take()?;
+
+        Some(Frame {
+            buffer,
+            len,
+            dropped: core::mem::take(&mut self.dropped),
+        })
+    }
+
+    /// Give back the buffer of a frame taken with [FrameCapture::take_frame].
+    pub fn release_frame(&mut self, buffer: BUF) {
+        assert!(self.other.is_none(), "Both buffers are already in use");
+        self.other = Some(buffer);
+    }
+
+    /// Wait for a frame and take it, see [FrameCapture::take_frame].
+    pub fn wait_for_frame(&mut self) -> Result<Frame<BUF>, DmaError> {
+        loop {
+            if let Some(frame) = self.take_frame() {
+                return Ok(frame);
+            }
+
+            self.poll()?;
+        }
+    }
+
+    /// Wait for a frame and take it, see [FrameCapture::take_frame].
+    ///
+    /// The DMA channel must be configured with `configure_for_async`.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_frame_async(&mut self) -> Result<Frame<BUF>, DmaError> {
+        loop {
+            if let Some(frame) = self.take_frame() {
+                return Ok(frame);
+            }
+
+            if !self.poll()? && DmaRxFuture::new(&mut self.camera.rx_channel).await.is_err() {
+                // The future cleared the error, the frame didn't fit into the
+                // buffer
+                self.camera.stop_unit();
+                self.dropped += 1;
+                self.start_capture()?;
+            }
+        }
+    }
+
+    /// The number of frames dropped since the previously taken frame.
+    pub fn dropped_frames(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Stop capturing, returns the buffers still owned by the capture.
+    pub fn stop(mut self) -> (BUF, Option<BUF>) {
+        self.camera.stop_unit();
+
+        (self.capturing.take().unwrap(), self.other.take())
+    }
+
+    fn start_capture(&mut self) -> Result<(), DmaError> {
+        let buffer = self.capturing.as_mut().unwrap();
+
+        self.camera.reset_unit_and_fifo();
+        self.camera.start_dma(false, buffer)?;
+        self.camera.start_unit();
+
+        Ok(())
+    }
+}
+
+impl<'t, 'd, RX: Rx, BUF: WriteBuffer> Drop for FrameCapture<'t, 'd, RX, BUF> {
+    fn drop(&mut self) {
+        self.camera.stop_unit();
+    }
 }
 
 pub struct RxEightBits {
@@ -0,0 +1,899 @@
+//! # LCD - RGB/DPI Mode
+//!
+//! ## Overview
+//! The LCD_CAM peripheral DPI driver drives parallel RGB panels, which are
+//! refreshed continuously using HSYNC, VSYNC, DE and PCLK timing. The driver
+//! mandates DMA (Direct Memory Access) for efficient data transfer.
+//!
+//! Pixels are sent over an 8 or 16 bit bus, with an 8 bit bus a pixel may
+//! also take two or three PCLK cycles (e.g. serial RGB888).
+//!
+//! A single frame is sent with [Dpi::send_frame_dma], [Dpi::send_frames_dma]
+//! refreshes the panel from a framebuffer until stopped. DMA can't fetch large
+//! framebuffers from PSRAM fast enough, [Dpi::send_frames_bounced] refreshes
+//! from any framebuffer through a small bounce buffer in internal RAM, which
+//! the CPU refills using [DpiBounceTransfer::refill].
+//!
+//! The VSYNC event marks the start of the vertical blanking. In bounce buffer
+//! mode [DpiBounceTransfer::set_next_framebuffer] swaps framebuffers at the
+//! start of a frame, so the panel never shows parts of two frames.
+//!
+//! ## Examples
+//! Following code shows how to refresh an 800x480 RGB565 panel from a
+//! framebuffer in PSRAM.
+//!
+//! ```no_run
+//! let tx_pins = TxSixteenBits::new(
+//!     io.pins.gpio8,
+//!     io.pins.gpio3,
+//!     io.pins.gpio46,
+//!     io.pins.gpio9,
+//!     io.pins.gpio1,
+//!     io.pins.gpio5,
+//!     io.pins.gpio6,
+//!     io.pins.gpio7,
+//!     io.pins.gpio15,
+//!     io.pins.gpio16,
+//!     io.pins.gpio4,
+//!     io.pins.gpio45,
+//!     io.pins.gpio48,
+//!     io.pins.gpio47,
+//!     io.pins.gpio21,
+//!     io.pins.gpio14,
+//! );
+//!
+//! let config = Config {
+//!     timing: FrameTiming {
+//!         horizontal_active_width: 800,
+//!         horizontal_front_porch: 8,
+//!         hsync_width: 4,
+//!         horizontal_back_porch: 8,
+//!         vertical_active_height: 480,
+//!         vertical_front_porch: 8,
+//!         vsync_width: 4,
+//!         vertical_back_porch: 8,
+//!     },
+//!     ..Default::default()
+//! };
+//!
+//! let lcd_cam = LcdCam::new(peripherals.LCD_CAM);
+//! let mut dpi = Dpi::new(lcd_cam.lcd, channel.tx, tx_pins, 16.MHz(), config, &clocks)
+//!     .unwrap()
+//!     .with_ctrl_pins(io.pins.gpio41, io.pins.gpio39, io.pins.gpio42)
+//!     .with_de(io.pins.gpio40);
+//!
+//! let mut transfer = dpi
+//!     .send_frames_bounced(&bounce_buffer, framebuffer)
+//!     .unwrap();
+//! loop {
+//!     transfer.refill().unwrap();
+//! }
+//! ```
+
+use core::{fmt::Formatter, mem::size_of};
+
+use embedded_dma::ReadBuffer;
+use fugit::HertzU32;
+
+use crate::{
+    clock::Clocks,
+    dma::{
+        dma_private::{DmaSupport, DmaSupportTx},
+        ChannelTx,
+        ChannelTypes,
+        DmaError,
+        DmaPeripheral,
+        DmaTransferTx,
+        LcdCamPeripheral,
+        RegisterAccess,
+        Tx,
+        TxChannel,
+        TxPrivate,
+    },
+    gpio::{Level, OutputPin, OutputSignal},
+    interrupt::InterruptHandler,
+    lcd_cam::{
+        lcd::{private::TxPins, ClockMode, DelayMode, Phase, Polarity},
+        private::calculate_clkm,
+        BitOrder,
+        ByteOrder,
+        Lcd,
+    },
+    peripheral::{Peripheral, PeripheralRef},
+    peripherals::{Interrupt, LCD_CAM},
+};
+
+/// Errors of the DPI driver configuration
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// A pixel doesn't take whole PCLK cycles on the bus, e.g. 24 bits per
+    /// pixel with a 16 bit bus.
+    InvalidPixelSize,
+    /// The active area or a sync pulse is empty, or a part of the frame
+    /// timing doesn't fit the hardware, see [FrameTiming].
+    InvalidTiming,
+}
+
+/// DPI (RGB) driver for parallel panels without a framebuffer of their own
+pub struct Dpi<'d, TX, P> {
+    lcd_cam: PeripheralRef<'d, LCD_CAM>,
+    tx_channel: TX,
+    _pins: P,
+    frame_len: usize,
+}
+
+impl<'d, T, R, P: TxPins> Dpi<'d, ChannelTx<'d, T, R>, P>
+where
+    T: TxChannel<R>,
+    R: ChannelTypes + RegisterAccess,
+    R::P: LcdCamPeripheral,
+{
+    /// Create the driver, `frequency` is the pixel clock.
+    ///
+    /// Fails if a pixel doesn't take whole PCLK cycles on the bus or if the
+    /// frame timing doesn't fit the hardware.
+    pub fn new(
+        lcd: Lcd<'d>,
+        mut channel: ChannelTx<'d, T, R>,
+        mut pins: P,
+        frequency: HertzU32,
+        config: Config,
+        clocks: &Clocks,
+    ) -> Result<Self, Error> {
+        let bus_width = size_of::<P::Word>() * 8;
+        let bits_per_pixel = config.bits_per_pixel as usize;
+        if bits_per_pixel % bus_width != 0 {
+            return Err(Error::InvalidPixelSize);
+        }
+        let cycles_per_pixel = bits_per_pixel / bus_width;
+
+        let timing = &config.timing;
+        timing.validate(cycles_per_pixel)?;
+
+        let lcd_cam = lcd.lcd_cam;
+
+        // Due to https://www.espressif.com/sites/default/files/documentation/esp32-s3_errata_en.pdf
+        // the LCD_PCLK divider must be at least 2. To make up for this the user
+        // provided frequency is doubled to match.
+
+        let (i, divider) = calculate_clkm(
+            (frequency.to_Hz() * 2) as _,
+            &[
+                clocks.xtal_clock.to_Hz() as _,
+                clocks.cpu_clock.to_Hz() as _,
+                clocks.crypto_pwm_clock.to_Hz() as _,
+            ],
+        );
+
+        lcd_cam.lcd_clock().write(|w| unsafe {
+            // Force enable the clock for all configuration registers.
+            w.clk_en()
+                .set_bit()
+                .lcd_clk_sel()
+                .bits((i + 1) as _)
+                .lcd_clkm_div_num()
+                .bits(divider.div_num as _)
+                .lcd_clkm_div_b()
+                .bits(divider.div_b as _)
+                .lcd_clkm_div_a()
+                .bits(divider.div_a as _)
+                // LCD_PCLK = LCD_CLK / 2
+                .lcd_clk_equ_sysclk()
+                .clear_bit()
+                .lcd_clkcnt_n()
+                .bits(2 - 1) // Must not be 0.
+                .lcd_ck_idle_edge()
+                .bit(config.clock_mode.polarity == Polarity::IdleHigh)
+                .lcd_ck_out_edge()
+                .bit(config.clock_mode.phase == Phase::ShiftHigh)
+        });
+
+        lcd_cam
+            .lcd_rgb_yuv()
+            .write(|w| w.lcd_conv_bypass().clear_bit());
+
+        lcd_cam.lcd_user().modify(|_, w| {
+            w.lcd_8bits_order()
+                .bit(false)
+                .lcd_bit_order()
+                .bit(false)
+                .lcd_byte_order()
+                .bit(false)
+                .lcd_2byte_en()
+                .bit(bus_width == 16)
+                .lcd_cmd()
+                .clear_bit()
+                .lcd_dummy()
+                .clear_bit()
+                .lcd_dout()
+                .set_bit()
+                // The length of a frame is given by the timing.
+                .lcd_always_out_en()
+                .set_bit()
+        });
+
+        let horizontal_active_cycles = timing.horizontal_active_width * cycles_per_pixel;
+        let horizontal_total_cycles = timing.hsync_width
+            + timing.horizontal_back_porch
+            + horizontal_active_cycles
+            + timing.horizontal_front_porch;
+        let vertical_total_lines = timing.vsync_width
+            + timing.vertical_back_porch
+            + timing.vertical_active_height
+            + timing.vertical_front_porch;
+
+        lcd_cam.lcd_ctrl().write(|w| unsafe {
+            w.lcd_rgb_mode_en()
+                .set_bit()
+                // Cycles from the start of HSYNC to the active area.
+                .lcd_hb_front()
+                .bits((timing.hsync_width + timing.horizontal_back_porch) - 1 as _)
+                .lcd_va_height()
+                .bits(timing.vertical_active_height - 1 as _)
+                .lcd_vt_height()
+                .bits(vertical_total_lines - 1 as _)
+        });
+        lcd_cam.lcd_ctrl1().write(|w| unsafe {
+            // Lines from the start of VSYNC to the active area.
+            w.lcd_vb_front()
+                .bits((timing.vsync_width + timing.vertical_back_porch) - 1 as _)
+                .lcd_ha_width()
+                .bits(horizontal_active_cycles - 1 as _)
+                .lcd_ht_width()
+                .bits(horizontal_total_cycles - 1 as _)
+        });
+        lcd_cam.lcd_ctrl2().write(|w| unsafe {
+            w.lcd_vsync_width()
+                .bits(timing.vsync_width - 1 as _)
+                .lcd_vsync_idle_pol()
+                .bit(config.vsync_idle_level.into())
+                .lcd_de_idle_pol()
+                .bit(config.de_idle_level.into())
+                // Keep HSYNC running during the vertical blanking.
+                .lcd_hs_blank_en()
+                .set_bit()
+                .lcd_hsync_width()
+                .bits(timing.hsync_width - 1 as _)
+                .lcd_hsync_idle_pol()
+                .bit(config.hsync_idle_level.into())
+                .lcd_hsync_position()
+                .bits(0)
+        });
+
+        lcd_cam.lcd_misc().write(|w| unsafe {
+            // Set the threshold for Async Tx FIFO full event. (5 bits)
+            w.lcd_afifo_threshold_num()
+                .bits((1 << 5) - 1)
+                .lcd_vfk_cyclelen()
+                .bits(0)
+                .lcd_vbk_cyclelen()
+                .bits(0)
+                // 1: Send the next frame data when the current frame is sent out.
+                // 0: LCD stops when the current frame is sent out.
+                .lcd_next_frame_en()
+                .clear_bit()
+                // Enable blank region when LCD sends data out.
+                .lcd_bk_en()
+                .set_bit()
+        });
+        lcd_cam.lcd_dly_mode().write(|w| unsafe {
+            w.lcd_de_mode()
+                .bits(config.de_mode as u8)
+                .lcd_hsync_mode()
+                .bits(config.hsync_mode as u8)
+                .lcd_vsync_mode()
+                .bits(config.vsync_mode as u8)
+        });
+        lcd_cam.lcd_data_dout_mode().write(|w| unsafe {
+            w.dout0_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout1_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout2_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout3_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout4_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout5_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout6_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout7_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout8_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout9_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout10_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout11_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout12_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout13_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout14_mode()
+                .bits(config.output_bit_mode as u8)
+                .dout15_mode()
+                .bits(config.output_bit_mode as u8)
+        });
+
+        lcd_cam.lcd_user().modify(|_, w| w.lcd_update().set_bit());
+
+        channel.init_channel();
+        pins.configure();
+
+        Ok(Self {
+            lcd_cam,
+            tx_channel: channel,
+            _pins: pins,
+            frame_len: timing.horizontal_active_width
+                * timing.vertical_active_height
+                * bits_per_pixel
+                / 8,
+        })
+    }
+}
+
+impl<'d, TX: Tx, P: TxPins> DmaSupport for Dpi<'d, TX, P> {
+    fn peripheral_wait_dma(&mut self, _is_tx: bool, _is_rx: bool) {
+        // LCD_START is cleared by the hardware once the frame was sent.
+        while self.lcd_cam.lcd_user().read().lcd_start().bit_is_set() {}
+    }
+
+    fn peripheral_dma_stop(&mut self) {
+        self.stop_refresh();
+    }
+}
+
+impl<'d, TX: Tx, P: TxPins> DmaSupportTx for Dpi<'d, TX, P> {
+    type TX = TX;
+
+    fn tx(&mut self) -> &mut Self::TX {
+        &mut self.tx_channel
+    }
+}
+
+impl<'d, TX: Tx, P: TxPins> Dpi<'d, TX, P> {
+    /// Set the order of the bytes of a pixel on the bus.
+    ///
+    /// With a 16 bit bus this swaps the bytes of each 16 bit word, with an 8
+    /// bit bus the bytes of each pair of bytes.
+    pub fn set_byte_order(&mut self, byte_order: ByteOrder) -> &mut Self {
+        let is_inverted = byte_order != ByteOrder::default();
+        self.lcd_cam.lcd_user().modify(|_, w| {
+            if size_of::<P::Word>() == 2 {
+                w.lcd_byte_order().bit(is_inverted)
+            } else {
+                w.lcd_8bits_order().bit(is_inverted)
+            }
+        });
+        self
+    }
+
+    /// Set the order of the bits of each word on the bus.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) -> &mut Self {
+        self.lcd_cam
+            .lcd_user()
+            .modify(|_, w| w.lcd_bit_order().bit(bit_order != BitOrder::default()));
+        self
+    }
+
+    /// Assign the VSYNC, HSYNC and PCLK output pins.
+    pub fn with_ctrl_pins<VSYNC: OutputPin, HSYNC: OutputPin, PCLK: OutputPin>(
+        self,
+        vsync: impl Peripheral<P = VSYNC> + 'd,
+        hsync: impl Peripheral<P = HSYNC> + 'd,
+        pclk: impl Peripheral<P = PCLK> + 'd,
+    ) -> Self {
+        crate::into_ref!(vsync);
+        crate::into_ref!(hsync);
+        crate::into_ref!(pclk);
+
+        vsync.set_to_push_pull_output(crate::private::Internal);
+        vsync.connect_peripheral_to_output(OutputSignal::LCD_V_SYNC, crate::private::Internal);
+
+        hsync.set_to_push_pull_output(crate::private::Internal);
+        hsync.connect_peripheral_to_output(OutputSignal::LCD_H_SYNC, crate::private::Internal);
+
+        pclk.set_to_push_pull_output(crate::private::Internal);
+        pclk.connect_peripheral_to_output(OutputSignal::LCD_PCLK, crate::private::Internal);
+
+        self
+    }
+
+    /// Use a data enable line, which is active during the active area.
+    pub fn with_de<DE: OutputPin>(self, de: impl Peripheral<P = DE> + 'd) -> Self {
+        crate::into_ref!(de);
+        de.set_to_push_pull_output(crate::private::Internal);
+        de.connect_peripheral_to_output(OutputSignal::LCD_H_ENABLE, crate::private::Internal);
+
+        self
+    }
+
+    /// Sets the interrupt handler of the LCD_CAM peripheral and enables it
+    /// with the handler's priority.
+    ///
+    /// Interrupts are not enabled at the peripheral level here, see
+    /// [Dpi::listen_vsync].
+    pub fn set_interrupt_handler(&mut self, handler: InterruptHandler) {
+        unsafe {
+            crate::interrupt::bind_interrupt(Interrupt::LCD_CAM, handler.handler());
+            crate::interrupt::enable(Interrupt::LCD_CAM, handler.priority()).unwrap();
+        }
+    }
+
+    /// Listen for the VSYNC event, the handler has to clear it e.g. via
+    /// [DpiTransfer::take_vsync].
+    pub fn listen_vsync(&mut self) {
+        self.lcd_cam
+            .lc_dma_int_ena()
+            .modify(|_, w| w.lcd_vsync_int_ena().set_bit());
+    }
+
+    /// Stop listening for the VSYNC event.
+    pub fn unlisten_vsync(&mut self) {
+        self.lcd_cam
+            .lc_dma_int_ena()
+            .modify(|_, w| w.lcd_vsync_int_ena().clear_bit());
+    }
+
+    /// The size of a frame in bytes.
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// Send a single frame.
+    pub fn send_frame_dma<'t, TXBUF>(
+        &'t mut self,
+        frame: &'t TXBUF,
+    ) -> Result<DmaTransferTx<Self>, DmaError>
+    where
+        TXBUF: ReadBuffer,
+    {
+        let (ptr, len) = unsafe { frame.read_buffer() };
+        if len * size_of::<TXBUF::Word>() < self.frame_len {
+            return Err(DmaError::BufferTooSmall);
+        }
+
+        self.start_dma(ptr as _, self.frame_len, false)?;
+
+        Ok(DmaTransferTx::new(self))
+    }
+
+    /// Refresh the panel from `framebuffer` until the transfer is stopped.
+    pub fn send_frames_dma<'t, TXBUF>(
+        &'t mut self,
+        framebuffer: &'t TXBUF,
+    ) -> Result<DpiTransfer<'t, 'd, TX, P>, DmaError>
+    where
+        TXBUF: ReadBuffer,
+    {
+        let (ptr, len) = unsafe { framebuffer.read_buffer() };
+        if len * size_of::<TXBUF::Word>() < self.frame_len {
+            return Err(DmaError::BufferTooSmall);
+        }
+
+        self.start_dma(ptr as _, self.frame_len, true)?;
+
+        Ok(DpiTransfer { dpi: self })
+    }
+
+    /// Refresh the panel from `framebuffer` through `bounce_buffer` until the
+    /// transfer is stopped.
+    ///
+    /// The bounce buffer must be in internal RAM and should hold a few lines,
+    /// the DMA sends it in a loop while [DpiBounceTransfer::refill] copies the
+    /// next part of the frame into the sent parts. The DMA channel signals
+    /// sent parts with its EOF interrupt, which is enabled here.
+    ///
+    /// Fails with [DmaError::BufferTooSmall] if the bounce buffer is empty or
+    /// the framebuffer is smaller than a frame.
+    pub fn send_frames_bounced<'t, TXBUF, FB>(
+        &'t mut self,
+        bounce_buffer: &'t TXBUF,
+        framebuffer: FB,
+    ) -> Result<DpiBounceTransfer<'t, 'd, TX, P, FB>, DmaError>
+    where
+        TXBUF: ReadBuffer,
+        FB: ReadBuffer,
+    {
+        let (ptr, len) = unsafe { bounce_buffer.read_buffer() };
+        let len = len * size_of::<TXBUF::Word>();
+        if len == 0 || !FrameSource::fits(&framebuffer, self.frame_len) {
+            return Err(DmaError::BufferTooSmall);
+        }
+
+        let mut source = FrameSource::new(framebuffer, self.frame_len);
+
+        // Start with the beginning of the first frame in the bounce buffer
+        let buffer = unsafe { core::slice::from_raw_parts_mut(ptr as *mut u8, len) };
+        let mut filled = 0;
+        while filled < len {
+            let read = source.read(&mut buffer[filled..]);
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+
+        self.start_dma(ptr as _, len, true)?;
+        self.tx_channel.listen_eof();
+
+        Ok(DpiBounceTransfer { dpi: self, source })
+    }
+
+    fn start_dma(&mut self, ptr: *const u8, len: usize, continuous: bool) -> Result<(), DmaError> {
+        // Reset LCD control unit and Async Tx FIFO
+        self.lcd_cam
+            .lcd_user()
+            .modify(|_, w| w.lcd_reset().set_bit());
+        self.lcd_cam
+            .lcd_misc()
+            .modify(|_, w| w.lcd_afifo_reset().set_bit());
+
+        self.tx_channel.prepare_transfer_without_start(
+            DmaPeripheral::LcdCam,
+            continuous,
+            ptr,
+            len,
+        )?;
+        self.tx_channel.start_transfer()?;
+
+        self.lcd_cam
+            .lcd_misc()
+            .modify(|_, w| w.lcd_next_frame_en().bit(continuous));
+        self.lcd_cam
+            .lc_dma_int_clr()
+            .write(|w| w.lcd_vsync_int_clr().set_bit());
+
+        // Before issuing lcd_start need to wait shortly for fifo to get data
+        // Otherwise, some garbage data will be sent out
+        crate::rom::ets_delay_us(1);
+
+        self.lcd_cam
+            .lcd_user()
+            .modify(|_, w| w.lcd_update().set_bit().lcd_start().set_bit());
+
+        Ok(())
+    }
+
+    fn stop_refresh(&mut self) {
+        self.lcd_cam
+            .lcd_misc()
+            .modify(|_, w| w.lcd_next_frame_en().clear_bit());
+        self.lcd_cam
+            .lcd_user()
+            .modify(|_, w| w.lcd_start().clear_bit());
+    }
+
+    fn take_vsync(&mut self) -> bool {
+        let is_set = self
+            .lcd_cam
+            .lc_dma_int_raw()
+            .read()
+            .lcd_vsync_int_raw()
+            .bit_is_set();
+        if is_set {
+            self.lcd_cam
+                .lc_dma_int_clr()
+                .write(|w| w.lcd_vsync_int_clr().set_bit());
+        }
+        is_set
+    }
+}
+
+impl<'d, TX, P> core::fmt::Debug for Dpi<'d, TX, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Dpi").finish()
+    }
+}
+
+/// A continuous refresh of the panel from a framebuffer.
+#[must_use]
+pub struct DpiTransfer<'t, 'd, TX: Tx, P: TxPins> {
+    dpi: &'t mut Dpi<'d, TX, P>,
+}
+
+impl<'t, 'd, TX: Tx, P: TxPins> DpiTransfer<'t, 'd, TX, P> {
+    /// Returns whether a VSYNC happened since the last call and clears the
+    /// event.
+    pub fn take_vsync(&mut self) -> bool {
+        self.dpi.take_vsync()
+    }
+
+    /// Wait for the next VSYNC, i.e. the start of the vertical blanking.
+    pub fn wait_for_vsync(&mut self) {
+        self.dpi.take_vsync();
+        while !self.dpi.take_vsync() {}
+    }
+
+    /// Stop refreshing the panel.
+    pub fn stop(self) -> Result<(), DmaError> {
+        self.dpi.stop_refresh();
+
+        if self.dpi.tx_channel.has_error() {
+            Err(DmaError::DescriptorError)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'t, 'd, TX: Tx, P: TxPins> Drop for DpiTransfer<'t, 'd, TX, P> {
+    fn drop(&mut self) {
+        self.dpi.stop_refresh();
+    }
+}
+
+/// A continuous refresh of the panel from a framebuffer through a bounce
+/// buffer.
+#[must_use]
+pub struct DpiBounceTransfer<'t, 'd, TX: Tx, P: TxPins, FB: ReadBuffer> {
+    dpi: &'t mut Dpi<'d, TX, P>,
+    source: FrameSource<FB>,
+}
+
+impl<'t, 'd, TX: Tx, P: TxPins, FB: ReadBuffer> DpiBounceTransfer<'t, 'd, TX, P, FB> {
+    /// Copy the next part of the frame into the parts of the bounce buffer
+    /// which were sent.
+    ///
+    /// This has to be called before the DMA wraps around the bounce buffer,
+    /// e.g. from the interrupt handler of the DMA channel, otherwise the
+    /// picture is shifted.
+    pub fn refill(&mut self) -> Result<(), DmaError> {
+        while self.dpi.tx_channel.available() > 0 {
+            let pushed = self
+                .dpi
+                .tx_channel
+                .push_with(|buffer| self.source.read(buffer))?;
+            if pushed == 0 {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Show `framebuffer` from the start of the next frame on.
+    ///
+    /// Returns a framebuffer which was set before but never shown. The
+    /// replaced framebuffer can be taken with
+    /// [DpiBounceTransfer::take_released_framebuffer] once the swap happened.
+    ///
+    /// Panics if the framebuffer is smaller than a frame.
+    pub fn set_next_framebuffer(&mut self, framebuffer: FB) -> Option<FB> {
+        self.source.set_next(framebuffer)
+    }
+
+    /// Takes the framebuffer which was replaced by the last swap.
+    ///
+    /// The swap waits until the released framebuffer was taken.
+    pub fn take_released_framebuffer(&mut self) -> Option<FB> {
+        self.source.released.take()
+    }
+
+    /// Returns whether a VSYNC happened since the last call and clears the
+    /// event.
+    pub fn take_vsync(&mut self) -> bool {
+        self.dpi.take_vsync()
+    }
+
+    /// Stop refreshing the panel, returns the shown framebuffer.
+    pub fn stop(mut self) -> Result<FB, DmaError> {
+        self.dpi.stop_refresh();
+        self.dpi.tx_channel.unlisten_eof();
+
+        if self.dpi.tx_channel.has_error() {
+            return Err(DmaError::DescriptorError);
+        }
+
+        // SAFETY: The transfer is forgotten after taking the fields out of it.
+        let framebuffer = unsafe { core::ptr::read(&self.source.current) };
+        unsafe { core::ptr::drop_in_place(&mut self.source.next) };
+        unsafe { core::ptr::drop_in_place(&mut self.source.released) };
+        core::mem::forget(self);
+
+        Ok(framebuffer)
+    }
+}
+
+impl<'t, 'd, TX: Tx, P: TxPins, FB: ReadBuffer> Drop for DpiBounceTransfer<'t, 'd, TX, P, FB> {
+    fn drop(&mut self) {
+        self.dpi.stop_refresh();
+        self.dpi.tx_channel.unlisten_eof();
+    }
+}
+
+/// Reads the frames to send from the framebuffers.
+struct FrameSource<FB: ReadBuffer> {
+    current: FB,
+    next: Option<FB>,
+    released: Option<FB>,
+    frame_len: usize,
+    offset: usize,
+}
+
+impl<FB: ReadBuffer> FrameSource<FB> {
+    fn new(framebuffer: FB, frame_len: usize) -> Self {
+        Self::check_len(&framebuffer, frame_len);
+
+        Self {
+            current: framebuffer,
+            next: None,
+            released: None,
+            frame_len,
+            offset: 0,
+        }
+    }
+
+    fn fits(framebuffer: &FB, frame_len: usize) -> bool {
+        let (_, len) = unsafe { framebuffer.read_buffer() };
+        len * size_of::<FB::Word>() >= frame_len
+    }
+
+    fn check_len(framebuffer: &FB, frame_len: usize) {
+        assert!(
+            Self::fits(framebuffer, frame_len),
+            "The framebuffer is smaller than a frame"
+        );
+    }
+
+    fn set_next(&mut self, framebuffer: FB) -> Option<FB> {
+        Self::check_len(&framebuffer, self.frame_len);
+        self.next.replace(framebuffer)
+    }
+
+    /// Copies the next bytes of the frame into `buffer`, stops at the end of
+    /// the frame.
+    fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let (ptr, _) = unsafe { self.current.read_buffer() };
+        let frame = unsafe { core::slice::from_raw_parts(ptr as *const u8, self.frame_len) };
+
+        let len = usize::min(buffer.len(), self.frame_len - self.offset);
+        buffer[..len].copy_from_slice(&frame[self.offset..][..len]);
+        self.offset += len;
+
+        if self.offset == self.frame_len {
+            self.offset = 0;
+
+            // The current framebuffer isn't read anymore after its last bytes
+            // were copied into the bounce buffer.
+            if self.released.is_none() {
+                if let Some(next) = self.next.take() {
+                    self.released = Some(core::mem::replace(&mut self.current, next));
+                }
+            }
+        }
+
+        len
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// DPI driver configuration
+pub struct Config {
+    /// The polarity and phase of PCLK.
+    pub clock_mode: ClockMode,
+
+    /// The size of a pixel, must be a multiple of the bus width.
+    pub bits_per_pixel: BitsPerPixel,
+
+    /// The timing of a frame, all zero by default so it always has to be set.
+    pub timing: FrameTiming,
+
+    /// The level of VSYNC outside of the pulse.
+    pub vsync_idle_level: Level,
+    /// The level of HSYNC outside of the pulse.
+    pub hsync_idle_level: Level,
+    /// The level of DE outside of the active area.
+    pub de_idle_level: Level,
+
+    /// The output DE is delayed by module clock LCD_CLK.
+    pub de_mode: DelayMode,
+    /// The output HSYNC is delayed by module clock LCD_CLK.
+    pub hsync_mode: DelayMode,
+    /// The output VSYNC is delayed by module clock LCD_CLK.
+    pub vsync_mode: DelayMode,
+    /// The output data bits are delayed by module clock LCD_CLK.
+    pub output_bit_mode: DelayMode,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            clock_mode: Default::default(),
+            bits_per_pixel: Default::default(),
+            timing: Default::default(),
+            vsync_idle_level: Level::High,
+            hsync_idle_level: Level::High,
+            de_idle_level: Level::Low,
+            de_mode: Default::default(),
+            hsync_mode: Default::default(),
+            vsync_mode: Default::default(),
+            output_bit_mode: Default::default(),
+        }
+    }
+}
+
+/// The number of bits of a pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BitsPerPixel {
+    /// E.g. RGB332, one cycle on an 8 bit bus.
+    Eight      = 8,
+    /// E.g. RGB565, one cycle on a 16 bit bus or two on an 8 bit bus.
+    #[default]
+    Sixteen    = 16,
+    /// E.g. RGB888, three cycles on an 8 bit bus.
+    TwentyFour = 24,
+}
+
+/// The timing of a frame.
+///
+/// The horizontal porches and the HSYNC pulse are counted in PCLK cycles, the
+/// vertical ones in lines.
+///
+/// The hardware limits:
+/// - the active area and the sync pulses to at least one pixel or line
+/// - the HSYNC and VSYNC pulses to 128 cycles or lines
+/// - HSYNC and the horizontal back porch together to 2048 cycles
+/// - VSYNC and the vertical back porch together to 256 lines
+/// - the active cycles of a line and the whole line to 4096 cycles
+/// - the active lines and the whole frame to 1024 lines
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameTiming {
+    /// Pixels of a line.
+    pub horizontal_active_width: usize,
+    /// Cycles between the end of the active area and HSYNC.
+    pub horizontal_front_porch: usize,
+    /// Cycles of the HSYNC pulse.
+    pub hsync_width: usize,
+    /// Cycles between HSYNC and the start of the active area.
+    pub horizontal_back_porch: usize,
+
+    /// Lines of a frame.
+    pub vertical_active_height: usize,
+    /// Lines between the end of the active area and VSYNC.
+    pub vertical_front_porch: usize,
+    /// Lines of the VSYNC pulse.
+    pub vsync_width: usize,
+    /// Lines between VSYNC and the start of the active area.
+    pub vertical_back_porch: usize,
+}
+
+impl FrameTiming {
+    fn validate(&self, cycles_per_pixel: usize) -> Result<(), Error> {
+        let horizontal_active_cycles = self.horizontal_active_width * cycles_per_pixel;
+        let horizontal_total_cycles = self.hsync_width
+            + self.horizontal_back_porch
+            + horizontal_active_cycles
+            + self.horizontal_front_porch;
+        let vertical_total_lines = self.vsync_width
+            + self.vertical_back_porch
+            + self.vertical_active_height
+            + self.vertical_front_porch;
+
+        // The registers hold the values minus one.
+        let fits = |value: usize, bits: u32| (1..=1 << bits).contains(&value);
+
+        let is_valid = fits(self.hsync_width, 7)
+            && fits(self.vsync_width, 7)
+            && fits(self.hsync_width + self.horizontal_back_porch, 11)
+            && fits(self.vsync_width + self.vertical_back_porch, 8)
+            && fits(horizontal_active_cycles, 12)
+            && fits(horizontal_total_cycles, 12)
+            && fits(self.vertical_active_height, 10)
+            && fits(vertical_total_lines, 10);
+
+        if is_valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidTiming)
+        }
+    }
+}
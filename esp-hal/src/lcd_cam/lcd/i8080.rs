@@ -34,14 +34,40 @@
 //!
 //! i8080.send(0x3A, 0, &[0x55]).unwrap(); // RGB565
 //! ```
+//!
+//! Initialization sequences are sent in one call.
+//!
+//! ```no_run
+//! const INIT: &[Step<u8>] = &[
+//!     Step::Command(Command::One(0x01), &[]), // Software reset
+//!     Step::Delay(120),
+//!     Step::Command(Command::One(0x3A), &[0x55]), // RGB565
+//!     Step::Command(Command::One(0x11), &[]),     // Sleep out
+//!     Step::Delay(5),
+//!     Step::Command(Command::One(0x29), &[]), // Display on
+//! ];
+//!
+//! i8080.send_sequence(&delay, INIT).unwrap();
+//! ```
+//!
+//! The bus can be 8, 9 or 16 bits wide. On a 9 bit bus the data is sent in
+//! the lower 9 bits of 16 bit words. 18 bit buses aren't supported, the
+//! peripheral only has 16 data lines.
 
-use core::{fmt::Formatter, mem::size_of};
+use core::{
+    fmt::Formatter,
+    mem::{size_of, size_of_val},
+};
 
 use embedded_dma::ReadBuffer;
 use fugit::HertzU32;
 
+pub use super::{TxEightBits, TxNineBits, TxSixteenBits};
+#[cfg(feature = "async")]
+use crate::dma::asynch::DmaTxFuture;
 use crate::{
     clock::Clocks,
+    delay::Delay,
     dma::{
         dma_private::{DmaSupport, DmaSupportTx},
         ChannelTx,
@@ -57,7 +83,7 @@ use crate::{
     },
     gpio::{OutputPin, OutputSignal},
     lcd_cam::{
-        lcd::{i8080::private::TxPins, ClockMode, DelayMode, Phase, Polarity},
+        lcd::{private::TxPins, ClockMode, DelayMode, Phase, Polarity},
         private::calculate_clkm,
         BitOrder,
         ByteOrder,
@@ -233,9 +259,7 @@ where
 
 impl<'d, TX: Tx, P: TxPins> DmaSupport for I8080<'d, TX, P> {
     fn peripheral_wait_dma(&mut self, _is_tx: bool, _is_rx: bool) {
-        let dma_int_raw = self.lcd_cam.lc_dma_int_raw();
-        // Wait until LCD_TRANS_DONE is set.
-        while dma_int_raw.read().lcd_trans_done_int_raw().bit_is_clear() {}
+        self.wait_for_trans_done();
         self.tear_down_send();
     }
 
@@ -306,15 +330,69 @@ where
         dummy: u8,
         data: &[P::Word],
     ) -> Result<(), DmaError> {
-        self.setup_send(cmd.into(), dummy);
-        self.start_write_bytes_dma(data.as_ptr() as _, core::mem::size_of_val(data))?;
-        self.start_send();
+        self.send_bytes(cmd.into(), dummy, data.as_ptr() as _, size_of_val(data))
+    }
 
-        let dma_int_raw = self.lcd_cam.lc_dma_int_raw();
-        // Wait until LCD_TRANS_DONE is set.
-        while dma_int_raw.read().lcd_trans_done_int_raw().bit_is_clear() {}
+    /// Sends the steps of `sequence` one after another, e.g. to initialize a
+    /// display.
+    ///
+    /// Parameters of up to [MAX_BOUNCED_PARAMETER_LEN] bytes are copied to RAM
+    /// first, so the sequence can be stored in flash. Longer parameters must
+    /// be in RAM, otherwise [DmaError::UnsupportedMemoryRegion] is returned
+    /// before the command is sent.
+    pub fn send_sequence(
+        &mut self,
+        delay: &Delay,
+        sequence: &[Step<'_, P::Word>],
+    ) -> Result<(), DmaError> {
+        for step in sequence {
+            match *step {
+                Step::Command(cmd, parameters) => {
+                    let mut buffer = [0u8; MAX_BOUNCED_PARAMETER_LEN];
+                    let (ptr, len) = bounce_parameters(parameters, &mut buffer)?;
+                    self.send_bytes(cmd, 0, ptr, len)?;
+                }
+                Step::Delay(ms) => delay.delay_millis(ms),
+            }
+        }
 
-        self.tear_down_send();
+        Ok(())
+    }
+
+    /// Sends a command followed by `data` without blocking the executor.
+    ///
+    /// The DMA channel must be configured with `configure_for_async`.
+    #[cfg(feature = "async")]
+    pub async fn send_dma_async(
+        &mut self,
+        cmd: impl Into<Command<P::Word>>,
+        dummy: u8,
+        data: &[P::Word],
+    ) -> Result<(), DmaError> {
+        self.send_bytes_async(cmd.into(), dummy, data.as_ptr() as _, size_of_val(data))
+            .await
+    }
+
+    /// Sends the steps of `sequence` one after another without blocking the
+    /// executor, see [I8080::send_sequence].
+    ///
+    /// The DMA channel must be configured with `configure_for_async`.
+    #[cfg(feature = "async")]
+    pub async fn send_sequence_async(
+        &mut self,
+        delay: &mut impl embedded_hal_async::delay::DelayNs,
+        sequence: &[Step<'_, P::Word>],
+    ) -> Result<(), DmaError> {
+        for step in sequence {
+            match *step {
+                Step::Command(cmd, parameters) => {
+                    let mut buffer = [0u8; MAX_BOUNCED_PARAMETER_LEN];
+                    let (ptr, len) = bounce_parameters(parameters, &mut buffer)?;
+                    self.send_bytes_async(cmd, 0, ptr, len).await?;
+                }
+                Step::Delay(ms) => delay.delay_ms(ms).await,
+            }
+        }
 
         Ok(())
     }
@@ -339,6 +417,54 @@ where
 }
 
 impl<'d, TX: Tx, P> I8080<'d, TX, P> {
+    fn send_bytes<T: Copy + Into<u16>>(
+        &mut self,
+        cmd: Command<T>,
+        dummy: u8,
+        ptr: *const u8,
+        len: usize,
+    ) -> Result<(), DmaError> {
+        let _guard = SendGuard;
+
+        self.setup_send(cmd, dummy);
+        self.start_write_bytes_dma(ptr, len)?;
+        self.start_send();
+        self.wait_for_trans_done();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    async fn send_bytes_async<T: Copy + Into<u16>>(
+        &mut self,
+        cmd: Command<T>,
+        dummy: u8,
+        ptr: *const u8,
+        len: usize,
+    ) -> Result<(), DmaError> {
+        // Also stops the transfer if the future is dropped
+        let _guard = SendGuard;
+
+        self.setup_send(cmd, dummy);
+        self.start_write_bytes_dma(ptr, len)?;
+        self.start_send();
+        if len > 0 {
+            DmaTxFuture::new(&mut self.tx_channel).await?;
+        }
+
+        // The DMA is done once the data is in the FIFO, only a few words are left
+        // to be sent out.
+        self.wait_for_trans_done();
+
+        Ok(())
+    }
+
+    fn wait_for_trans_done(&self) {
+        let dma_int_raw = self.lcd_cam.lc_dma_int_raw();
+        // Wait until LCD_TRANS_DONE is set.
+        while dma_int_raw.read().lcd_trans_done_int_raw().bit_is_clear() {}
+    }
+
     fn setup_send<T: Copy + Into<u16>>(&mut self, cmd: Command<T>, dummy: u8) {
         // Reset LCD control unit and Async Tx FIFO
         self.lcd_cam
@@ -405,13 +531,7 @@ impl<'d, TX: Tx, P> I8080<'d, TX, P> {
     }
 
     fn tear_down_send(&mut self) {
-        self.lcd_cam
-            .lcd_user()
-            .modify(|_, w| w.lcd_start().clear_bit());
-
-        self.lcd_cam
-            .lc_dma_int_clr()
-            .write(|w| w.lcd_trans_done_int_clr().clear_bit());
+        tear_down_send(&self.lcd_cam);
     }
 
     fn start_write_bytes_dma(&mut self, ptr: *const u8, len: usize) -> Result<(), DmaError> {
@@ -449,6 +569,30 @@ impl<'d, TX: Tx, P> I8080<'d, TX, P> {
     }
 }
 
+/// Stops the LCD unit when dropped, so a failed or cancelled transfer doesn't
+/// leave it running with CS asserted.
+struct SendGuard;
+
+impl Drop for SendGuard {
+    fn drop(&mut self) {
+        tear_down_send(unsafe { &*LCD_CAM::PTR });
+    }
+}
+
+fn tear_down_send(lcd_cam: &crate::peripherals::lcd_cam::RegisterBlock) {
+    lcd_cam.lcd_user().modify(|_, w| w.lcd_start().clear_bit());
+
+    // Return the control unit to idle in case the transfer didn't finish
+    lcd_cam.lcd_user().modify(|_, w| w.lcd_reset().set_bit());
+    lcd_cam
+        .lcd_misc()
+        .modify(|_, w| w.lcd_afifo_reset().set_bit());
+
+    lcd_cam
+        .lc_dma_int_clr()
+        .write(|w| w.lcd_trans_done_int_clr().clear_bit());
+}
+
 impl<'d, TX, P> core::fmt::Debug for I8080<'d, TX, P> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("I8080").finish()
@@ -514,280 +658,37 @@ impl<T> From<T> for Command<T> {
     }
 }
 
-pub struct TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7> {
-    pin_0: PeripheralRef<'d, P0>,
-    pin_1: PeripheralRef<'d, P1>,
-    pin_2: PeripheralRef<'d, P2>,
-    pin_3: PeripheralRef<'d, P3>,
-    pin_4: PeripheralRef<'d, P4>,
-    pin_5: PeripheralRef<'d, P5>,
-    pin_6: PeripheralRef<'d, P6>,
-    pin_7: PeripheralRef<'d, P7>,
-}
+/// The maximum length in bytes of parameters which are copied to RAM before
+/// they are sent by [I8080::send_sequence].
+pub const MAX_BOUNCED_PARAMETER_LEN: usize = 64;
 
-impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
-where
-    P0: OutputPin,
-    P1: OutputPin,
-    P2: OutputPin,
-    P3: OutputPin,
-    P4: OutputPin,
-    P5: OutputPin,
-    P6: OutputPin,
-    P7: OutputPin,
-{
-    #[allow(clippy::too_many_arguments)]
-    pub fn new(
-        pin_0: impl Peripheral<P = P0> + 'd,
-        pin_1: impl Peripheral<P = P1> + 'd,
-        pin_2: impl Peripheral<P = P2> + 'd,
-        pin_3: impl Peripheral<P = P3> + 'd,
-        pin_4: impl Peripheral<P = P4> + 'd,
-        pin_5: impl Peripheral<P = P5> + 'd,
-        pin_6: impl Peripheral<P = P6> + 'd,
-        pin_7: impl Peripheral<P = P7> + 'd,
-    ) -> Self {
-        crate::into_ref!(pin_0);
-        crate::into_ref!(pin_1);
-        crate::into_ref!(pin_2);
-        crate::into_ref!(pin_3);
-        crate::into_ref!(pin_4);
-        crate::into_ref!(pin_5);
-        crate::into_ref!(pin_6);
-        crate::into_ref!(pin_7);
-
-        Self {
-            pin_0,
-            pin_1,
-            pin_2,
-            pin_3,
-            pin_4,
-            pin_5,
-            pin_6,
-            pin_7,
-        }
-    }
-}
-
-impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> TxPins for TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
-where
-    P0: OutputPin,
-    P1: OutputPin,
-    P2: OutputPin,
-    P3: OutputPin,
-    P4: OutputPin,
-    P5: OutputPin,
-    P6: OutputPin,
-    P7: OutputPin,
-{
-    type Word = u8;
-
-    fn configure(&mut self) {
-        self.pin_0.set_to_push_pull_output(crate::private::Internal);
-        self.pin_0
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_0, crate::private::Internal);
-        self.pin_1.set_to_push_pull_output(crate::private::Internal);
-        self.pin_1
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_1, crate::private::Internal);
-        self.pin_2.set_to_push_pull_output(crate::private::Internal);
-        self.pin_2
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_2, crate::private::Internal);
-        self.pin_3.set_to_push_pull_output(crate::private::Internal);
-        self.pin_3
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_3, crate::private::Internal);
-        self.pin_4.set_to_push_pull_output(crate::private::Internal);
-        self.pin_4
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_4, crate::private::Internal);
-        self.pin_5.set_to_push_pull_output(crate::private::Internal);
-        self.pin_5
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_5, crate::private::Internal);
-        self.pin_6.set_to_push_pull_output(crate::private::Internal);
-        self.pin_6
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_6, crate::private::Internal);
-        self.pin_7.set_to_push_pull_output(crate::private::Internal);
-        self.pin_7
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_7, crate::private::Internal);
-    }
-}
-
-pub struct TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> {
-    pin_0: PeripheralRef<'d, P0>,
-    pin_1: PeripheralRef<'d, P1>,
-    pin_2: PeripheralRef<'d, P2>,
-    pin_3: PeripheralRef<'d, P3>,
-    pin_4: PeripheralRef<'d, P4>,
-    pin_5: PeripheralRef<'d, P5>,
-    pin_6: PeripheralRef<'d, P6>,
-    pin_7: PeripheralRef<'d, P7>,
-    pin_8: PeripheralRef<'d, P8>,
-    pin_9: PeripheralRef<'d, P9>,
-    pin_10: PeripheralRef<'d, P10>,
-    pin_11: PeripheralRef<'d, P11>,
-    pin_12: PeripheralRef<'d, P12>,
-    pin_13: PeripheralRef<'d, P13>,
-    pin_14: PeripheralRef<'d, P14>,
-    pin_15: PeripheralRef<'d, P15>,
+/// A step of an initialization sequence, see [I8080::send_sequence].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Step<'a, T> {
+    /// Send the command followed by its parameters.
+    Command(Command<T>, &'a [T]),
+    /// Wait for the given number of milliseconds.
+    Delay(u32),
 }
 
-impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
-    TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
-where
-    P0: OutputPin,
-    P1: OutputPin,
-    P2: OutputPin,
-    P3: OutputPin,
-    P4: OutputPin,
-    P5: OutputPin,
-    P6: OutputPin,
-    P7: OutputPin,
-    P8: OutputPin,
-    P9: OutputPin,
-    P10: OutputPin,
-    P11: OutputPin,
-    P12: OutputPin,
-    P13: OutputPin,
-    P14: OutputPin,
-    P15: OutputPin,
-{
-    #[allow(clippy::too_many_arguments)]
-    pub fn new(
-        pin_0: impl Peripheral<P = P0> + 'd,
-        pin_1: impl Peripheral<P = P1> + 'd,
-        pin_2: impl Peripheral<P = P2> + 'd,
-        pin_3: impl Peripheral<P = P3> + 'd,
-        pin_4: impl Peripheral<P = P4> + 'd,
-        pin_5: impl Peripheral<P = P5> + 'd,
-        pin_6: impl Peripheral<P = P6> + 'd,
-        pin_7: impl Peripheral<P = P7> + 'd,
-        pin_8: impl Peripheral<P = P8> + 'd,
-        pin_9: impl Peripheral<P = P9> + 'd,
-        pin_10: impl Peripheral<P = P10> + 'd,
-        pin_11: impl Peripheral<P = P11> + 'd,
-        pin_12: impl Peripheral<P = P12> + 'd,
-        pin_13: impl Peripheral<P = P13> + 'd,
-        pin_14: impl Peripheral<P = P14> + 'd,
-        pin_15: impl Peripheral<P = P15> + 'd,
-    ) -> Self {
-        crate::into_ref!(pin_0);
-        crate::into_ref!(pin_1);
-        crate::into_ref!(pin_2);
-        crate::into_ref!(pin_3);
-        crate::into_ref!(pin_4);
-        crate::into_ref!(pin_5);
-        crate::into_ref!(pin_6);
-        crate::into_ref!(pin_7);
-        crate::into_ref!(pin_8);
-        crate::into_ref!(pin_9);
-        crate::into_ref!(pin_10);
-        crate::into_ref!(pin_11);
-        crate::into_ref!(pin_12);
-        crate::into_ref!(pin_13);
-        crate::into_ref!(pin_14);
-        crate::into_ref!(pin_15);
-
-        Self {
-            pin_0,
-            pin_1,
-            pin_2,
-            pin_3,
-            pin_4,
-            pin_5,
-            pin_6,
-            pin_7,
-            pin_8,
-            pin_9,
-            pin_10,
-            pin_11,
-            pin_12,
-            pin_13,
-            pin_14,
-            pin_15,
+// Copies short parameters to `buffer`, the DMA can't read from flash. Longer
+// parameters are sent in place and have to be in RAM.
+fn bounce_parameters<T>(
+    parameters: &[T],
+    buffer: &mut [u8; MAX_BOUNCED_PARAMETER_LEN],
+) -> Result<(*const u8, usize), DmaError> {
+    let len = size_of_val(parameters);
+    if len > MAX_BOUNCED_PARAMETER_LEN {
+        if !crate::soc::is_valid_ram_address(parameters.as_ptr() as u32) {
+            return Err(DmaError::UnsupportedMemoryRegion);
         }
-    }
-}
 
-impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> TxPins
-    for TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
-where
-    P0: OutputPin,
-    P1: OutputPin,
-    P2: OutputPin,
-    P3: OutputPin,
-    P4: OutputPin,
-    P5: OutputPin,
-    P6: OutputPin,
-    P7: OutputPin,
-    P8: OutputPin,
-    P9: OutputPin,
-    P10: OutputPin,
-    P11: OutputPin,
-    P12: OutputPin,
-    P13: OutputPin,
-    P14: OutputPin,
-    P15: OutputPin,
-{
-    type Word = u16;
-    fn configure(&mut self) {
-        self.pin_0.set_to_push_pull_output(crate::private::Internal);
-        self.pin_0
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_0, crate::private::Internal);
-        self.pin_1.set_to_push_pull_output(crate::private::Internal);
-        self.pin_1
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_1, crate::private::Internal);
-        self.pin_2.set_to_push_pull_output(crate::private::Internal);
-        self.pin_2
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_2, crate::private::Internal);
-        self.pin_3.set_to_push_pull_output(crate::private::Internal);
-        self.pin_3
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_3, crate::private::Internal);
-        self.pin_4.set_to_push_pull_output(crate::private::Internal);
-        self.pin_4
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_4, crate::private::Internal);
-        self.pin_5.set_to_push_pull_output(crate::private::Internal);
-        self.pin_5
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_5, crate::private::Internal);
-        self.pin_6.set_to_push_pull_output(crate::private::Internal);
-        self.pin_6
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_6, crate::private::Internal);
-        self.pin_7.set_to_push_pull_output(crate::private::Internal);
-        self.pin_7
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_7, crate::private::Internal);
-        self.pin_8.set_to_push_pull_output(crate::private::Internal);
-        self.pin_8
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_8, crate::private::Internal);
-        self.pin_9.set_to_push_pull_output(crate::private::Internal);
-        self.pin_9
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_9, crate::private::Internal);
-        self.pin_10
-            .set_to_push_pull_output(crate::private::Internal);
-        self.pin_10
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_10, crate::private::Internal);
-        self.pin_11
-            .set_to_push_pull_output(crate::private::Internal);
-        self.pin_11
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_11, crate::private::Internal);
-        self.pin_12
-            .set_to_push_pull_output(crate::private::Internal);
-        self.pin_12
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_12, crate::private::Internal);
-        self.pin_13
-            .set_to_push_pull_output(crate::private::Internal);
-        self.pin_13
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_13, crate::private::Internal);
-        self.pin_14
-            .set_to_push_pull_output(crate::private::Internal);
-        self.pin_14
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_14, crate::private::Internal);
-        self.pin_15
-            .set_to_push_pull_output(crate::private::Internal);
-        self.pin_15
-            .connect_peripheral_to_output(OutputSignal::LCD_DATA_15, crate::private::Internal);
+        return Ok((parameters.as_ptr() as _, len));
     }
-}
 
-mod private {
-    pub trait TxPins {
-        type Word: Copy;
-        fn configure(&mut self);
-    }
+    let bytes = unsafe { core::slice::from_raw_parts(parameters.as_ptr() as *const u8, len) };
+    buffer[..len].copy_from_slice(bytes);
+
+    Ok((buffer.as_ptr(), len))
 }
@@ -1,11 +1,16 @@
 //! LCD
 //!
-//! This module is capable of operating in either RGB (not yet implemented),
-//! MOTO6800 or I8080 mode. For more information on these modes, please refer
-//! to the documentation in their respective modules.
+//! This module is capable of operating in either RGB, MOTO6800 or I8080 mode.
+//! For more information on these modes, please refer to the documentation in
+//! their respective modules.
 
-use crate::{peripheral::PeripheralRef, peripherals::LCD_CAM};
+use crate::{
+    gpio::{OutputPin, OutputSignal},
+    peripheral::{Peripheral, PeripheralRef},
+    peripherals::LCD_CAM,
+};
 
+pub mod dpi;
 pub mod i8080;
 
 pub struct Lcd<'d> {
@@ -46,3 +51,388 @@ pub enum DelayMode {
     /// Delayed by the falling edge of LCD_CLK.
     FallingEdge = 2,
 }
+
+pub struct TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7> {
+    pin_0: PeripheralRef<'d, P0>,
+    pin_1: PeripheralRef<'d, P1>,
+    pin_2: PeripheralRef<'d, P2>,
+    pin_3: PeripheralRef<'d, P3>,
+    pin_4: PeripheralRef<'d, P4>,
+    pin_5: PeripheralRef<'d, P5>,
+    pin_6: PeripheralRef<'d, P6>,
+    pin_7: PeripheralRef<'d, P7>,
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pin_0: impl Peripheral<P = P0> + 'd,
+        pin_1: impl Peripheral<P = P1> + 'd,
+        pin_2: impl Peripheral<P = P2> + 'd,
+        pin_3: impl Peripheral<P = P3> + 'd,
+        pin_4: impl Peripheral<P = P4> + 'd,
+        pin_5: impl Peripheral<P = P5> + 'd,
+        pin_6: impl Peripheral<P = P6> + 'd,
+        pin_7: impl Peripheral<P = P7> + 'd,
+    ) -> Self {
+        crate::into_ref!(pin_0);
+        crate::into_ref!(pin_1);
+        crate::into_ref!(pin_2);
+        crate::into_ref!(pin_3);
+        crate::into_ref!(pin_4);
+        crate::into_ref!(pin_5);
+        crate::into_ref!(pin_6);
+        crate::into_ref!(pin_7);
+
+        Self {
+            pin_0,
+            pin_1,
+            pin_2,
+            pin_3,
+            pin_4,
+            pin_5,
+            pin_6,
+            pin_7,
+        }
+    }
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7> TxPins for TxEightBits<'d, P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+{
+    type Word = u8;
+
+    fn configure(&mut self) {
+        self.pin_0.set_to_push_pull_output(crate::private::Internal);
+        self.pin_0
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_0, crate::private::Internal);
+        self.pin_1.set_to_push_pull_output(crate::private::Internal);
+        self.pin_1
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_1, crate::private::Internal);
+        self.pin_2.set_to_push_pull_output(crate::private::Internal);
+        self.pin_2
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_2, crate::private::Internal);
+        self.pin_3.set_to_push_pull_output(crate::private::Internal);
+        self.pin_3
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_3, crate::private::Internal);
+        self.pin_4.set_to_push_pull_output(crate::private::Internal);
+        self.pin_4
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_4, crate::private::Internal);
+        self.pin_5.set_to_push_pull_output(crate::private::Internal);
+        self.pin_5
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_5, crate::private::Internal);
+        self.pin_6.set_to_push_pull_output(crate::private::Internal);
+        self.pin_6
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_6, crate::private::Internal);
+        self.pin_7.set_to_push_pull_output(crate::private::Internal);
+        self.pin_7
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_7, crate::private::Internal);
+    }
+}
+
+/// A 9 bit bus, the data is sent in the lower 9 bits of 16 bit words.
+pub struct TxNineBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8> {
+    pin_0: PeripheralRef<'d, P0>,
+    pin_1: PeripheralRef<'d, P1>,
+    pin_2: PeripheralRef<'d, P2>,
+    pin_3: PeripheralRef<'d, P3>,
+    pin_4: PeripheralRef<'d, P4>,
+    pin_5: PeripheralRef<'d, P5>,
+    pin_6: PeripheralRef<'d, P6>,
+    pin_7: PeripheralRef<'d, P7>,
+    pin_8: PeripheralRef<'d, P8>,
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8> TxNineBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+    P8: OutputPin,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pin_0: impl Peripheral<P = P0> + 'd,
+        pin_1: impl Peripheral<P = P1> + 'd,
+        pin_2: impl Peripheral<P = P2> + 'd,
+        pin_3: impl Peripheral<P = P3> + 'd,
+        pin_4: impl Peripheral<P = P4> + 'd,
+        pin_5: impl Peripheral<P = P5> + 'd,
+        pin_6: impl Peripheral<P = P6> + 'd,
+        pin_7: impl Peripheral<P = P7> + 'd,
+        pin_8: impl Peripheral<P = P8> + 'd,
+    ) -> Self {
+        crate::into_ref!(pin_0);
+        crate::into_ref!(pin_1);
+        crate::into_ref!(pin_2);
+        crate::into_ref!(pin_3);
+        crate::into_ref!(pin_4);
+        crate::into_ref!(pin_5);
+        crate::into_ref!(pin_6);
+        crate::into_ref!(pin_7);
+        crate::into_ref!(pin_8);
+
+        Self {
+            pin_0,
+            pin_1,
+            pin_2,
+            pin_3,
+            pin_4,
+            pin_5,
+            pin_6,
+            pin_7,
+            pin_8,
+        }
+    }
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8> TxPins
+    for TxNineBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+    P8: OutputPin,
+{
+    type Word = u16;
+
+    fn configure(&mut self) {
+        self.pin_0.set_to_push_pull_output(crate::private::Internal);
+        self.pin_0
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_0, crate::private::Internal);
+        self.pin_1.set_to_push_pull_output(crate::private::Internal);
+        self.pin_1
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_1, crate::private::Internal);
+        self.pin_2.set_to_push_pull_output(crate::private::Internal);
+        self.pin_2
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_2, crate::private::Internal);
+        self.pin_3.set_to_push_pull_output(crate::private::Internal);
+        self.pin_3
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_3, crate::private::Internal);
+        self.pin_4.set_to_push_pull_output(crate::private::Internal);
+        self.pin_4
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_4, crate::private::Internal);
+        self.pin_5.set_to_push_pull_output(crate::private::Internal);
+        self.pin_5
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_5, crate::private::Internal);
+        self.pin_6.set_to_push_pull_output(crate::private::Internal);
+        self.pin_6
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_6, crate::private::Internal);
+        self.pin_7.set_to_push_pull_output(crate::private::Internal);
+        self.pin_7
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_7, crate::private::Internal);
+        self.pin_8.set_to_push_pull_output(crate::private::Internal);
+        self.pin_8
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_8, crate::private::Internal);
+    }
+}
+
+pub struct TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> {
+    pin_0: PeripheralRef<'d, P0>,
+    pin_1: PeripheralRef<'d, P1>,
+    pin_2: PeripheralRef<'d, P2>,
+    pin_3: PeripheralRef<'d, P3>,
+    pin_4: PeripheralRef<'d, P4>,
+    pin_5: PeripheralRef<'d, P5>,
+    pin_6: PeripheralRef<'d, P6>,
+    pin_7: PeripheralRef<'d, P7>,
+    pin_8: PeripheralRef<'d, P8>,
+    pin_9: PeripheralRef<'d, P9>,
+    pin_10: PeripheralRef<'d, P10>,
+    pin_11: PeripheralRef<'d, P11>,
+    pin_12: PeripheralRef<'d, P12>,
+    pin_13: PeripheralRef<'d, P13>,
+    pin_14: PeripheralRef<'d, P14>,
+    pin_15: PeripheralRef<'d, P15>,
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+    TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+    P8: OutputPin,
+    P9: OutputPin,
+    P10: OutputPin,
+    P11: OutputPin,
+    P12: OutputPin,
+    P13: OutputPin,
+    P14: OutputPin,
+    P15: OutputPin,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pin_0: impl Peripheral<P = P0> + 'd,
+        pin_1: impl Peripheral<P = P1> + 'd,
+        pin_2: impl Peripheral<P = P2> + 'd,
+        pin_3: impl Peripheral<P = P3> + 'd,
+        pin_4: impl Peripheral<P = P4> + 'd,
+        pin_5: impl Peripheral<P = P5> + 'd,
+        pin_6: impl Peripheral<P = P6> + 'd,
+        pin_7: impl Peripheral<P = P7> + 'd,
+        pin_8: impl Peripheral<P = P8> + 'd,
+        pin_9: impl Peripheral<P = P9> + 'd,
+        pin_10: impl Peripheral<P = P10> + 'd,
+        pin_11: impl Peripheral<P = P11> + 'd,
+        pin_12: impl Peripheral<P = P12> + 'd,
+        pin_13: impl Peripheral<P = P13> + 'd,
+        pin_14: impl Peripheral<P = P14> + 'd,
+        pin_15: impl Peripheral<P = P15> + 'd,
+    ) -> Self {
+        crate::into_ref!(pin_0);
+        crate::into_ref!(pin_1);
+        crate::into_ref!(pin_2);
+        crate::into_ref!(pin_3);
+        crate::into_ref!(pin_4);
+        crate::into_ref!(pin_5);
+        crate::into_ref!(pin_6);
+        crate::into_ref!(pin_7);
+        crate::into_ref!(pin_8);
+        crate::into_ref!(pin_9);
+        crate::into_ref!(pin_10);
+        crate::into_ref!(pin_11);
+        crate::into_ref!(pin_12);
+        crate::into_ref!(pin_13);
+        crate::into_ref!(pin_14);
+        crate::into_ref!(pin_15);
+
+        Self {
+            pin_0,
+            pin_1,
+            pin_2,
+            pin_3,
+            pin_4,
+            pin_5,
+            pin_6,
+            pin_7,
+            pin_8,
+            pin_9,
+            pin_10,
+            pin_11,
+            pin_12,
+            pin_13,
+            pin_14,
+            pin_15,
+        }
+    }
+}
+
+impl<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15> TxPins
+    for TxSixteenBits<'d, P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11, P12, P13, P14, P15>
+where
+    P0: OutputPin,
+    P1: OutputPin,
+    P2: OutputPin,
+    P3: OutputPin,
+    P4: OutputPin,
+    P5: OutputPin,
+    P6: OutputPin,
+    P7: OutputPin,
+    P8: OutputPin,
+    P9: OutputPin,
+    P10: OutputPin,
+    P11: OutputPin,
+    P12: OutputPin,
+    P13: OutputPin,
+    P14: OutputPin,
+    P15: OutputPin,
+{
+    type Word = u16;
+    fn configure(&mut self) {
+        self.pin_0.set_to_push_pull_output(crate::private::Internal);
+        self.pin_0
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_0, crate::private::Internal);
+        self.pin_1.set_to_push_pull_output(crate::private::Internal);
+        self.pin_1
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_1, crate::private::Internal);
+        self.pin_2.set_to_push_pull_output(crate::private::Internal);
+        self.pin_2
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_2, crate::private::Internal);
+        self.pin_3.set_to_push_pull_output(crate::private::Internal);
+        self.pin_3
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_3, crate::private::Internal);
+        self.pin_4.set_to_push_pull_output(crate::private::Internal);
+        self.pin_4
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_4, crate::private::Internal);
+        self.pin_5.set_to_push_pull_output(crate::private::Internal);
+        self.pin_5
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_5, crate::private::Internal);
+        self.pin_6.set_to_push_pull_output(crate::private::Internal);
+        self.pin_6
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_6, crate::private::Internal);
+        self.pin_7.set_to_push_pull_output(crate::private::Internal);
+        self.pin_7
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_7, crate::private::Internal);
+        self.pin_8.set_to_push_pull_output(crate::private::Internal);
+        self.pin_8
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_8, crate::private::Internal);
+        self.pin_9.set_to_push_pull_output(crate::private::Internal);
+        self.pin_9
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_9, crate::private::Internal);
+        self.pin_10
+            .set_to_push_pull_output(crate::private::Internal);
+        self.pin_10
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_10, crate::private::Internal);
+        self.pin_11
+            .set_to_push_pull_output(crate::private::Internal);
+        self.pin_11
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_11, crate::private::Internal);
+        self.pin_12
+            .set_to_push_pull_output(crate::private::Internal);
+        self.pin_12
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_12, crate::private::Internal);
+        self.pin_13
+            .set_to_push_pull_output(crate::private::Internal);
+        self.pin_13
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_13, crate::private::Internal);
+        self.pin_14
+            .set_to_push_pull_output(crate::private::Internal);
+        self.pin_14
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_14, crate::private::Internal);
+        self.pin_15
+            .set_to_push_pull_output(crate::private::Internal);
+        self.pin_15
+            .connect_peripheral_to_output(OutputSignal::LCD_DATA_15, crate::private::Internal);
+    }
+}
+
+mod private {
+    pub trait TxPins {
+        type Word: Copy;
+        fn configure(&mut self);
+    }
+}
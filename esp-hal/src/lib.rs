@@ -160,6 +160,8 @@ pub mod trace;
 pub mod twai;
 #[cfg(any(uart0, uart1, uart2))]
 pub mod uart;
+#[cfg(all(uhci0, gdma))]
+pub mod uhci;
 #[cfg(usb_device)]
 pub mod usb_serial_jtag;
 
@@ -0,0 +1,368 @@
+//! # MCPWM peripheral - capture module
+//!
+//! ## Overview
+//! The `capture` module is part of the `MCPWM` peripheral driver for `ESP`
+//! chips. It timestamps edges of external signals against the capture timer,
+//! e.g. to measure the period of hall sensor signals or the width of echo
+//! pulses.
+//!
+//! Every [`MCPWM`](super::McPwm) peripheral has one capture timer and three
+//! capture channels. Each channel is connected to a GPIO and stores the value
+//! of the capture timer when the selected edges occur.
+#![doc = ""]
+#![cfg_attr(
+    any(esp32, esp32s3),
+    doc = "The capture timer is clocked by the APB clock."
+)]
+#![cfg_attr(
+    any(esp32c6, esp32h2),
+    doc = "The capture timer is clocked by the peripheral clock."
+)]
+#![doc = ""]
+//! ## Example
+//! Measures the width of a high pulse on `pin`.
+//!
+//! ```no_run
+//! # use esp_hal::{mcpwm, prelude::*};
+//! use mcpwm::{
+//!     capture::{CapturePinConfig, Edge},
+//!     McPwm,
+//!     PeripheralClockConfig,
+//! };
+//!
+//! let clock_cfg = PeripheralClockConfig::with_frequency(&clocks, 40.MHz()).unwrap();
+//! let mut mcpwm = McPwm::new(peripherals.PWM0, clock_cfg);
+//!
+//! mcpwm.capture_timer.start();
+//! let mut echo = mcpwm.capture0.with_pin(pin, CapturePinConfig::BOTH_EDGES);
+//!
+//! let rising = loop {
+//!     let capture = echo.wait_for_capture();
+//!     if capture.edge == Edge::Rising {
+//!         break capture;
+//!     }
+//! };
+//! let falling = echo.wait_for_capture();
+//! let ticks = falling.ticks_since(&rising);
+//! ```
+
+use core::marker::PhantomData;
+
+use fugit::HertzU32;
+
+#[cfg(feature = "async")]
+use crate::mcpwm::asynch::{InterruptFuture, CAPTURE_WAKERS};
+use crate::{
+    gpio::InputPin,
+    mcpwm::PwmPeripheral,
+    peripheral::{Peripheral, PeripheralRef},
+    private,
+};
+
+/// The MCPWM capture timer
+///
+/// The capture timer is a free running 32-bit counter, shared by all capture
+/// channels of the peripheral.
+pub struct CaptureTimer<PWM> {
+    frequency: HertzU32,
+    phantom: PhantomData<PWM>,
+}
+
+impl<PWM: PwmPeripheral> CaptureTimer<PWM> {
+    pub(super) fn new(frequency: HertzU32) -> Self {
+        CaptureTimer {
+            frequency,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Start the capture timer
+    pub fn start(&mut self) {
+        // SAFETY:
+        // We only write to our CAP_TIMER_CFG register
+        let block = unsafe { &*PWM::block() };
+        block
+            .cap_timer_cfg()
+            .modify(|_, w| w.cap_timer_en().set_bit());
+    }
+
+    /// Stop the capture timer
+    pub fn stop(&mut self) {
+        // SAFETY:
+        // We only write to our CAP_TIMER_CFG register
+        let block = unsafe { &*PWM::block() };
+        block
+            .cap_timer_cfg()
+            .modify(|_, w| w.cap_timer_en().clear_bit());
+    }
+
+    /// Get the frequency the capture timer is counting with.
+    pub fn frequency(&self) -> HertzU32 {
+        self.frequency
+    }
+}
+
+/// A MCPWM capture channel
+pub struct CaptureChannel<const CAP: u8, PWM> {
+    phantom: PhantomData<PWM>,
+}
+
+impl<const CAP: u8, PWM: PwmPeripheral> CaptureChannel<CAP, PWM> {
+    pub(super) fn new() -> Self {
+        CaptureChannel {
+            phantom: PhantomData,
+        }
+    }
+
+    /// Capture edges on the given pin with the given configuration
+    pub fn with_pin<'d, Pin: InputPin>(
+        self,
+        pin: impl Peripheral<P = Pin> + 'd,
+        config: CapturePinConfig,
+    ) -> CapturePin<'d, Pin, PWM, CAP> {
+        CapturePin::new(pin, config)
+    }
+}
+
+/// The edges of the input signal that trigger a capture
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum CaptureEdges {
+    /// Capture on falling edges
+    Falling = 1,
+    /// Capture on rising edges
+    Rising  = 2,
+    /// Capture on rising and falling edges
+    Both    = 3,
+}
+
+/// Configuration of a capture channel
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CapturePinConfig {
+    edges: CaptureEdges,
+    prescaler: u8,
+    invert: bool,
+}
+
+impl CapturePinConfig {
+    /// A configuration capturing every rising edge
+    pub const RISING_EDGE: Self = Self::new(CaptureEdges::Rising, 0);
+    /// A configuration capturing every falling edge
+    pub const FALLING_EDGE: Self = Self::new(CaptureEdges::Falling, 0);
+    /// A configuration capturing every rising and falling edge
+    pub const BOTH_EDGES: Self = Self::new(CaptureEdges::Both, 0);
+
+    /// Get a configuration capturing the given edges.
+    ///
+    /// The input signal is divided by `prescaler + 1` before the edges are
+    /// detected, the prescaler counts rising edges. E.g. with a prescaler of
+    /// `3` and [`CaptureEdges::Rising`] every fourth rising edge is captured.
+    pub const fn new(edges: CaptureEdges, prescaler: u8) -> Self {
+        CapturePinConfig {
+            edges,
+            prescaler,
+            invert: false,
+        }
+    }
+
+    /// Invert the input signal before the edges are detected
+    pub const fn invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+}
+
+/// The edge which triggered a capture
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    /// A rising edge
+    Rising,
+    /// A falling edge
+    Falling,
+}
+
+/// A value of the capture timer captured on an edge
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Capture {
+    /// The value of the capture timer
+    pub value: u32,
+    /// The edge which triggered the capture
+    pub edge: Edge,
+}
+
+impl Capture {
+    /// Get the number of capture timer ticks between an earlier capture and
+    /// this one.
+    ///
+    /// The result is correct as long as less than `2^32` ticks passed.
+    pub fn ticks_since(&self, earlier: &Capture) -> u32 {
+        self.value.wrapping_sub(earlier.value)
+    }
+}
+
+/// A pin captured by an MCPWM capture channel
+pub struct CapturePin<'d, Pin, PWM, const CAP: u8> {
+    _pin: PeripheralRef<'d, Pin>,
+    phantom: PhantomData<PWM>,
+}
+
+impl<'d, Pin: InputPin, PWM: PwmPeripheral, const CAP: u8> CapturePin<'d, Pin, PWM, CAP> {
+    fn new(pin: impl Peripheral<P = Pin> + 'd, config: CapturePinConfig) -> Self {
+        crate::into_ref!(pin);
+
+        pin.set_to_input(private::Internal);
+        pin.connect_input_to_peripheral(PWM::capture_input_signal::<CAP>(), private::Internal);
+
+        let mut pin = CapturePin {
+            _pin: pin,
+            phantom: PhantomData,
+        };
+        pin.set_config(config);
+        pin
+    }
+
+    /// Apply the given configuration
+    pub fn set_config(&mut self, config: CapturePinConfig) {
+        // SAFETY:
+        // We only write to our CAP_CHx_CFG register
+        let block = unsafe { &*PWM::block() };
+        block.cap_ch_cfg(CAP as usize).write(|w| unsafe {
+            w.mode().bits(config.edges as u8);
+            w.prescale().bits(config.prescaler);
+            w.in_invert().bit(config.invert);
+            w.en().set_bit()
+        });
+    }
+
+    /// Trigger a capture in software
+    ///
+    /// The capture is reported like a capture on an edge, with the edge of the
+    /// last capture.
+    pub fn trigger(&mut self) {
+        // SAFETY:
+        // We only write to our CAP_CHx_CFG register
+        let block = unsafe { &*PWM::block() };
+        block
+            .cap_ch_cfg(CAP as usize)
+            .modify(|_, w| w.sw().set_bit());
+    }
+
+    /// Get the last capture
+    pub fn last_capture(&self) -> Capture {
+        // SAFETY:
+        // We only read from our CAP_CHx register and our bit of CAP_STATUS
+        let block = unsafe { &*PWM::block() };
+        let value = block.cap_ch(CAP as usize).read().value().bits();
+        let status = block.cap_status().read();
+        let falling = match CAP {
+            0 => status.cap0_edge().bit_is_set(),
+            1 => status.cap1_edge().bit_is_set(),
+            2 => status.cap2_edge().bit_is_set(),
+            _ => unreachable!(),
+        };
+
+        Capture {
+            value,
+            edge: if falling { Edge::Falling } else { Edge::Rising },
+        }
+    }
+
+    /// Wait for the next capture, blocking
+    pub fn wait_for_capture(&mut self) -> Capture {
+        self.clear_interrupt();
+        while !self.is_interrupt_set() {}
+        self.clear_interrupt();
+
+        self.last_capture()
+    }
+
+    /// Listen for the capture interrupt
+    ///
+    /// The interrupt handler is set with
+    /// [`McPwm::set_interrupt_handler`](super::McPwm::set_interrupt_handler).
+    pub fn listen(&mut self) {
+        Self::enable_interrupt(true);
+    }
+
+    /// Stop listening for the capture interrupt
+    pub fn unlisten(&mut self) {
+        Self::enable_interrupt(false);
+    }
+
+    /// Returns whether a capture happened since the interrupt was cleared
+    pub fn is_interrupt_set(&self) -> bool {
+        // SAFETY:
+        // We only read our bit of INT_RAW
+        let block = unsafe { &*PWM::block() };
+        let raw = block.int_raw().read();
+        match CAP {
+            0 => raw.cap0_int_raw().bit_is_set(),
+            1 => raw.cap1_int_raw().bit_is_set(),
+            2 => raw.cap2_int_raw().bit_is_set(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clear the capture interrupt
+    pub fn clear_interrupt(&mut self) {
+        // SAFETY:
+        // We only write our bit of INT_CLR
+        let block = unsafe { &*PWM::block() };
+        block.int_clr().write(|w| match CAP {
+            0 => w.cap0_int_clr().set_bit(),
+            1 => w.cap1_int_clr().set_bit(),
+            2 => w.cap2_int_clr().set_bit(),
+            _ => unreachable!(),
+        });
+    }
+
+    #[cfg(feature = "async")]
+    fn is_listening() -> bool {
+        // SAFETY:
+        // We only read our bit of INT_ENA
+        let block = unsafe { &*PWM::block() };
+        let ena = block.int_ena().read();
+        match CAP {
+            0 => ena.cap0_int_ena().bit_is_set(),
+            1 => ena.cap1_int_ena().bit_is_set(),
+            2 => ena.cap2_int_ena().bit_is_set(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn enable_interrupt(enable: bool) {
+        // SAFETY:
+        // We only modify our bit of INT_ENA
+        let block = unsafe { &*PWM::block() };
+        block.int_ena().modify(|_, w| match CAP {
+            0 => w.cap0_int_ena().bit(enable),
+            1 => w.cap1_int_ena().bit(enable),
+            2 => w.cap2_int_ena().bit(enable),
+            _ => unreachable!(),
+        });
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'d, Pin: InputPin, PWM: PwmPeripheral, const CAP: u8> CapturePin<'d, Pin, PWM, CAP> {
+    /// Wait for the next capture
+    ///
+    /// The peripheral must be created with
+    /// [`McPwm::new_async`](super::McPwm::new_async).
+    pub async fn wait_for_capture_async(&mut self) -> Capture {
+        self.clear_interrupt();
+        Self::enable_interrupt(true);
+        InterruptFuture::new(
+            &CAPTURE_WAKERS[PWM::index()][CAP as usize],
+            Self::is_listening,
+        )
+        .await;
+        self.clear_interrupt();
+
+        self.last_capture()
+    }
+}
@@ -0,0 +1,548 @@
+//! # MCPWM peripheral - fault module
+//!
+//! ## Overview
+//! The `fault` module is part of the `MCPWM` peripheral driver for `ESP`
+//! chips. It shuts down or overrides the PWM outputs in hardware when a fault
+//! signal, e.g. from an over-current comparator, becomes active.
+//!
+//! Every [`MCPWM`](super::McPwm) peripheral has three fault detectors, each
+//! connected to a GPIO, and one fault handler per operator. The fault handler
+//! decides which faults trip it and how the outputs of its operator react:
+//! * One-shot: the actions stay in effect until the trip is cleared by
+//!   software, e.g. to brake a motor on over-current.
+//! * Cycle-by-cycle: the actions stay in effect while the fault is active and
+//!   are cleared on the next configured timer event afterwards, e.g. to limit
+//!   the current per PWM cycle.
+//!
+//! ## Example
+//! Forces both outputs of operator0 low when `fault_pin` goes high, until the
+//! trip is cleared.
+//!
+//! ```no_run
+//! # use esp_hal::{mcpwm, prelude::*};
+//! use mcpwm::{
+//!     fault::{FaultActions, FaultPolarity, FaultSource},
+//!     operator::{PWMStream, UpdateAction},
+//! };
+//!
+//! let _fault = mcpwm.fault0.with_pin(fault_pin, FaultPolarity::ActiveHigh);
+//!
+//! mcpwm.fault_handler0.set_actions(
+//!     FaultActions::empty()
+//!         .one_shot_on(FaultSource::Fault0)
+//!         .on_one_shot(PWMStream::PWMA, UpdateAction::SetLow)
+//!         .on_one_shot(PWMStream::PWMB, UpdateAction::SetLow),
+//! );
+//!
+//! // after the fault was resolved
+//! if mcpwm.fault_handler0.status().one_shot {
+//!     mcpwm.fault_handler0.clear_one_shot();
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+#[cfg(feature = "async")]
+use crate::mcpwm::asynch::{InterruptFuture, FAULT_WAKERS, TRIP_WAKERS};
+use crate::{
+    gpio::InputPin,
+    mcpwm::{
+        operator::{PWMStream, UpdateAction},
+        PwmPeripheral,
+    },
+    peripheral::{Peripheral, PeripheralRef},
+    private,
+};
+
+/// A MCPWM fault detector
+pub struct FaultDetector<const F: u8, PWM> {
+    phantom: PhantomData<PWM>,
+}
+
+impl<const F: u8, PWM: PwmPeripheral> FaultDetector<F, PWM> {
+    pub(super) fn new() -> Self {
+        FaultDetector {
+            phantom: PhantomData,
+        }
+    }
+
+    /// Detect faults on the given pin
+    pub fn with_pin<'d, Pin: InputPin>(
+        self,
+        pin: impl Peripheral<P = Pin> + 'd,
+        polarity: FaultPolarity,
+    ) -> FaultPin<'d, Pin, PWM, F> {
+        FaultPin::new(pin, polarity)
+    }
+}
+
+/// The level of the fault signal indicating a fault
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultPolarity {
+    /// A fault is indicated by a low level
+    ActiveLow,
+    /// A fault is indicated by a high level
+    ActiveHigh,
+}
+
+/// A fault event which can trigger an interrupt
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultEvent {
+    /// The fault became active
+    Enter,
+    /// The fault became inactive
+    Exit,
+}
+
+/// A pin monitored by an MCPWM fault detector
+pub struct FaultPin<'d, Pin, PWM, const F: u8> {
+    _pin: PeripheralRef<'d, Pin>,
+    phantom: PhantomData<PWM>,
+}
+
+impl<'d, Pin: InputPin, PWM: PwmPeripheral, const F: u8> FaultPin<'d, Pin, PWM, F> {
+    fn new(pin: impl Peripheral<P = Pin> + 'd, polarity: FaultPolarity) -> Self {
+        crate::into_ref!(pin);
+
+        pin.set_to_input(private::Internal);
+        pin.connect_input_to_peripheral(PWM::fault_input_signal::<F>(), private::Internal);
+
+        // SAFETY:
+        // We only modify our bits of FAULT_DETECT
+        let block = unsafe { &*PWM::block() };
+        let active_high = polarity == FaultPolarity::ActiveHigh;
+        block.fault_detect().modify(|_, w| match F {
+            0 => w.f0_pole().bit(active_high).f0_en().set_bit(),
+            1 => w.f1_pole().bit(active_high).f1_en().set_bit(),
+            2 => w.f2_pole().bit(active_high).f2_en().set_bit(),
+            _ => unreachable!(),
+        });
+
+        FaultPin {
+            _pin: pin,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns whether the fault is currently active
+    pub fn is_active(&self) -> bool {
+        // SAFETY:
+        // We only read our bits of FAULT_DETECT
+        let block = unsafe { &*PWM::block() };
+        let reg = block.fault_detect().read();
+        match F {
+            0 => reg.event_f0().bit_is_set(),
+            1 => reg.event_f1().bit_is_set(),
+            2 => reg.event_f2().bit_is_set(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Listen for the given fault event
+    ///
+    /// The interrupt handler is set with
+    /// [`McPwm::set_interrupt_handler`](super::McPwm::set_interrupt_handler).
+    pub fn listen(&mut self, event: FaultEvent) {
+        Self::enable_interrupt(event, true);
+    }
+
+    /// Stop listening for the given fault event
+    pub fn unlisten(&mut self, event: FaultEvent) {
+        Self::enable_interrupt(event, false);
+    }
+
+    /// Returns whether the given fault event occurred since its interrupt was
+    /// cleared
+    pub fn is_interrupt_set(&self, event: FaultEvent) -> bool {
+        // SAFETY:
+        // We only read our bits of INT_RAW
+        let block = unsafe { &*PWM::block() };
+        let raw = block.int_raw().read();
+        match (F, event) {
+            (0, FaultEvent::Enter) => raw.fault0_int_raw().bit_is_set(),
+            (1, FaultEvent::Enter) => raw.fault1_int_raw().bit_is_set(),
+            (2, FaultEvent::Enter) => raw.fault2_int_raw().bit_is_set(),
+            (0, FaultEvent::Exit) => raw.fault0_clr_int_raw().bit_is_set(),
+            (1, FaultEvent::Exit) => raw.fault1_clr_int_raw().bit_is_set(),
+            (2, FaultEvent::Exit) => raw.fault2_clr_int_raw().bit_is_set(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clear the interrupt of the given fault event
+    pub fn clear_interrupt(&mut self, event: FaultEvent) {
+        // SAFETY:
+        // We only write our bits of INT_CLR
+        let block = unsafe { &*PWM::block() };
+        block.int_clr().write(|w| match (F, event) {
+            (0, FaultEvent::Enter) => w.fault0_int_clr().set_bit(),
+            (1, FaultEvent::Enter) => w.fault1_int_clr().set_bit(),
+            (2, FaultEvent::Enter) => w.fault2_int_clr().set_bit(),
+            (0, FaultEvent::Exit) => w.fault0_clr_int_clr().set_bit(),
+            (1, FaultEvent::Exit) => w.fault1_clr_int_clr().set_bit(),
+            (2, FaultEvent::Exit) => w.fault2_clr_int_clr().set_bit(),
+            _ => unreachable!(),
+        });
+    }
+
+    #[cfg(feature = "async")]
+    fn is_listening(event: FaultEvent) -> bool {
+        // SAFETY:
+        // We only read our bits of INT_ENA
+        let block = unsafe { &*PWM::block() };
+        let ena = block.int_ena().read();
+        match (F, event) {
+            (0, FaultEvent::Enter) => ena.fault0_int_ena().bit_is_set(),
+            (1, FaultEvent::Enter) => ena.fault1_int_ena().bit_is_set(),
+            (2, FaultEvent::Enter) => ena.fault2_int_ena().bit_is_set(),
+            (0, FaultEvent::Exit) => ena.fault0_clr_int_ena().bit_is_set(),
+            (1, FaultEvent::Exit) => ena.fault1_clr_int_ena().bit_is_set(),
+            (2, FaultEvent::Exit) => ena.fault2_clr_int_ena().bit_is_set(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn enable_interrupt(event: FaultEvent, enable: bool) {
+        // SAFETY:
+        // We only modify our bits of INT_ENA
+        let block = unsafe { &*PWM::block() };
+        block.int_ena().modify(|_, w| match (F, event) {
+            (0, FaultEvent::Enter) => w.fault0_int_ena().bit(enable),
+            (1, FaultEvent::Enter) => w.fault1_int_ena().bit(enable),
+            (2, FaultEvent::Enter) => w.fault2_int_ena().bit(enable),
+            (0, FaultEvent::Exit) => w.fault0_clr_int_ena().bit(enable),
+            (1, FaultEvent::Exit) => w.fault1_clr_int_ena().bit(enable),
+            (2, FaultEvent::Exit) => w.fault2_clr_int_ena().bit(enable),
+            _ => unreachable!(),
+        });
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'d, Pin: InputPin, PWM: PwmPeripheral, const F: u8> FaultPin<'d, Pin, PWM, F> {
+    /// Wait for the next occurrence of the given fault event
+    ///
+    /// The peripheral must be created with
+    /// [`McPwm::new_async`](super::McPwm::new_async).
+    pub async fn wait_for_event_async(&mut self, event: FaultEvent) {
+        self.clear_interrupt(event);
+        Self::enable_interrupt(event, true);
+        let index = match event {
+            FaultEvent::Enter => 0,
+            FaultEvent::Exit => 1,
+        };
+        InterruptFuture::new(&FAULT_WAKERS[PWM::index()][F as usize][index], || {
+            Self::is_listening(event)
+        })
+        .await;
+        self.clear_interrupt(event);
+    }
+}
+
+/// A source of faults for a [`FaultHandler`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultSource {
+    /// Faults forced by software, see [`FaultHandler::force_one_shot`] and
+    /// [`FaultHandler::force_cycle_by_cycle`]
+    Software,
+    /// Faults detected by fault detector 0
+    Fault0,
+    /// Faults detected by fault detector 1
+    Fault1,
+    /// Faults detected by fault detector 2
+    Fault2,
+}
+
+/// The kind of fault handler trip
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultTrip {
+    /// One-shot trip, active until cleared by software
+    OneShot,
+    /// Cycle-by-cycle trip, cleared on the next configured timer event after
+    /// the fault became inactive
+    CycleByCycle,
+}
+
+/// Settings for how a [`FaultHandler`] reacts to faults
+#[derive(Copy, Clone)]
+pub struct FaultActions {
+    cfg0: u32,
+    cbc_pulse: u8,
+}
+
+impl FaultActions {
+    /// `FaultActions` without any fault sources or actions
+    pub const fn empty() -> Self {
+        FaultActions {
+            cfg0: 0,
+            cbc_pulse: 0,
+        }
+    }
+
+    /// Trip the one-shot mode when `source` reports a fault
+    pub const fn one_shot_on(mut self, source: FaultSource) -> Self {
+        self.cfg0 |= match source {
+            FaultSource::Software => 1 << 4,
+            FaultSource::Fault2 => 1 << 5,
+            FaultSource::Fault1 => 1 << 6,
+            FaultSource::Fault0 => 1 << 7,
+        };
+        self
+    }
+
+    /// Trip the cycle-by-cycle mode when `source` reports a fault
+    ///
+    /// The trip is cleared on the timer events selected with
+    /// [`FaultActions::recover_on_timer_equals_zero`] and
+    /// [`FaultActions::recover_on_timer_equals_period`].
+    pub const fn cycle_by_cycle_on(mut self, source: FaultSource) -> Self {
+        self.cfg0 |= match source {
+            FaultSource::Software => 1 << 0,
+            FaultSource::Fault2 => 1 << 1,
+            FaultSource::Fault1 => 1 << 2,
+            FaultSource::Fault0 => 1 << 3,
+        };
+        self
+    }
+
+    /// Choose an `UpdateAction` for `stream` while the one-shot mode is
+    /// tripped
+    pub const fn on_one_shot(self, stream: PWMStream, action: UpdateAction) -> Self {
+        let offset = match stream {
+            PWMStream::PWMA => 12,
+            PWMStream::PWMB => 20,
+        };
+        self.with_action_at_offset(action, offset)
+    }
+
+    /// Choose an `UpdateAction` for `stream` while the cycle-by-cycle mode is
+    /// tripped
+    pub const fn on_cycle_by_cycle(self, stream: PWMStream, action: UpdateAction) -> Self {
+        let offset = match stream {
+            PWMStream::PWMA => 8,
+            PWMStream::PWMB => 16,
+        };
+        self.with_action_at_offset(action, offset)
+    }
+
+    /// Clear a cycle-by-cycle trip when the timer is equal to zero
+    pub const fn recover_on_timer_equals_zero(mut self) -> Self {
+        self.cbc_pulse |= 0b01;
+        self
+    }
+
+    /// Clear a cycle-by-cycle trip when the timer is equal to period
+    pub const fn recover_on_timer_equals_period(mut self) -> Self {
+        self.cbc_pulse |= 0b10;
+        self
+    }
+
+    // The action is applied both while the timer is counting down and while it's
+    // counting up
+    const fn with_action_at_offset(mut self, action: UpdateAction, offset: u32) -> Self {
+        let action = action as u32;
+        let mask = !(0b1111 << offset);
+        self.cfg0 = (self.cfg0 & mask) | (action << offset) | (action << (offset + 2));
+        self
+    }
+}
+
+/// The state of a [`FaultHandler`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FaultStatus {
+    /// The one-shot mode is tripped
+    pub one_shot: bool,
+    /// The cycle-by-cycle mode is tripped
+    pub cycle_by_cycle: bool,
+}
+
+/// The fault handler of a MCPWM operator
+///
+/// Overrides the outputs of the operator with the same number when a fault
+/// trips it.
+pub struct FaultHandler<const OP: u8, PWM> {
+    phantom: PhantomData<PWM>,
+}
+
+impl<const OP: u8, PWM: PwmPeripheral> FaultHandler<OP, PWM> {
+    pub(super) fn new() -> Self {
+        FaultHandler {
+            phantom: PhantomData,
+        }
+    }
+
+    /// Configure which faults trip the handler and how the outputs react
+    pub fn set_actions(&mut self, actions: FaultActions) {
+        // SAFETY:
+        // We only write to our FHx_CFG0 and FHx_CFG1 registers
+        let ch = unsafe { Self::ch() };
+
+        // SAFETY:
+        // `cfg0` is a valid bit pattern
+        ch.fh_cfg0().write(|w| unsafe { w.bits(actions.cfg0) });
+        ch.fh_cfg1()
+            .modify(|_, w| unsafe { w.cbcpulse().bits(actions.cbc_pulse) });
+    }
+
+    /// Get the state of the fault handler
+    pub fn status(&self) -> FaultStatus {
+        // SAFETY:
+        // We only read from our FHx_STATUS register
+        let status = unsafe { Self::ch() }.fh_status().read();
+
+        FaultStatus {
+            one_shot: status.ost_on().bit_is_set(),
+            cycle_by_cycle: status.cbc_on().bit_is_set(),
+        }
+    }
+
+    /// Clear a one-shot trip
+    ///
+    /// The handler trips again immediately if the fault is still active.
+    pub fn clear_one_shot(&mut self) {
+        // SAFETY:
+        // We only write to our FHx_CFG1 register
+        let ch = unsafe { Self::ch() };
+
+        // A rising edge clears the trip
+        ch.fh_cfg1().modify(|_, w| w.clr_ost().set_bit());
+        ch.fh_cfg1().modify(|_, w| w.clr_ost().clear_bit());
+    }
+
+    /// Force a one-shot trip
+    ///
+    /// Only has an effect if [`FaultSource::Software`] trips the one-shot
+    /// mode.
+    pub fn force_one_shot(&mut self) {
+        // SAFETY:
+        // We only write to our FHx_CFG1 register
+        let ch = unsafe { Self::ch() };
+
+        // A toggle forces the trip
+        ch.fh_cfg1()
+            .modify(|r, w| w.force_ost().bit(!r.force_ost().bit()));
+    }
+
+    /// Force a cycle-by-cycle trip
+    ///
+    /// Only has an effect if [`FaultSource::Software`] trips the
+    /// cycle-by-cycle mode.
+    pub fn force_cycle_by_cycle(&mut self) {
+        // SAFETY:
+        // We only write to our FHx_CFG1 register
+        let ch = unsafe { Self::ch() };
+
+        // A toggle forces the trip
+        ch.fh_cfg1()
+            .modify(|r, w| w.force_cbc().bit(!r.force_cbc().bit()));
+    }
+
+    /// Listen for trips of the given kind
+    ///
+    /// The interrupt handler is set with
+    /// [`McPwm::set_interrupt_handler`](super::McPwm::set_interrupt_handler).
+    pub fn listen(&mut self, trip: FaultTrip) {
+        Self::enable_interrupt(trip, true);
+    }
+
+    /// Stop listening for trips of the given kind
+    pub fn unlisten(&mut self, trip: FaultTrip) {
+        Self::enable_interrupt(trip, false);
+    }
+
+    /// Returns whether the handler tripped since the interrupt was cleared
+    pub fn is_interrupt_set(&self, trip: FaultTrip) -> bool {
+        // SAFETY:
+        // We only read our bits of INT_RAW
+        let block = unsafe { &*PWM::block() };
+        let raw = block.int_raw().read();
+        match (OP, trip) {
+            (0, FaultTrip::OneShot) => raw.tz0_ost_int_raw().bit_is_set(),
+            (1, FaultTrip::OneShot) => raw.tz1_ost_int_raw().bit_is_set(),
+            (2, FaultTrip::OneShot) => raw.tz2_ost_int_raw().bit_is_set(),
+            (0, FaultTrip::CycleByCycle) => raw.tz0_cbc_int_raw().bit_is_set(),
+            (1, FaultTrip::CycleByCycle) => raw.tz1_cbc_int_raw().bit_is_set(),
+            (2, FaultTrip::CycleByCycle) => raw.tz2_cbc_int_raw().bit_is_set(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clear the interrupt of the given trip kind
+    pub fn clear_interrupt(&mut self, trip: FaultTrip) {
+        // SAFETY:
+        // We only write our bits of INT_CLR
+        let block = unsafe { &*PWM::block() };
+        block.int_clr().write(|w| match (OP, trip) {
+            (0, FaultTrip::OneShot) => w.tz0_ost_int_clr().set_bit(),
+            (1, FaultTrip::OneShot) => w.tz1_ost_int_clr().set_bit(),
+            (2, FaultTrip::OneShot) => w.tz2_ost_int_clr().set_bit(),
+            (0, FaultTrip::CycleByCycle) => w.tz0_cbc_int_clr().set_bit(),
+            (1, FaultTrip::CycleByCycle) => w.tz1_cbc_int_clr().set_bit(),
+            (2, FaultTrip::CycleByCycle) => w.tz2_cbc_int_clr().set_bit(),
+            _ => unreachable!(),
+        });
+    }
+
+    #[cfg(feature = "async")]
+    fn is_listening(trip: FaultTrip) -> bool {
+        // SAFETY:
+        // We only read our bits of INT_ENA
+        let block = unsafe { &*PWM::block() };
+        let ena = block.int_ena().read();
+        match (OP, trip) {
+            (0, FaultTrip::OneShot) => ena.tz0_ost_int_ena().bit_is_set(),
+            (1, FaultTrip::OneShot) => ena.tz1_ost_int_ena().bit_is_set(),
+            (2, FaultTrip::OneShot) => ena.tz2_ost_int_ena().bit_is_set(),
+            (0, FaultTrip::CycleByCycle) => ena.tz0_cbc_int_ena().bit_is_set(),
+            (1, FaultTrip::CycleByCycle) => ena.tz1_cbc_int_ena().bit_is_set(),
+            (2, FaultTrip::CycleByCycle) => ena.tz2_cbc_int_ena().bit_is_set(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn enable_interrupt(trip: FaultTrip, enable: bool) {
+        // SAFETY:
+        // We only modify our bits of INT_ENA
+        let block = unsafe { &*PWM::block() };
+        block.int_ena().modify(|_, w| match (OP, trip) {
+            (0, FaultTrip::OneShot) => w.tz0_ost_int_ena().bit(enable),
+            (1, FaultTrip::OneShot) => w.tz1_ost_int_ena().bit(enable),
+            (2, FaultTrip::OneShot) => w.tz2_ost_int_ena().bit(enable),
+            (0, FaultTrip::CycleByCycle) => w.tz0_cbc_int_ena().bit(enable),
+            (1, FaultTrip::CycleByCycle) => w.tz1_cbc_int_ena().bit(enable),
+            (2, FaultTrip::CycleByCycle) => w.tz2_cbc_int_ena().bit(enable),
+            _ => unreachable!(),
+        });
+    }
+
+    unsafe fn ch() -> &'static crate::peripherals::mcpwm0::CH {
+        let block = unsafe { &*PWM::block() };
+        block.ch(OP as usize)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<const OP: u8, PWM: PwmPeripheral> FaultHandler<OP, PWM> {
+    /// Wait for the next trip of the given kind
+    ///
+    /// The peripheral must be created with
+    /// [`McPwm::new_async`](super::McPwm::new_async).
+    pub async fn wait_for_trip_async(&mut self, trip: FaultTrip) {
+        self.clear_interrupt(trip);
+        Self::enable_interrupt(trip, true);
+        let index = match trip {
+            FaultTrip::OneShot => 0,
+            FaultTrip::CycleByCycle => 1,
+        };
+        InterruptFuture::new(&TRIP_WAKERS[PWM::index()][OP as usize][index], || {
+            Self::is_listening(trip)
+        })
+        .await;
+        self.clear_interrupt(trip);
+    }
+}
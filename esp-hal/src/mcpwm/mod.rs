@@ -7,7 +7,7 @@
 //!     * The 16-bit counter in the PWM timer can work in count-up mode,
 //!       count-down mode or count-up-down mode.
 //!     * A hardware sync or software sync can trigger a reload on the PWM timer
-//!       with a phase register (Not yet implemented)
+//!       with a phase register
 //! * PWM Operators 0, 1 and 2
 //!     * Every PWM operator has two PWM outputs: PWMxA and PWMxB. They can work
 //!       independently, in symmetric and asymmetric configuration.
@@ -16,12 +16,19 @@
 //!       independently. (Not yet implemented)
 //!     * All events can trigger CPU interrupts. (Not yet implemented)
 //!     * Modulating of PWM output by high-frequency carrier signals, useful
-//!       when gate drivers are insulated with a transformer. (Not yet
-//!       implemented)
+//!       when gate drivers are insulated with a transformer.
 //!     * Period, time stamps and important control registers have shadow
 //!       registers with flexible updating methods.
-//! * Fault Detection Module (Not yet implemented)
-//! * Capture Module (Not yet implemented)
+//! * Fault Detection Module
+//!     * Three fault detectors monitor GPIOs with configurable polarity.
+//!     * Every operator has a fault handler, applying one-shot or
+//!       cycle-by-cycle actions to its outputs on hardware or software forced
+//!       faults.
+//! * Capture Module
+//!     * Three capture channels timestamp edges of external signals against a
+//!       32-bit capture timer.
+//!     * Every capture channel has an 8-bit prescaler and can be triggered by
+//!       software.
 #![doc = ""]
 #![cfg_attr(esp32, doc = "Clock source is PWM_CLOCK")]
 #![cfg_attr(esp32s3, doc = "Clock source is CRYPTO_PWM_CLOCK")]
@@ -61,17 +68,25 @@
 
 use core::{marker::PhantomData, ops::Deref};
 
-use fugit::HertzU32;
+use capture::{CaptureChannel, CaptureTimer};
+use fault::{FaultDetector, FaultHandler};
+use fugit::{HertzU32, NanosDurationU32};
 use operator::Operator;
-use timer::Timer;
+use timer::{SyncInput, Timer};
 
 use crate::{
     clock::Clocks,
-    gpio::OutputSignal,
+    gpio::{InputSignal, OutputSignal},
+    interrupt::InterruptHandler,
     peripheral::{Peripheral, PeripheralRef},
+    peripherals::Interrupt,
     system::{Peripheral as PeripheralEnable, PeripheralClockControl},
 };
 
+/// MCPWM capture channels
+pub mod capture;
+/// MCPWM fault detection and handling
+pub mod fault;
 /// MCPWM operators
 pub mod operator;
 /// MCPWM timers
@@ -89,12 +104,38 @@ pub struct McPwm<'d, PWM> {
     pub timer1: Timer<1, PWM>,
     /// Timer2
     pub timer2: Timer<2, PWM>,
+    /// GPIO sync input 0
+    pub sync_input0: SyncInput<0, PWM>,
+    /// GPIO sync input 1
+    pub sync_input1: SyncInput<1, PWM>,
+    /// GPIO sync input 2
+    pub sync_input2: SyncInput<2, PWM>,
     /// Operator0
     pub operator0: Operator<0, PWM>,
     /// Operator1
     pub operator1: Operator<1, PWM>,
     /// Operator2
     pub operator2: Operator<2, PWM>,
+    /// Capture timer
+    pub capture_timer: CaptureTimer<PWM>,
+    /// Capture channel 0
+    pub capture0: CaptureChannel<0, PWM>,
+    /// Capture channel 1
+    pub capture1: CaptureChannel<1, PWM>,
+    /// Capture channel 2
+    pub capture2: CaptureChannel<2, PWM>,
+    /// Fault detector 0
+    pub fault0: FaultDetector<0, PWM>,
+    /// Fault detector 1
+    pub fault1: FaultDetector<1, PWM>,
+    /// Fault detector 2
+    pub fault2: FaultDetector<2, PWM>,
+    /// Fault handler of operator0
+    pub fault_handler0: FaultHandler<0, PWM>,
+    /// Fault handler of operator1
+    pub fault_handler1: FaultHandler<1, PWM>,
+    /// Fault handler of operator2
+    pub fault_handler2: FaultHandler<2, PWM>,
 }
 
 impl<'d, PWM: PwmPeripheral> McPwm<'d, PWM> {
@@ -152,9 +193,48 @@ impl<'d, PWM: PwmPeripheral> McPwm<'d, PWM> {
             timer0: Timer::new(),
             timer1: Timer::new(),
             timer2: Timer::new(),
+            sync_input0: SyncInput::new(),
+            sync_input1: SyncInput::new(),
+            sync_input2: SyncInput::new(),
             operator0: Operator::new(),
             operator1: Operator::new(),
             operator2: Operator::new(),
+            capture_timer: CaptureTimer::new(peripheral_clock.capture_frequency),
+            capture0: CaptureChannel::new(),
+            capture1: CaptureChannel::new(),
+            capture2: CaptureChannel::new(),
+            fault0: FaultDetector::new(),
+            fault1: FaultDetector::new(),
+            fault2: FaultDetector::new(),
+            fault_handler0: FaultHandler::new(),
+            fault_handler1: FaultHandler::new(),
+            fault_handler2: FaultHandler::new(),
+        }
+    }
+
+    /// Create the peripheral with the interrupt handler needed by the async
+    /// APIs bound, i.e.
+    /// [`CapturePin::wait_for_capture_async`](capture::CapturePin::wait_for_capture_async),
+    /// [`FaultPin::wait_for_event_async`](fault::FaultPin::wait_for_event_async)
+    /// and [`FaultHandler::wait_for_trip_async`].
+    #[cfg(feature = "async")]
+    pub fn new_async(
+        peripheral: impl Peripheral<P = PWM> + 'd,
+        peripheral_clock: PeripheralClockConfig,
+    ) -> Self {
+        let mut this = Self::new(peripheral, peripheral_clock);
+        this.set_interrupt_handler(asynch::interrupt_handler);
+        this
+    }
+
+    /// Sets the interrupt handler of the peripheral and enables it
+    ///
+    /// Interrupts are not enabled at the peripheral level here, see e.g.
+    /// [`CapturePin::listen`](capture::CapturePin::listen).
+    pub fn set_interrupt_handler(&mut self, handler: InterruptHandler) {
+        unsafe {
+            crate::interrupt::bind_interrupt(PWM::interrupt(), handler.handler());
+            crate::interrupt::enable(PWM::interrupt(), handler.priority()).unwrap();
         }
     }
 }
@@ -163,6 +243,7 @@ impl<'d, PWM: PwmPeripheral> McPwm<'d, PWM> {
 #[derive(Copy, Clone)]
 pub struct PeripheralClockConfig<'a> {
     frequency: HertzU32,
+    capture_frequency: HertzU32,
     prescaler: u8,
     phantom: PhantomData<&'a Clocks<'a>>,
 }
@@ -185,8 +266,16 @@ impl<'a> PeripheralClockConfig<'a> {
         #[cfg(esp32h2)]
         let source_clock = clocks.xtal_clock;
 
+        let frequency = source_clock / (prescaler as u32 + 1);
+
+        #[cfg(any(esp32, esp32s3))]
+        let capture_frequency = clocks.apb_clock;
+        #[cfg(any(esp32c6, esp32h2))]
+        let capture_frequency = frequency;
+
         Self {
-            frequency: source_clock / (prescaler as u32 + 1),
+            frequency,
+            capture_frequency,
             prescaler,
             phantom: PhantomData,
         }
@@ -239,6 +328,56 @@ impl<'a> PeripheralClockConfig<'a> {
         self.frequency
     }
 
+    /// Convert a duration to peripheral clock ticks.
+    ///
+    /// Use
+    /// [`LinkedPins::set_rising_edge_deadtime_duration`](operator::LinkedPins::set_rising_edge_deadtime_duration)
+    /// for dead times, which are rounded up instead.
+    ///
+    /// ### Note:
+    /// The result is rounded down. If it doesn't fit into a `u16`
+    /// [`DurationError`] will be returned.
+    pub fn duration_to_ticks(&self, duration: NanosDurationU32) -> Result<u16, DurationError> {
+        duration_to_ticks(self.frequency, duration, false)
+    }
+
+    /// Get a carrier configuration with the given prescaler.
+    ///
+    /// `carrier_frequency = peripheral_clock / (prescaler + 1) / 8`
+    ///
+    /// The prescaler must be less than `16`. The carrier is high for `duty`
+    /// eighths of its period, `duty` must be less than `8`. The first pulse is
+    /// `first_pulse_width` carrier periods wide, in the range `1..=16`.
+    ///
+    /// ### Note:
+    /// If any of the values is out of range [`CarrierError`] will be returned.
+    pub fn carrier_with_prescaler(
+        &self,
+        prescaler: u8,
+        duty: u8,
+        first_pulse_width: u8,
+    ) -> Result<operator::CarrierConfig, CarrierError> {
+        operator::CarrierConfig::with_prescaler(self, prescaler, duty, first_pulse_width)
+    }
+
+    /// Get a carrier configuration with the given frequency.
+    ///
+    /// ### Note:
+    /// This will try to select an appropriate prescaler for the carrier.
+    /// If the calculated prescaler is not in the range `0..16`
+    /// [`CarrierError::Frequency`] will be returned.
+    ///
+    /// See [`PeripheralClockConfig::carrier_with_prescaler`] for how the
+    /// frequency is calculated and the valid ranges of the other values.
+    pub fn carrier_with_frequency(
+        &self,
+        target_freq: HertzU32,
+        duty: u8,
+        first_pulse_width: u8,
+    ) -> Result<operator::CarrierConfig, CarrierError> {
+        operator::CarrierConfig::with_frequency(self, target_freq, duty, first_pulse_width)
+    }
+
     /// Get a timer clock configuration with the given prescaler.
     ///
     /// The resulting timer frequency depends on the chosen
@@ -282,6 +421,41 @@ impl<'a> PeripheralClockConfig<'a> {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FrequencyError;
 
+/// Carrier configuration could not be created.
+/// Check the valid ranges in the corresponding method docs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CarrierError {
+    /// The target frequency can't be reached with a valid prescaler
+    Frequency,
+    /// The prescaler is out of range
+    Prescaler,
+    /// The duty is out of range
+    Duty,
+    /// The width of the first pulse is out of range
+    FirstPulseWidth,
+}
+
+/// Duration could not be converted to ticks.
+/// Check how the duration is converted in the corresponding method docs.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DurationError;
+
+fn duration_to_ticks(
+    frequency: HertzU32,
+    duration: NanosDurationU32,
+    round_up: bool,
+) -> Result<u16, DurationError> {
+    let cycles = duration.ticks() as u64 * frequency.raw() as u64;
+    let ticks = if round_up {
+        cycles.div_ceil(1_000_000_000)
+    } else {
+        cycles / 1_000_000_000
+    };
+    u16::try_from(ticks).map_err(|_| DurationError)
+}
+
 /// A MCPWM peripheral
 pub trait PwmPeripheral: Deref<Target = RegisterBlock> + crate::private::Sealed {
     /// Enable peripheral
@@ -290,6 +464,16 @@ pub trait PwmPeripheral: Deref<Target = RegisterBlock> + crate::private::Sealed
     fn block() -> *const RegisterBlock;
     /// Get operator GPIO mux output signal
     fn output_signal<const OP: u8, const IS_A: bool>() -> OutputSignal;
+    /// Get capture channel GPIO mux input signal
+    fn capture_input_signal<const CAP: u8>() -> InputSignal;
+    /// Get fault detector GPIO mux input signal
+    fn fault_input_signal<const F: u8>() -> InputSignal;
+    /// Get sync input GPIO mux input signal
+    fn sync_input_signal<const S: u8>() -> InputSignal;
+    /// Get the interrupt of the peripheral
+    fn interrupt() -> Interrupt;
+    /// Get the index of the peripheral
+    fn index() -> usize;
 }
 
 #[cfg(mcpwm0)]
@@ -313,6 +497,46 @@ impl PwmPeripheral for crate::peripherals::MCPWM0 {
             _ => unreachable!(),
         }
     }
+
+    fn capture_input_signal<const CAP: u8>() -> InputSignal {
+        match CAP {
+            0 => InputSignal::PWM0_CAP0,
+            1 => InputSignal::PWM0_CAP1,
+            2 => InputSignal::PWM0_CAP2,
+            _ => unreachable!(),
+        }
+    }
+
+    fn fault_input_signal<const F: u8>() -> InputSignal {
+        match F {
+            0 => InputSignal::PWM0_F0,
+            1 => InputSignal::PWM0_F1,
+            2 => InputSignal::PWM0_F2,
+            _ => unreachable!(),
+        }
+    }
+
+    fn sync_input_signal<const S: u8>() -> InputSignal {
+        match S {
+            0 => InputSignal::PWM0_SYNC0,
+            1 => InputSignal::PWM0_SYNC1,
+            2 => InputSignal::PWM0_SYNC2,
+            _ => unreachable!(),
+        }
+    }
+
+    fn interrupt() -> Interrupt {
+        #[cfg(any(esp32, esp32s3))]
+        let interrupt = Interrupt::PWM0;
+        #[cfg(any(esp32c6, esp32h2))]
+        let interrupt = Interrupt::MCPWM0;
+
+        interrupt
+    }
+
+    fn index() -> usize {
+        0
+    }
 }
 
 #[cfg(mcpwm1)]
@@ -336,4 +560,199 @@ impl PwmPeripheral for crate::peripherals::MCPWM1 {
             _ => unreachable!(),
         }
     }
+
+    fn capture_input_signal<const CAP: u8>() -> InputSignal {
+        match CAP {
+            0 => InputSignal::PWM1_CAP0,
+            1 => InputSignal::PWM1_CAP1,
+            2 => InputSignal::PWM1_CAP2,
+            _ => unreachable!(),
+        }
+    }
+
+    fn fault_input_signal<const F: u8>() -> InputSignal {
+        match F {
+            0 => InputSignal::PWM1_F0,
+            1 => InputSignal::PWM1_F1,
+            2 => InputSignal::PWM1_F2,
+            _ => unreachable!(),
+        }
+    }
+
+    fn sync_input_signal<const S: u8>() -> InputSignal {
+        match S {
+            0 => InputSignal::PWM1_SYNC0,
+            1 => InputSignal::PWM1_SYNC1,
+            2 => InputSignal::PWM1_SYNC2,
+            _ => unreachable!(),
+        }
+    }
+
+    fn interrupt() -> Interrupt {
+        #[cfg(any(esp32, esp32s3))]
+        let interrupt = Interrupt::PWM1;
+        #[cfg(any(esp32c6, esp32h2))]
+        let interrupt = Interrupt::MCPWM1;
+
+        interrupt
+    }
+
+    fn index() -> usize {
+        1
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) mod asynch {
+    use core::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use embassy_sync::waitqueue::AtomicWaker;
+    use procmacros::handler;
+
+    use super::*;
+
+    const INIT: AtomicWaker = AtomicWaker::new();
+    const INIT_PER_UNIT: [AtomicWaker; 3] = [INIT; 3];
+    const INIT_PER_EVENT: [AtomicWaker; 2] = [INIT; 2];
+    const INIT_PER_UNIT_AND_EVENT: [[AtomicWaker; 2]; 3] = [INIT_PER_EVENT; 3];
+
+    /// Wakers of the capture channels, per peripheral and channel
+    pub(super) static CAPTURE_WAKERS: [[AtomicWaker; 3]; 2] = [INIT_PER_UNIT; 2];
+    /// Wakers of the fault detectors, per peripheral, detector and
+    /// [`FaultEvent`](fault::FaultEvent)
+    pub(super) static FAULT_WAKERS: [[[AtomicWaker; 2]; 3]; 2] = [INIT_PER_UNIT_AND_EVENT; 2];
+    /// Wakers of the fault handlers, per peripheral, operator and
+    /// [`FaultTrip`](fault::FaultTrip)
+    pub(super) static TRIP_WAKERS: [[[AtomicWaker; 2]; 3]; 2] = [INIT_PER_UNIT_AND_EVENT; 2];
+
+    /// Completes once the interrupt it waits for is no longer enabled, which
+    /// the interrupt handler does when the interrupt fired
+    pub(super) struct InterruptFuture<F> {
+        waker: &'static AtomicWaker,
+        is_listening: F,
+    }
+
+    impl<F: Fn() -> bool> InterruptFuture<F> {
+        pub fn new(waker: &'static AtomicWaker, is_listening: F) -> Self {
+            Self {
+                waker,
+                is_listening,
+            }
+        }
+    }
+
+    impl<F: Fn() -> bool + Unpin> core::future::Future for InterruptFuture<F> {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.waker.register(ctx.waker());
+
+            if (self.is_listening)() {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        }
+    }
+
+    fn handle_interrupt<PWM: PwmPeripheral>() {
+        let block = unsafe { &*PWM::block() };
+        let st = block.int_st().read();
+        let capture = [
+            st.cap0_int_st().bit_is_set(),
+            st.cap1_int_st().bit_is_set(),
+            st.cap2_int_st().bit_is_set(),
+        ];
+        let fault = [
+            [
+                st.fault0_int_st().bit_is_set(),
+                st.fault0_clr_int_st().bit_is_set(),
+            ],
+            [
+                st.fault1_int_st().bit_is_set(),
+                st.fault1_clr_int_st().bit_is_set(),
+            ],
+            [
+                st.fault2_int_st().bit_is_set(),
+                st.fault2_clr_int_st().bit_is_set(),
+            ],
+        ];
+        let trip = [
+            [
+                st.tz0_ost_int_st().bit_is_set(),
+                st.tz0_cbc_int_st().bit_is_set(),
+            ],
+            [
+                st.tz1_ost_int_st().bit_is_set(),
+                st.tz1_cbc_int_st().bit_is_set(),
+            ],
+            [
+                st.tz2_ost_int_st().bit_is_set(),
+                st.tz2_cbc_int_st().bit_is_set(),
+            ],
+        ];
+
+        // Disable what fired, the interrupts stay pending until the waiting
+        // driver clears them
+        block.int_ena().modify(|r, w| {
+            w.cap0_int_ena()
+                .bit(r.cap0_int_ena().bit_is_set() && !capture[0]);
+            w.cap1_int_ena()
+                .bit(r.cap1_int_ena().bit_is_set() && !capture[1]);
+            w.cap2_int_ena()
+                .bit(r.cap2_int_ena().bit_is_set() && !capture[2]);
+            w.fault0_int_ena()
+                .bit(r.fault0_int_ena().bit_is_set() && !fault[0][0]);
+            w.fault1_int_ena()
+                .bit(r.fault1_int_ena().bit_is_set() && !fault[1][0]);
+            w.fault2_int_ena()
+                .bit(r.fault2_int_ena().bit_is_set() && !fault[2][0]);
+            w.fault0_clr_int_ena()
+                .bit(r.fault0_clr_int_ena().bit_is_set() && !fault[0][1]);
+            w.fault1_clr_int_ena()
+                .bit(r.fault1_clr_int_ena().bit_is_set() && !fault[1][1]);
+            w.fault2_clr_int_ena()
+                .bit(r.fault2_clr_int_ena().bit_is_set() && !fault[2][1]);
+            w.tz0_ost_int_ena()
+                .bit(r.tz0_ost_int_ena().bit_is_set() && !trip[0][0]);
+            w.tz1_ost_int_ena()
+                .bit(r.tz1_ost_int_ena().bit_is_set() && !trip[1][0]);
+            w.tz2_ost_int_ena()
+                .bit(r.tz2_ost_int_ena().bit_is_set() && !trip[2][0]);
+            w.tz0_cbc_int_ena()
+                .bit(r.tz0_cbc_int_ena().bit_is_set() && !trip[0][1]);
+            w.tz1_cbc_int_ena()
+                .bit(r.tz1_cbc_int_ena().bit_is_set() && !trip[1][1]);
+            w.tz2_cbc_int_ena()
+                .bit(r.tz2_cbc_int_ena().bit_is_set() && !trip[2][1])
+        });
+
+        let index = PWM::index();
+        for unit in 0..3 {
+            if capture[unit] {
+                CAPTURE_WAKERS[index][unit].wake();
+            }
+            for event in 0..2 {
+                if fault[unit][event] {
+                    FAULT_WAKERS[index][unit][event].wake();
+                }
+                if trip[unit][event] {
+                    TRIP_WAKERS[index][unit][event].wake();
+                }
+            }
+        }
+    }
+
+    /// Services the capture, fault and trip interrupts of all MCPWM
+    /// peripherals
+    #[handler]
+    pub(crate) fn interrupt_handler() {
+        #[cfg(mcpwm0)]
+        handle_interrupt::<crate::peripherals::MCPWM0>();
+        #[cfg(mcpwm1)]
+        handle_interrupt::<crate::peripherals::MCPWM1>();
+    }
 }
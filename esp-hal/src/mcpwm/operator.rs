@@ -12,9 +12,17 @@
 
 use core::marker::PhantomData;
 
+use fugit::{HertzU32, NanosDurationU32};
+
 use crate::{
     gpio::OutputPin,
-    mcpwm::{timer::Timer, PwmPeripheral},
+    mcpwm::{
+        timer::{CounterDirection, Timer, TimerClockConfig},
+        CarrierError,
+        DurationError,
+        PeripheralClockConfig,
+        PwmPeripheral,
+    },
     peripheral::{Peripheral, PeripheralRef},
     private,
 };
@@ -158,6 +166,30 @@ impl DeadTimeCfg {
     }
 }
 
+/// The clock of the deadtime generator, used to convert dead time durations to
+/// ticks
+///
+/// It has to match the clock selected with [`DeadTimeCfg::select_clock`].
+#[derive(Copy, Clone)]
+pub enum DeadTimeClock<'c, 'a> {
+    /// PWM_clk, the peripheral clock
+    Peripheral(&'c PeripheralClockConfig<'a>),
+    /// PT_clk, the prescaled clock of the timer the operator is connected to
+    Timer(&'c TimerClockConfig<'a>),
+}
+
+impl<'c, 'a> From<&'c PeripheralClockConfig<'a>> for DeadTimeClock<'c, 'a> {
+    fn from(clock: &'c PeripheralClockConfig<'a>) -> Self {
+        DeadTimeClock::Peripheral(clock)
+    }
+}
+
+impl<'c, 'a> From<&'c TimerClockConfig<'a>> for DeadTimeClock<'c, 'a> {
+    fn from(clock: &'c TimerClockConfig<'a>) -> Self {
+        DeadTimeClock::Timer(clock)
+    }
+}
+
 /// A MCPWM operator
 ///
 /// The PWM Operator submodule has the following functions:
@@ -165,9 +197,10 @@ impl DeadTimeCfg {
 ///   corresponding PWM timer.
 /// * Each signal out of the PWM signal pair includes a specific pattern of dead
 ///   time. (Not yet implemented)
-/// * Superimposes a carrier on the PWM signal, if configured to do so. (Not yet
-///   implemented)
-/// * Handles response under fault conditions. (Not yet implemented)
+/// * Superimposes a carrier on the PWM signal, if configured to do so, see
+///   [`Operator::set_carrier`].
+/// * Handles response under fault conditions, see
+///   [`FaultHandler`](super::fault::FaultHandler).
 pub struct Operator<const OP: u8, PWM> {
     phantom: PhantomData<PWM>,
 }
@@ -204,6 +237,17 @@ impl<const OP: u8, PWM: PwmPeripheral> Operator<OP, PWM> {
         });
     }
 
+    /// Superimpose a carrier on both outputs or disable the carrier with
+    /// `None`
+    pub fn set_carrier(&mut self, config: Option<CarrierConfig>) {
+        set_carrier::<OP, PWM>(config);
+    }
+
+    /// Select the sources of [`PwmEvent::Event0`] and [`PwmEvent::Event1`]
+    pub fn set_event_sources(&mut self, event0: EventSource, event1: EventSource) {
+        set_event_sources::<OP, PWM>(event0, event1);
+    }
+
     /// Use the A output with the given pin and configuration
     pub fn with_pin_a<'d, Pin: OutputPin>(
         self,
@@ -387,6 +431,53 @@ impl<'d, Pin: OutputPin, PWM: PwmPeripheral, const OP: u8, const IS_A: bool>
         }
     }
 
+    /// Force the output to the given level until the force is removed with
+    /// `None`
+    ///
+    /// The force takes effect immediately and overrides the actions of the
+    /// generator.
+    pub fn set_continuous_force(&mut self, level: Option<ForcedLevel>) {
+        // SAFETY:
+        // We only write to our GENx_FORCE fields of our output
+        let ch = unsafe { Self::ch() };
+        let mode = level.map_or(0, |level| level as u8);
+
+        ch.gen_force().modify(|_, w| unsafe {
+            // Apply the force immediately
+            w.cntuforce_upmethod().bits(0);
+            if IS_A {
+                w.a_cntuforce_mode().bits(mode)
+            } else {
+                w.b_cntuforce_mode().bits(mode)
+            }
+        });
+    }
+
+    /// Force the output to the given level once
+    ///
+    /// The force takes effect immediately, the next action of the generator
+    /// changes the output again.
+    pub fn force_once(&mut self, level: ForcedLevel) {
+        // SAFETY:
+        // We only write to our GENx_FORCE fields of our output
+        let ch = unsafe { Self::ch() };
+
+        // A toggle triggers the force
+        ch.gen_force().modify(|r, w| unsafe {
+            if IS_A {
+                w.a_nciforce_mode()
+                    .bits(level as u8)
+                    .a_nciforce()
+                    .bit(!r.a_nciforce().bit())
+            } else {
+                w.b_nciforce_mode()
+                    .bits(level as u8)
+                    .b_nciforce()
+                    .bit(!r.b_nciforce().bit())
+            }
+        });
+    }
+
     /// Get the period of the timer.
     pub fn get_period(&self) -> u16 {
         // SAFETY:
@@ -561,6 +652,35 @@ impl<'d, PinA: OutputPin, PinB: OutputPin, PWM: PwmPeripheral, const OP: u8>
         self.pin_a.set_timestamp(value)
     }
 
+    /// Force output A to the given level until the force is removed with `None`
+    pub fn set_continuous_force_a(&mut self, level: Option<ForcedLevel>) {
+        self.pin_a.set_continuous_force(level)
+    }
+    /// Force output B to the given level until the force is removed with `None`
+    pub fn set_continuous_force_b(&mut self, level: Option<ForcedLevel>) {
+        self.pin_b.set_continuous_force(level)
+    }
+
+    /// Force output A to the given level once
+    pub fn force_once_a(&mut self, level: ForcedLevel) {
+        self.pin_a.force_once(level)
+    }
+    /// Force output B to the given level once
+    pub fn force_once_b(&mut self, level: ForcedLevel) {
+        self.pin_b.force_once(level)
+    }
+
+    /// Superimpose a carrier on both outputs or disable the carrier with
+    /// `None`
+    pub fn set_carrier(&mut self, config: Option<CarrierConfig>) {
+        set_carrier::<OP, PWM>(config);
+    }
+
+    /// Select the sources of [`PwmEvent::Event0`] and [`PwmEvent::Event1`]
+    pub fn set_event_sources(&mut self, event0: EventSource, event1: EventSource) {
+        set_event_sources::<OP, PWM>(event0, event1);
+    }
+
     /// Configure the deadtime generator
     pub fn set_deadtime_cfg(&mut self, config: DeadTimeCfg) {
         #[cfg(esp32s3)]
@@ -587,12 +707,232 @@ impl<'d, PinA: OutputPin, PinB: OutputPin, PWM: PwmPeripheral, const OP: u8>
         dt_fed.write(|w| unsafe { w.fed().bits(dead_time) });
     }
 
+    /// Set the deadtime generator rising edge delay as a duration
+    ///
+    /// `clock` is the [`PeripheralClockConfig`] if the deadtime generator uses
+    /// PWM_clk (the default) or the [`TimerClockConfig`] of the operator's
+    /// timer if PT_clk was selected with [`DeadTimeCfg::select_clock`].
+    ///
+    /// ### Note:
+    /// The delay is rounded up to the next tick. If `clock` doesn't match the
+    /// configured clock or the delay doesn't fit into a `u16`
+    /// [`DurationError`] will be returned.
+    pub fn set_rising_edge_deadtime_duration<'c, 'a>(
+        &mut self,
+        clock: impl Into<DeadTimeClock<'c, 'a>>,
+        dead_time: NanosDurationU32,
+    ) -> Result<(), DurationError> {
+        let dead_time = Self::deadtime_ticks(clock.into(), dead_time)?;
+        self.set_rising_edge_deadtime(dead_time);
+        Ok(())
+    }
+    /// Set the deadtime generator falling edge delay as a duration
+    ///
+    /// See [`LinkedPins::set_rising_edge_deadtime_duration`].
+    pub fn set_falling_edge_deadtime_duration<'c, 'a>(
+        &mut self,
+        clock: impl Into<DeadTimeClock<'c, 'a>>,
+        dead_time: NanosDurationU32,
+    ) -> Result<(), DurationError> {
+        let dead_time = Self::deadtime_ticks(clock.into(), dead_time)?;
+        self.set_falling_edge_deadtime(dead_time);
+        Ok(())
+    }
+
+    fn deadtime_ticks(
+        clock: DeadTimeClock<'_, '_>,
+        dead_time: NanosDurationU32,
+    ) -> Result<u16, DurationError> {
+        #[cfg(esp32s3)]
+        let dt_cfg = unsafe { Self::ch() }.db_cfg();
+        #[cfg(not(esp32s3))]
+        let dt_cfg = unsafe { Self::ch() }.dt_cfg();
+        let pt_clk = dt_cfg.read().bits() & DeadTimeCfg::CLK_SEL != 0;
+
+        let frequency = match clock {
+            DeadTimeClock::Peripheral(clock) if !pt_clk => clock.frequency(),
+            DeadTimeClock::Timer(clock) if pt_clk => clock.tick_frequency(),
+            _ => return Err(DurationError),
+        };
+
+        super::duration_to_ticks(frequency, dead_time, true)
+    }
+
     unsafe fn ch() -> &'static crate::peripherals::mcpwm0::CH {
         let block = unsafe { &*PWM::block() };
         block.ch(OP as usize)
     }
 }
 
+fn set_event_sources<const OP: u8, PWM: PwmPeripheral>(event0: EventSource, event1: EventSource) {
+    // SAFETY:
+    // We only write to our GENx_CFG0 event selection fields
+    let block = unsafe { &*PWM::block() };
+    block.ch(OP as usize).gen_cfg0().modify(|_, w| unsafe {
+        w.t0_sel().bits(event0 as u8);
+        w.t1_sel().bits(event1 as u8)
+    });
+}
+
+/// A timing event of the generators of an operator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PwmEvent {
+    /// The timer is equal to zero (`TEZ`)
+    TimerEqualsZero       = 0,
+    /// The timer is equal to period (`TEP`)
+    TimerEqualsPeriod     = 2,
+    /// The timer is equal to timestamp A (`TEA`)
+    TimerEqualsTimestampA = 4,
+    /// The timer is equal to timestamp B (`TEB`)
+    TimerEqualsTimestampB = 6,
+    /// The event selected as event 0 (`T0`), see
+    /// [`Operator::set_event_sources`]
+    Event0                = 8,
+    /// The event selected as event 1 (`T1`), see
+    /// [`Operator::set_event_sources`]
+    Event1                = 10,
+}
+
+/// A source of [`PwmEvent::Event0`] or [`PwmEvent::Event1`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EventSource {
+    /// Fault 0 became active, see [`fault`](super::fault)
+    Fault0    = 0,
+    /// Fault 1 became active, see [`fault`](super::fault)
+    Fault1    = 1,
+    /// Fault 2 became active, see [`fault`](super::fault)
+    Fault2    = 2,
+    /// The timer of the operator was synced, see
+    /// [`Timer::sync_to`](super::timer::Timer::sync_to)
+    TimerSync = 3,
+    /// No event
+    None      = 4,
+}
+
+/// The level an output is forced to by software
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ForcedLevel {
+    /// Force the output low
+    Low  = 1,
+    /// Force the output high
+    High = 2,
+}
+
+/// Configuration of the carrier superimposed on the outputs of an operator
+///
+/// The carrier chops the high phases of the PWM signal into pulses, e.g. to
+/// drive gate drivers isolated with a pulse transformer. The first pulse of
+/// every high phase can be wider to turn the switch on quickly.
+///
+/// Use [`PeripheralClockConfig::carrier_with_prescaler`] or
+/// [`PeripheralClockConfig::carrier_with_frequency`] to get it.
+#[derive(Copy, Clone)]
+pub struct CarrierConfig {
+    frequency: HertzU32,
+    prescaler: u8,
+    duty: u8,
+    first_pulse_width: u8,
+    invert_output: bool,
+    invert_input: bool,
+}
+
+impl CarrierConfig {
+    pub(super) fn with_prescaler(
+        clock: &PeripheralClockConfig,
+        prescaler: u8,
+        duty: u8,
+        first_pulse_width: u8,
+    ) -> Result<Self, CarrierError> {
+        if prescaler > 15 {
+            return Err(CarrierError::Prescaler);
+        }
+        if duty > 7 {
+            return Err(CarrierError::Duty);
+        }
+        if !(1..=16).contains(&first_pulse_width) {
+            return Err(CarrierError::FirstPulseWidth);
+        }
+
+        Ok(CarrierConfig {
+            frequency: clock.frequency() / (prescaler as u32 + 1) / 8,
+            prescaler,
+            duty,
+            first_pulse_width,
+            invert_output: false,
+            invert_input: false,
+        })
+    }
+
+    pub(super) fn with_frequency(
+        clock: &PeripheralClockConfig,
+        target_freq: HertzU32,
+        duty: u8,
+        first_pulse_width: u8,
+    ) -> Result<Self, CarrierError> {
+        let target_clock = target_freq
+            .raw()
+            .checked_mul(8)
+            .ok_or(CarrierError::Frequency)?;
+        if target_clock == 0 || target_clock > clock.frequency().raw() {
+            return Err(CarrierError::Frequency);
+        }
+
+        let prescaler = clock.frequency().raw() / target_clock - 1;
+        if prescaler > 15 {
+            return Err(CarrierError::Frequency);
+        }
+
+        Self::with_prescaler(clock, prescaler as u8, duty, first_pulse_width)
+    }
+
+    /// Invert the output of the carrier module
+    #[must_use]
+    pub const fn invert_output(mut self, invert: bool) -> Self {
+        self.invert_output = invert;
+        self
+    }
+
+    /// Invert the PWM signal before the carrier is superimposed
+    #[must_use]
+    pub const fn invert_input(mut self, invert: bool) -> Self {
+        self.invert_input = invert;
+        self
+    }
+
+    /// Get the carrier frequency.
+    ///
+    /// ### Note:
+    /// The actual value is rounded down to the nearest `u32` value
+    pub fn frequency(&self) -> HertzU32 {
+        self.frequency
+    }
+
+    fn bits(&self) -> u32 {
+        1 | (self.prescaler as u32) << 1
+            | (self.duty as u32) << 5
+            | (self.first_pulse_width as u32 - 1) << 8
+            | (self.invert_output as u32) << 12
+            | (self.invert_input as u32) << 13
+    }
+}
+
+fn set_carrier<const OP: u8, PWM: PwmPeripheral>(config: Option<CarrierConfig>) {
+    // SAFETY:
+    // We only write to our CARRIERx_CFG register
+    let block = unsafe { &*PWM::block() };
+    let bits = config.map_or(0, |config| config.bits());
+
+    // SAFETY:
+    // `bits` is a valid bit pattern
+    block
+        .ch(OP as usize)
+        .carrier_cfg()
+        .write(|w| unsafe { w.bits(bits) });
+}
+
 /// An action the operator applies to an output
 #[non_exhaustive]
 #[repr(u32)]
@@ -696,6 +1036,40 @@ impl<const IS_A: bool> PwmActions<IS_A> {
         }
     }
 
+    /// Choose an `UpdateAction` for an event while the timer is counting in the
+    /// given direction
+    ///
+    /// E.g. center-aligned complementary PWM on a timer configured with
+    /// [`PwmWorkingMode::UpDown`](super::timer::PwmWorkingMode::UpDown):
+    /// ```no_run
+    /// let actions = PwmActions::empty()
+    ///     .on_event(
+    ///         PwmEvent::TimerEqualsTimestampA,
+    ///         CounterDirection::Increasing,
+    ///         UpdateAction::SetHigh,
+    ///     )
+    ///     .on_event(
+    ///         PwmEvent::TimerEqualsTimestampA,
+    ///         CounterDirection::Decreasing,
+    ///         UpdateAction::SetLow,
+    ///     );
+    /// ```
+    /// Both outputs of [`LinkedPins`] created with
+    /// [`DeadTimeCfg::new_ahc`] then switch complementary around the
+    /// timestamp, separated by the configured dead time.
+    pub const fn on_event(
+        self,
+        event: PwmEvent,
+        direction: CounterDirection,
+        action: UpdateAction,
+    ) -> Self {
+        let offset = match direction {
+            CounterDirection::Increasing => event as u32,
+            CounterDirection::Decreasing => event as u32 + 12,
+        };
+        self.with_value_at_offset(action as u32, offset)
+    }
+
     const fn with_value_at_offset(self, value: u32, offset: u32) -> Self {
         let mask = !(0b11 << offset);
         let value = (self.0 & mask) | (value << offset);
@@ -5,14 +5,46 @@
 //! Modulator)` driver for ESP chips. It provides an interface to configure and
 //! use timers for generating `PWM` signals used in motor control and other
 //! applications.
+//!
+//! Timers can be synchronized to each other or to external sync signals. On a
+//! sync event the counter of a timer is loaded with its phase value, which
+//! allows running several timers with exact phase relationships.
+//!
+//! ## Example
+//! Runs timer1 and timer2 shifted by 120° and 240° relative to timer0, e.g. to
+//! drive a three-phase inverter.
+//!
+//! ```no_run
+//! # use esp_hal::{mcpwm, prelude::*};
+//! use mcpwm::timer::{CounterDirection, PwmWorkingMode, SyncOutput, SyncSource};
+//!
+//! let timer_clock_cfg = clock_cfg
+//!     .timer_clock_with_frequency(299, PwmWorkingMode::Increase, 20.kHz())
+//!     .unwrap();
+//!
+//! mcpwm.timer0.set_sync_output(SyncOutput::TimerEqualsZero);
+//! mcpwm
+//!     .timer1
+//!     .sync_to(SyncSource::Timer0, 200, CounterDirection::Increasing);
+//! mcpwm
+//!     .timer2
+//!     .sync_to(SyncSource::Timer0, 100, CounterDirection::Increasing);
+//!
+//! mcpwm.timer0.start(timer_clock_cfg);
+//! mcpwm.timer1.start(timer_clock_cfg);
+//! mcpwm.timer2.start(timer_clock_cfg);
+//! ```
 
 use core::marker::PhantomData;
 
-use fugit::HertzU32;
+use fugit::{HertzU32, NanosDurationU32};
 
 use crate::{
     clock::Clocks,
-    mcpwm::{FrequencyError, PeripheralClockConfig, PwmPeripheral},
+    gpio::InputPin,
+    mcpwm::{DurationError, FrequencyError, PeripheralClockConfig, PwmPeripheral},
+    peripheral::{Peripheral, PeripheralRef},
+    private,
 };
 
 /// A MCPWM timer
@@ -65,18 +97,84 @@ impl<const TIM: u8, PWM: PwmPeripheral> Timer<TIM, PWM> {
     }
 
     /// Set the timer counter to the provided value
+    ///
+    /// ### Note:
+    /// This is done with a software sync, which replaces the phase set with
+    /// [`Timer::sync_to`] and generates a sync output event.
     pub fn set_counter(&mut self, phase: u16, direction: CounterDirection) {
         // SAFETY:
         // We only write to our TIMERx_SYNC register
         let tmr = unsafe { Self::tmr() };
-        let sw = tmr.sync().read().sw().bit_is_set();
-        tmr.sync().write(|w| {
+        tmr.sync().modify(|r, w| {
             w.phase_direction().bit(direction as u8 != 0);
             unsafe {
                 w.phase().bits(phase);
             }
-            w.sw().bit(!sw)
+            w.sw().bit(!r.sw().bit_is_set())
+        });
+    }
+
+    /// Load the phase into the counter whenever the given sync source
+    /// generates a sync event
+    ///
+    /// `phase` is the counter value right after the sync. E.g. when both
+    /// timers count up with the same period and the source syncs on zero, a
+    /// phase of `(period + 1) * (360 - lag) / 360` lags the source by `lag`
+    /// degrees.
+    pub fn sync_to(&mut self, source: SyncSource, phase: u16, direction: CounterDirection) {
+        // SAFETY:
+        // We only modify our TIMERx_SYNCISEL field
+        let block = unsafe { &*PWM::block() };
+        block.timer_synci_cfg().modify(|_, w| unsafe {
+            match TIM {
+                0 => w.timer0_syncisel().bits(source as u8),
+                1 => w.timer1_syncisel().bits(source as u8),
+                2 => w.timer2_syncisel().bits(source as u8),
+                _ => unreachable!(),
+            }
         });
+
+        // SAFETY:
+        // We only write to our TIMERx_SYNC register
+        let tmr = unsafe { Self::tmr() };
+        tmr.sync().modify(|_, w| {
+            w.phase_direction().bit(direction as u8 != 0);
+            unsafe {
+                w.phase().bits(phase);
+            }
+            w.synci_en().set_bit()
+        });
+    }
+
+    /// Stop reacting to sync events
+    pub fn disable_sync(&mut self) {
+        // SAFETY:
+        // We only write to our TIMERx_SYNC register
+        let tmr = unsafe { Self::tmr() };
+        tmr.sync().modify(|_, w| w.synci_en().clear_bit());
+    }
+
+    /// Trigger a sync in software
+    ///
+    /// The counter is loaded with the phase and a sync output event is
+    /// generated.
+    pub fn software_sync(&mut self) {
+        // SAFETY:
+        // We only write to our TIMERx_SYNC register
+        let tmr = unsafe { Self::tmr() };
+        tmr.sync().modify(|r, w| w.sw().bit(!r.sw().bit_is_set()));
+    }
+
+    /// Select which events of this timer generate its sync output
+    ///
+    /// Other timers use the sync output with [`SyncSource::Timer0`],
+    /// [`SyncSource::Timer1`] or [`SyncSource::Timer2`].
+    pub fn set_sync_output(&mut self, output: SyncOutput) {
+        // SAFETY:
+        // We only write to our TIMERx_SYNC register
+        let tmr = unsafe { Self::tmr() };
+        tmr.sync()
+            .modify(|_, w| unsafe { w.synco_sel().bits(output as u8) });
     }
 
     /// Read the counter value and counter direction of the timer
@@ -112,6 +210,7 @@ impl<const TIM: u8, PWM: PwmPeripheral> Timer<TIM, PWM> {
 #[derive(Copy, Clone)]
 pub struct TimerClockConfig<'a> {
     frequency: HertzU32,
+    tick_frequency: HertzU32,
     period: u16,
     prescaler: u8,
     mode: PwmWorkingMode,
@@ -130,10 +229,12 @@ impl<'a> TimerClockConfig<'a> {
             // The reference manual seems to provide an incorrect formula for UpDown
             PwmWorkingMode::UpDown => period as u32 * 2,
         };
-        let frequency = clock.frequency / (prescaler as u32 + 1) / cycle_period;
+        let tick_frequency = clock.frequency / (prescaler as u32 + 1);
+        let frequency = tick_frequency / cycle_period;
 
         TimerClockConfig {
             frequency,
+            tick_frequency,
             prescaler,
             period,
             mode,
@@ -163,10 +264,12 @@ impl<'a> TimerClockConfig<'a> {
         if prescaler > u8::MAX as u32 {
             return Err(FrequencyError);
         }
-        let frequency = clock.frequency / (prescaler + 1) / cycle_period;
+        let tick_frequency = clock.frequency / (prescaler + 1);
+        let frequency = tick_frequency / cycle_period;
 
         Ok(TimerClockConfig {
             frequency,
+            tick_frequency,
             prescaler: prescaler as u8,
             period,
             mode,
@@ -181,6 +284,24 @@ impl<'a> TimerClockConfig<'a> {
     pub fn frequency(&self) -> HertzU32 {
         self.frequency
     }
+
+    /// Convert a duration to timer ticks, e.g. to get the timestamp for a
+    /// pulse width.
+    ///
+    /// With [`PwmActions::UP_DOWN_ACTIVE_HIGH`](super::operator::PwmActions::UP_DOWN_ACTIVE_HIGH)
+    /// the output is high for twice the timestamp.
+    ///
+    /// ### Note:
+    /// The result is rounded down. If it doesn't fit into a `u16`
+    /// [`DurationError`] will be returned.
+    pub fn duration_to_ticks(&self, duration: NanosDurationU32) -> Result<u16, DurationError> {
+        super::duration_to_ticks(self.tick_frequency, duration, false)
+    }
+
+    /// The frequency at which the timer counts, also known as PT_clk
+    pub(super) fn tick_frequency(&self) -> HertzU32 {
+        self.tick_frequency
+    }
 }
 
 /// PWM working mode
@@ -204,6 +325,94 @@ pub enum PwmWorkingMode {
     UpDown   = 3,
 }
 
+/// A source of sync events for a [`Timer`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SyncSource {
+    /// No sync events
+    None     = 0,
+    /// The sync output of timer0, see [`Timer::set_sync_output`]
+    Timer0   = 1,
+    /// The sync output of timer1, see [`Timer::set_sync_output`]
+    Timer1   = 2,
+    /// The sync output of timer2, see [`Timer::set_sync_output`]
+    Timer2   = 3,
+    /// The GPIO sync input 0, see [`SyncInput`]
+    SyncPin0 = 4,
+    /// The GPIO sync input 1, see [`SyncInput`]
+    SyncPin1 = 5,
+    /// The GPIO sync input 2, see [`SyncInput`]
+    SyncPin2 = 6,
+}
+
+/// The events generating the sync output of a [`Timer`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SyncOutput {
+    /// Forward the sync events of the timer's sync source
+    SyncInput         = 0,
+    /// The timer is equal to zero
+    TimerEqualsZero   = 1,
+    /// The timer is equal to period
+    TimerEqualsPeriod = 2,
+    /// Only software syncs, see [`Timer::software_sync`]
+    Software          = 3,
+}
+
+/// A GPIO sync input of the MCPWM peripheral
+pub struct SyncInput<const S: u8, PWM> {
+    phantom: PhantomData<PWM>,
+}
+
+impl<const S: u8, PWM: PwmPeripheral> SyncInput<S, PWM> {
+    pub(super) fn new() -> Self {
+        SyncInput {
+            phantom: PhantomData,
+        }
+    }
+
+    /// Receive sync events on the rising edges of the given pin
+    ///
+    /// With `invert` the falling edges generate the sync events.
+    pub fn with_pin<'d, Pin: InputPin>(
+        self,
+        pin: impl Peripheral<P = Pin> + 'd,
+        invert: bool,
+    ) -> SyncPin<'d, Pin, PWM, S> {
+        SyncPin::new(pin, invert)
+    }
+}
+
+/// A pin used as GPIO sync input of the MCPWM peripheral
+pub struct SyncPin<'d, Pin, PWM, const S: u8> {
+    _pin: PeripheralRef<'d, Pin>,
+    phantom: PhantomData<PWM>,
+}
+
+impl<'d, Pin: InputPin, PWM: PwmPeripheral, const S: u8> SyncPin<'d, Pin, PWM, S> {
+    fn new(pin: impl Peripheral<P = Pin> + 'd, invert: bool) -> Self {
+        crate::into_ref!(pin);
+
+        pin.set_to_input(private::Internal);
+        pin.connect_input_to_peripheral(PWM::sync_input_signal::<S>(), private::Internal);
+
+        // SAFETY:
+        // We only modify our EXTERNAL_SYNCIx_INVERT field
+        let block = unsafe { &*PWM::block() };
+        block.timer_synci_cfg().modify(|_, w| match S {
+            0 => w.external_synci0_invert().bit(invert),
+            1 => w.external_synci1_invert().bit(invert),
+            2 => w.external_synci2_invert().bit(invert),
+            _ => unreachable!(),
+        });
+
+        SyncPin {
+            _pin: pin,
+            phantom: PhantomData,
+        }
+    }
+}
+
 /// The direction the timer counter is changing
 #[derive(Debug)]
 #[repr(u8)]
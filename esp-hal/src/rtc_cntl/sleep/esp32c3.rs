@@ -1,4 +1,11 @@
-use super::{TimerWakeupSource, WakeSource, WakeTriggers, WakeupLevel};
+use super::{
+    TimerWakeupSource,
+    Uart0WakeupSource,
+    Uart1WakeupSource,
+    WakeSource,
+    WakeTriggers,
+    WakeupLevel,
+};
 use crate::{
     gpio::{RtcFunction, RtcPinWithResistors},
     regi2c_write_mask,
@@ -115,6 +122,24 @@ impl WakeSource for TimerWakeupSource {
     }
 }
 
+impl WakeSource for Uart0WakeupSource {
+    fn apply(&self, _rtc: &Rtc, triggers: &mut WakeTriggers, _sleep_config: &mut RtcSleepConfig) {
+        triggers.set_uart0(true);
+        let uart = unsafe { &*crate::peripherals::UART0::PTR };
+        uart.sleep_conf()
+            .modify(|_, w| unsafe { w.active_threshold().bits(self.active_threshold()) });
+    }
+}
+
+impl WakeSource for Uart1WakeupSource {
+    fn apply(&self, _rtc: &Rtc, triggers: &mut WakeTriggers, _sleep_config: &mut RtcSleepConfig) {
+        triggers.set_uart1(true);
+        let uart = unsafe { &*crate::peripherals::UART1::PTR };
+        uart.sleep_conf()
+            .modify(|_, w| unsafe { w.active_threshold().bits(self.active_threshold()) });
+    }
+}
+
 impl<'a, 'b> RtcioWakeupSource<'a, 'b> {
     fn apply_pin(&self, pin: &mut dyn RtcPinWithResistors, level: WakeupLevel) {
         // The pullup/pulldown part is like in gpio_deep_sleep_wakeup_prepare
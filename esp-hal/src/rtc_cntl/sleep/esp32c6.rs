@@ -17,7 +17,15 @@ use crate::{
             RtcCalSel,
             SavedClockConfig,
         },
-        sleep::{Ext1WakeupSource, TimerWakeupSource, WakeSource, WakeTriggers, WakeupLevel},
+        sleep::{
+            Ext1WakeupSource,
+            TimerWakeupSource,
+            Uart0WakeupSource,
+            Uart1WakeupSource,
+            WakeSource,
+            WakeTriggers,
+            WakeupLevel,
+        },
         Rtc,
         RtcClock,
     },
@@ -55,6 +63,32 @@ impl WakeSource for TimerWakeupSource {
     }
 }
 
+impl WakeSource for Uart0WakeupSource {
+    fn apply(&self, _rtc: &Rtc, triggers: &mut WakeTriggers, _sleep_config: &mut RtcSleepConfig) {
+        triggers.set_uart0(true);
+        let uart = unsafe { &*crate::peripherals::UART0::PTR };
+        uart.sleep_conf2().modify(|_, w| unsafe {
+            w.wk_mode_sel()
+                .bits(0)
+                .active_threshold()
+                .bits(self.active_threshold())
+        });
+    }
+}
+
+impl WakeSource for Uart1WakeupSource {
+    fn apply(&self, _rtc: &Rtc, triggers: &mut WakeTriggers, _sleep_config: &mut RtcSleepConfig) {
+        triggers.set_uart1(true);
+        let uart = unsafe { &*crate::peripherals::UART1::PTR };
+        uart.sleep_conf2().modify(|_, w| unsafe {
+            w.wk_mode_sel()
+                .bits(0)
+                .active_threshold()
+                .bits(self.active_threshold())
+        });
+    }
+}
+
 impl Ext1WakeupSource<'_, '_> {
     /// Returns the currently configured wakeup pins.
     fn wakeup_pins() -> u8 {
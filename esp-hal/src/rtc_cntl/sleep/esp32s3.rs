@@ -2,6 +2,8 @@ use super::{
     Ext0WakeupSource,
     Ext1WakeupSource,
     TimerWakeupSource,
+    Uart0WakeupSource,
+    Uart1WakeupSource,
     WakeSource,
     WakeTriggers,
     WakeupLevel,
@@ -110,6 +112,24 @@ impl WakeSource for TimerWakeupSource {
     }
 }
 
+impl WakeSource for Uart0WakeupSource {
+    fn apply(&self, _rtc: &Rtc, triggers: &mut WakeTriggers, _sleep_config: &mut RtcSleepConfig) {
+        triggers.set_uart0(true);
+        let uart = unsafe { &*crate::peripherals::UART0::PTR };
+        uart.sleep_conf()
+            .modify(|_, w| unsafe { w.active_threshold().bits(self.active_threshold()) });
+    }
+}
+
+impl WakeSource for Uart1WakeupSource {
+    fn apply(&self, _rtc: &Rtc, triggers: &mut WakeTriggers, _sleep_config: &mut RtcSleepConfig) {
+        triggers.set_uart1(true);
+        let uart = unsafe { &*crate::peripherals::UART1::PTR };
+        uart.sleep_conf()
+            .modify(|_, w| unsafe { w.active_threshold().bits(self.active_threshold()) });
+    }
+}
+
 impl<P: RtcPin> WakeSource for Ext0WakeupSource<'_, P> {
     fn apply(&self, _rtc: &Rtc, triggers: &mut WakeTriggers, sleep_config: &mut RtcSleepConfig) {
         // don't power down RTC peripherals
@@ -195,3 +195,78 @@ bitfield::bitfield! {
 pub trait WakeSource {
     fn apply(&self, rtc: &Rtc, triggers: &mut WakeTriggers, sleep_config: &mut RtcSleepConfig);
 }
+
+/// The number of RXD edges the UART always waits for in addition to the
+/// configured `active_threshold`
+#[cfg(any(esp32, esp32c3, esp32s3, esp32c6))]
+const UART_MIN_WAKEUP_THRESHOLD: u16 = 3;
+
+/// UART0 wakeup source
+///
+/// The chip wakes up from light sleep once the number of rising edges on RXD
+/// reaches the given threshold. The characters received until then are lost,
+/// so the remote device typically sends a few wakeup characters before sending
+/// the actual data.
+///
+/// This wakeup source can be used to wake up from light sleep only.
+#[derive(Debug, Clone, Copy)]
+#[cfg(any(esp32, esp32c3, esp32s3, esp32c6))]
+pub struct Uart0WakeupSource {
+    threshold: u16,
+}
+
+#[cfg(any(esp32, esp32c3, esp32s3, esp32c6))]
+impl Uart0WakeupSource {
+    /// Create a new UART0 wakeup source
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is smaller than 3 or larger than 1023.
+    pub fn new(threshold: u16) -> Self {
+        assert!(
+            (UART_MIN_WAKEUP_THRESHOLD..=0x3ff).contains(&threshold),
+            "Invalid threshold"
+        );
+        Self { threshold }
+    }
+
+    /// The value of the `active_threshold` field
+    fn active_threshold(&self) -> u16 {
+        self.threshold - UART_MIN_WAKEUP_THRESHOLD
+    }
+}
+
+/// UART1 wakeup source
+///
+/// The chip wakes up from light sleep once the number of rising edges on RXD
+/// reaches the given threshold. The characters received until then are lost,
+/// so the remote device typically sends a few wakeup characters before sending
+/// the actual data.
+///
+/// This wakeup source can be used to wake up from light sleep only.
+#[derive(Debug, Clone, Copy)]
+#[cfg(any(esp32, esp32c3, esp32s3, esp32c6))]
+pub struct Uart1WakeupSource {
+    threshold: u16,
+}
+
+#[cfg(any(esp32, esp32c3, esp32s3, esp32c6))]
+impl Uart1WakeupSource {
+    /// Create a new UART1 wakeup source
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is smaller than 3 or larger than 1023.
+    pub fn new(threshold: u16) -> Self {
+        assert!(
+            (UART_MIN_WAKEUP_THRESHOLD..=0x3ff).contains(&threshold),
+            "Invalid threshold"
+        );
+        Self { threshold }
+    }
+
+    /// The value of the `active_threshold` field
+    fn active_threshold(&self) -> u16 {
+        self.threshold - UART_MIN_WAKEUP_THRESHOLD
+    }
+}
@@ -324,12 +324,30 @@ pub enum InputSignal {
     SUBSPID           = 128,
     SUBSPIHD          = 129,
     SUBSPIWP          = 130,
+    I2S0I_DATA_IN0    = 143,
+    I2S0I_DATA_IN1    = 144,
+    I2S0I_DATA_IN2    = 145,
+    I2S0I_DATA_IN3    = 146,
+    I2S0I_DATA_IN4    = 147,
+    I2S0I_DATA_IN5    = 148,
+    I2S0I_DATA_IN6    = 149,
+    I2S0I_DATA_IN7    = 150,
+    I2S0I_DATA_IN8    = 151,
+    I2S0I_DATA_IN9    = 152,
+    I2S0I_DATA_IN10   = 153,
+    I2S0I_DATA_IN11   = 154,
+    I2S0I_DATA_IN12   = 155,
+    I2S0I_DATA_IN13   = 156,
+    I2S0I_DATA_IN14   = 157,
     I2S0I_DATA_IN15   = 158,
     SUBSPID4          = 167,
     SUBSPID5          = 168,
     SUBSPID6          = 169,
     SUBSPID7          = 170,
     SUBSPIDQS         = 171,
+    I2S0I_H_SYNC      = 190,
+    I2S0I_V_SYNC      = 191,
+    I2S0I_H_ENABLE    = 192,
     PCMFSYNC          = 203,
     PCMCLK            = 204,
 }
@@ -425,6 +443,29 @@ pub enum OutputSignal {
     FSPICD           = 137,
     SPI3_CD          = 139,
     SPI3_DQS         = 140,
+    I2S0O_DATA_OUT0  = 143,
+    I2S0O_DATA_OUT1  = 144,
+    I2S0O_DATA_OUT2  = 145,
+    I2S0O_DATA_OUT3  = 146,
+    I2S0O_DATA_OUT4  = 147,
+    I2S0O_DATA_OUT5  = 148,
+    I2S0O_DATA_OUT6  = 149,
+    I2S0O_DATA_OUT7  = 150,
+    I2S0O_DATA_OUT8  = 151,
+    I2S0O_DATA_OUT9  = 152,
+    I2S0O_DATA_OUT10 = 153,
+    I2S0O_DATA_OUT11 = 154,
+    I2S0O_DATA_OUT12 = 155,
+    I2S0O_DATA_OUT13 = 156,
+    I2S0O_DATA_OUT14 = 157,
+    I2S0O_DATA_OUT15 = 158,
+    I2S0O_DATA_OUT16 = 159,
+    I2S0O_DATA_OUT17 = 160,
+    I2S0O_DATA_OUT18 = 161,
+    I2S0O_DATA_OUT19 = 162,
+    I2S0O_DATA_OUT20 = 163,
+    I2S0O_DATA_OUT21 = 164,
+    I2S0O_DATA_OUT22 = 165,
     I2S0O_DATA_OUT23 = 166,
     SUBSPID4         = 167,
     SUBSPID5         = 168,
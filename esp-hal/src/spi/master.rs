@@ -93,7 +93,12 @@ pub mod prelude {
 #[cfg(not(any(esp32, esp32s2)))]
 #[derive(EnumSetType)]
 pub enum SpiInterrupt {
+    /// A transaction has finished. When running queued transactions this is
+    /// asserted after each one of them.
     TransDone,
+    /// All transactions queued with [dma::SpiDma::queue_transfer] have
+    /// finished.
+    DmaSegmentedTransferDone,
 }
 
 /// The size of the FIFO buffer for SPI
@@ -362,6 +367,149 @@ impl Address {
     }
 }
 
+/// Magic value marking a valid configuration buffer of a segmented transfer
+#[cfg(not(any(esp32, esp32s2)))]
+const SEG_CONF_MAGIC: u32 = 0xA;
+
+/// Registers loaded from the configuration buffer of a segmented transfer:
+/// ADDR, CTRL, USER, USER1, USER2, MS_DLEN and MISC
+#[cfg(not(any(esp32, esp32s2)))]
+const SEG_CONF_BITMAP: u32 = 0b1111_1011;
+
+/// Length of the configuration buffer in words, the bitmap followed by one
+/// word per register
+#[cfg(not(any(esp32, esp32s2)))]
+const SEG_CONF_LEN: usize = 8;
+
+#[cfg(not(any(esp32, esp32s2)))]
+enum TransactionData<'a> {
+    None,
+    Write(&'a [u8]),
+    Read(&'a mut [u8]),
+}
+
+/// A half-duplex transaction to be queued with
+/// [dma::SpiDma::queue_transfer].
+///
+/// Each transaction has its own command, address, dummy and data phases. The
+/// hardware loads the configuration of every transaction from memory, so a
+/// whole batch runs without CPU intervention.
+#[cfg(not(any(esp32, esp32s2)))]
+pub struct QueuedTransaction<'a> {
+    data_mode: SpiDataMode,
+    cmd: Command,
+    address: Address,
+    dummy: u8,
+    data: TransactionData<'a>,
+    conf: [u32; SEG_CONF_LEN],
+}
+
+#[cfg(not(any(esp32, esp32s2)))]
+impl<'a> QueuedTransaction<'a> {
+    /// A transaction writing `buffer` after the command, address and dummy
+    /// phases.
+    pub fn write(
+        data_mode: SpiDataMode,
+        cmd: Command,
+        address: Address,
+        dummy: u8,
+        buffer: &'a [u8],
+    ) -> Self {
+        Self::new(
+            data_mode,
+            cmd,
+            address,
+            dummy,
+            TransactionData::Write(buffer),
+        )
+    }
+
+    /// A transaction reading into `buffer` after the command, address and
+    /// dummy phases.
+    pub fn read(
+        data_mode: SpiDataMode,
+        cmd: Command,
+        address: Address,
+        dummy: u8,
+        buffer: &'a mut [u8],
+    ) -> Self {
+        Self::new(
+            data_mode,
+            cmd,
+            address,
+            dummy,
+            TransactionData::Read(buffer),
+        )
+    }
+
+    /// A transaction without a data phase.
+    pub fn command(cmd: Command, address: Address, dummy: u8) -> Self {
+        Self::new(
+            SpiDataMode::Single,
+            cmd,
+            address,
+            dummy,
+            TransactionData::None,
+        )
+    }
+
+    fn new(
+        data_mode: SpiDataMode,
+        cmd: Command,
+        address: Address,
+        dummy: u8,
+        data: TransactionData<'a>,
+    ) -> Self {
+        Self {
+            data_mode,
+            cmd,
+            address,
+            dummy,
+            data,
+            conf: [0; SEG_CONF_LEN],
+        }
+    }
+
+    fn is_write(&self) -> bool {
+        matches!(self.data, TransactionData::Write(_))
+    }
+
+    fn len(&self) -> usize {
+        match &self.data {
+            TransactionData::None => 0,
+            TransactionData::Write(buffer) => buffer.len(),
+            TransactionData::Read(buffer) => buffer.len(),
+        }
+    }
+
+    /// The buffers this transaction pushes to the TX DMA channel, its
+    /// configuration followed by the data to write
+    fn tx_buffers(&self) -> impl Iterator<Item = (*const u8, usize)> + '_ {
+        let data = match &self.data {
+            TransactionData::Write(buffer) if !buffer.is_empty() => {
+                Some((buffer.as_ptr(), buffer.len()))
+            }
+            _ => None,
+        };
+
+        core::iter::once((
+            self.conf.as_ptr() as *const u8,
+            core::mem::size_of_val(&self.conf),
+        ))
+        .chain(data)
+    }
+
+    /// The buffer this transaction receives into from the RX DMA channel
+    fn rx_buffer(&mut self) -> Option<(*mut u8, usize)> {
+        match &mut self.data {
+            TransactionData::Read(buffer) if !buffer.is_empty() => {
+                Some((buffer.as_mut_ptr(), buffer.len()))
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Read and Write in half duplex mode.
 pub trait HalfDuplexReadWrite {
     type Error;
@@ -389,7 +537,7 @@ pub trait HalfDuplexReadWrite {
 
 /// SPI peripheral driver
 pub struct Spi<'d, T, M> {
-    spi: PeripheralRef<'d, T>,
+    pub(crate) spi: PeripheralRef<'d, T>,
     _mode: PhantomData<M>,
 }
 
@@ -738,6 +886,64 @@ where
     }
 }
 
+#[cfg(esp32s3)]
+impl<'d, T> Spi<'d, T, HalfDuplexMode>
+where
+    T: OctalInstance,
+{
+    pub fn with_sio4<SIO4: OutputPin + InputPin>(
+        self,
+        sio4: impl Peripheral<P = SIO4> + 'd,
+    ) -> Self {
+        crate::into_ref!(sio4);
+        sio4.enable_output(true, private::Internal);
+        sio4.connect_peripheral_to_output(self.spi.sio4_output_signal(), private::Internal);
+        sio4.enable_input(true, private::Internal);
+        sio4.connect_input_to_peripheral(self.spi.sio4_input_signal(), private::Internal);
+
+        self
+    }
+
+    pub fn with_sio5<SIO5: OutputPin + InputPin>(
+        self,
+        sio5: impl Peripheral<P = SIO5> + 'd,
+    ) -> Self {
+        crate::into_ref!(sio5);
+        sio5.enable_output(true, private::Internal);
+        sio5.connect_peripheral_to_output(self.spi.sio5_output_signal(), private::Internal);
+        sio5.enable_input(true, private::Internal);
+        sio5.connect_input_to_peripheral(self.spi.sio5_input_signal(), private::Internal);
+
+        self
+    }
+
+    pub fn with_sio6<SIO6: OutputPin + InputPin>(
+        self,
+        sio6: impl Peripheral<P = SIO6> + 'd,
+    ) -> Self {
+        crate::into_ref!(sio6);
+        sio6.enable_output(true, private::Internal);
+        sio6.connect_peripheral_to_output(self.spi.sio6_output_signal(), private::Internal);
+        sio6.enable_input(true, private::Internal);
+        sio6.connect_input_to_peripheral(self.spi.sio6_input_signal(), private::Internal);
+
+        self
+    }
+
+    pub fn with_sio7<SIO7: OutputPin + InputPin>(
+        self,
+        sio7: impl Peripheral<P = SIO7> + 'd,
+    ) -> Self {
+        crate::into_ref!(sio7);
+        sio7.enable_output(true, private::Internal);
+        sio7.connect_peripheral_to_output(self.spi.sio7_output_signal(), private::Internal);
+        sio7.enable_input(true, private::Internal);
+        sio7.connect_input_to_peripheral(self.spi.sio7_input_signal(), private::Internal);
+
+        self
+    }
+}
+
 impl<T, M> HalfDuplexReadWrite for Spi<'_, T, M>
 where
     T: Instance,
@@ -761,6 +967,8 @@ where
             return Err(Error::Unsupported);
         }
 
+        self.spi
+            .check_data_modes(cmd.mode(), address.mode(), data_mode)?;
         self.spi
             .init_spi_data_mode(cmd.mode(), address.mode(), data_mode);
         self.spi.read_bytes_half_duplex(cmd, address, dummy, buffer)
@@ -778,6 +986,8 @@ where
             return Err(Error::FifoSizeExeeded);
         }
 
+        self.spi
+            .check_data_modes(cmd.mode(), address.mode(), data_mode)?;
         self.spi
             .init_spi_data_mode(cmd.mode(), address.mode(), data_mode);
         self.spi
@@ -835,6 +1045,8 @@ pub mod dma {
     use super::*;
     #[cfg(spi3)]
     use crate::dma::Spi3Peripheral;
+    #[cfg(not(any(esp32, esp32s2)))]
+    use crate::dma::{DmaError, RxPrivate};
     use crate::{
         dma::{
             dma_private::{DmaSupport, DmaSupportRx, DmaSupportTx},
@@ -1165,55 +1377,8 @@ pub mod dma {
                 return Err(super::Error::MaxDmaTransferSizeExceeded);
             }
 
-            self.spi.init_half_duplex(
-                false,
-                !cmd.is_none(),
-                !address.is_none(),
-                false,
-                dummy != 0,
-                len == 0,
-            );
             self.spi
-                .init_spi_data_mode(cmd.mode(), address.mode(), data_mode);
-
-            // set cmd, address, dummy cycles
-            let reg_block = self.spi.register_block();
-            if !cmd.is_none() {
-                reg_block.user2().modify(|_, w| unsafe {
-                    w.usr_command_bitlen()
-                        .bits((cmd.width() - 1) as u8)
-                        .usr_command_value()
-                        .bits(cmd.value())
-                });
-            }
-
-            #[cfg(not(esp32))]
-            if !address.is_none() {
-                reg_block.user1().modify(|_, w| unsafe {
-                    w.usr_addr_bitlen().bits((address.width() - 1) as u8)
-                });
-
-                let addr = address.value() << (32 - address.width());
-                reg_block
-                    .addr()
-                    .write(|w| unsafe { w.usr_addr_value().bits(addr) });
-            }
-
-            #[cfg(esp32)]
-            if !address.is_none() {
-                reg_block.user1().modify(|r, w| unsafe {
-                    w.bits(r.bits() & !(0x3f << 26) | (((address.width() - 1) as u32) & 0x3f) << 26)
-                });
-
-                let addr = address.value() << (32 - address.width());
-                reg_block.addr().write(|w| unsafe { w.bits(addr) });
-            }
-
-            if dummy > 0 {
-                reg_block
-                    .user1()
-                    .modify(|_, w| unsafe { w.usr_dummy_cyclelen().bits(dummy - 1) });
-            }
+                .setup_half_duplex_phases(false, data_mode, &cmd, &address, dummy, len)?;
 
             unsafe {
                 self.spi
@@ -1240,59 +1405,117 @@ pub mod dma {
                 return Err(super::Error::MaxDmaTransferSizeExceeded);
             }
 
-            self.spi.init_half_duplex(
-                true,
-                !cmd.is_none(),
-                !address.is_none(),
-                false,
-                dummy != 0,
-                len == 0,
-            );
             self.spi
-                .init_spi_data_mode(cmd.mode(), address.mode(), data_mode);
-
-            // set cmd, address, dummy cycles
-            let reg_block = self.spi.register_block();
-            if !cmd.is_none() {
-                reg_block.user2().modify(|_, w| unsafe {
-                    w.usr_command_bitlen()
-                        .bits((cmd.width() - 1) as u8)
-                        .usr_command_value()
-                        .bits(cmd.value())
-                });
-            }
+                .setup_half_duplex_phases(true, data_mode, &cmd, &address, dummy, len)?;
 
-            #[cfg(not(esp32))]
-            if !address.is_none() {
-                reg_block.user1().modify(|_, w| unsafe {
-                    w.usr_addr_bitlen().bits((address.width() - 1) as u8)
-                });
+            self.spi
+                .start_write_bytes_dma(ptr, len, &mut self.channel.tx, false)?;
+            Ok(DmaTransferTx::new(self))
+        }
 
-                let addr = address.value() << (32 - address.width());
-                reg_block
-                    .addr()
-                    .write(|w| unsafe { w.usr_addr_value().bits(addr) });
+        /// Run a batch of half-duplex transactions.
+        ///
+        /// The transactions are executed back to back by the hardware without
+        /// CPU intervention. [SpiInterrupt::TransDone] is asserted after each
+        /// transaction and [SpiInterrupt::DmaSegmentedTransferDone] once the
+        /// whole batch has finished.
+        ///
+        /// The descriptors of the DMA channel are shared by all transactions,
+        /// every transaction needs one TX descriptor for its configuration
+        /// plus the descriptors for its data. The maximum amount of data per
+        /// transaction is 32736 bytes.
+        #[cfg(not(any(esp32, esp32s2)))]
+        #[cfg_attr(feature = "place-spi-driver-in-ram", ram)]
+        pub fn queue_transfer<'t>(
+            &'t mut self,
+            transactions: &'t mut [QueuedTransaction<'_>],
+        ) -> Result<SpiDmaQueueTransfer<'t, 'd, T, C, M, DmaMode>, super::Error> {
+            let result = unsafe {
+                self.spi
+                    .start_queued_dma(transactions, &mut self.channel.tx, &mut self.channel.rx)
+            };
+            if let Err(err) = result {
+                self.spi.end_queued_dma();
+                return Err(err);
             }
 
-            #[cfg(esp32)]
-            if !address.is_none() {
-                reg_block.user1().modify(|r, w| unsafe {
-                    w.bits(r.bits() & !(0x3f << 26) | (((address.width() - 1) as u32) & 0x3f) << 26)
-                });
+            Ok(SpiDmaQueueTransfer { spi_dma: self })
+        }
+    }
 
-                let addr = address.value() << (32 - address.width());
-                reg_block.addr().write(|w| unsafe { w.bits(addr) });
-            }
+    /// An in-progress batch of transactions started with
+    /// [SpiDma::queue_transfer].
+    #[cfg(not(any(esp32, esp32s2)))]
+    #[must_use]
+    pub struct SpiDmaQueueTransfer<'t, 'd, T, C, M, DmaMode>
+    where
+        T: InstanceDma<C::Tx<'d>, C::Rx<'d>>,
+        C: ChannelTypes,
+        C::P: SpiPeripheral,
+        M: IsHalfDuplex,
+        DmaMode: Mode,
+    {
+        spi_dma: &'t mut SpiDma<'d, T, C, M, DmaMode>,
+    }
 
-            if dummy > 0 {
-                reg_block
-                    .user1()
-                    .modify(|_, w| unsafe { w.usr_dummy_cyclelen().bits(dummy - 1) });
-            }
+    #[cfg(not(any(esp32, esp32s2)))]
+    impl<'t, 'd, T, C, M, DmaMode> SpiDmaQueueTransfer<'t, 'd, T, C, M, DmaMode>
+    where
+        T: InstanceDma<C::Tx<'d>, C::Rx<'d>>,
+        C: ChannelTypes,
+        C::P: SpiPeripheral,
+        M: IsHalfDuplex,
+        DmaMode: Mode,
+    {
+        /// Wait for all transactions to finish.
+        ///
+        /// Returns [DmaError::DescriptorError] if the DMA or the SPI rejected
+        /// a descriptor or a transaction's configuration, the remaining
+        /// transactions are not run in that case.
+        pub fn wait(mut self) -> Result<(), DmaError> {
+            self.finish()
+        }
 
-            self.spi
-                .start_write_bytes_dma(ptr, len, &mut self.channel.tx, false)?;
-            Ok(DmaTransferTx::new(self))
+        /// Check if all transactions have finished.
+        pub fn is_done(&mut self) -> bool {
+            self.spi_dma.spi.is_queue_done() || self.has_error()
+        }
+
+        fn has_error(&mut self) -> bool {
+            self.spi_dma.tx().has_error()
+                || self.spi_dma.rx().has_error()
+                || self.spi_dma.spi.has_queue_error()
+        }
+
+        fn finish(&mut self) -> Result<(), DmaError> {
+            let result = loop {
+                if self.has_error() {
+                    break Err(DmaError::DescriptorError);
+                }
+                if self.spi_dma.spi.is_queue_done() {
+                    self.spi_dma.spi.flush().ok();
+                    break Ok(());
+                }
+            };
+
+            // Later transfers must not load their configuration from the DMA
+            self.spi_dma.spi.end_queued_dma();
+
+            result
+        }
+    }
+
+    #[cfg(not(any(esp32, esp32s2)))]
+    impl<'t, 'd, T, C, M, DmaMode> Drop for SpiDmaQueueTransfer<'t, 'd, T, C, M, DmaMode>
+    where
+        T: InstanceDma<C::Tx<'d>, C::Rx<'d>>,
+        C: ChannelTypes,
+        C::P: SpiPeripheral,
+        M: IsHalfDuplex,
+        DmaMode: Mode,
+    {
+        fn drop(&mut self) {
+            self.finish().ok();
         }
     }
 
@@ -1938,6 +2161,131 @@ where
         Ok(())
     }
 
+    /// Starts a segmented transfer running all of `transactions` back to back
+    #[cfg(not(any(esp32, esp32s2)))]
+    #[cfg_attr(feature = "place-spi-driver-in-ram", ram)]
+    unsafe fn start_queued_dma(
+        &mut self,
+        transactions: &mut [QueuedTransaction<'_>],
+        tx: &mut TX,
+        rx: &mut RX,
+    ) -> Result<(), Error> {
+        if transactions.is_empty() {
+            return Err(Error::DmaError(crate::dma::DmaError::BufferTooSmall));
+        }
+
+        // Program every transaction into the registers once and capture the
+        // resulting values, the hardware reloads them from the configuration
+        // buffer in front of each transaction's data.
+        let count = transactions.len();
+        for (i, transaction) in transactions.iter_mut().enumerate() {
+            let len = transaction.len();
+            if len > MAX_DMA_SIZE {
+                return Err(Error::MaxDmaTransferSizeExceeded);
+            }
+
+            self.setup_half_duplex_phases(
+                transaction.is_write(),
+                transaction.data_mode,
+                &transaction.cmd,
+                &transaction.address,
+                transaction.dummy,
+                len,
+            )?;
+            self.configure_datalen(len as u32 * 8);
+
+            let reg_block = self.register_block();
+            reg_block
+                .user()
+                .modify(|_, w| w.usr_conf_nxt().bit(i + 1 < count));
+
+            transaction.conf = [
+                SEG_CONF_MAGIC << 28 | SEG_CONF_BITMAP,
+                reg_block.addr().read().bits(),
+                reg_block.ctrl().read().bits(),
+                reg_block.user().read().bits(),
+                reg_block.user1().read().bits(),
+                reg_block.user2().read().bits(),
+                reg_block.ms_dlen().read().bits(),
+                reg_block.misc().read().bits(),
+            ];
+        }
+
+        let reg_block = self.register_block();
+
+        tx.is_done();
+        rx.is_done();
+
+        self.enable_dma();
+        reg_block.slave().modify(|_, w| unsafe {
+            w.usr_conf()
+                .set_bit()
+                .dma_seg_magic_value()
+                .bits(SEG_CONF_MAGIC as u8)
+        });
+        self.update();
+
+        tx.prepare_chain_without_start(
+            self.dma_peripheral(),
+            &mut transactions.iter().flat_map(QueuedTransaction::tx_buffers),
+        )
+        .and_then(|_| tx.start_transfer())?;
+
+        if transactions.iter_mut().any(|t| t.rx_buffer().is_some()) {
+            rx.prepare_chain_without_start(
+                self.dma_peripheral(),
+                &mut transactions
+                    .iter_mut()
+                    .filter_map(QueuedTransaction::rx_buffer),
+            )
+            .and_then(|_| rx.start_transfer())?;
+        }
+
+        self.clear_dma_interrupts();
+        reg_block.dma_int_clr().write(|w| {
+            w.dma_seg_trans_done()
+                .clear_bit_by_one()
+                .seg_magic_err()
+                .clear_bit_by_one()
+        });
+        reset_dma_before_usr_cmd(reg_block);
+
+        reg_block.cmd().modify(|_, w| w.usr().set_bit());
+
+        Ok(())
+    }
+
+    /// Checks if all transactions of a segmented transfer have finished
+    #[cfg(not(any(esp32, esp32s2)))]
+    fn is_queue_done(&self) -> bool {
+        self.register_block()
+            .dma_int_raw()
+            .read()
+            .dma_seg_trans_done()
+            .bit_is_set()
+    }
+
+    /// Checks if the SPI rejected the configuration of a transaction of a
+    /// segmented transfer
+    #[cfg(not(any(esp32, esp32s2)))]
+    fn has_queue_error(&self) -> bool {
+        self.register_block()
+            .dma_int_raw()
+            .read()
+            .seg_magic_err()
+            .bit_is_set()
+    }
+
+    /// Leaves the segmented transfer mode, so the following transfers use the
+    /// configuration in the registers again
+    #[cfg(not(any(esp32, esp32s2)))]
+    fn end_queued_dma(&mut self) {
+        let reg_block = self.register_block();
+        reg_block.slave().modify(|_, w| w.usr_conf().clear_bit());
+        reg_block.user().modify(|_, w| w.usr_conf_nxt().clear_bit());
+        self.update();
+    }
+
     fn dma_peripheral(&self) -> DmaPeripheral {
         match self.spi_num() {
             2 => DmaPeripheral::Spi2,
@@ -2060,6 +2408,89 @@ where
 {
 }
 
+/// Calculates the value of the `CLOCK` register for the given bus frequency
+// taken from https://github.com/apache/incubator-nuttx/blob/8267a7618629838231256edfa666e44b5313348e/arch/risc-v/src/esp32c3/esp32c3_spi.c#L496
+pub(crate) fn clock_register_value(frequency: HertzU32, clocks: &Clocks) -> u32 {
+    #[cfg(not(esp32h2))]
+    let apb_clk_freq: HertzU32 = HertzU32::Hz(clocks.apb_clock.to_Hz());
+    // ESP32-H2 is using PLL_48M_CLK source instead of APB_CLK
+    #[cfg(esp32h2)]
+    let apb_clk_freq: HertzU32 = HertzU32::Hz(clocks.pll_48m_clock.to_Hz());
+
+    let reg_val: u32;
+    let duty_cycle = 128;
+
+    // In HW, n, h and l fields range from 1 to 64, pre ranges from 1 to 8K.
+    // The value written to register is one lower than the used value.
+
+    if frequency > ((apb_clk_freq / 4) * 3) {
+        // Using APB frequency directly will give us the best result here.
+        reg_val = 1 << 31;
+    } else {
+        /* For best duty cycle resolution, we want n to be as close to 32 as
+         * possible, but we also need a pre/n combo that gets us as close as
+         * possible to the intended frequency. To do this, we bruteforce n and
+         * calculate the best pre to go along with that. If there's a choice
+         * between pre/n combos that give the same result, use the one with the
+         * higher n.
+         */
+
+        let mut pre: i32;
+        let mut bestn: i32 = -1;
+        let mut bestpre: i32 = -1;
+        let mut besterr: i32 = 0;
+        let mut errval: i32;
+
+        /* Start at n = 2. We need to be able to set h/l so we have at least
+         * one high and one low pulse.
+         */
+
+        for n in 2..64 {
+            /* Effectively, this does:
+             *   pre = round((APB_CLK_FREQ / n) / frequency)
+             */
+
+            pre = ((apb_clk_freq.raw() as i32 / n) + (frequency.raw() as i32 / 2))
+                / frequency.raw() as i32;
+
+            if pre <= 0 {
+                pre = 1;
+            }
+
+            if pre > 16 {
+                pre = 16;
+            }
+
+            errval = (apb_clk_freq.raw() as i32 / (pre * n) - frequency.raw() as i32).abs();
+            if bestn == -1 || errval <= besterr {
+                besterr = errval;
+                bestn = n;
+                bestpre = pre;
+            }
+        }
+
+        let n: i32 = bestn;
+        pre = bestpre;
+        let l: i32 = n;
+
+        /* Effectively, this does:
+         *   h = round((duty_cycle * n) / 256)
+         */
+
+        let mut h: i32 = (duty_cycle * n + 127) / 256;
+        if h <= 0 {
+            h = 1;
+        }
+
+        reg_val = (l as u32 - 1)
+            | ((h as u32 - 1) << 6)
+            | ((n as u32 - 1) << 12)
+            | ((pre as u32 - 1) << 18);
+    }
+
+    reg_val
+}
+
 #[doc(hidden)]
 pub trait ExtendedInstance: Instance {
     fn sio0_input_signal(&self) -> InputSignal;
@@ -2075,6 +2506,26 @@ pub trait ExtendedInstance: Instance {
     fn sio3_input_signal(&self) -> InputSignal;
 }
 
+#[cfg(esp32s3)]
+#[doc(hidden)]
+pub trait OctalInstance: ExtendedInstance {
+    fn sio4_output_signal(&self) -> OutputSignal;
+
+    fn sio4_input_signal(&self) -> InputSignal;
+
+    fn sio5_output_signal(&self) -> OutputSignal;
+
+    fn sio5_input_signal(&self) -> InputSignal;
+
+    fn sio6_output_signal(&self) -> OutputSignal;
+
+    fn sio6_input_signal(&self) -> InputSignal;
+
+    fn sio7_output_signal(&self) -> OutputSignal;
+
+    fn sio7_input_signal(&self) -> InputSignal;
+}
+
 #[doc(hidden)]
 pub trait Instance: private::Sealed {
     fn register_block(&self) -> &RegisterBlock;
@@ -2087,6 +2538,10 @@ pub trait Instance: private::Sealed {
 
     fn cs_signal(&self) -> OutputSignal;
 
+    /// All hardware CS outputs, starting with CS0
+    #[cfg(not(esp32))]
+    fn cs_signals(&self) -> &'static [OutputSignal];
+
     fn enable_peripheral(&self);
 
     fn spi_num(&self) -> u8;
@@ -2158,6 +2613,27 @@ pub trait Instance: private::Sealed {
         reg_block.slave().write(|w| unsafe { w.bits(0) });
     }
 
+    /// Returns [Error::Unsupported] if the peripheral lacks the data lines for
+    /// any of the given modes, only SPI2 has the lines for the octal modes
+    #[cfg_attr(not(esp32s3), allow(unused_variables))]
+    fn check_data_modes(
+        &self,
+        cmd_mode: SpiDataMode,
+        address_mode: SpiDataMode,
+        data_mode: SpiDataMode,
+    ) -> Result<(), Error> {
+        #[cfg(esp32s3)]
+        if self.spi_num() != 2
+            && [cmd_mode, address_mode, data_mode]
+                .iter()
+                .any(|mode| matches!(mode, SpiDataMode::Octal | SpiDataMode::OctalDtr))
+        {
+            return Err(Error::Unsupported);
+        }
+
+        Ok(())
+    }
+
     #[cfg(not(esp32))]
     fn init_spi_data_mode(
         &mut self,
@@ -2176,6 +2652,10 @@ pub trait Instance: private::Sealed {
             SpiDataMode::Quad => reg_block
                 .ctrl()
                 .modify(|_, w| w.fcmd_dual().clear_bit().fcmd_quad().set_bit()),
+            #[cfg(esp32s3)]
+            SpiDataMode::Octal | SpiDataMode::OctalDtr => reg_block
+                .ctrl()
+                .modify(|_, w| w.fcmd_dual().clear_bit().fcmd_quad().clear_bit()),
         }
 
         match address_mode {
@@ -2188,6 +2668,10 @@ pub trait Instance: private::Sealed {
             SpiDataMode::Quad => reg_block
                 .ctrl()
                 .modify(|_, w| w.faddr_dual().clear_bit().faddr_quad().set_bit()),
+            #[cfg(esp32s3)]
+            SpiDataMode::Octal | SpiDataMode::OctalDtr => reg_block
+                .ctrl()
+                .modify(|_, w| w.faddr_dual().clear_bit().faddr_quad().clear_bit()),
         }
 
         match data_mode {
@@ -2215,6 +2699,47 @@ pub trait Instance: private::Sealed {
                     .user()
                     .modify(|_, w| w.fwrite_quad().set_bit().fwrite_dual().clear_bit());
             }
+            #[cfg(esp32s3)]
+            SpiDataMode::Octal | SpiDataMode::OctalDtr => {
+                reg_block
+                    .ctrl()
+                    .modify(|_, w| w.fread_quad().clear_bit().fread_dual().clear_bit());
+                reg_block
+                    .user()
+                    .modify(|_, w| w.fwrite_quad().clear_bit().fwrite_dual().clear_bit());
+            }
+        }
+
+        #[cfg(esp32s3)]
+        {
+            let octal =
+                |mode: SpiDataMode| matches!(mode, SpiDataMode::Octal | SpiDataMode::OctalDtr);
+            let dtr = |mode: SpiDataMode| mode == SpiDataMode::OctalDtr;
+
+            reg_block.ctrl().modify(|_, w| {
+                w.fcmd_oct()
+                    .bit(octal(cmd_mode))
+                    .faddr_oct()
+                    .bit(octal(address_mode))
+                    .fread_oct()
+                    .bit(octal(data_mode))
+            });
+            reg_block
+                .user()
+                .modify(|_, w| w.fwrite_oct().bit(octal(data_mode)));
+
+            // Double transfer rate is configured per phase, the clock has to
+            // toggle on both edges as soon as any of them uses it
+            reg_block.misc().modify(|_, w| {
+                w.cmd_dtr_en()
+                    .bit(dtr(cmd_mode))
+                    .addr_dtr_en()
+                    .bit(dtr(address_mode))
+                    .data_dtr_en()
+                    .bit(dtr(data_mode))
+                    .clk_data_dtr_en()
+                    .bit(dtr(cmd_mode) || dtr(address_mode) || dtr(data_mode))
+            });
         }
     }
 
@@ -2362,88 +2887,33 @@ pub trait Instance: private::Sealed {
         }
     }
 
-    // taken from https://github.com/apache/incubator-nuttx/blob/8267a7618629838231256edfa666e44b5313348e/arch/risc-v/src/esp32c3/esp32c3_spi.c#L496
     fn setup(&mut self, frequency: HertzU32, clocks: &Clocks) {
-        #[cfg(not(esp32h2))]
-        let apb_clk_freq: HertzU32 = HertzU32::Hz(clocks.apb_clock.to_Hz());
-        // ESP32-H2 is using PLL_48M_CLK source instead of APB_CLK
-        #[cfg(esp32h2)]
-        let apb_clk_freq: HertzU32 = HertzU32::Hz(clocks.pll_48m_clock.to_Hz());
-
-        let reg_val: u32;
-        let duty_cycle = 128;
-
-        // In HW, n, h and l fields range from 1 to 64, pre ranges from 1 to 8K.
-        // The value written to register is one lower than the used value.
-
-        if frequency > ((apb_clk_freq / 4) * 3) {
-            // Using APB frequency directly will give us the best result here.
-            reg_val = 1 << 31;
-        } else {
-            /* For best duty cycle resolution, we want n to be as close to 32 as
-             * possible, but we also need a pre/n combo that gets us as close as
-             * possible to the intended frequency. To do this, we bruteforce n and
-             * calculate the best pre to go along with that. If there's a choice
-             * between pre/n combos that give the same result, use the one with the
-             * higher n.
-             */
-
-            let mut pre: i32;
-            let mut bestn: i32 = -1;
-            let mut bestpre: i32 = -1;
-            let mut besterr: i32 = 0;
-            let mut errval: i32;
-
-            /* Start at n = 2. We need to be able to set h/l so we have at least
-             * one high and one low pulse.
-             */
-
-            for n in 2..64 {
-                /* Effectively, this does:
-                 *   pre = round((APB_CLK_FREQ / n) / frequency)
-                 */
-
-                pre = ((apb_clk_freq.raw() as i32 / n) + (frequency.raw() as i32 / 2))
-                    / frequency.raw() as i32;
-
-                if pre <= 0 {
-                    pre = 1;
-                }
-
-                if pre > 16 {
-                    pre = 16;
-                }
-
-                errval = (apb_clk_freq.raw() as i32 / (pre * n) - frequency.raw() as i32).abs();
-                if bestn == -1 || errval <= besterr {
-                    besterr = errval;
-                    bestn = n;
-                    bestpre = pre;
-                }
-            }
-
-            let n: i32 = bestn;
-            pre = bestpre;
-            let l: i32 = n;
-
-            /* Effectively, this does:
-             *   h = round((duty_cycle * n) / 256)
-             */
-
-            let mut h: i32 = (duty_cycle * n + 127) / 256;
-            if h <= 0 {
-                h = 1;
-            }
-
-            reg_val = (l as u32 - 1)
-                | ((h as u32 - 1) << 6)
-                | ((n as u32 - 1) << 12)
-                | ((pre as u32 - 1) << 18);
-        }
-
         self.register_block()
             .clock()
-            .write(|w| unsafe { w.bits(reg_val) });
+            .write(|w| unsafe { w.bits(clock_register_value(frequency, clocks)) });
+    }
+
+    /// Enables only the hardware CS output with the given index and keeps it
+    /// asserted across transfers until called with `None`, which also
+    /// disables all hardware CS outputs
+    #[cfg(not(esp32))]
+    fn set_active_cs(&mut self, cs: Option<usize>) {
+        self.register_block().misc().modify(|_, w| {
+            w.cs0_dis()
+                .bit(cs != Some(0))
+                .cs1_dis()
+                .bit(cs != Some(1))
+                .cs2_dis()
+                .bit(cs != Some(2))
+                .cs3_dis()
+                .bit(cs != Some(3))
+                .cs4_dis()
+                .bit(cs != Some(4))
+                .cs5_dis()
+                .bit(cs != Some(5))
+                .cs_keep_active()
+                .bit(cs.is_some())
+        });
     }
 
     /// Set the interrupt handler
@@ -2461,6 +2931,11 @@ pub trait Instance: private::Sealed {
                         .dma_int_ena()
                         .modify(|_, w| w.trans_done().set_bit());
                 }
+                SpiInterrupt::DmaSegmentedTransferDone => {
+                    reg_block
+                        .dma_int_ena()
+                        .modify(|_, w| w.dma_seg_trans_done().set_bit());
+                }
             }
         }
     }
@@ -2477,6 +2952,11 @@ pub trait Instance: private::Sealed {
                         .dma_int_ena()
                         .modify(|_, w| w.trans_done().clear_bit());
                 }
+                SpiInterrupt::DmaSegmentedTransferDone => {
+                    reg_block
+                        .dma_int_ena()
+                        .modify(|_, w| w.dma_seg_trans_done().clear_bit());
+                }
             }
         }
     }
@@ -2492,6 +2972,9 @@ pub trait Instance: private::Sealed {
         if ints.trans_done().bit() {
             res.insert(SpiInterrupt::TransDone);
         }
+        if ints.dma_seg_trans_done().bit() {
+            res.insert(SpiInterrupt::DmaSegmentedTransferDone);
+        }
 
         res
     }
@@ -2508,6 +2991,11 @@ pub trait Instance: private::Sealed {
                         .dma_int_clr()
                         .write(|w| w.trans_done().clear_bit_by_one());
                 }
+                SpiInterrupt::DmaSegmentedTransferDone => {
+                    reg_block
+                        .dma_int_clr()
+                        .write(|w| w.dma_seg_trans_done().clear_bit_by_one());
+                }
             }
         }
     }
@@ -2563,6 +3051,12 @@ pub trait Instance: private::Sealed {
     }
 
     fn ch_bus_freq(&mut self, frequency: HertzU32, clocks: &Clocks) {
+        self.set_clock_register(clock_register_value(frequency, clocks));
+    }
+
+    /// Changes the bus clock to a value calculated by
+    /// [clock_register_value]
+    fn set_clock_register(&mut self, reg_val: u32) {
         // Disable clock source
         #[cfg(not(any(esp32, esp32s2)))]
         self.register_block().clk_gate().modify(|_, w| {
@@ -2575,7 +3069,9 @@ pub trait Instance: private::Sealed {
         });
 
         // Change clock frequency
-        self.setup(frequency, clocks);
+        self.register_block()
+            .clock()
+            .write(|w| unsafe { w.bits(reg_val) });
 
         // Enable clock source
         #[cfg(not(any(esp32, esp32s2)))]
@@ -2846,6 +3342,71 @@ pub trait Instance: private::Sealed {
         self.update();
     }
 
+    /// Configures the command, address and dummy phases of a half-duplex
+    /// transaction transferring `len` bytes of data
+    fn setup_half_duplex_phases(
+        &mut self,
+        is_write: bool,
+        data_mode: SpiDataMode,
+        cmd: &Command,
+        address: &Address,
+        dummy: u8,
+        len: usize,
+    ) -> Result<(), Error> {
+        self.check_data_modes(cmd.mode(), address.mode(), data_mode)?;
+
+        self.init_half_duplex(
+            is_write,
+            !cmd.is_none(),
+            !address.is_none(),
+            false,
+            dummy != 0,
+            len == 0,
+        );
+        self.init_spi_data_mode(cmd.mode(), address.mode(), data_mode);
+
+        // set cmd, address, dummy cycles
+        let reg_block = self.register_block();
+        if !cmd.is_none() {
+            reg_block.user2().modify(|_, w| unsafe {
+                w.usr_command_bitlen()
+                    .bits((cmd.width() - 1) as u8)
+                    .usr_command_value()
+                    .bits(cmd.value())
+            });
+        }
+
+        #[cfg(not(esp32))]
+        if !address.is_none() {
+            reg_block
+                .user1()
+                .modify(|_, w| unsafe { w.usr_addr_bitlen().bits((address.width() - 1) as u8) });
+
+            let addr = address.value() << (32 - address.width());
+            reg_block
+                .addr()
+                .write(|w| unsafe { w.usr_addr_value().bits(addr) });
+        }
+
+        #[cfg(esp32)]
+        if !address.is_none() {
+            reg_block.user1().modify(|r, w| unsafe {
+                w.bits(r.bits() & !(0x3f << 26) | (((address.width() - 1) as u32) & 0x3f) << 26)
+            });
+
+            let addr = address.value() << (32 - address.width());
+            reg_block.addr().write(|w| unsafe { w.bits(addr) });
+        }
+
+        if dummy > 0 {
+            reg_block
+                .user1()
+                .modify(|_, w| unsafe { w.usr_dummy_cyclelen().bits(dummy - 1) });
+        }
+
+        Ok(())
+    }
+
     fn write_bytes_half_duplex(
         &mut self,
         cmd: Command,
@@ -3044,6 +3605,18 @@ impl Instance for crate::peripherals::SPI2 {
         OutputSignal::FSPICS0
     }
 
+    #[inline(always)]
+    fn cs_signals(&self) -> &'static [OutputSignal] {
+        &[
+            OutputSignal::FSPICS0,
+            OutputSignal::FSPICS1,
+            OutputSignal::FSPICS2,
+            OutputSignal::FSPICS3,
+            OutputSignal::FSPICS4,
+            OutputSignal::FSPICS5,
+        ]
+    }
+
     #[inline(always)]
     fn enable_peripheral(&self) {
         PeripheralClockControl::enable(crate::system::Peripheral::Spi2);
@@ -3236,6 +3809,18 @@ impl Instance for crate::peripherals::SPI2 {
         OutputSignal::FSPICS0
     }
 
+    #[inline(always)]
+    fn cs_signals(&self) -> &'static [OutputSignal] {
+        &[
+            OutputSignal::FSPICS0,
+            OutputSignal::FSPICS1,
+            OutputSignal::FSPICS2,
+            OutputSignal::FSPICS3,
+            OutputSignal::FSPICS4,
+            OutputSignal::FSPICS5,
+        ]
+    }
+
     #[inline(always)]
     fn enable_peripheral(&self) {
         PeripheralClockControl::enable(crate::system::Peripheral::Spi2)
@@ -3280,6 +3865,49 @@ impl ExtendedInstance for crate::peripherals::SPI2 {
     }
 }
 
+#[cfg(esp32s3)]
+impl OctalInstance for crate::peripherals::SPI2 {
+    #[inline(always)]
+    fn sio4_output_signal(&self) -> OutputSignal {
+        OutputSignal::FSPIIO4
+    }
+
+    #[inline(always)]
+    fn sio4_input_signal(&self) -> InputSignal {
+        InputSignal::FSPIIO4
+    }
+
+    #[inline(always)]
+    fn sio5_output_signal(&self) -> OutputSignal {
+        OutputSignal::FSPIIO5
+    }
+
+    #[inline(always)]
+    fn sio5_input_signal(&self) -> InputSignal {
+        InputSignal::FSPIIO5
+    }
+
+    #[inline(always)]
+    fn sio6_output_signal(&self) -> OutputSignal {
+        OutputSignal::FSPIIO6
+    }
+
+    #[inline(always)]
+    fn sio6_input_signal(&self) -> InputSignal {
+        InputSignal::FSPIIO6
+    }
+
+    #[inline(always)]
+    fn sio7_output_signal(&self) -> OutputSignal {
+        OutputSignal::FSPIIO7
+    }
+
+    #[inline(always)]
+    fn sio7_input_signal(&self) -> InputSignal {
+        InputSignal::FSPIIO7
+    }
+}
+
 #[cfg(any(esp32s2, esp32s3))]
 impl Instance for crate::peripherals::SPI3 {
     #[inline(always)]
@@ -3313,6 +3941,15 @@ impl Instance for crate::peripherals::SPI3 {
         OutputSignal::SPI3_CS0
     }
 
+    #[inline(always)]
+    fn cs_signals(&self) -> &'static [OutputSignal] {
+        &[
+            OutputSignal::SPI3_CS0,
+            OutputSignal::SPI3_CS1,
+            OutputSignal::SPI3_CS2,
+        ]
+    }
+
     #[inline(always)]
     fn enable_peripheral(&self) {
         PeripheralClockControl::enable(crate::system::Peripheral::Spi3)
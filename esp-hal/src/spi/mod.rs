@@ -7,6 +7,7 @@
 use crate::dma::DmaError;
 
 pub mod master;
+pub mod shared_bus;
 #[cfg(not(esp32))]
 pub mod slave;
 
@@ -18,6 +19,8 @@ pub enum Error {
     MaxDmaTransferSizeExceeded,
     FifoSizeExeeded,
     Unsupported,
+    /// The bus is in use by a transaction of another device
+    Busy,
     Unknown,
 }
 
@@ -61,12 +64,20 @@ pub trait IsHalfDuplex: DuplexMode {}
 /// Single = 1 bit, 2 wires
 /// Dual = 2 bit, 2 wires
 /// Quad = 4 bit, 4 wires
+/// Octal = 8 bit, 8 wires (SPI2 on ESP32-S3 only, transfers using it on SPI3
+/// fail with [Error::Unsupported])
+/// OctalDtr = 8 bit, 8 wires, sampled on both clock edges (SPI2 on ESP32-S3
+/// only)
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SpiDataMode {
     Single,
     Dual,
     Quad,
+    #[cfg(esp32s3)]
+    Octal,
+    #[cfg(esp32s3)]
+    OctalDtr,
 }
 
 /// Full-duplex operation
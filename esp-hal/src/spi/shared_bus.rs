@@ -0,0 +1,544 @@
+//! # Shared SPI bus
+//!
+//! ## Overview
+//!
+//! Several devices can share one SPI master bus, each with its own chip
+//! select, [SpiMode], bit order and bus frequency. The bus (a
+//! [Spi](super::master::Spi) or [SpiDma](super::master::dma::SpiDma) in full
+//! duplex mode) is moved into a [SpiBusController], which hands out
+//! [SpiBusDevice]s. Before each transaction the bus is reconfigured for the
+//! device, if the previous transaction was for a device with a different
+//! configuration.
+//!
+//! Chip select can either be one of the peripheral's hardware CS outputs
+//! (CS0 to CS5 on SPI2, CS0 to CS2 on SPI3) or any GPIO. Either way CS is kept
+//! asserted for the whole transaction. Hardware CS outputs are not available
+//! on the ESP32, as it can't keep them asserted across transfers.
+//!
+//! [SpiBusDevice] implements `embedded_hal::spi::SpiDevice`. With the `async`
+//! feature, `AsyncSpiBusController` guards the bus with an embassy
+//! `Mutex`, so devices can be used from different tasks, and
+//! `AsyncSpiBusDevice` implements `embedded_hal_async::spi::SpiDevice`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! let spi = Spi::new(peripherals.SPI2, 1.MHz(), SpiMode::Mode0, &clocks).with_pins(
+//!     Some(sclk),
+//!     Some(mosi),
+//!     Some(miso),
+//!     NO_PIN,
+//! );
+//! let bus = SpiBusController::new(spi);
+//!
+//! let mut flash = bus
+//!     .device_with_hw_cs(
+//!         io.pins.gpio10,
+//!         HwChipSelect::Cs0,
+//!         DeviceConfig::default().frequency(20.MHz()),
+//!         &clocks,
+//!     )
+//!     .unwrap();
+//! let mut sensor = bus.device(
+//!     AnyOutput::new(io.pins.gpio4, Level::High),
+//!     DeviceConfig::default()
+//!         .mode(SpiMode::Mode3)
+//!         .bit_order(SpiBitOrder::LSBFirst),
+//!     &clocks,
+//! );
+//!
+//! sensor.transaction(|bus| bus.transfer(&mut buffer))?;
+//! ```
+
+use core::cell::RefCell;
+
+use fugit::HertzU32;
+
+use super::{
+    master::{clock_register_value, dma::SpiDma, Instance, InstanceDma, Spi},
+    Error,
+    FullDuplexMode,
+    SpiBitOrder,
+    SpiMode,
+};
+use crate::{
+    clock::Clocks,
+    dma::{ChannelTypes, SpiPeripheral},
+    gpio::AnyOutput,
+    Mode,
+};
+#[cfg(not(esp32))]
+use crate::{gpio::OutputPin, peripheral::Peripheral, private};
+
+/// Hardware chip select outputs of the SPI peripheral
+#[cfg(not(esp32))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HwChipSelect {
+    Cs0,
+    Cs1,
+    Cs2,
+    /// Only available on SPI2
+    Cs3,
+    /// Only available on SPI2
+    Cs4,
+    /// Only available on SPI2
+    Cs5,
+}
+
+/// Per device bus configuration
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceConfig {
+    pub frequency: HertzU32,
+    pub mode: SpiMode,
+    /// Used for both reading and writing
+    pub bit_order: SpiBitOrder,
+}
+
+impl DeviceConfig {
+    pub fn frequency(mut self, frequency: HertzU32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn mode(mut self, mode: SpiMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn bit_order(mut self, bit_order: SpiBitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        DeviceConfig {
+            frequency: HertzU32::MHz(1),
+            mode: SpiMode::Mode0,
+            bit_order: SpiBitOrder::MSBFirst,
+        }
+    }
+}
+
+/// A bus which can be shared by a [SpiBusController]
+#[doc(hidden)]
+pub trait SharedBus {
+    type Instance: Instance;
+
+    fn instance(&mut self) -> &mut Self::Instance;
+}
+
+impl<'d, T> SharedBus for Spi<'d, T, FullDuplexMode>
+where
+    T: Instance,
+{
+    type Instance = T;
+
+    fn instance(&mut self) -> &mut T {
+        &mut self.spi
+    }
+}
+
+impl<'d, T, C, DmaMode> SharedBus for SpiDma<'d, T, C, FullDuplexMode, DmaMode>
+where
+    T: InstanceDma<C::Tx<'d>, C::Rx<'d>>,
+    C: ChannelTypes,
+    C::P: SpiPeripheral,
+    DmaMode: Mode,
+{
+    type Instance = T;
+
+    fn instance(&mut self) -> &mut T {
+        &mut self.spi
+    }
+}
+
+/// Bus configuration with the clock register value precalculated, so devices
+/// don't need to hold on to [Clocks]
+#[derive(Clone, Copy, PartialEq)]
+struct BusConfig {
+    clock_reg: u32,
+    mode: SpiMode,
+    bit_order: SpiBitOrder,
+}
+
+impl BusConfig {
+    fn new(config: DeviceConfig, clocks: &Clocks) -> Self {
+        BusConfig {
+            clock_reg: clock_register_value(config.frequency, clocks),
+            mode: config.mode,
+            bit_order: config.bit_order,
+        }
+    }
+}
+
+struct BusState<B> {
+    bus: B,
+    /// Configuration of the last transaction, `None` before the first one
+    config: Option<BusConfig>,
+}
+
+enum ChipSelect<'d> {
+    #[cfg(not(esp32))]
+    Hardware(usize),
+    Gpio(AnyOutput<'d>),
+}
+
+/// Chip select and configuration of a device
+struct DeviceState<'d> {
+    cs: ChipSelect<'d>,
+    config: BusConfig,
+}
+
+impl<'d> DeviceState<'d> {
+    /// Reconfigures the bus if needed and asserts CS
+    fn select<B: SharedBus>(&mut self, state: &mut BusState<B>) {
+        let spi = state.bus.instance();
+
+        if state.config != Some(self.config) {
+            spi.set_clock_register(self.config.clock_reg);
+            spi.set_data_mode(self.config.mode);
+            spi.set_bit_order(self.config.bit_order, self.config.bit_order);
+            state.config = Some(self.config);
+        }
+
+        match &mut self.cs {
+            #[cfg(not(esp32))]
+            ChipSelect::Hardware(index) => spi.set_active_cs(Some(*index)),
+            ChipSelect::Gpio(pin) => {
+                #[cfg(not(esp32))]
+                spi.set_active_cs(None);
+                pin.set_low();
+            }
+        }
+    }
+
+    /// Deasserts CS, the bus has to be flushed before
+    #[cfg_attr(esp32, allow(unused_variables))]
+    fn deselect<B: SharedBus>(&mut self, state: &mut BusState<B>) {
+        match &mut self.cs {
+            #[cfg(not(esp32))]
+            ChipSelect::Hardware(_) => state.bus.instance().set_active_cs(None),
+            ChipSelect::Gpio(pin) => pin.set_high(),
+        }
+    }
+}
+
+/// A device selected for a transaction: the bus is configured for the device
+/// and CS is asserted until this is dropped, also when a transaction is
+/// cancelled
+struct Selection<'s, 'd, B: SharedBus> {
+    device: &'s mut DeviceState<'d>,
+    state: &'s mut BusState<B>,
+}
+
+impl<'s, 'd, B: SharedBus> Selection<'s, 'd, B> {
+    fn new(device: &'s mut DeviceState<'d>, state: &'s mut BusState<B>) -> Self {
+        device.select(state);
+
+        Selection { device, state }
+    }
+
+    fn bus(&mut self) -> &mut B {
+        &mut self.state.bus
+    }
+}
+
+impl<'s, 'd, B: SharedBus> Drop for Selection<'s, 'd, B> {
+    fn drop(&mut self) {
+        self.device.deselect(self.state);
+    }
+}
+
+/// Creates the chip select of a device using a hardware CS output
+#[cfg(not(esp32))]
+fn hw_chip_select<'d, B: SharedBus, CS: OutputPin>(
+    bus: &mut B,
+    pin: impl Peripheral<P = CS> + 'd,
+    cs: HwChipSelect,
+) -> Result<ChipSelect<'d>, Error> {
+    let spi = bus.instance();
+    let signal = *spi
+        .cs_signals()
+        .get(cs as usize)
+        .ok_or(Error::Unsupported)?;
+
+    crate::into_ref!(pin);
+    pin.set_to_push_pull_output(private::Internal);
+    pin.connect_peripheral_to_output(signal, private::Internal);
+
+    Ok(ChipSelect::Hardware(cs as usize))
+}
+
+/// Shares an SPI bus between several [SpiBusDevice]s
+pub struct SpiBusController<B> {
+    state: RefCell<BusState<B>>,
+}
+
+impl<B> SpiBusController<B>
+where
+    B: SharedBus,
+{
+    /// Takes ownership of the bus. Hardware CS outputs are disabled until a
+    /// device using them starts a transaction.
+    #[cfg_attr(esp32, allow(unused_mut))]
+    pub fn new(mut bus: B) -> Self {
+        #[cfg(not(esp32))]
+        bus.instance().set_active_cs(None);
+
+        SpiBusController {
+            state: RefCell::new(BusState { bus, config: None }),
+        }
+    }
+
+    /// Creates a device using a GPIO as its chip select. The pin should be
+    /// initialized high.
+    pub fn device<'a, 'd>(
+        &'a self,
+        cs: AnyOutput<'d>,
+        config: DeviceConfig,
+        clocks: &Clocks,
+    ) -> SpiBusDevice<'a, 'd, B> {
+        SpiBusDevice {
+            controller: self,
+            device: DeviceState {
+                cs: ChipSelect::Gpio(cs),
+                config: BusConfig::new(config, clocks),
+            },
+        }
+    }
+
+    /// Creates a device using one of the peripheral's hardware CS outputs,
+    /// routed to `pin`.
+    ///
+    /// Returns [Error::Unsupported] if the peripheral doesn't have the given
+    /// CS output and [Error::Busy] if a transaction is running.
+    #[cfg(not(esp32))]
+    pub fn device_with_hw_cs<'a, 'd, CS: OutputPin>(
+        &'a self,
+        pin: impl Peripheral<P = CS> + 'd,
+        cs: HwChipSelect,
+        config: DeviceConfig,
+        clocks: &Clocks,
+    ) -> Result<SpiBusDevice<'a, 'd, B>, Error> {
+        let mut state = self.state.try_borrow_mut().map_err(|_| Error::Busy)?;
+        let cs = hw_chip_select(&mut state.bus, pin, cs)?;
+
+        Ok(SpiBusDevice {
+            controller: self,
+            device: DeviceState {
+                cs,
+                config: BusConfig::new(config, clocks),
+            },
+        })
+    }
+
+    /// Releases the bus
+    pub fn free(self) -> B {
+        self.state.into_inner().bus
+    }
+}
+
+/// A device on a bus shared through a [SpiBusController]
+pub struct SpiBusDevice<'a, 'd, B> {
+    controller: &'a SpiBusController<B>,
+    device: DeviceState<'d>,
+}
+
+impl<'a, 'd, B> SpiBusDevice<'a, 'd, B>
+where
+    B: SharedBus,
+{
+    /// Runs `f` with exclusive access to the bus, configured for this device
+    /// and with CS asserted. `f` has to leave the bus flushed.
+    ///
+    /// Returns [Error::Busy] if called while another device's transaction is
+    /// running, e.g. from an interrupt handler.
+    pub fn transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut B) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        let mut state = self
+            .controller
+            .state
+            .try_borrow_mut()
+            .map_err(|_| Error::Busy)?;
+        let mut selection = Selection::new(&mut self.device, &mut state);
+
+        f(selection.bus())
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+mod ehal1 {
+    use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice};
+
+    use super::*;
+
+    impl<'a, 'd, B> ErrorType for SpiBusDevice<'a, 'd, B> {
+        type Error = Error;
+    }
+
+    impl<'a, 'd, B> SpiDevice for SpiBusDevice<'a, 'd, B>
+    where
+        B: SharedBus + SpiBus<Error = Error>,
+    {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Error> {
+            SpiBusDevice::transaction(self, |bus| {
+                for operation in operations {
+                    match operation {
+                        Operation::Read(words) => bus.read(words)?,
+                        Operation::Write(words) => bus.write(words)?,
+                        Operation::Transfer(read, write) => bus.transfer(read, write)?,
+                        Operation::TransferInPlace(words) => bus.transfer_in_place(words)?,
+                        Operation::DelayNs(ns) => {
+                            bus.flush()?;
+                            crate::rom::ets_delay_us(ns.div_ceil(1000));
+                        }
+                    }
+                }
+
+                bus.flush()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use embassy_futures::yield_now;
+    use embassy_sync::{blocking_mutex::raw::RawMutex, mutex::Mutex};
+    use embedded_hal::spi::{ErrorType, Operation};
+    use embedded_hal_async::spi::{SpiBus, SpiDevice};
+
+    use super::*;
+
+    /// Shares an SPI bus between several [AsyncSpiBusDevice]s, which can be
+    /// used from different tasks
+    pub struct AsyncSpiBusController<RM, B>
+    where
+        RM: RawMutex,
+    {
+        state: Mutex<RM, BusState<B>>,
+    }
+
+    impl<RM, B> AsyncSpiBusController<RM, B>
+    where
+        RM: RawMutex,
+        B: SharedBus,
+    {
+        /// Takes ownership of the bus. Hardware CS outputs are disabled until
+        /// a device using them starts a transaction.
+        #[cfg_attr(esp32, allow(unused_mut))]
+        pub fn new(mut bus: B) -> Self {
+            #[cfg(not(esp32))]
+            bus.instance().set_active_cs(None);
+
+            AsyncSpiBusController {
+                state: Mutex::new(BusState { bus, config: None }),
+            }
+        }
+
+        /// Creates a device using a GPIO as its chip select. The pin should
+        /// be initialized high.
+        pub fn device<'a, 'd>(
+            &'a self,
+            cs: AnyOutput<'d>,
+            config: DeviceConfig,
+            clocks: &Clocks,
+        ) -> AsyncSpiBusDevice<'a, 'd, RM, B> {
+            AsyncSpiBusDevice {
+                controller: self,
+                device: DeviceState {
+                    cs: ChipSelect::Gpio(cs),
+                    config: BusConfig::new(config, clocks),
+                },
+            }
+        }
+
+        /// Creates a device using one of the peripheral's hardware CS
+        /// outputs, routed to `pin`.
+        ///
+        /// Returns [Error::Unsupported] if the peripheral doesn't have the
+        /// given CS output and [Error::Busy] if a transaction is running.
+        #[cfg(not(esp32))]
+        pub fn device_with_hw_cs<'a, 'd, CS: OutputPin>(
+            &'a self,
+            pin: impl Peripheral<P = CS> + 'd,
+            cs: HwChipSelect,
+            config: DeviceConfig,
+            clocks: &Clocks,
+        ) -> Result<AsyncSpiBusDevice<'a, 'd, RM, B>, Error> {
+            let mut state = self.state.try_lock().map_err(|_| Error::Busy)?;
+            let cs = hw_chip_select(&mut state.bus, pin, cs)?;
+
+            Ok(AsyncSpiBusDevice {
+                controller: self,
+                device: DeviceState {
+                    cs,
+                    config: BusConfig::new(config, clocks),
+                },
+            })
+        }
+
+        /// Releases the bus
+        pub fn free(self) -> B {
+            self.state.into_inner().bus
+        }
+    }
+
+    /// A device on a bus shared through an [AsyncSpiBusController]
+    pub struct AsyncSpiBusDevice<'a, 'd, RM, B>
+    where
+        RM: RawMutex,
+    {
+        controller: &'a AsyncSpiBusController<RM, B>,
+        device: DeviceState<'d>,
+    }
+
+    impl<'a, 'd, RM, B> ErrorType for AsyncSpiBusDevice<'a, 'd, RM, B>
+    where
+        RM: RawMutex,
+    {
+        type Error = Error;
+    }
+
+    impl<'a, 'd, RM, B> SpiDevice for AsyncSpiBusDevice<'a, 'd, RM, B>
+    where
+        RM: RawMutex,
+        B: SharedBus + SpiBus<Error = Error>,
+    {
+        async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Error> {
+            let mut state = self.controller.state.lock().await;
+            // Deasserts CS when the transaction completes or is cancelled
+            let mut selection = Selection::new(&mut self.device, &mut state);
+            let bus = selection.bus();
+
+            for operation in operations {
+                match operation {
+                    Operation::Read(words) => bus.read(words).await?,
+                    Operation::Write(words) => bus.write(words).await?,
+                    Operation::Transfer(read, write) => bus.transfer(read, write).await?,
+                    Operation::TransferInPlace(words) => bus.transfer_in_place(words).await?,
+                    Operation::DelayNs(ns) => {
+                        bus.flush().await?;
+                        // Let other tasks run while CS stays asserted
+                        let deadline = crate::time::current_time()
+                            + fugit::MicrosDurationU64::micros(ns.div_ceil(1000) as u64);
+                        while crate::time::current_time() < deadline {
+                            yield_now().await;
+                        }
+                    }
+                }
+            }
+
+            bus.flush().await
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use asynch::{AsyncSpiBusController, AsyncSpiBusDevice};
@@ -20,11 +20,21 @@
 //!
 //! There are several options for working with the SPI peripheral in slave mode,
 //! but the code currently only supports single transfers (not segmented
-//! transfers), full duplex, single bit (not dual or quad SPI), and DMA mode
-//! (not CPU mode). It also does not support blocking operations, as the actual
-//! transfer is controlled by the SPI master; if these are necessary,
-//! then the DmaTransfer trait instance can be wait()ed on or polled for
-//! is_done().
+//! transfers), full duplex and single bit (not dual or quad SPI).
+//!
+//! Short transfers, e.g. register style accesses, can be handled by the CPU
+//! through the peripheral's data buffer (64 bytes, 72 bytes on ESP32-S2)
+//! without setting up DMA. [Spi::transfer] arms the slave and blocks until the
+//! master deasserts CS:
+//!
+//! ```rust
+//! let mut rx = [0u8; 4];
+//! let count = spi.transfer(&[0xde, 0xad, 0xbe, 0xef], &mut rx).unwrap();
+//! ```
+//!
+//! Larger transfers use DMA. As the actual transfer is controlled by the SPI
+//! master, the DmaTransfer trait instance can be wait()ed on or polled for
+//! is_done():
 //!
 //! ```rust
 //! let dma = Gdma::new(peripherals.DMA);
@@ -45,6 +55,13 @@
 //! // When the master sends enough clock pulses, is_done() will be true.
 //! (tx_buf, rx_buf, spi) = transfer.wait();
 //! ```
+//!
+//! With a DMA channel configured for async operation, `SpiDma::read`,
+//! `SpiDma::write` and `SpiDma::transfer` complete as soon as the master
+//! deasserts CS, even if fewer bytes than the buffers can hold were clocked.
+//! All transfer methods return, or make available via
+//! `SpiDma::received_bytes`, the number of bytes the master actually clocked.
+//! Dropping an async transfer before it completes stops the DMA.
 
 use core::marker::PhantomData;
 
@@ -52,6 +69,7 @@ use super::{Error, FullDuplexMode, SpiMode};
 use crate::{
     dma::{DmaPeripheral, Rx, Tx},
     gpio::{InputPin, InputSignal, OutputPin, OutputSignal},
+    interrupt::InterruptHandler,
     peripheral::{Peripheral, PeripheralRef},
     peripherals::spi2::RegisterBlock,
     private,
@@ -71,6 +89,12 @@ pub mod prelude {
 
 const MAX_DMA_SIZE: usize = 32768 - 32;
 
+/// The size of the data buffer used for transfers without DMA
+#[cfg(not(esp32s2))]
+const FIFO_SIZE: usize = 64;
+#[cfg(esp32s2)]
+const FIFO_SIZE: usize = 72;
+
 /// SPI peripheral driver
 pub struct Spi<'d, T, M> {
     spi: PeripheralRef<'d, T>,
@@ -124,6 +148,42 @@ where
 
         spi
     }
+
+    /// Exchanges data with the master without using DMA.
+    ///
+    /// Loads `write` into the data buffer, waits until the master has run a
+    /// transaction (asserted and deasserted CS) and copies the received data
+    /// into `read`. Both buffers are limited to 64 bytes (72 bytes on
+    /// ESP32-S2). Bytes the master clocks beyond the end of `write` are
+    /// undefined.
+    ///
+    /// Returns the number of bytes the master clocked, which can be smaller
+    /// or larger than either buffer.
+    pub fn transfer(&mut self, write: &[u8], read: &mut [u8]) -> Result<usize, Error> {
+        if write.len() > FIFO_SIZE || read.len() > FIFO_SIZE {
+            return Err(Error::FifoSizeExeeded);
+        }
+
+        self.spi.start_fifo_transfer(write);
+        self.spi.flush()?;
+
+        let count = self.spi.received_bytes();
+        let len = usize::min(count, read.len());
+        self.spi.read_fifo(&mut read[..len]);
+
+        Ok(count)
+    }
+
+    /// Receives data from the master without using DMA, see
+    /// [Spi::transfer].
+    pub fn read(&mut self, words: &mut [u8]) -> Result<usize, Error> {
+        self.transfer(&[], words)
+    }
+
+    /// Sends data to the master without using DMA, see [Spi::transfer].
+    pub fn write(&mut self, words: &[u8]) -> Result<usize, Error> {
+        self.transfer(words, &mut [])
+    }
 }
 
 pub mod dma {
@@ -292,6 +352,15 @@ pub mod dma {
         C::P: SpiPeripheral,
         DmaMode: Mode,
     {
+        /// Returns the number of bytes the master clocked in the last
+        /// transaction.
+        ///
+        /// Only valid once the transaction is complete, i.e. the master has
+        /// deasserted CS.
+        pub fn received_bytes(&self) -> usize {
+            self.spi.received_bytes()
+        }
+
         /// Register a buffer for a DMA write.
         ///
         /// This will return a [SpiDmaTransferTx] owning the buffer(s) and the
@@ -384,6 +453,167 @@ pub mod dma {
     }
 }
 
+#[cfg(feature = "async")]
+mod asynch {
+    use core::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use embassy_sync::waitqueue::AtomicWaker;
+    use procmacros::handler;
+
+    use super::{dma::SpiDma, *};
+    use crate::dma::{ChannelTypes, SpiPeripheral};
+
+    const NUM_SPI: usize = if cfg!(spi3) { 2 } else { 1 };
+
+    const INIT: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; NUM_SPI] = [INIT; NUM_SPI];
+
+    /// Resolves when the master deasserts CS. The interrupt handler disables
+    /// the transaction done interrupt, which is how completion is detected.
+    pub(crate) struct TransDoneFuture<'a, T>
+    where
+        T: Instance,
+    {
+        instance: &'a mut T,
+    }
+
+    impl<'a, T> TransDoneFuture<'a, T>
+    where
+        T: Instance,
+    {
+        pub fn new(instance: &'a mut T) -> Self {
+            let handler = match instance.spi_num() {
+                2 => spi2_handler,
+                #[cfg(spi3)]
+                3 => spi3_handler,
+                _ => panic!("Illegal SPI instance"),
+            };
+            instance.set_interrupt_handler(handler);
+            instance.listen_trans_done(true);
+
+            Self { instance }
+        }
+    }
+
+    impl<'a, T> core::future::Future for TransDoneFuture<'a, T>
+    where
+        T: Instance,
+    {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+            WAKERS[self.instance.spi_num() as usize - 2].register(ctx.waker());
+
+            if self.instance.is_listening_trans_done() {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        }
+    }
+
+    impl<'a, T> Drop for TransDoneFuture<'a, T>
+    where
+        T: Instance,
+    {
+        fn drop(&mut self) {
+            if self.instance.is_listening_trans_done() {
+                // Dropped before the master deasserted CS, make sure the DMA
+                // stops accessing the buffers
+                self.instance.listen_trans_done(false);
+                self.instance.abort_dma();
+            }
+        }
+    }
+
+    impl<'d, T, C> SpiDma<'d, T, C, crate::Async>
+    where
+        T: InstanceDma<C::Tx<'d>, C::Rx<'d>>,
+        C: ChannelTypes,
+        C::P: SpiPeripheral,
+    {
+        /// Receives data from the master via DMA.
+        ///
+        /// Completes when the master deasserts CS and returns the number of
+        /// bytes the master clocked. At most 32736 bytes can be received.
+        pub async fn read(&mut self, words: &mut [u8]) -> Result<usize, Error> {
+            if words.len() > MAX_DMA_SIZE {
+                return Err(Error::MaxDmaTransferSizeExceeded);
+            }
+
+            unsafe {
+                self.spi.start_read_bytes_dma(
+                    words.as_mut_ptr(),
+                    words.len(),
+                    &mut self.channel.rx,
+                )?;
+            }
+            TransDoneFuture::new(&mut *self.spi).await;
+
+            Ok(self.spi.received_bytes())
+        }
+
+        /// Sends data to the master via DMA.
+        ///
+        /// Completes when the master deasserts CS and returns the number of
+        /// bytes the master clocked. At most 32736 bytes can be sent.
+        pub async fn write(&mut self, words: &[u8]) -> Result<usize, Error> {
+            if words.len() > MAX_DMA_SIZE {
+                return Err(Error::MaxDmaTransferSizeExceeded);
+            }
+
+            self.spi
+                .start_write_bytes_dma(words.as_ptr(), words.len(), &mut self.channel.tx)?;
+            TransDoneFuture::new(&mut *self.spi).await;
+
+            Ok(self.spi.received_bytes())
+        }
+
+        /// Exchanges data with the master via DMA.
+        ///
+        /// Completes when the master deasserts CS and returns the number of
+        /// bytes the master clocked. At most 32736 bytes can be sent and
+        /// received.
+        pub async fn transfer(&mut self, write: &[u8], read: &mut [u8]) -> Result<usize, Error> {
+            if write.len() > MAX_DMA_SIZE || read.len() > MAX_DMA_SIZE {
+                return Err(Error::MaxDmaTransferSizeExceeded);
+            }
+
+            unsafe {
+                self.spi.start_transfer_dma(
+                    write.as_ptr(),
+                    write.len(),
+                    read.as_mut_ptr(),
+                    read.len(),
+                    &mut self.channel.tx,
+                    &mut self.channel.rx,
+                )?;
+            }
+            TransDoneFuture::new(&mut *self.spi).await;
+
+            Ok(self.spi.received_bytes())
+        }
+    }
+
+    #[handler]
+    fn spi2_handler() {
+        unsafe { crate::peripherals::SPI2::steal() }.listen_trans_done(false);
+
+        WAKERS[0].wake();
+    }
+
+    #[cfg(spi3)]
+    #[handler]
+    fn spi3_handler() {
+        unsafe { crate::peripherals::SPI3::steal() }.listen_trans_done(false);
+
+        WAKERS[1].wake();
+    }
+}
+
 #[doc(hidden)]
 pub trait InstanceDma<TX, RX>: Instance
 where
@@ -656,6 +886,8 @@ pub trait Instance: private::Sealed {
 
     fn spi_num(&self) -> u8;
 
+    fn set_interrupt_handler(&mut self, handler: InterruptHandler);
+
     /// Initialize for full-duplex 1 bit mode
     fn init(&mut self) {
         let reg_block = self.register_block();
@@ -778,6 +1010,108 @@ pub trait Instance: private::Sealed {
         Ok(())
     }
 
+    /// Loads the data buffer and arms the slave for a transfer without DMA
+    fn start_fifo_transfer(&mut self, words: &[u8]) {
+        let reg_block = self.register_block();
+
+        self.disable_dma();
+        reset_dma_before_usr_cmd(reg_block);
+
+        for (chunk, w_reg) in words.chunks(4).zip(reg_block.w_iter()) {
+            let mut bytes = [0u8; 4];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            w_reg.write(|w| w.buf().set(u32::from_le_bytes(bytes)));
+        }
+
+        reg_block
+            .user()
+            .modify(|_, w| w.usr_miso().set_bit().usr_mosi().set_bit());
+
+        self.setup_for_flush();
+        reg_block.cmd().modify(|_, w| w.usr().set_bit());
+    }
+
+    /// Copies data received without DMA out of the data buffer
+    fn read_fifo(&self, words: &mut [u8]) {
+        for (chunk, w_reg) in words.chunks_mut(4).zip(self.register_block().w_iter()) {
+            let bytes = w_reg.read().bits().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    /// Number of whole bytes the master clocked in the last transaction
+    #[cfg(not(esp32s2))]
+    fn received_bytes(&self) -> usize {
+        self.register_block().slave1().read().data_bitlen().bits() as usize / 8
+    }
+
+    /// Number of whole bytes the master clocked in the last transaction
+    #[cfg(esp32s2)]
+    fn received_bytes(&self) -> usize {
+        self.register_block()
+            .slv_rd_byte()
+            .read()
+            .data_bytelen()
+            .bits() as usize
+    }
+
+    #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+    fn disable_dma(&self) {
+        self.register_block()
+            .dma_conf()
+            .modify(|_, w| w.dma_tx_ena().clear_bit().dma_rx_ena().clear_bit());
+    }
+
+    #[cfg(esp32s2)]
+    fn disable_dma(&self) {
+        // for non GDMA the DMA channel only gets connected in
+        // `assign_tx_device` / `assign_rx_device`
+    }
+
+    /// Enables or disables the transaction done interrupt
+    #[cfg(not(esp32s2))]
+    fn listen_trans_done(&self, enable: bool) {
+        self.register_block()
+            .dma_int_ena()
+            .modify(|_, w| w.trans_done().bit(enable));
+    }
+
+    /// Enables or disables the transaction done interrupt
+    #[cfg(esp32s2)]
+    fn listen_trans_done(&self, enable: bool) {
+        self.register_block()
+            .slave()
+            .modify(|_, w| w.int_trans_done_en().bit(enable));
+    }
+
+    #[cfg(not(esp32s2))]
+    fn is_listening_trans_done(&self) -> bool {
+        self.register_block()
+            .dma_int_ena()
+            .read()
+            .trans_done()
+            .bit_is_set()
+    }
+
+    #[cfg(esp32s2)]
+    fn is_listening_trans_done(&self) -> bool {
+        self.register_block()
+            .slave()
+            .read()
+            .int_trans_done_en()
+            .bit_is_set()
+    }
+
+    /// Stops an ongoing DMA transfer, the DMA does not access the buffers
+    /// afterwards
+    fn abort_dma(&self) {
+        let reg_block = self.register_block();
+
+        self.disable_dma();
+        reset_dma_before_usr_cmd(reg_block);
+        reset_dma_before_load_dma_dscr(reg_block);
+    }
+
     // Clear the transaction-done interrupt flag so flush() can work properly. Not
     // used in DMA mode.
     fn setup_for_flush(&self) {
@@ -828,6 +1162,12 @@ impl Instance for crate::peripherals::SPI2 {
     fn spi_num(&self) -> u8 {
         2
     }
+
+    #[inline(always)]
+    fn set_interrupt_handler(&mut self, handler: InterruptHandler) {
+        self.bind_spi2_interrupt(handler.handler());
+        crate::interrupt::enable(crate::peripherals::Interrupt::SPI2, handler.priority()).unwrap();
+    }
 }
 
 #[cfg(any(esp32s2, esp32s3))]
@@ -866,6 +1206,12 @@ impl Instance for crate::peripherals::SPI2 {
     fn spi_num(&self) -> u8 {
         2
     }
+
+    #[inline(always)]
+    fn set_interrupt_handler(&mut self, handler: InterruptHandler) {
+        self.bind_spi2_interrupt(handler.handler());
+        crate::interrupt::enable(crate::peripherals::Interrupt::SPI2, handler.priority()).unwrap();
+    }
 }
 
 #[cfg(any(esp32s2, esp32s3))]
@@ -904,4 +1250,10 @@ impl Instance for crate::peripherals::SPI3 {
     fn spi_num(&self) -> u8 {
         3
     }
+
+    #[inline(always)]
+    fn set_interrupt_handler(&mut self, handler: InterruptHandler) {
+        self.bind_spi3_interrupt(handler.handler());
+        crate::interrupt::enable(crate::peripherals::Interrupt::SPI3, handler.priority()).unwrap();
+    }
 }
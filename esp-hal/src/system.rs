@@ -97,6 +97,8 @@ pub enum Peripheral {
     Trace0,
     #[cfg(lcd_cam)]
     LcdCam,
+    #[cfg(all(uhci0, gdma))]
+    Uhci0,
 }
 
 /// The `DPORT`/`PCR`/`SYSTEM` peripheral split into its different logical
@@ -478,6 +480,11 @@ impl PeripheralClockControl {
                 perip_clk_en1.modify(|_, w| w.lcd_cam_clk_en().set_bit());
                 perip_rst_en1.modify(|_, w| w.lcd_cam_rst().clear_bit());
             }
+            #[cfg(all(uhci0, gdma))]
+            Peripheral::Uhci0 => {
+                perip_clk_en0.modify(|_, w| w.uhci0_clk_en().set_bit());
+                perip_rst_en0.modify(|_, w| w.uhci0_rst().clear_bit());
+            }
         });
     }
 
@@ -682,6 +689,11 @@ impl PeripheralClockControl {
                 perip_rst_en1.modify(|_, w| w.lcd_cam_rst().set_bit());
                 perip_rst_en1.modify(|_, w| w.lcd_cam_rst().clear_bit());
             }
+            #[cfg(all(uhci0, gdma))]
+            Peripheral::Uhci0 => {
+                perip_rst_en0.modify(|_, w| w.uhci0_rst().set_bit());
+                perip_rst_en0.modify(|_, w| w.uhci0_rst().clear_bit());
+            }
         });
     }
 }
@@ -788,6 +800,14 @@ impl PeripheralClockControl {
                 system
                     .twai1_conf()
                     .modify(|_, w| w.twai1_rst_en().clear_bit());
+
+                // use Xtal clk-src
+                system.twai1_func_clk_conf().modify(|_, w| {
+                    w.twai1_func_clk_en()
+                        .set_bit()
+                        .twai1_func_clk_sel()
+                        .variant(false)
+                });
             }
             #[cfg(aes)]
             Peripheral::Aes => {
@@ -874,6 +894,13 @@ impl PeripheralClockControl {
                     .parl_io_conf()
                     .modify(|_, w| w.parl_rst_en().clear_bit());
             }
+            #[cfg(all(uhci0, gdma))]
+            Peripheral::Uhci0 => {
+                system.uhci_conf().modify(|_, w| w.uhci_clk_en().set_bit());
+                system
+                    .uhci_conf()
+                    .modify(|_, w| w.uhci_rst_en().clear_bit());
+            }
             #[cfg(hmac)]
             Peripheral::Hmac => {
                 system.hmac_conf().modify(|_, w| w.hmac_clk_en().set_bit());
@@ -1066,6 +1093,13 @@ impl PeripheralClockControl {
                     .parl_io_conf()
                     .modify(|_, w| w.parl_rst_en().clear_bit());
             }
+            #[cfg(all(uhci0, gdma))]
+            Peripheral::Uhci0 => {
+                system.uhci_conf().modify(|_, w| w.uhci_rst_en().set_bit());
+                system
+                    .uhci_conf()
+                    .modify(|_, w| w.uhci_rst_en().clear_bit());
+            }
             #[cfg(hmac)]
             Peripheral::Hmac => {
                 system.hmac_conf().modify(|_, w| w.hmac_rst_en().set_bit());
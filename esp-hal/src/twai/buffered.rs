@@ -0,0 +1,398 @@
+//! # Interrupt driven TWAI receive and transmit queues
+//!
+//! ## Overview
+//! The hardware receive FIFO of the TWAI controller is only 64 bytes long and
+//! overflows quickly during bursts of bus traffic. [BufferedTwai] moves every
+//! received frame into a software ring buffer from the TWAI interrupt handler,
+//! capturing the time of reception, and queues frames to be transmitted while
+//! the transmit buffer of the controller is occupied.
+//!
+//! The storage for both queues is provided by a [TwaiBuffers] whose depths are
+//! chosen at compile time.
+//!
+//! ## Example
+//! ```no_run
+//! static mut BUFFERS: twai::buffered::TwaiBuffers<64, 8> = twai::buffered::TwaiBuffers::new();
+//!
+//! let can = can_config.start();
+//! let mut can = can.into_buffered(unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS) });
+//!
+//! loop {
+//!     let received = block!(can.receive()).unwrap();
+//!     println!("{:?} received at {}", received.frame, received.timestamp);
+//!
+//!     block!(can.transmit(&received.frame)).unwrap();
+//! }
+//! ```
+
+use core::{cell::RefCell, mem::MaybeUninit};
+
+use critical_section::Mutex;
+use procmacros::handler;
+
+use super::*;
+#[cfg(twai0)]
+use crate::peripherals::TWAI0;
+#[cfg(twai1)]
+use crate::peripherals::TWAI1;
+
+/// A received frame together with the time it was captured at.
+#[derive(Clone, Copy, Debug)]
+pub struct TimestampedFrame {
+    /// The received frame.
+    pub frame: EspTwaiFrame,
+    /// The time the frame was moved out of the hardware FIFO, see
+    /// [crate::time::current_time].
+    pub timestamp: fugit::Instant<u64, 1, 1_000_000>,
+}
+
+/// Storage for the receive and transmit queues of a [BufferedTwai].
+///
+/// `RX` is the number of received frames and `TX` the number of frames
+/// waiting for transmission which can be held.
+pub struct TwaiBuffers<const RX: usize, const TX: usize> {
+    rx: [MaybeUninit<TimestampedFrame>; RX],
+    tx: [MaybeUninit<EspTwaiFrame>; TX],
+}
+
+impl<const RX: usize, const TX: usize> TwaiBuffers<RX, TX> {
+    /// Create new, empty queue storage.
+    pub const fn new() -> Self {
+        Self {
+            rx: [MaybeUninit::uninit(); RX],
+            tx: [MaybeUninit::uninit(); TX],
+        }
+    }
+}
+
+impl<const RX: usize, const TX: usize> Default for TwaiBuffers<RX, TX> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A fixed capacity FIFO on top of storage owned by the user.
+struct RingBuffer<E> {
+    buffer: *mut MaybeUninit<E>,
+    capacity: usize,
+    head: usize,
+    len: usize,
+}
+
+// The buffers are only accessed from within a critical section.
+unsafe impl<E> Send for RingBuffer<E> {}
+
+impl<E: Copy> RingBuffer<E> {
+    fn new(buffer: &'static mut [MaybeUninit<E>]) -> Self {
+        Self {
+            buffer: buffer.as_mut_ptr(),
+            capacity: buffer.len(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        if self.len == self.capacity {
+            return Err(element);
+        }
+
+        let index = (self.head + self.len) % self.capacity;
+        unsafe { self.buffer.add(index).write(MaybeUninit::new(element)) };
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let element = unsafe { self.buffer.add(self.head).read().assume_init() };
+        self.head = (self.head + 1) % self.capacity;
+        self.len -= 1;
+
+        Some(element)
+    }
+}
+
+struct BufferedState {
+    rx: RingBuffer<TimestampedFrame>,
+    tx: RingBuffer<EspTwaiFrame>,
+    rx_dropped: u32,
+    rx_overruns: u32,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_STATE: Mutex<RefCell<Option<BufferedState>>> = Mutex::new(RefCell::new(None));
+static STATE: [Mutex<RefCell<Option<BufferedState>>>; NUM_TWAI] = [NO_STATE; NUM_TWAI];
+
+#[cfg(feature = "async")]
+mod wakers {
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::NUM_TWAI;
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW_AW: AtomicWaker = AtomicWaker::new();
+    pub(super) static RX_WAKERS: [AtomicWaker; NUM_TWAI] = [NEW_AW; NUM_TWAI];
+    pub(super) static TX_WAKERS: [AtomicWaker; NUM_TWAI] = [NEW_AW; NUM_TWAI];
+}
+
+/// An active TWAI peripheral with software receive and transmit queues.
+///
+/// Created by [Twai::into_buffered]. The driver installs its own interrupt
+/// handler for the peripheral.
+pub struct BufferedTwai<'d, T, DM: crate::Mode>
+where
+    T: OperationInstance,
+{
+    _twai: Twai<'d, T, DM>,
+}
+
+impl<'d, T, DM> Twai<'d, T, DM>
+where
+    T: OperationInstance,
+    DM: crate::Mode,
+{
+    /// Move received frames into a software queue from the interrupt handler
+    /// and queue frames to be transmitted while the controller is busy.
+    pub fn into_buffered<const RX: usize, const TX: usize>(
+        self,
+        buffers: &'static mut TwaiBuffers<RX, TX>,
+    ) -> BufferedTwai<'d, T, DM> {
+        assert!(RX > 0 && TX > 0);
+
+        let TwaiBuffers { rx, tx } = buffers;
+
+        critical_section::with(|cs| {
+            STATE[T::NUMBER].borrow_ref_mut(cs).replace(BufferedState {
+                rx: RingBuffer::new(rx),
+                tx: RingBuffer::new(tx),
+                rx_dropped: 0,
+                rx_overruns: 0,
+            });
+        });
+
+        let interrupt = T::buffered_handler();
+        unsafe {
+            crate::interrupt::bind_interrupt(T::INTERRUPT, interrupt.handler());
+            crate::interrupt::enable(T::INTERRUPT, interrupt.priority()).unwrap();
+        }
+
+        T::enable_interrupts();
+
+        BufferedTwai { _twai: self }
+    }
+}
+
+impl<'d, T, DM> BufferedTwai<'d, T, DM>
+where
+    T: OperationInstance,
+    DM: crate::Mode,
+{
+    fn with_state<R>(f: impl FnOnce(&mut BufferedState) -> R) -> R {
+        critical_section::with(|cs| {
+            let mut state = STATE[T::NUMBER].borrow_ref_mut(cs);
+            f(state.as_mut().unwrap())
+        })
+    }
+
+    /// Queue a frame for transmission.
+    ///
+    /// The frame is handed to the controller right away if it is idle,
+    /// otherwise it is sent from the interrupt handler once the preceding
+    /// frames were transmitted. Returns [nb::Error::WouldBlock] if the transmit
+    /// queue is full.
+    pub fn transmit(&mut self, frame: &EspTwaiFrame) -> nb::Result<(), EspTwaiError> {
+        let register_block = T::register_block();
+
+        Self::with_state(|state| {
+            let status = register_block.status().read();
+
+            // Check that the peripheral is not in a bus off state.
+            if status.bus_off_st().bit_is_set() {
+                return Err(nb::Error::Other(EspTwaiError::BusOff));
+            }
+
+            if state.tx.len == 0 && status.tx_buf_st().bit_is_set() {
+                T::write_frame(frame);
+                return Ok(());
+            }
+
+            state.tx.push(*frame).map_err(|_| nb::Error::WouldBlock)
+        })
+    }
+
+    /// Take the oldest received frame out of the receive queue.
+    pub fn receive(&mut self) -> nb::Result<TimestampedFrame, EspTwaiError> {
+        Self::with_state(|state| {
+            if let Some(frame) = state.rx.pop() {
+                return Ok(frame);
+            }
+
+            if T::register_block()
+                .status()
+                .read()
+                .bus_off_st()
+                .bit_is_set()
+            {
+                return Err(nb::Error::Other(EspTwaiError::BusOff));
+            }
+
+            Err(nb::Error::WouldBlock)
+        })
+    }
+
+    /// Number of received frames waiting in the receive queue.
+    pub fn rx_queue_len(&self) -> usize {
+        Self::with_state(|state| state.rx.len)
+    }
+
+    /// Number of frames waiting in the transmit queue.
+    pub fn tx_queue_len(&self) -> usize {
+        Self::with_state(|state| state.tx.len)
+    }
+
+    /// Number of received frames dropped because the receive queue was full.
+    pub fn rx_dropped_count(&self) -> u32 {
+        Self::with_state(|state| state.rx_dropped)
+    }
+
+    /// Number of times frames were lost because the hardware receive FIFO
+    /// overran.
+    pub fn rx_overrun_count(&self) -> u32 {
+        Self::with_state(|state| state.rx_overruns)
+    }
+
+    /// Reset the dropped frame and overrun counters.
+    pub fn reset_counters(&mut self) {
+        Self::with_state(|state| {
+            state.rx_dropped = 0;
+            state.rx_overruns = 0;
+        })
+    }
+
+    /// Discard all frames in the receive queue.
+    pub fn clear_receive_queue(&mut self) {
+        Self::with_state(|state| while state.rx.pop().is_some() {})
+    }
+
+    /// Discard all frames which were not handed to the controller yet.
+    pub fn clear_transmit_queue(&mut self) {
+        Self::with_state(|state| while state.tx.pop().is_some() {})
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'d, T, DM> BufferedTwai<'d, T, DM>
+where
+    T: OperationInstance,
+    DM: crate::Mode,
+{
+    /// Queue a frame for transmission, waiting for space in the transmit
+    /// queue.
+    pub async fn transmit_async(&mut self, frame: &EspTwaiFrame) -> Result<(), EspTwaiError> {
+        core::future::poll_fn(|cx| {
+            wakers::TX_WAKERS[T::NUMBER].register(cx.waker());
+
+            match self.transmit(frame) {
+                Ok(()) => core::task::Poll::Ready(Ok(())),
+                Err(nb::Error::Other(err)) => core::task::Poll::Ready(Err(err)),
+                Err(nb::Error::WouldBlock) => core::task::Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Wait for a frame to be received.
+    pub async fn receive_async(&mut self) -> Result<TimestampedFrame, EspTwaiError> {
+        core::future::poll_fn(|cx| {
+            wakers::RX_WAKERS[T::NUMBER].register(cx.waker());
+
+            match self.receive() {
+                Ok(frame) => core::task::Poll::Ready(Ok(frame)),
+                Err(nb::Error::Other(err)) => core::task::Poll::Ready(Err(err)),
+                Err(nb::Error::WouldBlock) => core::task::Poll::Pending,
+            }
+        })
+        .await
+    }
+}
+
+impl<'d, T, DM> Drop for BufferedTwai<'d, T, DM>
+where
+    T: OperationInstance,
+    DM: crate::Mode,
+{
+    fn drop(&mut self) {
+        T::disable_interrupts();
+
+        critical_section::with(|cs| {
+            STATE[T::NUMBER].borrow_ref_mut(cs).take();
+        });
+    }
+}
+
+fn handle_interrupt<T: OperationInstance>() {
+    let timestamp = crate::time::current_time();
+
+    let register_block = T::register_block();
+    let intr_status = T::read_interrupts();
+
+    T::record_alerts(intr_status);
+
+    critical_section::with(|cs| {
+        let mut state = STATE[T::NUMBER].borrow_ref_mut(cs);
+        let Some(state) = state.as_mut() else {
+            return;
+        };
+
+        if intr_status & OVERRUN_INT != 0 {
+            state.rx_overruns = state.rx_overruns.wrapping_add(1);
+            register_block.cmd().write(|w| w.clr_overrun().set_bit());
+        }
+
+        // Drain the hardware FIFO, every frame read is released.
+        while register_block.status().read().rx_buf_st().bit_is_set() {
+            let frame = T::read_frame();
+            if !T::software_filter_accepts(&frame) {
+                continue;
+            }
+
+            if state
+                .rx
+                .push(TimestampedFrame { frame, timestamp })
+                .is_err()
+            {
+                state.rx_dropped = state.rx_dropped.wrapping_add(1);
+            }
+        }
+
+        let status = register_block.status().read();
+        if status.tx_buf_st().bit_is_set() && status.bus_off_st().bit_is_clear() {
+            if let Some(frame) = state.tx.pop() {
+                T::write_frame(&frame);
+            }
+        }
+    });
+
+    #[cfg(feature = "async")]
+    {
+        wakers::RX_WAKERS[T::NUMBER].wake();
+        wakers::TX_WAKERS[T::NUMBER].wake();
+    }
+}
+
+#[cfg(twai0)]
+#[handler]
+pub(super) fn twai0() {
+    handle_interrupt::<TWAI0>();
+}
+
+#[cfg(twai1)]
+#[handler]
+pub(super) fn twai1() {
+    handle_interrupt::<TWAI1>();
+}
@@ -2,10 +2,29 @@
 //!
 //! These are acceptance filters that limit which packets are received by the
 //! TWAI peripheral.
+//!
+//! Besides the filters mapping directly to the hardware, a [FilterSet]
+//! describes the identifiers an application is interested in. The best
+//! fitting hardware filter is computed from it, and frames that pass the
+//! hardware filter but aren't part of the set are discarded in software.
+//!
+//! ```no_run
+//! // Receive the J1939 engine speed (EEC1) and the CANopen SDO responses of
+//! // node 5.
+//! let filters = twai::filter::FilterSet::new()
+//!     .with(twai::filter::IdFilter::j1939_pgn(0xF004))
+//!     .with(twai::filter::IdFilter::canopen_cob_id(0b1011, 5));
+//!
+//! let coverage = can_config.set_filter_set(&filters);
+//! println!(
+//!     "{} unwanted ids pass the hardware filter",
+//!     coverage.false_positives()
+//! );
+//! ```
 
-use super::{ExtendedId, StandardId};
+use super::{EspTwaiFrame, ExtendedId, Id, StandardId};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterType {
     Single,
     Dual,
@@ -522,3 +541,432 @@ impl Filter for DualExtendedFilter {
         self.raw
     }
 }
+
+/// The maximum number of entries of a [FilterSet].
+pub const MAX_FILTER_SET_ENTRIES: usize = 16;
+
+const STANDARD_ID_BITS: u32 = 11;
+const EXTENDED_ID_BITS: u32 = 29;
+
+/// A set of identifiers which should be received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdFilter {
+    /// All standard ids in the inclusive range.
+    StandardRange(StandardId, StandardId),
+    /// All extended ids in the inclusive range.
+    ExtendedRange(ExtendedId, ExtendedId),
+    /// All standard ids whose bits selected by `mask` equal those of `code`.
+    StandardMasked {
+        /// The bit values to match.
+        code: u16,
+        /// Set bits select the bits of the id to compare.
+        mask: u16,
+    },
+    /// All extended ids whose bits selected by `mask` equal those of `code`.
+    ExtendedMasked {
+        /// The bit values to match.
+        code: u32,
+        /// Set bits select the bits of the id to compare.
+        mask: u32,
+    },
+}
+
+impl IdFilter {
+    /// Match a single id.
+    pub fn id(id: Id) -> Self {
+        match id {
+            Id::Standard(id) => Self::StandardRange(id, id),
+            Id::Extended(id) => Self::ExtendedRange(id, id),
+        }
+    }
+
+    /// Match every frame of a J1939 parameter group, from any source address.
+    ///
+    /// For PDU1 format parameter groups (PDU format below 240) frames sent to
+    /// any destination address are matched.
+    pub fn j1939_pgn(pgn: u32) -> Self {
+        let pgn = pgn & 0x3_ffff;
+        let pdu_format = (pgn >> 8) & 0xff;
+
+        // The PGN occupies bits 8 to 25 of the identifier. For PDU1 the lowest
+        // byte of the PGN is the destination address.
+        let pgn_mask = if pdu_format < 240 { 0x3_ff00 } else { 0x3_ffff };
+
+        Self::ExtendedMasked {
+            code: (pgn & pgn_mask) << 8,
+            mask: pgn_mask << 8,
+        }
+    }
+
+    /// Match a CANopen COB-ID made up of a 4 bit function code and a 7 bit
+    /// node id.
+    pub fn canopen_cob_id(function_code: u8, node_id: u8) -> Self {
+        Self::StandardMasked {
+            code: ((function_code as u16 & 0xf) << 7) | (node_id as u16 & 0x7f),
+            mask: 0x7ff,
+        }
+    }
+
+    /// Match a CANopen function code sent by any node.
+    pub fn canopen_function_code(function_code: u8) -> Self {
+        Self::StandardMasked {
+            code: (function_code as u16 & 0xf) << 7,
+            mask: 0x780,
+        }
+    }
+
+    /// Check if this matches the given id.
+    pub fn matches(&self, id: Id) -> bool {
+        match (*self, id) {
+            (Self::StandardRange(first, last), Id::Standard(id)) => {
+                (first.as_raw()..=last.as_raw()).contains(&id.as_raw())
+            }
+            (Self::ExtendedRange(first, last), Id::Extended(id)) => {
+                (first.as_raw()..=last.as_raw()).contains(&id.as_raw())
+            }
+            (Self::StandardMasked { code, mask }, Id::Standard(id)) => {
+                (id.as_raw() ^ code) & mask == 0
+            }
+            (Self::ExtendedMasked { code, mask }, Id::Extended(id)) => {
+                (id.as_raw() ^ code) & mask == 0
+            }
+            _ => false,
+        }
+    }
+
+    fn is_extended(&self) -> bool {
+        matches!(self, Self::ExtendedRange(..) | Self::ExtendedMasked { .. })
+    }
+
+    /// The number of ids matched.
+    fn len(&self) -> u32 {
+        match *self {
+            Self::StandardRange(first, last) => {
+                (last.as_raw() as u32).saturating_sub(first.as_raw() as u32) + 1
+            }
+            Self::ExtendedRange(first, last) => last.as_raw().saturating_sub(first.as_raw()) + 1,
+            Self::StandardMasked { mask, .. } => {
+                1 << (STANDARD_ID_BITS - (mask as u32 & 0x7ff).count_ones())
+            }
+            Self::ExtendedMasked { mask, .. } => {
+                1 << (EXTENDED_ID_BITS - (mask & 0x1fff_ffff).count_ones())
+            }
+        }
+    }
+
+    /// The smallest code and mask pair matching all ids of this filter.
+    fn pattern(&self) -> Pattern {
+        let (first, last, width) = match *self {
+            Self::StandardRange(first, last) => (
+                first.as_raw() as u32,
+                last.as_raw() as u32,
+                STANDARD_ID_BITS,
+            ),
+            Self::ExtendedRange(first, last) => (first.as_raw(), last.as_raw(), EXTENDED_ID_BITS),
+            Self::StandardMasked { code, mask } => {
+                let mask = mask as u32 & 0x7ff;
+                return Pattern {
+                    code: code as u32 & mask,
+                    mask,
+                };
+            }
+            Self::ExtendedMasked { code, mask } => {
+                let mask = mask & 0x1fff_ffff;
+                return Pattern {
+                    code: code & mask,
+                    mask,
+                };
+            }
+        };
+
+        // Keep the bits above the highest bit differing between both ends of the
+        // range.
+        let differing = first ^ last;
+        let mask = if differing == 0 {
+            (1 << width) - 1
+        } else {
+            !((1u32 << (32 - differing.leading_zeros())) - 1) & ((1 << width) - 1)
+        };
+
+        Pattern {
+            code: first & mask,
+            mask,
+        }
+    }
+}
+
+/// A code and mask pair, set bits in the mask select the bits to compare.
+#[derive(Debug, Clone, Copy)]
+struct Pattern {
+    code: u32,
+    mask: u32,
+}
+
+impl Pattern {
+    fn merge(self, other: Pattern) -> Pattern {
+        let mask = self.mask & other.mask & !(self.code ^ other.code);
+        Pattern {
+            code: self.code & mask,
+            mask,
+        }
+    }
+
+    /// The number of ids of the given width matched.
+    fn len(&self, width: u32) -> u32 {
+        1 << (width - self.mask.count_ones())
+    }
+
+    /// The number of ids of the given width matched by both patterns.
+    fn overlap(&self, other: &Pattern, width: u32) -> u32 {
+        if (self.code ^ other.code) & self.mask & other.mask != 0 {
+            0
+        } else {
+            1 << (width - (self.mask | other.mask).count_ones())
+        }
+    }
+}
+
+/// How well a hardware filter computed from a [FilterSet] fits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FilterCoverage {
+    /// The number of ids requested by the set, assuming its entries don't
+    /// overlap.
+    pub requested: u32,
+    /// The number of ids of the requested format accepted by the hardware
+    /// filter.
+    pub accepted: u32,
+}
+
+impl FilterCoverage {
+    /// The number of ids passing the hardware filter which are not part of
+    /// the set.
+    pub fn false_positives(&self) -> u32 {
+        self.accepted.saturating_sub(self.requested)
+    }
+}
+
+/// An acceptance filter computed from a [FilterSet].
+///
+/// Depending on the set this is either a single or a dual filter, see
+/// [ComputedFilter::filter_type]. As the type is only known at runtime, this
+/// doesn't implement [Filter].
+#[derive(Debug, Clone, Copy)]
+pub struct ComputedFilter {
+    raw: [u8; 8],
+    filter_type: FilterType,
+}
+
+impl ComputedFilter {
+    /// The type of the filter.
+    pub fn filter_type(&self) -> FilterType {
+        self.filter_type
+    }
+
+    /// Get the register level representation of the filter.
+    pub fn to_registers(&self) -> [u8; 8] {
+        self.raw
+    }
+}
+
+/// The identifiers an application wants to receive.
+///
+/// The set is turned into the best fitting single or dual acceptance filter
+/// by [FilterSet::hardware_filter], and can be used to discard the frames
+/// which still pass the hardware filter with [FilterSet::matches].
+#[derive(Debug, Clone, Copy)]
+pub struct FilterSet {
+    entries: [Option<IdFilter>; MAX_FILTER_SET_ENTRIES],
+    len: usize,
+}
+
+impl Default for FilterSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FilterSet {
+    /// Create an empty set, matching no frames.
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; MAX_FILTER_SET_ENTRIES],
+            len: 0,
+        }
+    }
+
+    /// Add the ids matched by `filter` to the set.
+    ///
+    /// Panics if the set already holds [MAX_FILTER_SET_ENTRIES] entries.
+    pub fn with(mut self, filter: IdFilter) -> Self {
+        assert!(self.len < MAX_FILTER_SET_ENTRIES, "FilterSet is full");
+
+        self.entries[self.len] = Some(filter);
+        self.len += 1;
+        self
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &IdFilter> {
+        self.entries[..self.len].iter().flatten()
+    }
+
+    /// Check if a frame is part of the set.
+    pub fn matches(&self, frame: &EspTwaiFrame) -> bool {
+        self.iter().any(|filter| filter.matches(frame.id))
+    }
+
+    /// Compute an acceptance filter accepting all ids of the set with few
+    /// false positives.
+    ///
+    /// The dual filter is found by greedily merging the entries whose
+    /// patterns are closest, so it isn't necessarily the best possible one.
+    ///
+    /// Sets mixing standard and extended ids result in a filter accepting
+    /// every frame.
+    pub fn hardware_filter(&self) -> (ComputedFilter, FilterCoverage) {
+        let requested = self.iter().fold(0u32, |acc, f| acc.saturating_add(f.len()));
+
+        let extended = self.iter().filter(|f| f.is_extended()).count();
+        if self.len == 0 || (extended != 0 && extended != self.len) {
+            let filter = SingleExtendedFilter::new_from_code_mask(
+                ExtendedId::ZERO,
+                ExtendedId::ZERO,
+                false,
+                false,
+            );
+            return (
+                ComputedFilter {
+                    raw: filter.raw,
+                    filter_type: FilterType::Single,
+                },
+                FilterCoverage {
+                    requested,
+                    accepted: (1 << STANDARD_ID_BITS) + (1 << EXTENDED_ID_BITS),
+                },
+            );
+        }
+
+        let mut patterns = [Pattern { code: 0, mask: 0 }; MAX_FILTER_SET_ENTRIES];
+        for (pattern, filter) in patterns.iter_mut().zip(self.iter()) {
+            *pattern = filter.pattern();
+        }
+        let patterns = &patterns[..self.len];
+
+        let is_extended = extended != 0;
+        let width = if is_extended {
+            EXTENDED_ID_BITS
+        } else {
+            STANDARD_ID_BITS
+        };
+
+        let single = patterns.iter().copied().reduce(Pattern::merge).unwrap();
+        let mut best_accepted = single.len(width);
+        let mut best_dual = None;
+
+        // The dual extended filter only compares the 16 most significant bits of
+        // the id.
+        let dual_mask = if is_extended {
+            0xffff << (EXTENDED_ID_BITS - 16)
+        } else {
+            u32::MAX
+        };
+
+        let mut groups = [Pattern { code: 0, mask: 0 }; MAX_FILTER_SET_ENTRIES];
+        for (group, pattern) in groups.iter_mut().zip(patterns) {
+            *group = Pattern {
+                code: pattern.code & dual_mask,
+                mask: pattern.mask & dual_mask,
+            };
+        }
+
+        // Repeatedly merge the two groups giving the smallest pattern until two
+        // are left. Trying every split of the entries would take exponential
+        // time.
+        let mut len = patterns.len();
+        while len > 2 {
+            let mut best = (0, 1, u32::MAX);
+            for i in 0..len {
+                for j in i + 1..len {
+                    let merged = groups[i].merge(groups[j]).len(width);
+                    if merged < best.2 {
+                        best = (i, j, merged);
+                    }
+                }
+            }
+
+            let (i, j, _) = best;
+            groups[i] = groups[i].merge(groups[j]);
+            groups[j] = groups[len - 1];
+            len -= 1;
+        }
+
+        if len == 2 {
+            let (first, second) = (groups[0], groups[1]);
+            let accepted = first.len(width) + second.len(width) - first.overlap(&second, width);
+            if accepted < best_accepted {
+                best_accepted = accepted;
+                best_dual = Some((first, second));
+            }
+        }
+
+        let (raw, filter_type) = match (best_dual, is_extended) {
+            (None, false) => (
+                SingleStandardFilter::new_from_code_mask(
+                    StandardId::new(single.code as u16).unwrap(),
+                    StandardId::new(single.mask as u16).unwrap(),
+                    false,
+                    false,
+                    [0, 0],
+                    [0, 0],
+                )
+                .raw,
+                FilterType::Single,
+            ),
+            (None, true) => (
+                SingleExtendedFilter::new_from_code_mask(
+                    ExtendedId::new(single.code).unwrap(),
+                    ExtendedId::new(single.mask).unwrap(),
+                    false,
+                    false,
+                )
+                .raw,
+                FilterType::Single,
+            ),
+            (Some((first, second)), false) => (
+                DualStandardFilter::new_from_code_mask(
+                    StandardId::new(first.code as u16).unwrap(),
+                    StandardId::new(first.mask as u16).unwrap(),
+                    false,
+                    false,
+                    0,
+                    0,
+                    StandardId::new(second.code as u16).unwrap(),
+                    StandardId::new(second.mask as u16).unwrap(),
+                    false,
+                    false,
+                )
+                .raw,
+                FilterType::Dual,
+            ),
+            (Some((first, second)), true) => {
+                let shift = EXTENDED_ID_BITS - 16;
+                (
+                    DualExtendedFilter::new_from_code_mask(
+                        [(first.code >> shift) as u16, (second.code >> shift) as u16],
+                        [(first.mask >> shift) as u16, (second.mask >> shift) as u16],
+                    )
+                    .raw,
+                    FilterType::Dual,
+                )
+            }
+        };
+
+        (
+            ComputedFilter { raw, filter_type },
+            FilterCoverage {
+                requested,
+                accepted: best_accepted,
+            },
+        )
+    }
+}
@@ -76,9 +76,13 @@
 //! }
 //! ```
 
-use core::marker::PhantomData;
+use core::{cell::RefCell, convert::Infallible, marker::PhantomData};
 
-use self::filter::{Filter, FilterType};
+use critical_section::Mutex;
+use enumset::{EnumSet, EnumSetType};
+use portable_atomic::{AtomicBool, AtomicU32, Ordering};
+
+use self::filter::{Filter, FilterCoverage, FilterSet, FilterType};
 use crate::{
     clock::Clocks,
     gpio::{InputPin, InputSignal, OutputPin, OutputSignal},
@@ -88,6 +92,7 @@ use crate::{
     system::{self, PeripheralClockControl},
 };
 
+pub mod buffered;
 pub mod filter;
 
 /// CAN error kind
@@ -585,6 +590,9 @@ impl BaudRate {
     //
     // see https://github.com/espressif/esp-idf/tree/master/components/hal/include/hal/twai_types.h
     const fn timing(self) -> TimingConfig {
+        #[cfg(esp32h2)]
+        let is_custom = matches!(self, Self::Custom(_));
+
         #[allow(unused_mut)]
         let mut timing = match self {
             Self::B125K => TimingConfig {
@@ -618,16 +626,90 @@ impl BaudRate {
             Self::Custom(timing_config) => timing_config,
         };
 
-        #[cfg(esp32c6)]
+        #[cfg(any(esp32c6, esp32h2))]
         {
             // clock source on ESP32-C6 is xtal (40MHz)
+            // clock source on ESP32-H2 is xtal (32MHz), the predefined timings use 16
+            // instead of 20 time quanta per bit there
             timing.baud_rate_prescaler /= 2;
         }
 
+        #[cfg(esp32h2)]
+        if !is_custom {
+            timing.tseg_1 = 11;
+        }
+
         timing
     }
 }
 
+/// The operating mode of the TWAI controller.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TwaiMode {
+    /// The controller takes part in bus communication, acknowledging
+    /// received frames and signalling errors.
+    #[default]
+    Normal,
+    /// The controller does not require an acknowledgement for transmitted
+    /// frames. Transmitted frames are also received by the controller itself
+    /// which allows testing a single node without any other node on the bus.
+    SelfTest,
+    /// The controller only receives frames. It never acknowledges frames,
+    /// signals errors or transmits, which makes it suitable for passively
+    /// monitoring a bus.
+    ListenOnly,
+}
+
+/// Events reported by the TWAI controller.
+///
+/// Alerts are collected from the controller's interrupts, see
+/// [Twai::alerts].
+#[derive(Debug, EnumSetType)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Alert {
+    /// One of the error counters reached the error warning limit.
+    AboveErrorWarning,
+    /// Both error counters dropped below the error warning limit.
+    BelowErrorWarning,
+    /// The controller became error passive.
+    ErrorPassive,
+    /// The controller became error active again.
+    ErrorActive,
+    /// The controller entered the bus-off state.
+    BusOff,
+    /// The controller finished bus-off recovery.
+    BusRecovered,
+    /// The controller lost arbitration while transmitting.
+    ArbitrationLost,
+    /// A bus error was detected.
+    BusError,
+    /// A received frame was lost because the receive FIFO was full.
+    RxOverrun,
+}
+
+const RX_INT: u32 = 1 << 0;
+const TX_INT: u32 = 1 << 1;
+const ERR_WARN_INT: u32 = 1 << 2;
+const OVERRUN_INT: u32 = 1 << 3;
+const ERR_PASSIVE_INT: u32 = 1 << 5;
+const ARB_LOST_INT: u32 = 1 << 6;
+const BUS_ERR_INT: u32 = 1 << 7;
+
+const NUM_TWAI: usize = 2;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_ALERTS: AtomicU32 = AtomicU32::new(0);
+static ALERTS: [AtomicU32; NUM_TWAI] = [NO_ALERTS; NUM_TWAI];
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NOT_RECOVERING: AtomicBool = AtomicBool::new(false);
+static RECOVERING: [AtomicBool; NUM_TWAI] = [NOT_RECOVERING; NUM_TWAI];
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_FILTER_SET: Mutex<RefCell<Option<FilterSet>>> = Mutex::new(RefCell::new(None));
+static SOFTWARE_FILTERS: [Mutex<RefCell<Option<FilterSet>>>; NUM_TWAI] = [NO_FILTER_SET; NUM_TWAI];
+
 /// An inactive TWAI peripheral in the "Reset"/configuration state.
 pub struct TwaiConfiguration<'d, T, DM: crate::Mode> {
     peripheral: PhantomData<&'d PeripheralRef<'d, T>>,
@@ -671,6 +753,10 @@ where
                 crate::interrupt::bind_interrupt(T::INTERRUPT, interrupt.handler());
                 crate::interrupt::enable(T::INTERRUPT, interrupt.priority()).unwrap();
             }
+        } else {
+            // The interrupt status is only latched for enabled sources, the blocking
+            // `Twai::alerts` polls it without an interrupt handler being bound.
+            T::enable_alert_interrupts();
         }
 
         cfg
@@ -682,7 +768,7 @@ where
     fn set_baud_rate(&mut self, baud_rate: BaudRate, _clocks: &Clocks) {
         // TWAI is clocked from the APB_CLK according to Table 6-4 [ESP32C3 Reference Manual](https://www.espressif.com/sites/default/files/documentation/esp32-c3_technical_reference_manual_en.pdf)
         // Included timings are all for 80MHz so assert that we are running at 80MHz.
-        #[cfg(not(esp32c6))]
+        #[cfg(not(any(esp32c6, esp32h2)))]
         assert!(_clocks.apb_clock == fugit::HertzU32::MHz(80));
 
         // Unpack the baud rate timings and convert them to the values needed for the
@@ -725,16 +811,36 @@ where
     ///
     /// [ESP32C3 Reference Manual](https://www.espressif.com/sites/default/files/documentation/esp32-c3_technical_reference_manual_en.pdf#subsubsection.29.4.6)
     pub fn set_filter(&mut self, filter: impl Filter) {
+        // Any software filter installed by `set_filter_set` doesn't apply anymore.
+        critical_section::with(|cs| SOFTWARE_FILTERS[T::NUMBER].replace(cs, None));
+
+        self.set_hardware_filter(filter.filter_type(), filter.to_registers());
+    }
+
+    /// Receive only the frames matching the given [FilterSet].
+    ///
+    /// The best fitting acceptance filter is computed from the set and
+    /// programmed into the peripheral. Frames passing the acceptance filter
+    /// which are not part of the set are discarded by the driver before
+    /// they are returned or queued.
+    ///
+    /// Returns how many ids not part of the set pass the acceptance filter.
+    pub fn set_filter_set(&mut self, filters: &FilterSet) -> FilterCoverage {
+        let (filter, coverage) = filters.hardware_filter();
+        self.set_hardware_filter(filter.filter_type(), filter.to_registers());
+
+        critical_section::with(|cs| SOFTWARE_FILTERS[T::NUMBER].replace(cs, Some(*filters)));
+
+        coverage
+    }
+
+    fn set_hardware_filter(&mut self, filter_type: FilterType, registers: [u8; 8]) {
         // Set or clear the rx filter mode bit depending on the filter type.
-        let filter_mode_bit = filter.filter_type() == FilterType::Single;
+        let filter_mode_bit = filter_type == FilterType::Single;
         T::register_block()
             .mode()
             .modify(|_, w| w.rx_filter_mode().bit(filter_mode_bit));
 
-        // Convert the filter into values for the registers and store them to the
-        // registers.
-        let registers = filter.to_registers();
-
         // Copy the filter to the peripheral.
         unsafe {
             copy_to_data_register(T::register_block().data_0().as_ptr(), &registers);
@@ -753,6 +859,20 @@ where
             .write(|w| unsafe { w.err_warning_limit().bits(limit) });
     }
 
+    /// Set the operating mode the controller will use once started.
+    ///
+    /// In [TwaiMode::ListenOnly] frames cannot be transmitted. In
+    /// [TwaiMode::SelfTest] transmitted frames are received by the controller
+    /// itself.
+    pub fn set_mode(&mut self, mode: TwaiMode) {
+        T::register_block().mode().modify(|_, w| {
+            w.listen_only_mode()
+                .bit(mode == TwaiMode::ListenOnly)
+                .self_test_mode()
+                .bit(mode == TwaiMode::SelfTest)
+        });
+    }
+
     /// Put the peripheral into Operation Mode, allowing the transmission and
     /// reception of packets using the new object.
     pub fn start(self) -> Twai<'d, T, DM> {
@@ -913,6 +1033,52 @@ where
             .bit_is_set()
     }
 
+    /// Start recovering from the bus-off state.
+    ///
+    /// The controller enters the error active state again once it has
+    /// observed 128 occurrences of 11 consecutive recessive bits on the bus.
+    /// Does nothing if the controller is not in the bus-off state.
+    pub fn initiate_bus_off_recovery(&mut self) {
+        if !self.is_bus_off() || self.is_bus_off_recovering() {
+            return;
+        }
+
+        RECOVERING[T::NUMBER].store(true, Ordering::Relaxed);
+
+        // The controller enters reset mode when going bus-off, leaving it starts
+        // the recovery sequence.
+        T::register_block()
+            .mode()
+            .modify(|_, w| w.reset_mode().clear_bit());
+    }
+
+    /// Check if the controller is currently recovering from the bus-off
+    /// state.
+    pub fn is_bus_off_recovering(&self) -> bool {
+        self.is_bus_off()
+            && T::register_block()
+                .mode()
+                .read()
+                .reset_mode()
+                .bit_is_clear()
+    }
+
+    /// Recover from the bus-off state.
+    ///
+    /// Starts the recovery if needed and returns `WouldBlock` until it
+    /// finished, which takes at least 128 × 11 bit periods and never happens
+    /// while the bus is disturbed. Returns `Ok(())` if the controller is not
+    /// in the bus-off state.
+    pub fn recover_from_bus_off(&mut self) -> nb::Result<(), Infallible> {
+        self.initiate_bus_off_recovery();
+
+        if self.is_bus_off() {
+            Err(nb::Error::WouldBlock)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Get the number of messages that the peripheral has available in the
     /// receive FIFO.
     ///
@@ -954,6 +1120,22 @@ where
     }
 }
 
+impl<'d, T> Twai<'d, T, crate::Blocking>
+where
+    T: OperationInstance,
+{
+    /// Returns the alerts raised since the last call and clears them.
+    ///
+    /// This reads (and thereby clears) the controller's interrupt status, so
+    /// it shouldn't be mixed with a user provided interrupt handler. The
+    /// interrupt sources for the alerts are only enabled if no interrupt
+    /// handler was passed to the constructor.
+    pub fn alerts(&mut self) -> EnumSet<Alert> {
+        T::record_alerts(T::read_interrupts());
+        T::take_alerts(EnumSet::all())
+    }
+}
+
 /// Interface to the CAN transmitter part.
 pub struct TwaiTx<'d, T, DM: crate::Mode> {
     _peripheral: PhantomData<&'d T>,
@@ -974,9 +1156,8 @@ where
     ///
     /// [ESP32C3 Reference Manual](https://www.espressif.com/sites/default/files/documentation/esp32-c3_technical_reference_manual_en.pdf#subsubsection.29.4.4.2)
     ///
-    /// NOTE: TODO: This may not work if using the self reception/self test
-    /// functionality. See notes 1 and 2 in the "Frame Identifier" section
-    /// of the reference manual.
+    /// In [TwaiMode::SelfTest] the frame is transmitted using a self
+    /// reception request, so it will also be received by this controller.
     pub fn transmit(&mut self, frame: &EspTwaiFrame) -> nb::Result<(), EspTwaiError> {
         let register_block = T::register_block();
         let status = register_block.status().read();
@@ -1034,6 +1215,12 @@ where
         // - There is a message in the FIFO because we checked status
         let frame = T::read_frame();
 
+        // Frames passing the acceptance filter might still be rejected by the
+        // software filter.
+        if !T::software_filter_accepts(&frame) {
+            return nb::Result::Err(nb::Error::WouldBlock);
+        }
+
         Ok(frame)
     }
 }
@@ -1157,11 +1344,123 @@ pub trait Instance: crate::private::Sealed {
     #[cfg(feature = "async")]
     fn async_handler() -> InterruptHandler;
 
+    fn buffered_handler() -> InterruptHandler;
+
     fn register_block() -> &'static RegisterBlock;
 
     fn enable_peripheral();
 
-    fn enable_interrupts();
+    /// Enable the interrupt sources reported as [Alert]s, used by the
+    /// blocking driver.
+    fn enable_alert_interrupts() {
+        cfg_if::cfg_if! {
+            if #[cfg(any(esp32c6, esp32h2))] {
+                Self::register_block().interrupt_enable().modify(|_, w| {
+                    w.err_warning_int_ena()
+                        .set_bit()
+                        .data_overrun_int_ena()
+                        .set_bit()
+                        .bus_err_int_ena()
+                        .set_bit()
+                        .arbitration_lost_int_ena()
+                        .set_bit()
+                        .err_passive_int_ena()
+                        .set_bit()
+                });
+            } else {
+                Self::register_block().int_ena().modify(|_, w| {
+                    w.err_warn_int_ena()
+                        .set_bit()
+                        .overrun_int_ena()
+                        .set_bit()
+                        .bus_err_int_ena()
+                        .set_bit()
+                        .arb_lost_int_ena()
+                        .set_bit()
+                        .err_passive_int_ena()
+                        .set_bit()
+                });
+            }
+        }
+    }
+
+    /// Enable the interrupts used by the async and buffered drivers.
+    fn enable_interrupts() {
+        cfg_if::cfg_if! {
+            if #[cfg(any(esp32c6, esp32h2))] {
+                Self::register_block().interrupt_enable().modify(|_, w| {
+                    w.ext_receive_int_ena()
+                        .set_bit()
+                        .ext_transmit_int_ena()
+                        .set_bit()
+                        .err_warning_int_ena()
+                        .set_bit()
+                        .data_overrun_int_ena()
+                        .set_bit()
+                        .bus_err_int_ena()
+                        .set_bit()
+                        .arbitration_lost_int_ena()
+                        .set_bit()
+                        .err_passive_int_ena()
+                        .set_bit()
+                });
+            } else {
+                Self::register_block().int_ena().modify(|_, w| {
+                    w.rx_int_ena()
+                        .set_bit()
+                        .tx_int_ena()
+                        .set_bit()
+                        .err_warn_int_ena()
+                        .set_bit()
+                        .overrun_int_ena()
+                        .set_bit()
+                        .bus_err_int_ena()
+                        .set_bit()
+                        .arb_lost_int_ena()
+                        .set_bit()
+                        .err_passive_int_ena()
+                        .set_bit()
+                });
+            }
+        }
+    }
+
+    /// Disable the given interrupts, leaving the other ones untouched.
+    fn disable_interrupts_masked(mask: u32) {
+        cfg_if::cfg_if! {
+            if #[cfg(any(esp32c6, esp32h2))] {
+                Self::register_block()
+                    .interrupt_enable()
+                    .modify(|r, w| unsafe { w.bits(r.bits() & !mask) });
+            } else {
+                Self::register_block()
+                    .int_ena()
+                    .modify(|r, w| unsafe { w.bits(r.bits() & !mask) });
+            }
+        }
+    }
+
+    /// Disable all interrupts of the peripheral.
+    fn disable_interrupts() {
+        cfg_if::cfg_if! {
+            if #[cfg(any(esp32c6, esp32h2))] {
+                Self::register_block().interrupt_enable().reset();
+            } else {
+                Self::register_block().int_ena().reset();
+            }
+        }
+    }
+
+    /// Read and clear the interrupt status.
+    fn read_interrupts() -> u32 {
+        cfg_if::cfg_if! {
+            if #[cfg(any(esp32c6, esp32h2))] {
+                Self::register_block().interrupt().read().bits()
+            } else {
+                Self::register_block().int_raw().read().bits()
+            }
+        }
+    }
 }
 
 pub trait OperationInstance: Instance {
@@ -1252,8 +1551,75 @@ pub trait OperationInstance: Instance {
         }
 
         // Set the transmit request command, this will lock the transmit buffer until
-        // the transmission is complete or aborted.
-        register_block.cmd().write(|w| w.tx_req().set_bit());
+        // the transmission is complete or aborted. In self test mode use a self
+        // reception request so the frame is received as well.
+        if register_block.mode().read().self_test_mode().bit_is_set() {
+            register_block.cmd().write(|w| w.self_rx_req().set_bit());
+        } else {
+            register_block.cmd().write(|w| w.tx_req().set_bit());
+        }
+    }
+
+    /// Check a received frame against the software filter, if any.
+    fn software_filter_accepts(frame: &EspTwaiFrame) -> bool {
+        critical_section::with(|cs| {
+            SOFTWARE_FILTERS[Self::NUMBER]
+                .borrow_ref(cs)
+                .as_ref()
+                .map_or(true, |filters| filters.matches(frame))
+        })
+    }
+
+    /// Translate the interrupt status into alerts and record them.
+    fn record_alerts(intr_status: u32) {
+        let register_block = Self::register_block();
+        let status = register_block.status().read();
+
+        let mut alerts = EnumSet::empty();
+
+        if intr_status & ERR_WARN_INT != 0 {
+            if status.bus_off_st().bit_is_set() {
+                alerts |= Alert::BusOff;
+            } else if RECOVERING[Self::NUMBER].swap(false, Ordering::Relaxed) {
+                alerts |= Alert::BusRecovered;
+            } else if status.err_st().bit_is_set() {
+                alerts |= Alert::AboveErrorWarning;
+            } else {
+                alerts |= Alert::BelowErrorWarning;
+            }
+        }
+
+        if intr_status & ERR_PASSIVE_INT != 0 {
+            let rx_err_cnt = register_block.rx_err_cnt().read().rx_err_cnt().bits();
+            let tx_err_cnt = register_block.tx_err_cnt().read().tx_err_cnt().bits();
+
+            if rx_err_cnt > 127 || tx_err_cnt > 127 {
+                alerts |= Alert::ErrorPassive;
+            } else {
+                alerts |= Alert::ErrorActive;
+            }
+        }
+
+        if intr_status & OVERRUN_INT != 0 {
+            alerts |= Alert::RxOverrun;
+        }
+
+        if intr_status & ARB_LOST_INT != 0 {
+            alerts |= Alert::ArbitrationLost;
+        }
+
+        if intr_status & BUS_ERR_INT != 0 {
+            alerts |= Alert::BusError;
+        }
+
+        ALERTS[Self::NUMBER].fetch_or(alerts.as_u32(), Ordering::Relaxed);
+    }
+
+    /// Remove the given alerts from the recorded ones, returning those which
+    /// were pending.
+    fn take_alerts(alerts: EnumSet<Alert>) -> EnumSet<Alert> {
+        let pending = ALERTS[Self::NUMBER].fetch_and(!alerts.as_u32(), Ordering::Relaxed);
+        EnumSet::from_u32_truncated(pending) & alerts
     }
 
     /// Read a frame from the peripheral.
@@ -1321,137 +1687,48 @@ pub trait OperationInstance: Instance {
     }
 }
 
-#[cfg(any(esp32, esp32c3, esp32s2, esp32s3))]
-impl Instance for crate::peripherals::TWAI0 {
-    const SYSTEM_PERIPHERAL: system::Peripheral = system::Peripheral::Twai0;
-    const NUMBER: usize = 0;
-
-    const INPUT_SIGNAL: InputSignal = InputSignal::TWAI_RX;
-    const OUTPUT_SIGNAL: OutputSignal = OutputSignal::TWAI_TX;
+macro_rules! impl_instance {
+    ($inst:ident, $num:literal, $sys:ident, $rx:ident, $tx:ident, $handler:ident) => {
+        impl Instance for crate::peripherals::$inst {
+            const SYSTEM_PERIPHERAL: system::Peripheral = system::Peripheral::$sys;
+            const NUMBER: usize = $num;
 
-    const INTERRUPT: crate::peripherals::Interrupt = crate::peripherals::Interrupt::TWAI0;
+            const INPUT_SIGNAL: InputSignal = InputSignal::$rx;
+            const OUTPUT_SIGNAL: OutputSignal = OutputSignal::$tx;
 
-    #[cfg(feature = "async")]
-    fn async_handler() -> InterruptHandler {
-        asynch::twai0
-    }
+            const INTERRUPT: crate::peripherals::Interrupt = crate::peripherals::Interrupt::$inst;
 
-    #[inline(always)]
-    fn register_block() -> &'static RegisterBlock {
-        unsafe { &*crate::peripherals::TWAI0::PTR }
-    }
-
-    fn enable_peripheral() {
-        PeripheralClockControl::enable(crate::system::Peripheral::Twai0);
-    }
-
-    fn enable_interrupts() {
-        let register_block = Self::register_block();
-        register_block.int_ena().modify(|_, w| {
-            w.rx_int_ena()
-                .set_bit()
-                .tx_int_ena()
-                .set_bit()
-                .bus_err_int_ena()
-                .set_bit()
-                .arb_lost_int_ena()
-                .set_bit()
-                .err_passive_int_ena()
-                .set_bit()
-        });
-    }
-}
-
-#[cfg(any(esp32, esp32c3, esp32s2, esp32s3))]
-impl OperationInstance for crate::peripherals::TWAI0 {}
-
-#[cfg(esp32c6)]
-impl Instance for crate::peripherals::TWAI0 {
-    const SYSTEM_PERIPHERAL: system::Peripheral = system::Peripheral::Twai0;
-    const NUMBER: usize = 0;
-
-    const INPUT_SIGNAL: InputSignal = InputSignal::TWAI0_RX;
-    const OUTPUT_SIGNAL: OutputSignal = OutputSignal::TWAI0_TX;
-
-    const INTERRUPT: crate::peripherals::Interrupt = crate::peripherals::Interrupt::TWAI0;
+            #[cfg(feature = "async")]
+            fn async_handler() -> InterruptHandler {
+                asynch::$handler
+            }
 
-    #[cfg(feature = "async")]
-    fn async_handler() -> InterruptHandler {
-        asynch::twai0
-    }
+            fn buffered_handler() -> InterruptHandler {
+                buffered::$handler
+            }
 
-    #[inline(always)]
-    fn register_block() -> &'static RegisterBlock {
-        unsafe { &*crate::peripherals::TWAI0::PTR }
-    }
+            #[inline(always)]
+            fn register_block() -> &'static RegisterBlock {
+                unsafe { &*crate::peripherals::$inst::PTR }
+            }
 
-    fn enable_peripheral() {
-        PeripheralClockControl::enable(crate::system::Peripheral::Twai0);
-    }
+            fn enable_peripheral() {
+                PeripheralClockControl::enable(Self::SYSTEM_PERIPHERAL);
+            }
+        }
 
-    fn enable_interrupts() {
-        let register_block = Self::register_block();
-        register_block.interrupt_enable().modify(|_, w| {
-            w.ext_receive_int_ena()
-                .set_bit()
-                .ext_transmit_int_ena()
-                .set_bit()
-                .bus_err_int_ena()
-                .set_bit()
-                .arbitration_lost_int_ena()
-                .set_bit()
-                .err_passive_int_ena()
-                .set_bit()
-        });
-    }
+        impl OperationInstance for crate::peripherals::$inst {}
+    };
 }
 
-#[cfg(esp32c6)]
-impl OperationInstance for crate::peripherals::TWAI0 {}
-
-#[cfg(esp32c6)]
-impl Instance for crate::peripherals::TWAI1 {
-    const SYSTEM_PERIPHERAL: system::Peripheral = system::Peripheral::Twai1;
-    const NUMBER: usize = 1;
-
-    const INPUT_SIGNAL: InputSignal = InputSignal::TWAI1_RX;
-    const OUTPUT_SIGNAL: OutputSignal = OutputSignal::TWAI1_TX;
-
-    const INTERRUPT: crate::peripherals::Interrupt = crate::peripherals::Interrupt::TWAI1;
-
-    #[cfg(feature = "async")]
-    fn async_handler() -> InterruptHandler {
-        asynch::twai1
-    }
-
-    #[inline(always)]
-    fn register_block() -> &'static RegisterBlock {
-        unsafe { &*crate::peripherals::TWAI1::PTR }
-    }
-
-    fn enable_peripheral() {
-        PeripheralClockControl::enable(crate::system::Peripheral::Twai1);
-    }
+#[cfg(any(esp32, esp32c3, esp32s2, esp32s3))]
+impl_instance!(TWAI0, 0, Twai0, TWAI_RX, TWAI_TX, twai0);
 
-    fn enable_interrupts() {
-        let register_block = Self::register_block();
-        register_block.interrupt_enable().modify(|_, w| {
-            w.ext_receive_int_ena()
-                .set_bit()
-                .ext_transmit_int_ena()
-                .set_bit()
-                .bus_err_int_ena()
-                .set_bit()
-                .arbitration_lost_int_ena()
-                .set_bit()
-                .err_passive_int_ena()
-                .set_bit()
-        });
-    }
-}
+#[cfg(all(twai0, any(esp32c6, esp32h2)))]
+impl_instance!(TWAI0, 0, Twai0, TWAI0_RX, TWAI0_TX, twai0);
 
-#[cfg(esp32c6)]
-impl OperationInstance for crate::peripherals::TWAI1 {}
+#[cfg(twai1)]
+impl_instance!(TWAI1, 1, Twai1, TWAI1_RX, TWAI1_TX, twai1);
 
 #[cfg(feature = "async")]
 mod asynch {
@@ -1465,8 +1742,9 @@ mod asynch {
     use procmacros::handler;
 
     use super::*;
+    #[cfg(twai0)]
     use crate::peripherals::TWAI0;
-    #[cfg(esp32c6)]
+    #[cfg(twai1)]
     use crate::peripherals::TWAI1;
 
     pub struct TwaiAsyncState {
@@ -1485,7 +1763,6 @@ mod asynch {
         }
     }
 
-    const NUM_TWAI: usize = 2;
     const NEW_STATE: TwaiAsyncState = TwaiAsyncState::new();
     pub(crate) static TWAI_STATE: [TwaiAsyncState; NUM_TWAI] = [NEW_STATE; NUM_TWAI];
 
@@ -1500,6 +1777,54 @@ mod asynch {
         pub async fn receive_async(&mut self) -> Result<EspTwaiFrame, EspTwaiError> {
             self.rx.receive_async().await
         }
+
+        /// Recover from the bus-off state, waiting until the recovery
+        /// finished.
+        ///
+        /// Returns immediately if the controller is not in the bus-off state.
+        pub async fn recover_from_bus_off_async(&mut self) {
+            self.initiate_bus_off_recovery();
+
+            T::enable_interrupts();
+            poll_fn(|cx| {
+                T::async_state().err_waker.register(cx.waker());
+
+                if self.is_bus_off() {
+                    T::enable_interrupts();
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            })
+            .await
+        }
+
+        /// Returns the alerts raised since the last call and clears them.
+        ///
+        /// Alerts are only recorded while the interrupts are enabled, i.e.
+        /// after any of the async functions was called.
+        pub fn alerts(&mut self) -> EnumSet<Alert> {
+            T::take_alerts(EnumSet::all())
+        }
+
+        /// Wait until any of the given alerts is raised.
+        ///
+        /// Returns the raised alerts out of the given ones and clears them.
+        pub async fn wait_for_alerts_async(&mut self, alerts: EnumSet<Alert>) -> EnumSet<Alert> {
+            T::enable_interrupts();
+            poll_fn(|cx| {
+                T::async_state().err_waker.register(cx.waker());
+
+                let raised = T::take_alerts(alerts);
+                if raised.is_empty() {
+                    T::enable_interrupts();
+                    Poll::Pending
+                } else {
+                    Poll::Ready(raised)
+                }
+            })
+            .await
+        }
     }
 
     impl<'d, T> TwaiTx<'d, T, crate::Async>
@@ -1563,21 +1888,18 @@ mod asynch {
         }
     }
 
-    #[cfg(any(esp32c3, esp32, esp32s2, esp32s3))]
-    #[handler]
-    pub(super) fn twai0() {
-        let register_block = TWAI0::register_block();
+    fn handle_interrupt<T: OperationInstance>() {
+        let register_block = T::register_block();
 
-        let intr_enable = register_block.int_ena().read();
-        let intr_status = register_block.int_raw().read();
+        let intr_status = T::read_interrupts();
 
-        let async_state = TWAI0::async_state();
+        let async_state = T::async_state();
 
-        if intr_status.tx_int_st().bit_is_set() {
+        if intr_status & TX_INT != 0 {
             async_state.tx_waker.wake();
         }
 
-        if intr_status.rx_int_st().bit_is_set() {
+        if intr_status & RX_INT != 0 {
             let status = register_block.status().read();
 
             let rx_queue = &async_state.rx_queue;
@@ -1590,111 +1912,32 @@ mod asynch {
                 let _ = rx_queue.try_send(Err(EspTwaiError::EmbeddedHAL(ErrorKind::Overrun)));
             }
 
-            let frame = TWAI0::read_frame();
+            let frame = T::read_frame();
 
-            let _ = rx_queue.try_send(Ok(frame));
-
-            register_block.cmd().write(|w| w.release_buf().set_bit());
+            if T::software_filter_accepts(&frame) {
+                let _ = rx_queue.try_send(Ok(frame));
+            }
         }
 
-        if intr_status.bits() & 0b11111100 > 0 {
+        if intr_status & 0b11111100 > 0 {
+            T::record_alerts(intr_status);
             async_state.err_waker.wake();
         }
 
-        unsafe {
-            register_block
-                .int_ena()
-                .modify(|_, w| w.bits(intr_enable.bits() & (!intr_status.bits() | 1)));
-        }
+        // Keep the receive interrupt enabled, the other ones are re-enabled by the
+        // futures waiting for them.
+        T::disable_interrupts_masked(intr_status & !RX_INT);
     }
 
-    #[cfg(esp32c6)]
+    #[cfg(twai0)]
     #[handler]
     pub(super) fn twai0() {
-        let register_block = TWAI0::register_block();
-
-        let intr_enable = register_block.interrupt_enable().read();
-        let intr_status = register_block.interrupt().read();
-
-        let async_state = TWAI0::async_state();
-
-        if intr_status.transmit_int_st().bit_is_set() {
-            async_state.tx_waker.wake();
-        }
-
-        if intr_status.receive_int_st().bit_is_set() {
-            let status = register_block.status().read();
-
-            let rx_queue = &async_state.rx_queue;
-
-            if status.bus_off_st().bit_is_set() {
-                let _ = rx_queue.try_send(Err(EspTwaiError::BusOff));
-            }
-
-            if status.miss_st().bit_is_set() {
-                let _ = rx_queue.try_send(Err(EspTwaiError::EmbeddedHAL(ErrorKind::Overrun)));
-            }
-
-            let frame = TWAI0::read_frame();
-
-            let _ = rx_queue.try_send(Ok(frame));
-
-            register_block.cmd().write(|w| w.release_buf().set_bit());
-        }
-
-        if intr_status.bits() & 0b11111100 > 0 {
-            async_state.err_waker.wake();
-        }
-
-        unsafe {
-            register_block
-                .interrupt_enable()
-                .modify(|_, w| w.bits(intr_enable.bits() & (!intr_status.bits() | 1)));
-        }
+        handle_interrupt::<TWAI0>();
     }
 
-    #[cfg(esp32c6)]
+    #[cfg(twai1)]
     #[handler]
     pub(super) fn twai1() {
-        let register_block = TWAI1::register_block();
-
-        let intr_enable = register_block.interrupt_enable().read();
-        let intr_status = register_block.interrupt().read();
-
-        let async_state = TWAI1::async_state();
-
-        if intr_status.transmit_int_st().bit_is_set() {
-            async_state.tx_waker.wake();
-        }
-
-        if intr_status.receive_int_st().bit_is_set() {
-            let status = register_block.status().read();
-
-            let rx_queue = &async_state.rx_queue;
-
-            if status.bus_off_st().bit_is_set() {
-                let _ = rx_queue.try_send(Err(EspTwaiError::BusOff));
-            }
-
-            if status.miss_st().bit_is_set() {
-                let _ = rx_queue.try_send(Err(EspTwaiError::EmbeddedHAL(ErrorKind::Overrun)));
-            }
-
-            let frame = TWAI1::read_frame();
-
-            let _ = rx_queue.try_send(Ok(frame));
-
-            register_block.cmd().write(|w| w.release_buf().set_bit());
-        }
-
-        if intr_status.bits() & 0b11111100 > 0 {
-            async_state.err_waker.wake();
-        }
-
-        unsafe {
-            register_block
-                .interrupt_enable()
-                .modify(|_, w| w.bits(intr_enable.bits() & (!intr_status.bits() | 1)));
-        }
+        handle_interrupt::<TWAI1>();
     }
 }
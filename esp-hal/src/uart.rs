@@ -47,6 +47,46 @@
 //! uart1.write_bytes("Hello, world!".as_bytes())?;
 //! ```
 //!
+//! #### RS-485
+//!
+//! In RS-485 mode the peripheral drives the RTS signal, which enables the
+//! line driver of the transceiver while data is being transmitted. The bus is
+//! released by the hardware once the last stop bit (and the optional extra bit
+//! period) has been sent:
+//!
+//! ```no_run
+//! let pins = AllPins::new(io.pins.gpio1, io.pins.gpio2, NO_PIN, io.pins.gpio3);
+//! let config = Config::default().rs485(
+//!     Rs485Config::default()
+//!         .collision_detection(true)
+//!         .delay_after_stop(true),
+//! );
+//!
+//! let mut uart1 = Uart::new_with_config(peripherals.UART1, config, Some(pins), &clocks, None);
+//! // returns once the bus has been released
+//! uart1.write_bytes(&[0x01, 0x03, 0x00, 0x00])?;
+//! ```
+//!
+//! #### Break Conditions and Inverted Signals
+//!
+//! Protocols like LIN or DMX512 start a frame with a BREAK condition (the line
+//! held low for longer than a character). Signals of transceivers with
+//! inverting level shifters can be inverted in the configuration:
+//!
+//! ```no_run
+//! let config = Config::default()
+//!     .baudrate(250_000)
+//!     .inversion(Inversion::default().rx(true));
+//!
+//! let mut uart1 = Uart::new_with_config(peripherals.UART1, config, Some(pins), &clocks, None);
+//! // 88us break at 250kBaud, followed by the mark after break
+//! uart1.send_break(22);
+//! // The break is generated by the hardware, the CPU is free to do other work
+//! // until it has been sent
+//! nb::block!(uart1.flush_break())?;
+//! uart1.write_bytes(&dmx_frame)?;
+//! ```
+//!
 //! #### Splitting the UART into TX and RX Components
 //!
 //! ```no_run
@@ -82,6 +122,7 @@ use crate::{
 
 const CONSOLE_UART_NUM: usize = 0;
 const UART_FIFO_SIZE: u16 = 128;
+const MAX_RS485_TX_DELAY: u8 = 15;
 
 #[cfg(not(any(esp32, esp32s2)))]
 use crate::soc::constants::RC_FAST_CLK;
@@ -103,6 +144,8 @@ pub enum Error {
     RxFrameError,
     #[cfg(feature = "async")]
     RxParityError,
+    /// A collision was detected on the RS-485 bus while transmitting
+    Rs485Collision,
 }
 
 #[cfg(feature = "embedded-hal")]
@@ -172,6 +215,109 @@ pub mod config {
         STOP2   = 3,
     }
 
+    /// RS-485 half-duplex configuration
+    ///
+    /// The peripheral's RS-485 mode drives the RTS signal, which is connected
+    /// to the driver enable input of the transceiver. It is asserted while
+    /// transmitting and released afterwards.
+    #[derive(Debug, Default, Copy, Clone)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct Rs485Config {
+        /// Compare the data on the bus with the transmitted data and report a
+        /// mismatch as [super::Error::Rs485Collision]
+        pub collision_detection: bool,
+        /// Don't receive the data transmitted by this UART. Collision
+        /// detection needs the receiver to monitor the bus while transmitting,
+        /// so this has no effect when it is enabled.
+        pub loopback_suppression: bool,
+        /// Insert one bit period between enabling the driver and the start
+        /// bit
+        pub delay_before_start: bool,
+        /// Keep the driver enabled for one bit period after the last stop bit
+        /// before the bus is released
+        pub delay_after_stop: bool,
+        /// Delay of the transmitter's internal data signal (0 to 15), see
+        /// `UART_RS485_TX_DLY_NUM` in the technical reference manual. Values
+        /// above 15 are clamped.
+        pub tx_delay: u8,
+    }
+
+    impl Rs485Config {
+        /// Enables or disables collision detection
+        pub fn collision_detection(mut self, enable: bool) -> Self {
+            self.collision_detection = enable;
+            self
+        }
+
+        /// Enables or disables loopback suppression
+        pub fn loopback_suppression(mut self, enable: bool) -> Self {
+            self.loopback_suppression = enable;
+            self
+        }
+
+        /// Enables or disables the extra bit period before the start bit
+        pub fn delay_before_start(mut self, enable: bool) -> Self {
+            self.delay_before_start = enable;
+            self
+        }
+
+        /// Enables or disables the extra bit period after the last stop bit
+        pub fn delay_after_stop(mut self, enable: bool) -> Self {
+            self.delay_after_stop = enable;
+            self
+        }
+
+        /// Sets the delay of the transmitter's internal data signal, values
+        /// above 15 are clamped
+        pub fn tx_delay(mut self, delay: u8) -> Self {
+            self.tx_delay = delay.min(super::MAX_RS485_TX_DELAY);
+            self
+        }
+    }
+
+    /// Inversion of the UART signals
+    ///
+    /// Inverted signals are e.g. needed by transceivers with inverting level
+    /// shifters.
+    #[derive(Debug, Default, Copy, Clone, PartialEq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct Inversion {
+        /// Invert the TXD output
+        pub tx: bool,
+        /// Invert the RXD input
+        pub rx: bool,
+        /// Invert the RTS output
+        pub rts: bool,
+        /// Invert the CTS input
+        pub cts: bool,
+    }
+
+    impl Inversion {
+        /// Invert the TXD output
+        pub fn tx(mut self, invert: bool) -> Self {
+            self.tx = invert;
+            self
+        }
+
+        /// Invert the RXD input
+        pub fn rx(mut self, invert: bool) -> Self {
+            self.rx = invert;
+            self
+        }
+
+        /// Invert the RTS output
+        pub fn rts(mut self, invert: bool) -> Self {
+            self.rts = invert;
+            self
+        }
+
+        /// Invert the CTS input
+        pub fn cts(mut self, invert: bool) -> Self {
+            self.cts = invert;
+            self
+        }
+    }
+
     /// UART Configuration
     #[derive(Debug, Copy, Clone)]
     #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -181,6 +327,10 @@ pub mod config {
         pub parity: Parity,
         pub stop_bits: StopBits,
         pub clock_source: super::ClockSource,
+        /// RS-485 half-duplex mode, disabled if `None`
+        pub rs485: Option<Rs485Config>,
+        /// Signals to invert
+        pub inversion: Inversion,
     }
 
     impl Config {
@@ -219,6 +369,16 @@ pub mod config {
             self
         }
 
+        pub fn rs485(mut self, rs485: Rs485Config) -> Self {
+            self.rs485 = Some(rs485);
+            self
+        }
+
+        pub fn inversion(mut self, inversion: Inversion) -> Self {
+            self.inversion = inversion;
+            self
+        }
+
         pub fn symbol_length(&self) -> u8 {
             let mut length: u8 = 1; // start bit
             length += match self.data_bits {
@@ -250,6 +410,8 @@ pub mod config {
                 clock_source: super::ClockSource::Xtal,
                 #[cfg(not(any(esp32c6, esp32h2, lp_uart)))]
                 clock_source: super::ClockSource::Apb,
+                rs485: None,
+                inversion: Inversion::default(),
             }
         }
     }
@@ -401,6 +563,7 @@ pub struct Uart<'d, T, M> {
 /// UART (Transmit)
 pub struct UartTx<'d, T, M> {
     phantom: PhantomData<(&'d mut T, M)>,
+    rs485: Option<config::Rs485Config>,
 }
 
 /// UART (Receive)
@@ -418,20 +581,91 @@ where
     fn new_inner() -> Self {
         Self {
             phantom: PhantomData,
+            rs485: None,
         }
     }
 
     /// Writes bytes
+    ///
+    /// In RS-485 mode this waits for the transmission to finish, so that
+    /// collisions can be reported.
     pub fn write_bytes(&mut self, data: &[u8]) -> Result<usize, Error> {
         let count = data.len();
 
         data.iter()
             .try_for_each(|c| nb::block!(self.write_byte(*c)))?;
 
+        if self.rs485.is_some() {
+            nb::block!(self.flush_tx())?;
+        }
+
         Ok(count)
     }
 
+    /// Sends a BREAK condition, i.e. holds the TX line low for the given
+    /// number of bit periods, after all pending data was transmitted
+    ///
+    /// The break is generated by the hardware, this returns right away. Use
+    /// [`UartTx::flush_break`] to find out when the break and the following
+    /// idle period (the "mark after break") have been sent. Don't write
+    /// further data before that, it would delay the break.
+    pub fn send_break(&mut self, bits: u8) {
+        self.start_break(bits);
+    }
+
+    /// Checks whether a break started with [`UartTx::send_break`] has been
+    /// sent, including the mark after break
+    ///
+    /// Returns `Ok(())` right away if no break is pending.
+    pub fn flush_break(&mut self) -> nb::Result<(), Error> {
+        let reg_block = T::register_block();
+
+        if reg_block.conf0().read().txd_brk().bit_is_clear() {
+            return Ok(());
+        }
+
+        if reg_block.int_raw().read().tx_brk_idle_done().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.finish_break().map_err(nb::Error::Other)
+    }
+
+    fn start_break(&mut self, bits: u8) {
+        let reg_block = T::register_block();
+
+        reg_block
+            .idle_conf()
+            .modify(|_, w| unsafe { w.tx_brk_num().bits(bits) });
+        reg_block.int_clr().write(|w| {
+            w.tx_brk_done()
+                .clear_bit_by_one()
+                .tx_brk_idle_done()
+                .clear_bit_by_one()
+        });
+        reg_block.conf0().modify(|_, w| w.txd_brk().set_bit());
+        T::sync_regs();
+    }
+
+    fn finish_break(&mut self) -> Result<(), Error> {
+        let reg_block = T::register_block();
+
+        reg_block.conf0().modify(|_, w| w.txd_brk().clear_bit());
+        T::sync_regs();
+        reg_block.int_clr().write(|w| {
+            w.tx_brk_done()
+                .clear_bit_by_one()
+                .tx_brk_idle_done()
+                .clear_bit_by_one()
+        });
+
+        self.rs485_check_collision()
+    }
+
     fn write_byte(&mut self, word: u8) -> nb::Result<(), Error> {
+        // Stop queueing data as soon as the hardware detected a collision
+        self.rs485_check_collision()?;
+
         if T::get_tx_fifo_count() < UART_FIFO_SIZE {
             T::register_block()
                 .fifo()
@@ -444,12 +678,38 @@ where
     }
 
     fn flush_tx(&self) -> nb::Result<(), Error> {
+        if self.rs485.is_some() {
+            if T::get_tx_fifo_count() > 0 || !T::is_tx_idle() {
+                return Err(nb::Error::WouldBlock);
+            }
+
+            return self.rs485_check_collision().map_err(nb::Error::Other);
+        }
+
         if T::is_tx_idle() {
             Ok(())
         } else {
             Err(nb::Error::WouldBlock)
         }
     }
+
+    /// Returns an error if the hardware detected a collision on the RS-485
+    /// bus since the last check
+    fn rs485_check_collision(&self) -> Result<(), Error> {
+        let Some(rs485) = self.rs485 else {
+            return Ok(());
+        };
+
+        let reg_block = T::register_block();
+        if rs485.collision_detection && reg_block.int_raw().read().rs485_clash().bit_is_set() {
+            reg_block
+                .int_clr()
+                .write(|w| w.rs485_clash().clear_bit_by_one());
+            return Err(Error::Rs485Collision);
+        }
+
+        Ok(())
+    }
 }
 
 impl<'d, T, M> UartRx<'d, T, M>
@@ -572,6 +832,8 @@ where
         serial.change_data_bits(config.data_bits);
         serial.change_parity(config.parity);
         serial.change_stop_bits(config.stop_bits);
+        serial.change_rs485(config.rs485, config.stop_bits);
+        serial.change_inversion(config.inversion);
 
         if let Some(interrupt) = interrupt {
             unsafe {
@@ -797,6 +1059,20 @@ where
             .modify(|_, w| w.rxfifo_full().clear_bit());
     }
 
+    /// Listen for BREAK-DETECTED interrupts
+    pub fn listen_break_detected(&mut self) {
+        T::register_block()
+            .int_ena()
+            .modify(|_, w| w.brk_det().set_bit());
+    }
+
+    /// Stop listening for BREAK-DETECTED interrupts
+    pub fn unlisten_break_detected(&mut self) {
+        T::register_block()
+            .int_ena()
+            .modify(|_, w| w.brk_det().clear_bit());
+    }
+
     /// Checks if AT-CMD interrupt is set
     pub fn at_cmd_interrupt_set(&self) -> bool {
         T::register_block()
@@ -820,6 +1096,11 @@ where
             .bit_is_set()
     }
 
+    /// Checks if BREAK-DETECTED interrupt is set
+    pub fn break_detected_interrupt_set(&self) -> bool {
+        T::register_block().int_raw().read().brk_det().bit_is_set()
+    }
+
     /// Reset AT-CMD interrupt
     pub fn reset_at_cmd_interrupt(&self) {
         T::register_block()
@@ -841,6 +1122,13 @@ where
             .write(|w| w.rxfifo_full().clear_bit_by_one());
     }
 
+    /// Reset BREAK-DETECTED interrupt
+    pub fn reset_break_detected_interrupt(&self) {
+        T::register_block()
+            .int_clr()
+            .write(|w| w.brk_det().clear_bit_by_one());
+    }
+
     /// Write a byte out over the UART
     pub fn write_byte(&mut self, word: u8) -> nb::Result<(), Error> {
         self.tx.write_byte(word)
@@ -851,6 +1139,16 @@ where
         self.tx.flush_tx()
     }
 
+    /// See [`UartTx::send_break`]
+    pub fn send_break(&mut self, bits: u8) {
+        self.tx.send_break(bits)
+    }
+
+    /// See [`UartTx::flush_break`]
+    pub fn flush_break(&mut self) -> nb::Result<(), Error> {
+        self.tx.flush_break()
+    }
+
     /// Read a byte from the UART
     pub fn read_byte(&mut self) -> nb::Result<u8, Error> {
         self.rx.read_byte()
@@ -886,6 +1184,84 @@ where
         self
     }
 
+    fn change_rs485(
+        &mut self,
+        rs485: Option<config::Rs485Config>,
+        stop_bits: config::StopBits,
+    ) -> &mut Self {
+        let reg_block = T::register_block();
+
+        // On the ESP32 DL1 also implements the second stop bit, see
+        // `change_stop_bits`
+        let extra_stop_bit = cfg!(esp32) && stop_bits == config::StopBits::STOP2;
+
+        match rs485 {
+            Some(rs485) => {
+                reg_block.rs485_conf().modify(|_, w| unsafe {
+                    w.rs485_en()
+                        .set_bit()
+                        .rs485tx_rx_en()
+                        .bit(rs485.collision_detection || !rs485.loopback_suppression)
+                        .rs485rxby_tx_en()
+                        .clear_bit()
+                        .dl0_en()
+                        .bit(rs485.delay_before_start)
+                        .dl1_en()
+                        .bit(rs485.delay_after_stop || extra_stop_bit)
+                        .rs485_tx_dly_num()
+                        .bits(rs485.tx_delay.min(MAX_RS485_TX_DELAY))
+                });
+
+                // idle level of RTS, the driver stays disabled while the
+                // peripheral isn't transmitting
+                reg_block.conf0().modify(|_, w| w.sw_rts().set_bit());
+                reg_block
+                    .int_clr()
+                    .write(|w| w.rs485_clash().clear_bit_by_one());
+            }
+            None => {
+                reg_block.rs485_conf().modify(|_, w| unsafe {
+                    w.rs485_en()
+                        .clear_bit()
+                        .rs485tx_rx_en()
+                        .clear_bit()
+                        .rs485rxby_tx_en()
+                        .clear_bit()
+                        .dl0_en()
+                        .clear_bit()
+                        .dl1_en()
+                        .bit(extra_stop_bit)
+                        .rs485_tx_dly_num()
+                        .bits(0)
+                });
+            }
+        }
+
+        self.sync_regs();
+
+        self.tx.rs485 = rs485;
+
+        self
+    }
+
+    /// Change the inversion of the UART signals
+    pub fn change_inversion(&mut self, inversion: config::Inversion) -> &mut Self {
+        T::register_block().conf0().modify(|_, w| {
+            w.txd_inv()
+                .bit(inversion.tx)
+                .rxd_inv()
+                .bit(inversion.rx)
+                .rts_inv()
+                .bit(inversion.rts)
+                .cts_inv()
+                .bit(inversion.cts)
+        });
+
+        self.sync_regs();
+
+        self
+    }
+
     fn change_data_bits(&mut self, data_bits: config::DataBits) -> &mut Self {
         T::register_block()
             .conf0()
@@ -1109,26 +1485,11 @@ where
         }
     }
 
-    #[cfg(any(esp32c3, esp32c6, esp32h2, esp32s3))] // TODO introduce a cfg symbol for this
     #[inline(always)]
     fn sync_regs(&self) {
-        #[cfg(any(esp32c6, esp32h2))]
-        let update_reg = T::register_block().reg_update();
-
-        #[cfg(any(esp32c3, esp32s3))]
-        let update_reg = T::register_block().id();
-
-        update_reg.modify(|_, w| w.reg_update().set_bit());
-
-        while update_reg.read().reg_update().bit_is_set() {
-            // wait
-        }
+        T::sync_regs();
     }
 
-    #[cfg(not(any(esp32c3, esp32c6, esp32h2, esp32s3)))]
-    #[inline(always)]
-    fn sync_regs(&mut self) {}
-
     fn rxfifo_reset(&mut self) {
         T::register_block()
             .conf0()
@@ -1160,6 +1521,26 @@ pub trait Instance: crate::private::Sealed {
     fn uart_number() -> usize;
     fn interrupt() -> Interrupt;
 
+    #[cfg(any(esp32c3, esp32c6, esp32h2, esp32s3))] // TODO introduce a cfg symbol for this
+    #[inline(always)]
+    fn sync_regs() {
+        #[cfg(any(esp32c6, esp32h2))]
+        let update_reg = Self::register_block().reg_update();
+
+        #[cfg(any(esp32c3, esp32s3))]
+        let update_reg = Self::register_block().id();
+
+        update_reg.modify(|_, w| w.reg_update().set_bit());
+
+        while update_reg.read().reg_update().bit_is_set() {
+            // wait
+        }
+    }
+
+    #[cfg(not(any(esp32c3, esp32c6, esp32h2, esp32s3)))]
+    #[inline(always)]
+    fn sync_regs() {}
+
     fn disable_tx_interrupts() {
         Self::register_block().int_clr().write(|w| {
             w.txfifo_empty()
@@ -1642,6 +2023,7 @@ mod asynch {
     pub(crate) enum TxEvent {
         TxDone,
         TxFiFoEmpty,
+        TxBrkIdleDone,
     }
     #[derive(EnumSetType, Debug)]
     pub(crate) enum RxEvent {
@@ -1652,6 +2034,7 @@ mod asynch {
         RxGlitchDetected,
         RxFrameError,
         RxParityError,
+        RxBreakDetected,
     }
 
     /// A future that resolves when the passed interrupt is triggered,
@@ -1694,6 +2077,7 @@ mod asynch {
                     RxEvent::RxGlitchDetected => interrupts_enabled.glitch_det().bit_is_clear(),
                     RxEvent::RxFrameError => interrupts_enabled.frm_err().bit_is_clear(),
                     RxEvent::RxParityError => interrupts_enabled.parity_err().bit_is_clear(),
+                    RxEvent::RxBreakDetected => interrupts_enabled.brk_det().bit_is_clear(),
                 };
                 if event_triggered {
                     events_triggered |= event;
@@ -1722,6 +2106,7 @@ mod asynch {
                             RxEvent::RxGlitchDetected => w.glitch_det().set_bit(),
                             RxEvent::RxFrameError => w.frm_err().set_bit(),
                             RxEvent::RxParityError => w.parity_err().set_bit(),
+                            RxEvent::RxBreakDetected => w.brk_det().set_bit(),
                         };
                     }
                     w
@@ -1754,6 +2139,7 @@ mod asynch {
                     RxEvent::RxParityError => int_ena.modify(|_, w| w.parity_err().clear_bit()),
                     RxEvent::RxFifoOvf => int_ena.modify(|_, w| w.rxfifo_ovf().clear_bit()),
                     RxEvent::RxFifoTout => int_ena.modify(|_, w| w.rxfifo_tout().clear_bit()),
+                    RxEvent::RxBreakDetected => int_ena.modify(|_, w| w.brk_det().clear_bit()),
                 }
             }
         }
@@ -1775,6 +2161,7 @@ mod asynch {
                 event_triggered |= match event {
                     TxEvent::TxDone => interrupts_enabled.tx_done().bit_is_clear(),
                     TxEvent::TxFiFoEmpty => interrupts_enabled.txfifo_empty().bit_is_clear(),
+                    TxEvent::TxBrkIdleDone => interrupts_enabled.tx_brk_idle_done().bit_is_clear(),
                 }
             }
             event_triggered
@@ -1795,6 +2182,7 @@ mod asynch {
                         match event {
                             TxEvent::TxDone => w.tx_done().set_bit(),
                             TxEvent::TxFiFoEmpty => w.txfifo_empty().set_bit(),
+                            TxEvent::TxBrkIdleDone => w.tx_brk_idle_done().set_bit(),
                         };
                     }
                     w
@@ -1820,6 +2208,9 @@ mod asynch {
                 match event {
                     TxEvent::TxDone => int_ena.modify(|_, w| w.tx_done().clear_bit()),
                     TxEvent::TxFiFoEmpty => int_ena.modify(|_, w| w.txfifo_empty().clear_bit()),
+                    TxEvent::TxBrkIdleDone => {
+                        int_ena.modify(|_, w| w.tx_brk_idle_done().clear_bit())
+                    }
                 }
             }
         }
@@ -1884,6 +2275,16 @@ mod asynch {
         pub async fn flush_async(&mut self) -> Result<(), Error> {
             self.tx.flush_async().await
         }
+
+        /// See [`UartTx::send_break_async`]
+        pub async fn send_break_async(&mut self, bits: u8) -> Result<(), Error> {
+            self.tx.send_break_async(bits).await
+        }
+
+        /// See [`UartRx::wait_for_break_async`]
+        pub async fn wait_for_break_async(&mut self) {
+            self.rx.wait_for_break_async().await
+        }
     }
 
     impl<T> UartTx<'_, T, Async>
@@ -1900,7 +2301,8 @@ mod asynch {
                 }
 
                 for byte in &words[offset..next_offset] {
-                    self.write_byte(*byte).unwrap(); // should never fail
+                    // only fails if a collision was detected on the RS-485 bus
+                    nb::block!(self.write_byte(*byte))?;
                     count += 1;
                 }
 
@@ -1912,10 +2314,28 @@ mod asynch {
                 UartTxFuture::<T>::new(TxEvent::TxFiFoEmpty.into()).await;
             }
 
+            // in RS-485 mode collisions are reported once the data is out
+            if self.rs485.is_some() {
+                self.flush_async().await?;
+            }
+
             Ok(count)
         }
 
         pub async fn flush_async(&mut self) -> Result<(), Error> {
+            if self.rs485.is_some() {
+                // A stale TX_DONE would complete the future right away, if the
+                // transmission ends after clearing it, TX_DONE is raised again
+                T::register_block()
+                    .int_clr()
+                    .write(|w| w.tx_done().clear_bit_by_one());
+                if T::get_tx_fifo_count() > 0 || !T::is_tx_idle() {
+                    UartTxFuture::<T>::new(TxEvent::TxDone.into()).await;
+                }
+
+                return self.rs485_check_collision();
+            }
+
             let count = T::get_tx_fifo_count();
             if count > 0 {
                 UartTxFuture::<T>::new(TxEvent::TxDone.into()).await;
@@ -1923,6 +2343,23 @@ mod asynch {
 
             Ok(())
         }
+
+        /// Sends a BREAK condition for the given number of bit periods after
+        /// all pending data was transmitted, see [`UartTx::send_break`]
+        pub async fn send_break_async(&mut self, bits: u8) -> Result<(), Error> {
+            self.start_break(bits);
+
+            if T::register_block()
+                .int_raw()
+                .read()
+                .tx_brk_idle_done()
+                .bit_is_clear()
+            {
+                UartTxFuture::<T>::new(TxEvent::TxBrkIdleDone.into()).await;
+            }
+
+            self.finish_break()
+        }
     }
 
     impl<T> UartRx<'_, T, Async>
@@ -1978,9 +2415,10 @@ mod asynch {
                         RxEvent::RxGlitchDetected => return Err(Error::RxGlitchDetected),
                         RxEvent::RxFrameError => return Err(Error::RxFrameError),
                         RxEvent::RxParityError => return Err(Error::RxParityError),
-                        RxEvent::RxFifoFull | RxEvent::RxCmdCharDetected | RxEvent::RxFifoTout => {
-                            continue
-                        }
+                        RxEvent::RxFifoFull
+                        | RxEvent::RxCmdCharDetected
+                        | RxEvent::RxFifoTout
+                        | RxEvent::RxBreakDetected => continue,
                     }
                 }
                 // Unfortunately, the uart's rx-timeout counter counts up whenever there is
@@ -1996,6 +2434,18 @@ mod asynch {
                 }
             }
         }
+
+        /// Waits until a BREAK condition was received
+        ///
+        /// A break is also received as a NULL character with a frame error, so
+        /// a pending [`UartRx::read_async`] returns [`Error::RxFrameError`].
+        pub async fn wait_for_break_async(&mut self) {
+            T::register_block()
+                .int_clr()
+                .write(|w| w.brk_det().clear_bit_by_one());
+
+            UartRxFuture::<T>::new(RxEvent::RxBreakDetected.into()).await;
+        }
     }
 
     impl<T> embedded_io_async::Read for Uart<'_, T, Async>
@@ -2064,8 +2514,11 @@ mod asynch {
             || interrupts.at_cmd_char_det().bit_is_set()
             || interrupts.glitch_det().bit_is_set()
             || interrupts.frm_err().bit_is_set()
-            || interrupts.parity_err().bit_is_set();
-        let tx_wake = interrupts.tx_done().bit_is_set() || interrupts.txfifo_empty().bit_is_set();
+            || interrupts.parity_err().bit_is_set()
+            || interrupts.brk_det().bit_is_set();
+        let tx_wake = interrupts.tx_done().bit_is_set()
+            || interrupts.txfifo_empty().bit_is_set()
+            || interrupts.tx_brk_idle_done().bit_is_set();
         uart.int_clr().write(|w| unsafe { w.bits(interrupt_bits) });
         uart.int_ena()
             .modify(|r, w| unsafe { w.bits(r.bits() & !interrupt_bits) });
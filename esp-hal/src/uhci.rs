@@ -0,0 +1,601 @@
+//! # UART DMA (UHCI)
+//!
+//! ## Overview
+//! The UHCI peripheral connects a UART to the general purpose DMA controller.
+//! This allows receiving and transmitting large amounts of data without the
+//! CPU having to service the UART FIFOs.
+//!
+//! The driver is available on the chips with a general purpose DMA controller
+//! and a UHCI peripheral: `ESP32-C3`, `ESP32-C6`, `ESP32-H2` and `ESP32-S3`.
+//! Only `UHCI0` is supported. The `ESP32` and `ESP32-S2` are not supported,
+//! their UHCI peripherals are connected to a different DMA engine.
+//!
+//! Received data can be split into frames: the UHCI closes the current DMA
+//! descriptor (generating an EOF) when the UART RX line was idle for a
+//! configurable number of bit periods or after a configurable number of bytes.
+//! In a circular transfer every frame ends up in its own descriptor, so
+//! [DmaTransferRxCircular::pop] hands out one frame at a time as long as a
+//! frame fits into a single descriptor.
+//!
+//! ## Example
+//! ```no_run
+//! let uart = Uart::new_with_config(
+//!     peripherals.UART1,
+//!     uart::config::Config::default(),
+//!     Some(TxRxPins::new_tx_rx(io.pins.gpio4, io.pins.gpio5)),
+//!     &clocks,
+//!     None,
+//! );
+//!
+//! let mut uart_dma = UartDma::new(
+//!     peripherals.UHCI0,
+//!     uart,
+//!     dma_channel.configure(
+//!         false,
+//!         &mut tx_descriptors,
+//!         &mut rx_descriptors,
+//!         DmaPriority::Priority0,
+//!     ),
+//!     uhci::Config::default().rx_idle_timeout(20),
+//! );
+//!
+//! let transfer = uart_dma.tx.write_dma(&tx_buffer).unwrap();
+//! transfer.wait().unwrap();
+//!
+//! let mut transfer = uart_dma.rx.read_dma_circular(&mut rx_buffer).unwrap();
+//! loop {
+//!     let avail = transfer.available();
+//!     if avail > 0 {
+//!         let mut frame = [0u8; 4092];
+//!         transfer.pop(&mut frame[..avail]).unwrap();
+//!     }
+//! }
+//! ```
+#![warn(missing_docs)]
+
+use embedded_dma::{ReadBuffer, WriteBuffer};
+
+use crate::{
+    dma::{
+        dma_private::{DmaSupport, DmaSupportRx, DmaSupportTx},
+        Channel,
+        ChannelTypes,
+        DmaError,
+        DmaPeripheral,
+        DmaTransferRx,
+        DmaTransferRxCircular,
+        DmaTransferTx,
+        RxPrivate,
+        TxPrivate,
+        UhciPeripheral,
+    },
+    peripheral::{Peripheral, PeripheralRef},
+    peripherals::UHCI0,
+    system::PeripheralClockControl,
+    uart::{Instance, Uart, UartRx, UartTx},
+    Mode,
+};
+
+/// UART DMA errors
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// General DMA error
+    DmaError(DmaError),
+    /// The buffer is empty
+    InvalidArgument,
+}
+
+impl From<DmaError> for Error {
+    fn from(value: DmaError) -> Self {
+        Error::DmaError(value)
+    }
+}
+
+/// UHCI configuration
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// End the current RX frame after the RX line was idle for the given
+    /// number of bit periods (max. 1023)
+    pub rx_idle_timeout: Option<u16>,
+    /// End the current RX frame after the given number of bytes (max. 8191)
+    pub rx_max_frame_len: Option<u16>,
+}
+
+impl Config {
+    /// End the current RX frame after the RX line was idle for the given
+    /// number of bit periods
+    pub fn rx_idle_timeout(mut self, bit_periods: u16) -> Self {
+        self.rx_idle_timeout = Some(bit_periods);
+        self
+    }
+
+    /// End the current RX frame after the given number of bytes
+    pub fn rx_max_frame_len(mut self, len: u16) -> Self {
+        self.rx_max_frame_len = Some(len);
+        self
+    }
+}
+
+/// UART driven by DMA through the UHCI peripheral
+#[allow(missing_docs)]
+pub struct UartDma<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    CH::P: UhciPeripheral,
+    DM: Mode,
+{
+    _uhci: PeripheralRef<'d, UHCI0>,
+    pub tx: UartDmaTx<'d, T, CH, DM>,
+    pub rx: UartDmaRx<'d, T, CH, DM>,
+}
+
+impl<'d, T, CH, DM> UartDma<'d, T, CH, DM>
+where
+    T: Instance + 'd,
+    CH: ChannelTypes,
+    CH::P: UhciPeripheral,
+    DM: Mode,
+{
+    /// Create a new UART DMA driver from an already configured UART.
+    ///
+    /// Data received and transmitted by the UART is routed through the given
+    /// DMA channel from now on.
+    pub fn new(
+        uhci: impl Peripheral<P = UHCI0> + 'd,
+        uart: Uart<'d, T, DM>,
+        mut channel: Channel<'d, CH, DM>,
+        config: Config,
+    ) -> Self {
+        crate::into_ref!(uhci);
+
+        PeripheralClockControl::enable(crate::system::Peripheral::Uhci0);
+
+        let regs = uhci_regs();
+
+        // No SLIP framing, no headers, no CRC - the UHCI is a transparent byte pipe
+        regs.conf0().write(|w| w.clk_en().set_bit());
+        regs.conf1().write(|w| unsafe { w.bits(0) });
+
+        attach_uart(T::uart_number());
+
+        if let Some(bit_periods) = config.rx_idle_timeout {
+            T::register_block()
+                .idle_conf()
+                .modify(|_, w| unsafe { w.rx_idle_thrhd().bits(bit_periods.min(0x3ff)) });
+        }
+
+        if let Some(len) = config.rx_max_frame_len {
+            regs.pkt_thres()
+                .write(|w| unsafe { w.pkt_thrs().bits(len.min(0x1fff)) });
+        }
+
+        regs.conf0().modify(|_, w| {
+            w.uart_idle_eof_en()
+                .bit(config.rx_idle_timeout.is_some())
+                .len_eof_en()
+                .bit(config.rx_max_frame_len.is_some())
+        });
+
+        reset_tx();
+        reset_rx();
+
+        channel.tx.init_channel();
+        channel.rx.init_channel();
+
+        let (uart_tx, uart_rx) = uart.split();
+
+        Self {
+            _uhci: uhci,
+            tx: UartDmaTx {
+                _uart: uart_tx,
+                tx_channel: channel.tx,
+            },
+            rx: UartDmaRx {
+                _uart: uart_rx,
+                rx_channel: channel.rx,
+            },
+        }
+    }
+}
+
+/// Transmitting half of a [UartDma]
+pub struct UartDmaTx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    _uart: UartTx<'d, T, DM>,
+    tx_channel: CH::Tx<'d>,
+}
+
+impl<'d, T, CH, DM> core::fmt::Debug for UartDmaTx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UartDmaTx").finish()
+    }
+}
+
+impl<'d, T, CH, DM> UartDmaTx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    CH::P: UhciPeripheral,
+    DM: Mode,
+{
+    /// Perform a DMA write.
+    ///
+    /// This will return a [DmaTransferTx]. Waiting for the transfer also waits
+    /// for the UART to shift out all data.
+    pub fn write_dma<'t, TXBUF>(
+        &'t mut self,
+        words: &'t TXBUF,
+    ) -> Result<DmaTransferTx<Self>, Error>
+    where
+        TXBUF: ReadBuffer<Word = u8>,
+    {
+        let (ptr, len) = unsafe { words.read_buffer() };
+
+        if len == 0 {
+            return Err(Error::InvalidArgument);
+        }
+
+        reset_tx();
+
+        self.tx_channel
+            .prepare_transfer_without_start(DmaPeripheral::Uhci0, false, ptr, len)
+            .and_then(|_| self.tx_channel.start_transfer())?;
+
+        Ok(DmaTransferTx::new(self))
+    }
+}
+
+impl<'d, T, CH, DM> DmaSupport for UartDmaTx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    fn peripheral_wait_dma(&mut self, _is_tx: bool, _is_rx: bool) {
+        while !self.tx_channel.is_done() && !self.tx_channel.has_error() {}
+
+        while T::get_tx_fifo_count() != 0 || !T::is_tx_idle() {}
+    }
+
+    fn peripheral_dma_stop(&mut self) {
+        reset_tx();
+    }
+}
+
+impl<'d, T, CH, DM> DmaSupportTx for UartDmaTx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    type TX = CH::Tx<'d>;
+
+    fn tx(&mut self) -> &mut Self::TX {
+        &mut self.tx_channel
+    }
+}
+
+/// Receiving half of a [UartDma]
+pub struct UartDmaRx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    _uart: UartRx<'d, T, DM>,
+    rx_channel: CH::Rx<'d>,
+}
+
+impl<'d, T, CH, DM> core::fmt::Debug for UartDmaRx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UartDmaRx").finish()
+    }
+}
+
+impl<'d, T, CH, DM> UartDmaRx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    CH::P: UhciPeripheral,
+    DM: Mode,
+{
+    /// Perform a DMA read.
+    ///
+    /// The transfer ends when the buffer is full or when the current frame
+    /// ends (see [Config]). Use [UartDmaRx::received_len] after waiting for
+    /// the transfer to get the number of bytes received.
+    pub fn read_dma<'t, RXBUF>(
+        &'t mut self,
+        words: &'t mut RXBUF,
+    ) -> Result<DmaTransferRx<Self>, Error>
+    where
+        RXBUF: WriteBuffer<Word = u8>,
+    {
+        let (ptr, len) = unsafe { words.write_buffer() };
+        self.start_rx_transfer(ptr, len, false)?;
+
+        Ok(DmaTransferRx::new(self))
+    }
+
+    /// Continuously receive into the given buffer.
+    ///
+    /// Every frame closes the descriptor it was received into, so
+    /// [DmaTransferRxCircular::available] reports the size of the next frame
+    /// (or of the next full descriptor if frames are longer than a
+    /// descriptor).
+    pub fn read_dma_circular<'t, RXBUF>(
+        &'t mut self,
+        words: &'t mut RXBUF,
+    ) -> Result<DmaTransferRxCircular<Self>, Error>
+    where
+        RXBUF: WriteBuffer<Word = u8>,
+    {
+        let (ptr, len) = unsafe { words.write_buffer() };
+        self.start_rx_transfer(ptr, len, true)?;
+
+        Ok(DmaTransferRxCircular::new(self))
+    }
+
+    /// Number of bytes received by the last (non-circular) read
+    pub fn received_len(&self) -> usize {
+        self.rx_channel.received_len()
+    }
+
+    fn start_rx_transfer(&mut self, ptr: *mut u8, len: usize, circular: bool) -> Result<(), Error> {
+        if len == 0 {
+            return Err(Error::InvalidArgument);
+        }
+
+        reset_rx();
+
+        unsafe {
+            self.rx_channel
+                .prepare_transfer_without_start(circular, DmaPeripheral::Uhci0, ptr, len)
+                .and_then(|_| self.rx_channel.start_transfer())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'d, T, CH, DM> DmaSupport for UartDmaRx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    fn peripheral_wait_dma(&mut self, _is_tx: bool, _is_rx: bool) {
+        while !(self.rx_channel.is_done()
+            || self.rx_channel.has_error()
+            || self.rx_channel.has_eof_error()
+            || self.rx_channel.has_dscr_empty_error())
+        {}
+    }
+
+    fn peripheral_dma_stop(&mut self) {
+        reset_rx();
+    }
+}
+
+impl<'d, T, CH, DM> DmaSupportRx for UartDmaRx<'d, T, CH, DM>
+where
+    T: Instance,
+    CH: ChannelTypes,
+    DM: Mode,
+{
+    type RX = CH::Rx<'d>;
+
+    fn rx(&mut self) -> &mut Self::RX {
+        &mut self.rx_channel
+    }
+}
+
+fn uhci_regs() -> &'static crate::peripherals::uhci0::RegisterBlock {
+    unsafe { &*UHCI0::PTR }
+}
+
+fn attach_uart(uart_number: usize) {
+    let regs = uhci_regs();
+
+    #[cfg(any(esp32c3, esp32s3))]
+    regs.conf0().modify(|_, w| {
+        w.uart0_ce()
+            .bit(uart_number == 0)
+            .uart1_ce()
+            .bit(uart_number == 1)
+    });
+
+    #[cfg(esp32s3)]
+    regs.conf0()
+        .modify(|_, w| w.uart2_ce().bit(uart_number == 2));
+
+    #[cfg(any(esp32c6, esp32h2))]
+    regs.conf0()
+        .modify(|_, w| unsafe { w.uart_sel().bits(uart_number as u8) });
+}
+
+fn reset_tx() {
+    let regs = uhci_regs();
+    regs.conf0().modify(|_, w| w.tx_rst().set_bit());
+    regs.conf0().modify(|_, w| w.tx_rst().clear_bit());
+}
+
+fn reset_rx() {
+    let regs = uhci_regs();
+    regs.conf0().modify(|_, w| w.rx_rst().set_bit());
+    regs.conf0().modify(|_, w| w.rx_rst().clear_bit());
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use embedded_dma::WriteBuffer;
+
+    use super::{reset_rx, reset_tx, Error, UartDmaRx, UartDmaTx};
+    use crate::{
+        dma::{
+            asynch::{DmaRxDoneChFuture, DmaRxFuture, DmaTxFuture},
+            ChannelTypes,
+            DmaError,
+            DmaPeripheral,
+            RxPrivate,
+            TxPrivate,
+            UhciPeripheral,
+        },
+        uart::Instance,
+        Async,
+    };
+
+    impl<'d, T, CH> UartDmaTx<'d, T, CH, Async>
+    where
+        T: Instance,
+        CH: ChannelTypes,
+        CH::P: UhciPeripheral,
+    {
+        /// Write the given data via DMA.
+        ///
+        /// Completes once the DMA handed all data to the UART, the last bytes
+        /// may still be in the UART TX FIFO.
+        pub async fn write_dma_async(&mut self, words: &[u8]) -> Result<(), Error> {
+            if words.is_empty() {
+                return Err(Error::InvalidArgument);
+            }
+
+            reset_tx();
+
+            let future = DmaTxFuture::new(&mut self.tx_channel);
+            future.tx.prepare_transfer_without_start(
+                DmaPeripheral::Uhci0,
+                false,
+                words.as_ptr(),
+                words.len(),
+            )?;
+            future.tx.start_transfer()?;
+            future.await?;
+
+            Ok(())
+        }
+    }
+
+    impl<'d, T, CH> UartDmaRx<'d, T, CH, Async>
+    where
+        T: Instance,
+        CH: ChannelTypes,
+        CH::P: UhciPeripheral,
+    {
+        /// Receive one frame via DMA.
+        ///
+        /// Completes when the buffer is full or the frame ended and returns
+        /// the number of bytes received.
+        pub async fn read_dma_async(&mut self, words: &mut [u8]) -> Result<usize, Error> {
+            if words.is_empty() {
+                return Err(Error::InvalidArgument);
+            }
+
+            reset_rx();
+
+            let mut future = DmaRxFuture::new(&mut self.rx_channel);
+            unsafe {
+                future.rx().prepare_transfer_without_start(
+                    false,
+                    DmaPeripheral::Uhci0,
+                    words.as_mut_ptr(),
+                    words.len(),
+                )?;
+            }
+            future.rx().start_transfer()?;
+            future.await?;
+
+            Ok(self.rx_channel.received_len())
+        }
+
+        /// Continuously receive into the given buffer. Returns
+        /// [UartDmaRxCircularAsync]
+        pub fn read_dma_circular_async<RXBUF>(
+            mut self,
+            mut words: RXBUF,
+        ) -> Result<UartDmaRxCircularAsync<'d, T, CH, RXBUF>, Error>
+        where
+            RXBUF: WriteBuffer<Word = u8>,
+        {
+            let (ptr, len) = unsafe { words.write_buffer() };
+            self.start_rx_transfer(ptr, len, true)?;
+
+            Ok(UartDmaRxCircularAsync {
+                uart_rx: self,
+                _buffer: words,
+            })
+        }
+    }
+
+    /// An in-progress async circular DMA read transfer.
+    #[non_exhaustive]
+    pub struct UartDmaRxCircularAsync<'d, T, CH, BUFFER>
+    where
+        T: Instance,
+        CH: ChannelTypes,
+    {
+        uart_rx: UartDmaRx<'d, T, CH, Async>,
+        _buffer: BUFFER,
+    }
+
+    impl<'d, T, CH, BUFFER> UartDmaRxCircularAsync<'d, T, CH, BUFFER>
+    where
+        T: Instance,
+        CH: ChannelTypes,
+    {
+        /// How many bytes can be popped from the DMA transaction.
+        /// Will wait for more than 0 bytes available.
+        pub async fn available(&mut self) -> Result<usize, Error> {
+            loop {
+                let res = self.uart_rx.rx_channel.available();
+
+                if res != 0 {
+                    break Ok(res);
+                }
+
+                DmaRxDoneChFuture::new(&mut self.uart_rx.rx_channel).await?;
+            }
+        }
+
+        /// Pop the next frame (or descriptor) from the DMA transaction.
+        ///
+        /// The buffer needs to be large enough to hold a whole descriptor.
+        pub async fn pop(&mut self, data: &mut [u8]) -> Result<usize, Error> {
+            let avail = self.available().await?;
+            if data.len() < avail {
+                return Err(DmaError::BufferTooSmall.into());
+            }
+            Ok(self.uart_rx.rx_channel.pop(&mut data[..avail])?)
+        }
+    }
+
+    impl<'d, T, CH, BUFFER> Drop for UartDmaRxCircularAsync<'d, T, CH, BUFFER>
+    where
+        T: Instance,
+        CH: ChannelTypes,
+    {
+        fn drop(&mut self) {
+            reset_rx();
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use asynch::UartDmaRxCircularAsync;
@@ -65,7 +65,8 @@ async fn main(_spawner: Spawner) {
             DmaPriority::Priority0,
         ),
         &clocks,
-    );
+    )
+    .unwrap();
 
     #[cfg(esp32)]
     {
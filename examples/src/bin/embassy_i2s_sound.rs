@@ -88,7 +88,8 @@ async fn main(_spawner: Spawner) {
             DmaPriority::Priority0,
         ),
         &clocks,
-    );
+    )
+    .unwrap();
 
     let i2s_tx = i2s
         .i2s_tx
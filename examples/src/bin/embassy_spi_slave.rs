@@ -0,0 +1,87 @@
+//! Embassy SPI slave
+//!
+//! Folowing pins are used:
+//! SCLK    GPIO0
+//! MISO    GPIO1
+//! MOSI    GPIO2
+//! CS      GPIO3
+//!
+//! Depending on your target and the board you are using you have to change the
+//! pins.
+//!
+//! Connect an SPI master to the pins. Every transfer is answered with the data
+//! received in the previous transfer, the number of bytes the master clocked is
+//! printed once it deasserts CS.
+
+//% CHIPS: esp32c2 esp32c3 esp32c6 esp32h2 esp32s2 esp32s3
+//% FEATURES: async embassy embassy-time-timg0 embassy-generic-timers
+
+#![no_std]
+#![no_main]
+
+use embassy_executor::Spawner;
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    dma::*,
+    dma_descriptors,
+    embassy::{self},
+    gpio::Io,
+    peripherals::Peripherals,
+    prelude::*,
+    spi::{
+        slave::{prelude::*, Spi},
+        SpiMode,
+    },
+    system::SystemControl,
+    timer::timg::TimerGroup,
+};
+
+#[main]
+async fn main(_spawner: Spawner) {
+    esp_println::println!("Init!");
+    let peripherals = Peripherals::take();
+    let system = SystemControl::new(peripherals.SYSTEM);
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let timg0 = TimerGroup::new_async(peripherals.TIMG0, &clocks);
+    embassy::init(&clocks, timg0);
+
+    let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+    let sclk = io.pins.gpio0;
+    let miso = io.pins.gpio1;
+    let mosi = io.pins.gpio2;
+    let cs = io.pins.gpio3;
+
+    let dma = Dma::new(peripherals.DMA);
+
+    #[cfg(feature = "esp32s2")]
+    let dma_channel = dma.spi2channel;
+    #[cfg(not(feature = "esp32s2"))]
+    let dma_channel = dma.channel0;
+
+    let (mut descriptors, mut rx_descriptors) = dma_descriptors!(256);
+
+    let mut spi = Spi::new(peripherals.SPI2, sclk, mosi, miso, cs, SpiMode::Mode0).with_dma(
+        dma_channel.configure_for_async(
+            false,
+            &mut descriptors,
+            &mut rx_descriptors,
+            DmaPriority::Priority0,
+        ),
+    );
+
+    let mut send_buffer = [0u8; 256];
+    let mut receive_buffer = [0u8; 256];
+    loop {
+        let count = spi
+            .transfer(&send_buffer, &mut receive_buffer)
+            .await
+            .unwrap();
+        let count = count.min(receive_buffer.len());
+
+        esp_println::println!("Received {} bytes: {:x?}", count, &receive_buffer[..count]);
+
+        send_buffer[..count].copy_from_slice(&receive_buffer[..count]);
+    }
+}
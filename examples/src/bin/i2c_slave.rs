@@ -0,0 +1,62 @@
+//! Serve a small register file as I2C slave
+//!
+//! The master first writes the index of a register and then reads the
+//! register contents after a repeated START. Any other write updates the
+//! registers starting at the given index.
+//!
+//! The following wiring is assumed:
+//! - SDA => GPIO4
+//! - SCL => GPIO5
+
+//% CHIPS: esp32 esp32c2 esp32c3 esp32c6 esp32h2 esp32s2 esp32s3
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    gpio::Io,
+    i2c::slave::{Config, I2cSlave},
+    peripherals::Peripherals,
+    prelude::*,
+    system::SystemControl,
+};
+use esp_println::println;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let system = SystemControl::new(peripherals.SYSTEM);
+    let _clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+
+    let mut i2c = I2cSlave::new(
+        peripherals.I2C0,
+        io.pins.gpio4,
+        io.pins.gpio5,
+        Config::new(0x55),
+        None,
+    );
+
+    let mut registers = [0u8; 16];
+
+    loop {
+        let mut data = [0u8; 17];
+        let len = i2c.read(&mut data).unwrap();
+        if len == 0 {
+            continue;
+        }
+
+        let index = data[0] as usize % registers.len();
+        if i2c.is_read_request() {
+            let sent = i2c.write(&registers[index..]).unwrap();
+            println!("Master read {} bytes from register {}", sent, index);
+        } else {
+            let count = usize::min(len - 1, registers.len() - index);
+            registers[index..][..count].copy_from_slice(&data[1..][..count]);
+            println!("Master wrote {:02x?} to register {}", &data[1..len], index);
+        }
+    }
+}
@@ -61,7 +61,8 @@ fn main() -> ! {
             DmaPriority::Priority0,
         ),
         &clocks,
-    );
+    )
+    .unwrap();
 
     #[cfg(esp32)]
     {
@@ -79,7 +79,8 @@ fn main() -> ! {
             DmaPriority::Priority0,
         ),
         &clocks,
-    );
+    )
+    .unwrap();
 
     let mut i2s_tx = i2s
         .i2s_tx
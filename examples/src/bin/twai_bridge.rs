@@ -0,0 +1,72 @@
+//! This example forwards every CAN frame received on one bus to a second bus
+//! and vice versa, using both TWAI controllers of the chip.
+//!
+//! Wiring (each bus needs its own CAN transceiver):
+//! - TWAI0: TX => GPIO0, RX => GPIO1
+//! - TWAI1: TX => GPIO2, RX => GPIO3
+
+//% CHIPS: esp32c6
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    gpio::Io,
+    peripherals::Peripherals,
+    prelude::*,
+    system::SystemControl,
+    twai::{self, buffered::TwaiBuffers},
+};
+use esp_println::println;
+
+const CAN_BAUDRATE: twai::BaudRate = twai::BaudRate::B500K;
+
+static mut BUFFERS0: TwaiBuffers<32, 8> = TwaiBuffers::new();
+static mut BUFFERS1: TwaiBuffers<32, 8> = TwaiBuffers::new();
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let system = SystemControl::new(peripherals.SYSTEM);
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+
+    let can0 = twai::TwaiConfiguration::new(
+        peripherals.TWAI0,
+        io.pins.gpio0,
+        io.pins.gpio1,
+        &clocks,
+        CAN_BAUDRATE,
+        None,
+    )
+    .start();
+    let mut can0 = can0.into_buffered(unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS0) });
+
+    let can1 = twai::TwaiConfiguration::new(
+        peripherals.TWAI1,
+        io.pins.gpio2,
+        io.pins.gpio3,
+        &clocks,
+        CAN_BAUDRATE,
+        None,
+    )
+    .start();
+    let mut can1 = can1.into_buffered(unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS1) });
+
+    loop {
+        if let Ok(received) = can0.receive() {
+            if can1.transmit(&received.frame).is_err() {
+                println!("Dropped a frame for bus 1");
+            }
+        }
+
+        if let Ok(received) = can1.receive() {
+            if can0.transmit(&received.frame).is_err() {
+                println!("Dropped a frame for bus 0");
+            }
+        }
+    }
+}
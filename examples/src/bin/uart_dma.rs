@@ -0,0 +1,90 @@
+//! This shows how to transfer data via UART using DMA through the UHCI
+//! peripheral.
+//! You can short the TX and RX pin to receive what was written. Received data
+//! is split into frames when the RX line is idle.
+//!
+//! The following wiring is assumed:
+//! - TX => GPIO4
+//! - RX => GPIO5
+
+//% CHIPS: esp32c3 esp32c6 esp32h2 esp32s3
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    delay::Delay,
+    dma::{Dma, DmaPriority},
+    dma_circular_buffers,
+    gpio::Io,
+    peripherals::Peripherals,
+    prelude::*,
+    system::SystemControl,
+    uart::{config::Config, TxRxPins, Uart},
+    uhci::{self, UartDma},
+};
+use esp_println::println;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let system = SystemControl::new(peripherals.SYSTEM);
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+    let pins = TxRxPins::new_tx_rx(io.pins.gpio4, io.pins.gpio5);
+
+    let uart = Uart::new_with_config(
+        peripherals.UART1,
+        Config::default(),
+        Some(pins),
+        &clocks,
+        None,
+    );
+
+    let dma = Dma::new(peripherals.DMA);
+    let dma_channel = dma.channel0;
+
+    let (tx_buffer, mut tx_descriptors, mut rx_buffer, mut rx_descriptors) =
+        dma_circular_buffers!(256, 4 * 256);
+
+    let uart_dma = UartDma::new(
+        peripherals.UHCI0,
+        uart,
+        dma_channel.configure(
+            false,
+            &mut tx_descriptors,
+            &mut rx_descriptors,
+            DmaPriority::Priority0,
+        ),
+        uhci::Config::default().rx_idle_timeout(20),
+    );
+    let (mut uart_tx, mut uart_rx) = (uart_dma.tx, uart_dma.rx);
+
+    for (i, b) in tx_buffer.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+
+    let delay = Delay::new(&clocks);
+
+    let mut transfer = uart_rx.read_dma_circular(&mut rx_buffer).unwrap();
+    let mut frame = [0u8; 4092];
+
+    loop {
+        uart_tx.write_dma(&tx_buffer).unwrap().wait().unwrap();
+
+        delay.delay_millis(250);
+
+        loop {
+            let avail = transfer.available();
+            if avail == 0 {
+                break;
+            }
+
+            transfer.pop(&mut frame[..avail]).unwrap();
+            println!("Received frame of {} bytes", avail);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! I2C Test
+//!
+//! Folowing pins are used:
+//! SDA    GPIO2
+//! SCL    GPIO0
+//!
+//! No device is connected to the bus. GPIO4 holds SDA low to simulate a stuck
+//! slave, connect SDA (GPIO2) and GPIO4 pins.
+
+//% CHIPS: esp32 esp32c2 esp32c3 esp32c6 esp32h2 esp32s2 esp32s3
+
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    gpio::{Gpio4, Io, Level, OutputOpenDrain, Pull},
+    i2c::{Error, I2C},
+    peripherals::{Peripherals, I2C0},
+    prelude::*,
+    system::SystemControl,
+    Blocking,
+};
+
+/// No device answers to this address
+const ADDRESS: u8 = 0x55;
+
+struct Context {
+    i2c: I2C<'static, I2C0, Blocking>,
+    sda_hold: OutputOpenDrain<'static, Gpio4>,
+}
+
+impl Context {
+    pub fn init() -> Self {
+        let peripherals = Peripherals::take();
+        let system = SystemControl::new(peripherals.SYSTEM);
+        let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+        let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+        let sda_hold = OutputOpenDrain::new(io.pins.gpio4, Level::High, Pull::Up);
+
+        let i2c = I2C::new(
+            peripherals.I2C0,
+            io.pins.gpio2,
+            io.pins.gpio0,
+            100.kHz(),
+            &clocks,
+            None,
+        );
+
+        Context { i2c, sda_hold }
+    }
+}
+
+#[cfg(test)]
+#[embedded_test::tests]
+mod tests {
+    use defmt::{assert, assert_eq};
+
+    use super::*;
+
+    #[init]
+    fn init() -> Context {
+        Context::init()
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_missing_device_is_not_acknowledged(mut ctx: Context) {
+        assert_eq!(ctx.i2c.write(ADDRESS, &[0x00]), Err(Error::AckCheckFailed));
+        assert_eq!(
+            ctx.i2c.read(ADDRESS, &mut [0u8; 1]),
+            Err(Error::AckCheckFailed)
+        );
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_segmented_transactions_are_not_acknowledged(mut ctx: Context) {
+        // More data than fits into the FIFO, the transactions are split into
+        // several command lists
+        let data = [0x5a; 100];
+        let mut buffer = [0u8; 100];
+
+        assert_eq!(ctx.i2c.write(ADDRESS, &data), Err(Error::AckCheckFailed));
+        assert_eq!(
+            ctx.i2c.write_read(ADDRESS, &data, &mut buffer),
+            Err(Error::AckCheckFailed)
+        );
+
+        // The controller is usable afterwards
+        assert_eq!(ctx.i2c.write(ADDRESS, &[0x00]), Err(Error::AckCheckFailed));
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_empty_read_is_rejected(mut ctx: Context) {
+        assert_eq!(
+            ctx.i2c.read(ADDRESS, &mut []),
+            Err(Error::InvalidZeroLength)
+        );
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_clear_bus(mut ctx: Context) {
+        ctx.sda_hold.set_low();
+        assert_eq!(ctx.i2c.clear_bus(), Err(Error::BusStuck));
+
+        ctx.sda_hold.set_high();
+        assert_eq!(ctx.i2c.clear_bus(), Ok(()));
+
+        assert_eq!(ctx.i2c.write(ADDRESS, &[0x00]), Err(Error::AckCheckFailed));
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_stuck_bus_fails_transactions(mut ctx: Context) {
+        ctx.sda_hold.set_low();
+        assert!(ctx.i2c.write(ADDRESS, &[0x00]).is_err());
+
+        ctx.sda_hold.set_high();
+        assert_eq!(ctx.i2c.clear_bus(), Ok(()));
+
+        assert_eq!(ctx.i2c.write(ADDRESS, &[0x00]), Err(Error::AckCheckFailed));
+    }
+}
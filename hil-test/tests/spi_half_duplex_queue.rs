@@ -0,0 +1,176 @@
+//! SPI Half Duplex Segmented Transfer Test
+//!
+//! Folowing pins are used:
+//! SCLK    GPIO0
+//! MISO    GPIO2
+//! CS      GPIO5
+//!
+//! GPIO4 drives the level read on MISO, connect MISO (GPIO2) and GPIO4 pins.
+
+//% CHIPS: esp32c2 esp32c3 esp32c6 esp32h2 esp32s3
+
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    dma::{Dma, DmaDescriptor, DmaPriority},
+    dma_buffers,
+    gpio::{Io, Level, Output},
+    peripherals::Peripherals,
+    prelude::*,
+    spi::{
+        master::{prelude::*, Address, Command, QueuedTransaction, Spi},
+        SpiDataMode,
+        SpiMode,
+    },
+    system::SystemControl,
+};
+
+#[cfg(test)]
+#[embedded_test::tests]
+mod tests {
+    use defmt::assert_eq;
+
+    use super::*;
+
+    #[test]
+    #[timeout(3)]
+    fn test_queued_transactions() {
+        let peripherals = Peripherals::take();
+        let system = SystemControl::new(peripherals.SYSTEM);
+        let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+        let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+        let _miso_level = Output::new(io.pins.gpio4, Level::High);
+
+        let dma = Dma::new(peripherals.DMA);
+        let dma_channel = dma.channel0;
+
+        // Every transaction needs a descriptor for its configuration and one
+        // for its data
+        let mut tx_descriptors = [DmaDescriptor::EMPTY; 8];
+        let mut rx_descriptors = [DmaDescriptor::EMPTY; 8];
+
+        let mut spi = Spi::new_half_duplex(peripherals.SPI2, 100.kHz(), SpiMode::Mode0, &clocks)
+            .with_sck(io.pins.gpio0)
+            .with_miso(io.pins.gpio2)
+            .with_cs(io.pins.gpio5)
+            .with_dma(dma_channel.configure(
+                false,
+                &mut tx_descriptors,
+                &mut rx_descriptors,
+                DmaPriority::Priority0,
+            ));
+
+        let data = [0xde, 0xad, 0xbe, 0xef];
+        let mut first = [0u8; 4];
+        let mut second = [0u8; 8];
+
+        let mut transactions = [
+            QueuedTransaction::command(
+                Command::Command8(0x06, SpiDataMode::Single),
+                Address::None,
+                0,
+            ),
+            QueuedTransaction::write(
+                SpiDataMode::Single,
+                Command::Command8(0x02, SpiDataMode::Single),
+                Address::Address24(0x1000, SpiDataMode::Single),
+                0,
+                &data,
+            ),
+            QueuedTransaction::read(
+                SpiDataMode::Single,
+                Command::Command8(0x03, SpiDataMode::Single),
+                Address::Address24(0x1000, SpiDataMode::Single),
+                0,
+                &mut first,
+            ),
+            QueuedTransaction::read(
+                SpiDataMode::Single,
+                Command::Command8(0x0b, SpiDataMode::Single),
+                Address::Address24(0x2000, SpiDataMode::Single),
+                8,
+                &mut second,
+            ),
+        ];
+
+        let transfer = spi.queue_transfer(&mut transactions).unwrap();
+        transfer.wait().unwrap();
+
+        assert_eq!(first, [0xff; 4]);
+        assert_eq!(second, [0xff; 8]);
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_transfer_after_queue() {
+        let peripherals = Peripherals::take();
+        let system = SystemControl::new(peripherals.SYSTEM);
+        let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+        let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+        let mut miso_level = Output::new(io.pins.gpio4, Level::High);
+
+        let dma = Dma::new(peripherals.DMA);
+        let dma_channel = dma.channel0;
+
+        let mut tx_descriptors = [DmaDescriptor::EMPTY; 8];
+        let mut rx_descriptors = [DmaDescriptor::EMPTY; 8];
+
+        let mut spi = Spi::new_half_duplex(peripherals.SPI2, 100.kHz(), SpiMode::Mode0, &clocks)
+            .with_sck(io.pins.gpio0)
+            .with_miso(io.pins.gpio2)
+            .with_cs(io.pins.gpio5)
+            .with_dma(dma_channel.configure(
+                false,
+                &mut tx_descriptors,
+                &mut rx_descriptors,
+                DmaPriority::Priority0,
+            ));
+
+        let mut queued = [0u8; 4];
+        let mut transactions = [
+            QueuedTransaction::read(
+                SpiDataMode::Single,
+                Command::Command8(0x03, SpiDataMode::Single),
+                Address::Address24(0, SpiDataMode::Single),
+                0,
+                &mut queued,
+            ),
+            QueuedTransaction::command(
+                Command::Command8(0x04, SpiDataMode::Single),
+                Address::None,
+                0,
+            ),
+        ];
+        spi.queue_transfer(&mut transactions)
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert_eq!(queued, [0xff; 4]);
+
+        // A regular transfer after the batch must use the configuration in the
+        // registers, not wait for one from the DMA
+        miso_level.set_low();
+
+        let (_, _, mut buffer, _) = dma_buffers!(4);
+        buffer.fill(0xaa);
+
+        let transfer = spi
+            .read(
+                SpiDataMode::Single,
+                Command::None,
+                Address::None,
+                0,
+                &mut buffer,
+            )
+            .unwrap();
+        transfer.wait().unwrap();
+
+        assert_eq!(*buffer, [0x00; 4]);
+    }
+}
@@ -0,0 +1,106 @@
+//! TWAI Test
+//!
+//! Folowing pins are used:
+//! TX    GPIO2
+//! RX    GPIO4
+//!
+//! Connect TX (GPIO2) and RX (GPIO4) pins, no transceiver is needed as the
+//! controller receives its own frames in self test mode.
+
+//% CHIPS: esp32c3 esp32c6 esp32h2 esp32s2 esp32s3
+
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use embedded_hal_02::can::Frame;
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    gpio::Io,
+    peripherals::{Peripherals, TWAI0},
+    system::SystemControl,
+    twai::{
+        filter::{FilterSet, IdFilter},
+        BaudRate,
+        EspTwaiFrame,
+        StandardId,
+        Twai,
+        TwaiConfiguration,
+        TwaiMode,
+    },
+    Blocking,
+};
+use nb::block;
+
+fn frame(id: u16, data: &[u8]) -> EspTwaiFrame {
+    EspTwaiFrame::new(StandardId::new(id).unwrap().into(), data).unwrap()
+}
+
+fn twai(filters: &FilterSet) -> Twai<'static, TWAI0, Blocking> {
+    let peripherals = Peripherals::take();
+    let system = SystemControl::new(peripherals.SYSTEM);
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+
+    let mut config = TwaiConfiguration::new(
+        peripherals.TWAI0,
+        io.pins.gpio2,
+        io.pins.gpio4,
+        &clocks,
+        BaudRate::B1000K,
+        None,
+    );
+    config.set_filter_set(filters);
+    config.set_mode(TwaiMode::SelfTest);
+
+    config.start()
+}
+
+#[cfg(test)]
+#[embedded_test::tests]
+mod tests {
+    use defmt::assert_eq;
+
+    use super::*;
+
+    #[test]
+    #[timeout(3)]
+    fn test_filter_set_exact_match() {
+        let filters = FilterSet::new()
+            .with(IdFilter::id(StandardId::new(0x585).unwrap().into()))
+            .with(IdFilter::id(StandardId::new(0x100).unwrap().into()));
+        let mut twai = twai(&filters);
+
+        // Rejected by the acceptance filter
+        block!(twai.transmit(&frame(0x200, &[1]))).unwrap();
+        block!(twai.transmit(&frame(0x585, &[2]))).unwrap();
+
+        let received = block!(twai.receive()).unwrap();
+        assert_eq!(received.data(), &[2]);
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_filter_set_discards_false_positives() {
+        // The acceptance filter can't separate 0x585 and 0x586 from their
+        // neighbours while also matching 0x100
+        let filters = FilterSet::new()
+            .with(IdFilter::id(StandardId::new(0x585).unwrap().into()))
+            .with(IdFilter::id(StandardId::new(0x586).unwrap().into()))
+            .with(IdFilter::id(StandardId::new(0x100).unwrap().into()));
+
+        let (_, coverage) = filters.hardware_filter();
+        assert_eq!(coverage.false_positives(), 2);
+
+        let mut twai = twai(&filters);
+
+        // Passes the acceptance filter, discarded by the driver
+        block!(twai.transmit(&frame(0x587, &[1]))).unwrap();
+        block!(twai.transmit(&frame(0x586, &[2]))).unwrap();
+
+        let received = block!(twai.receive()).unwrap();
+        assert_eq!(received.data(), &[2]);
+    }
+}
@@ -0,0 +1,229 @@
+//! TWAI filter set tests
+
+//% CHIPS: esp32 esp32c3 esp32c6 esp32h2 esp32s2 esp32s3
+
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use esp_backtrace as _;
+use esp_hal::twai::{
+    filter::{
+        DualExtendedFilter,
+        DualStandardFilter,
+        Filter,
+        FilterCoverage,
+        FilterSet,
+        FilterType,
+        IdFilter,
+    },
+    EspTwaiFrame,
+    ExtendedId,
+    Id,
+    StandardId,
+};
+
+fn standard(raw: u16) -> Id {
+    Id::Standard(StandardId::new(raw).unwrap())
+}
+
+fn extended(raw: u32) -> Id {
+    Id::Extended(ExtendedId::new(raw).unwrap())
+}
+
+#[cfg(test)]
+#[embedded_test::tests]
+mod tests {
+    use defmt::{assert, assert_eq};
+
+    use super::*;
+
+    #[init]
+    fn init() {}
+
+    #[test]
+    fn test_j1939_pdu2_pgn_matches_any_source() {
+        let filter = IdFilter::j1939_pgn(0xF004);
+
+        assert!(
+            filter
+                == IdFilter::ExtendedMasked {
+                    code: 0xF0_0400,
+                    mask: 0x3FF_FF00,
+                }
+        );
+        assert!(filter.matches(extended(0x0CF0_0400)));
+        assert!(filter.matches(extended(0x18F0_0421)));
+        assert!(!filter.matches(extended(0x0CF0_0500)));
+        assert!(!filter.matches(standard(0x400)));
+    }
+
+    #[test]
+    fn test_j1939_pdu1_pgn_matches_any_destination() {
+        let filter = IdFilter::j1939_pgn(0xEA00);
+
+        assert!(
+            filter
+                == IdFilter::ExtendedMasked {
+                    code: 0xEA_0000,
+                    mask: 0x3FF_0000,
+                }
+        );
+        assert!(filter.matches(extended(0x18EA_2100)));
+        assert!(filter.matches(extended(0x18EA_FF42)));
+        assert!(!filter.matches(extended(0x18EB_0000)));
+    }
+
+    #[test]
+    fn test_canopen_ids() {
+        let cob_id = IdFilter::canopen_cob_id(0b1011, 5);
+        assert!(cob_id.matches(standard(0x585)));
+        assert!(!cob_id.matches(standard(0x586)));
+        assert!(!cob_id.matches(extended(0x585)));
+
+        let function_code = IdFilter::canopen_function_code(0b1011);
+        assert!(function_code.matches(standard(0x581)));
+        assert!(function_code.matches(standard(0x5FF)));
+        assert!(!function_code.matches(standard(0x601)));
+    }
+
+    #[test]
+    fn test_single_id_gives_exact_single_filter() {
+        let set = FilterSet::new().with(IdFilter::canopen_cob_id(0b1011, 5));
+        let (filter, coverage) = set.hardware_filter();
+
+        assert!(filter.filter_type() == FilterType::Single);
+        // Code 0x585 in the upper 11 bits, RTR and payload don't care.
+        assert_eq!(
+            filter.to_registers(),
+            [0xB0, 0xA0, 0x00, 0x00, 0x00, 0x1F, 0xFF, 0xFF]
+        );
+        assert_eq!(
+            coverage,
+            FilterCoverage {
+                requested: 1,
+                accepted: 1,
+            }
+        );
+        assert_eq!(coverage.false_positives(), 0);
+    }
+
+    #[test]
+    fn test_distant_standard_ids_use_dual_filter() {
+        let set = FilterSet::new()
+            .with(IdFilter::id(standard(0x100)))
+            .with(IdFilter::id(standard(0x7FF)));
+        let (filter, coverage) = set.hardware_filter();
+
+        let expected = DualStandardFilter::new_from_code_mask(
+            StandardId::new(0x100).unwrap(),
+            StandardId::new(0x7FF).unwrap(),
+            false,
+            false,
+            0,
+            0,
+            StandardId::new(0x7FF).unwrap(),
+            StandardId::new(0x7FF).unwrap(),
+            false,
+            false,
+        );
+
+        assert!(filter.filter_type() == FilterType::Dual);
+        assert_eq!(filter.to_registers(), expected.to_registers());
+        assert_eq!(
+            coverage,
+            FilterCoverage {
+                requested: 2,
+                accepted: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_j1939_pgns_use_dual_extended_filter() {
+        let set = FilterSet::new()
+            .with(IdFilter::j1939_pgn(0xF004))
+            .with(IdFilter::j1939_pgn(0xFEF1));
+        let (filter, coverage) = set.hardware_filter();
+
+        // The dual extended filter only compares the upper 16 bits of the id,
+        // which leaves the lowest 5 bits of the PGN unchecked.
+        let expected = DualExtendedFilter::new_from_code_mask([0x780, 0x7F7], [0x1FFF, 0x1FFF]);
+
+        assert!(filter.filter_type() == FilterType::Dual);
+        assert_eq!(filter.to_registers(), expected.to_registers());
+        assert_eq!(
+            coverage,
+            FilterCoverage {
+                requested: 2 << 11,
+                accepted: 2 << 16,
+            }
+        );
+    }
+
+    #[test]
+    fn test_full_set_merges_into_two_groups() {
+        // Sixteen ids which can be split into two groups of eight, each covered
+        // exactly by one filter of the dual filter.
+        let mut set = FilterSet::new();
+        for node_id in 0..8 {
+            set = set
+                .with(IdFilter::id(standard(0x180 + node_id)))
+                .with(IdFilter::id(standard(0x580 + node_id)));
+        }
+        let (filter, coverage) = set.hardware_filter();
+
+        assert!(filter.filter_type() == FilterType::Dual);
+        assert_eq!(
+            coverage,
+            FilterCoverage {
+                requested: 16,
+                accepted: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn test_id_range_is_covered_by_its_common_prefix() {
+        let set = FilterSet::new().with(IdFilter::StandardRange(
+            StandardId::new(0x120).unwrap(),
+            StandardId::new(0x12F).unwrap(),
+        ));
+        let (filter, coverage) = set.hardware_filter();
+
+        assert!(filter.filter_type() == FilterType::Single);
+        assert_eq!(
+            coverage,
+            FilterCoverage {
+                requested: 16,
+                accepted: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mixed_id_formats_accept_everything() {
+        let set = FilterSet::new()
+            .with(IdFilter::canopen_cob_id(0b1011, 5))
+            .with(IdFilter::j1939_pgn(0xF004));
+        let (filter, coverage) = set.hardware_filter();
+
+        assert!(filter.filter_type() == FilterType::Single);
+        assert_eq!(filter.to_registers(), [0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(coverage.accepted, (1 << 11) + (1 << 29));
+    }
+
+    #[test]
+    fn test_set_matches_frames() {
+        let set = FilterSet::new()
+            .with(IdFilter::canopen_cob_id(0b1011, 5))
+            .with(IdFilter::canopen_cob_id(0b1011, 6));
+
+        let frame = |id| EspTwaiFrame::new(id, &[]).unwrap();
+
+        assert!(set.matches(&frame(standard(0x585))));
+        assert!(set.matches(&frame(standard(0x586))));
+        assert!(!set.matches(&frame(standard(0x587))));
+        assert!(!FilterSet::new().matches(&frame(standard(0x585))));
+    }
+}
@@ -20,7 +20,12 @@ use esp_hal::{
     peripherals::{Peripherals, UART0},
     prelude::*,
     system::SystemControl,
-    uart::{config::Config, ClockSource, TxRxPins, Uart},
+    uart::{
+        config::{Config, Inversion},
+        ClockSource,
+        TxRxPins,
+        Uart,
+    },
     Blocking,
 };
 use nb::block;
@@ -54,7 +59,7 @@ impl Context {
 #[cfg(test)]
 #[embedded_test::tests]
 mod tests {
-    use defmt::assert_eq;
+    use defmt::{assert, assert_eq};
 
     use super::*;
 
@@ -144,4 +149,33 @@ mod tests {
             assert_eq!(read, Ok(253));
         }
     }
+
+    #[test]
+    #[timeout(3)]
+    fn test_send_break(mut ctx: Context) {
+        ctx.uart.reset_break_detected_interrupt();
+
+        ctx.uart.send_break(20);
+        block!(ctx.uart.flush_break()).unwrap();
+        assert!(ctx.uart.break_detected_interrupt_set());
+
+        // The break may have been received as a NUL character
+        while ctx.uart.read_byte().is_ok() {}
+
+        ctx.uart.write(0x42).ok();
+        let read = block!(ctx.uart.read());
+        assert_eq!(read, Ok(0x42));
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_send_receive_inverted(mut ctx: Context) {
+        // TX and RX are connected, inverting both cancels out
+        ctx.uart
+            .change_inversion(Inversion::default().tx(true).rx(true));
+
+        ctx.uart.write(0x42).ok();
+        let read = block!(ctx.uart.read());
+        assert_eq!(read, Ok(0x42));
+    }
 }
@@ -71,4 +71,13 @@ mod tests {
         ctx.rx.read_async(&mut buf[..]).await.unwrap();
         assert_eq!(&buf[..], SEND);
     }
+
+    #[test]
+    #[timeout(3)]
+    async fn test_send_break(mut ctx: Context) {
+        let (sent, _) =
+            embassy_futures::join::join(ctx.tx.send_break_async(20), ctx.rx.wait_for_break_async())
+                .await;
+        sent.unwrap();
+    }
 }
@@ -0,0 +1,120 @@
+//! UART RS-485 Test
+//!
+//! Folowing pins are used:
+//! TX    GPIP2
+//! RX    GPIO4
+//!
+//! Connect TX (GPIO2) and RX (GPIO4) pins.
+
+//% CHIPS: esp32 esp32c2 esp32c3 esp32c6 esp32h2 esp32s2 esp32s3
+
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use esp_backtrace as _;
+use esp_hal::{
+    clock::ClockControl,
+    gpio::Io,
+    peripherals::{Peripherals, UART0},
+    system::SystemControl,
+    uart::{
+        config::{Config, Rs485Config},
+        TxRxPins,
+        Uart,
+    },
+    Blocking,
+};
+use nb::block;
+
+fn uart(rs485: Rs485Config) -> Uart<'static, UART0, Blocking> {
+    let peripherals = Peripherals::take();
+    let system = SystemControl::new(peripherals.SYSTEM);
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
+    let pins = TxRxPins::new_tx_rx(io.pins.gpio2, io.pins.gpio4);
+
+    Uart::new_with_config(
+        peripherals.UART0,
+        Config::default().rs485(rs485),
+        Some(pins),
+        &clocks,
+        None,
+    )
+}
+
+fn read_exact(uart: &mut Uart<'static, UART0, Blocking>, buffer: &mut [u8]) {
+    for byte in buffer {
+        *byte = block!(uart.read_byte()).unwrap();
+    }
+}
+
+#[cfg(test)]
+#[embedded_test::tests]
+mod tests {
+    use defmt::{assert, assert_eq};
+
+    use super::*;
+
+    const DATA: [u8; 4] = [0x01, 0x03, 0x00, 0x00];
+
+    #[test]
+    #[timeout(3)]
+    fn test_rs485_receives_own_data() {
+        let mut uart = uart(Rs485Config::default());
+
+        uart.write_bytes(&DATA).unwrap();
+        block!(uart.flush_tx()).unwrap();
+
+        let mut buffer = [0u8; DATA.len()];
+        read_exact(&mut uart, &mut buffer);
+        assert_eq!(buffer, DATA);
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_rs485_collision_detection() {
+        // The receiver sees exactly what was sent, so there is no collision
+        let mut uart = uart(
+            Rs485Config::default()
+                .collision_detection(true)
+                .delay_before_start(true)
+                .delay_after_stop(true),
+        );
+
+        uart.write_bytes(&DATA).unwrap();
+        block!(uart.flush_tx()).unwrap();
+
+        let mut buffer = [0u8; DATA.len()];
+        read_exact(&mut uart, &mut buffer);
+        assert_eq!(buffer, DATA);
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_rs485_loopback_suppression() {
+        let mut uart = uart(Rs485Config::default().loopback_suppression(true));
+
+        uart.write_bytes(&DATA).unwrap();
+        block!(uart.flush_tx()).unwrap();
+
+        assert!(matches!(uart.read_byte(), Err(nb::Error::WouldBlock)));
+    }
+
+    #[test]
+    #[timeout(3)]
+    fn test_rs485_tx_delay_is_clamped() {
+        let mut uart = uart(Rs485Config {
+            tx_delay: u8::MAX,
+            ..Rs485Config::default()
+        });
+
+        uart.write_bytes(&DATA).unwrap();
+        block!(uart.flush_tx()).unwrap();
+
+        let mut buffer = [0u8; DATA.len()];
+        read_exact(&mut uart, &mut buffer);
+        assert_eq!(buffer, DATA);
+    }
+}